@@ -0,0 +1,150 @@
+/*
+Copyright 2024 Owain Davies
+SPDX-License-Identifier: Apache-2.0 OR MIT
+*/
+
+//! Python bindings exposing the loading, merging, and plotting of saved
+//! benchmark results, so results of Rust benchmarks can be post-processed in
+//! notebooks with the same plotting logic.
+//!
+//! ```python
+//! from benchplot_py import Results
+//!
+//! old = Results.load("old.json")
+//! new = Results.load("new.json")
+//! new.plot("comparison.svg", overlay=old, title="Sorting")
+//! dict(zip(new.functions, zip(*new.times())))
+//! ```
+
+use benchplot::{Baseline, PlotBuilderError};
+use pyo3::exceptions::PyRuntimeError;
+use pyo3::prelude::*;
+use std::path::PathBuf;
+
+/// Saved benchmark results, as written by `Baseline::save` or
+/// `Baseline::to_json`.
+#[pyclass(name = "Results", module = "benchplot_py", frozen)]
+struct Results {
+    inner: Baseline,
+}
+
+fn plot_error(err: PlotBuilderError) -> PyErr {
+    PyRuntimeError::new_err(err.to_string())
+}
+
+#[pymethods]
+impl Results {
+    /// Loads results from a file in JSON or in the format of `Baseline::save`.
+    #[staticmethod]
+    fn load(path: PathBuf) -> PyResult<Self> {
+        Ok(Self {
+            inner: Baseline::load(path)?,
+        })
+    }
+
+    /// Parses results rendered as JSON by `Baseline::to_json`.
+    #[staticmethod]
+    fn from_json(json: &str) -> PyResult<Self> {
+        Ok(Self {
+            inner: Baseline::from_json(json)?,
+        })
+    }
+
+    /// The label identifying the run, if any.
+    #[getter]
+    fn label(&self) -> Option<String> {
+        self.inner.label().map(String::from)
+    }
+
+    /// The names of the functions, in the order of the columns of `times`.
+    #[getter]
+    fn functions(&self) -> Vec<String> {
+        self.inner.functions().to_vec()
+    }
+
+    /// The input sizes, in the order of the rows of `times`.
+    #[getter]
+    fn sizes(&self) -> Vec<usize> {
+        self.inner.data().sizes().to_vec()
+    }
+
+    /// Returns the timings in seconds, one list per input size with one
+    /// timing per function, or NaN where a timing is missing.
+    fn times(&self) -> Vec<Vec<f64>> {
+        self.inner
+            .data()
+            .iter()
+            .map(|(_, times)| times.to_vec())
+            .collect()
+    }
+
+    /// Merges these results with `other`, whose timings take precedence.
+    fn merge(&self, other: PyRef<'_, Self>) -> Self {
+        Self {
+            inner: self.inner.merge(&other.inner),
+        }
+    }
+
+    /// Renders the results as JSON.
+    fn to_json(&self) -> String {
+        self.inner.to_json()
+    }
+
+    /// Saves the results to a file, as JSON if its name ends with `.json`.
+    fn save(&self, path: PathBuf) -> PyResult<()> {
+        if path.extension().is_some_and(|ext| ext == "json") {
+            std::fs::write(&path, self.inner.to_json() + "\n")?;
+        } else {
+            self.inner.save(&path)?;
+        }
+        Ok(())
+    }
+
+    /// Plots the results to an SVG or bitmap file, with the results of
+    /// `overlay`, if any, drawn as dashed lines for comparison.
+    #[pyo3(signature = (path, overlay=None, title=None))]
+    fn plot(
+        &self,
+        py: Python<'_>,
+        path: PathBuf,
+        overlay: Option<PyRef<'_, Self>>,
+        title: Option<String>,
+    ) -> PyResult<()> {
+        let overlay = overlay.map(|overlay| overlay.inner.clone());
+        py.allow_threads(|| {
+            self.inner.plot(&path, |mut plot| {
+                if let Some(baseline) = &overlay {
+                    plot = plot.overlay(baseline);
+                }
+                if let Some(title) = &title {
+                    plot = plot.title(title);
+                }
+                plot
+            })
+        })
+        .map_err(plot_error)
+    }
+
+    /// Writes an HTML report of the results, with their plot and a table of
+    /// the timings, to the directory `dir`.
+    fn write_report(&self, py: Python<'_>, dir: PathBuf) -> PyResult<()> {
+        py.allow_threads(|| self.inner.write_report(&dir))?;
+        Ok(())
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Results(label={:?}, functions={:?}, sizes={:?})",
+            self.inner.label(),
+            self.inner.functions(),
+            self.inner.data().sizes()
+        )
+    }
+}
+
+/// Loads, merges, and plots benchplot results.
+#[pymodule]
+fn benchplot_py(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<Results>()?;
+    Ok(())
+}
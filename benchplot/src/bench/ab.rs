@@ -0,0 +1,412 @@
+/*
+Copyright 2024 Owain Davies
+SPDX-License-Identifier: Apache-2.0 OR MIT
+*/
+
+use super::aggregation;
+use super::significance::mann_whitney;
+#[cfg(feature = "plot")]
+use crate::{util, DefaultTheme, PlotBuilderError, Theme};
+use crate::{Aggregation, Bench, RunError, Threading};
+#[cfg(feature = "plot")]
+use plotters::prelude::*;
+use std::fmt::Debug;
+#[cfg(feature = "plot")]
+use std::path::Path;
+
+/// Number of alternating rounds the samples of an A/B comparison are split
+/// into, so that slow drifts in the state of the machine affect both
+/// functions alike.
+const ROUNDS: usize = 10;
+
+/// Number of bins of the histograms of [`AbComparison::plot`].
+#[cfg(feature = "plot")]
+const HISTOGRAM_BINS: usize = 40;
+
+/// The timings of two functions at a single input size, as measured by
+/// [`Bench::compare_at`], with statistics comparing their distributions.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AbComparison {
+    /// The input size the functions were timed at.
+    pub size: usize,
+
+    /// The names of the two functions, A and B.
+    pub names: [String; 2],
+
+    /// The timings in seconds of every call of A and of B.
+    pub samples: [Vec<f64>; 2],
+}
+
+impl AbComparison {
+    /// Returns the median timings in seconds of A and of B.
+    pub fn medians(&self) -> [f64; 2] {
+        self.samples.each_ref().map(|times| {
+            Aggregation::Median.aggregate_nanos(&aggregation::to_nanos(times))
+        })
+    }
+
+    /// Returns how many times faster A is than B, as the ratio of their
+    /// median timings, e.g., `1.25` if A takes 20% less time and `0.8` if it
+    /// takes 25% more.
+    pub fn speedup(&self) -> f64 {
+        let [a, b] = self.medians();
+        b / a
+    }
+
+    /// Returns Cliff's delta of the timings, the probability that a call of
+    /// A is faster than a call of B minus the probability that it is slower.
+    ///
+    /// The effect size ranges from `-1` when every call of A is slower than
+    /// every call of B to `1` when every call of A is faster, and is `0` when
+    /// neither tends to be faster. Unlike the speedup, it accounts for the
+    /// spread of the timings: magnitudes below about 0.15 are usually deemed
+    /// negligible and above about 0.5 large.
+    pub fn effect_size(&self) -> f64 {
+        let [a, b] = &self.samples;
+        if a.is_empty() || b.is_empty() {
+            return f64::NAN;
+        }
+        let mut b = b.clone();
+        b.sort_by(f64::total_cmp);
+        let (mut faster, mut slower) = (0, 0);
+        for &time in a {
+            faster += b.len() - b.partition_point(|&other| other <= time);
+            slower += b.partition_point(|&other| other < time);
+        }
+        (faster as f64 - slower as f64) / (a.len() * b.len()) as f64
+    }
+
+    /// Returns the two-sided p-value of the Mann-Whitney U test of whether
+    /// the timings of A and B come from the same distribution.
+    pub fn p_value(&self) -> f64 {
+        mann_whitney(&self.samples[0], &self.samples[1])
+    }
+
+    /// Returns a one-line summary of the comparison, e.g.,
+    /// `n=1000: Merge Sort is 1.25x faster than Quick Sort (Cliff's delta
+    /// 0.62, p=1.3e-12)`, naming the faster function first, with the effect
+    /// size from its point of view.
+    pub fn summary(&self) -> String {
+        let speedup = self.speedup();
+        let (faster, slower, ratio, effect_size) = if speedup >= 1.0 {
+            (&self.names[0], &self.names[1], speedup, self.effect_size())
+        } else {
+            (
+                &self.names[1],
+                &self.names[0],
+                speedup.recip(),
+                -self.effect_size(),
+            )
+        };
+        format!(
+            "n={}: {} is {:.2}x faster than {} (Cliff's delta {:.2}, \
+             p={:.1e})",
+            self.size,
+            faster,
+            ratio,
+            slower,
+            effect_size,
+            self.p_value()
+        )
+    }
+
+    /// Saves a figure comparing the distributions of the timings of A and B
+    /// to an SVG file: their overlaid histograms on the left and their
+    /// empirical cumulative distribution functions (ECDFs) on the right,
+    /// under the [`AbComparison::summary`] as the title.
+    ///
+    /// The histograms show the fraction of the calls of each function in
+    /// each bin, so that they are comparable whatever the number of samples.
+    /// The slowest 1% of the timings are gathered in the last bin, so that
+    /// rare outliers do not squeeze the rest of the distribution.
+    #[cfg(feature = "plot")]
+    pub fn plot<P: AsRef<Path>>(
+        &self,
+        path: P,
+    ) -> Result<(), PlotBuilderError> {
+        let path = path.as_ref();
+        if !path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("svg"))
+        {
+            return Err(PlotBuilderError::UnsupportedFormat {
+                path: path.to_path_buf(),
+            });
+        }
+        let mut pooled: Vec<f64> = self.samples.concat();
+        pooled.retain(|time| time.is_finite());
+        if pooled.is_empty() {
+            return Err(PlotBuilderError::NoData);
+        }
+        pooled.sort_by(f64::total_cmp);
+        let min = pooled[0];
+        let max = pooled[pooled.len() - 1];
+        let clip = pooled[(pooled.len() - 1) * 99 / 100];
+        // A range of zero width cannot be drawn.
+        let (min, clip, max) = if max > min {
+            (
+                min,
+                clip.max(min + (max - min) / HISTOGRAM_BINS as f64),
+                max,
+            )
+        } else {
+            (min * 0.9, min * 1.1, min * 1.1)
+        };
+        let width = (clip - min) / HISTOGRAM_BINS as f64;
+        let histograms: Vec<Vec<f64>> = self
+            .samples
+            .iter()
+            .map(|times| {
+                let mut counts = vec![0.0; HISTOGRAM_BINS];
+                for &time in times.iter().filter(|time| time.is_finite()) {
+                    let bin = ((time - min) / width) as usize;
+                    counts[bin.min(HISTOGRAM_BINS - 1)] += 1.0;
+                }
+                counts
+                    .iter()
+                    .map(|count| count / times.len() as f64)
+                    .collect()
+            })
+            .collect();
+        let max_fraction =
+            histograms.iter().flatten().copied().fold(0.0, f64::max);
+
+        let palette = DefaultTheme.palette();
+        let font = DefaultTheme.font_family();
+        let root = SVGBackend::new(path, (1000, 500)).into_drawing_area();
+        root.fill(&DefaultTheme.background())?;
+        let area = root.titled(&self.summary(), (font.as_str(), 18))?;
+        let (left, right) = area.split_horizontally(500);
+
+        let mut chart = ChartBuilder::on(&left)
+            .margin(15)
+            .x_label_area_size(40)
+            .y_label_area_size(50)
+            .build_cartesian_2d(min..clip, 0.0..max_fraction * 1.1)?;
+        chart
+            .configure_mesh()
+            .x_desc("Time")
+            .y_desc("Fraction of calls")
+            .x_labels(5)
+            .x_label_formatter(&|x| util::format_seconds(*x))
+            .label_style((font.as_str(), 12))
+            .draw()?;
+        for ((name, counts), &color) in
+            self.names.iter().zip(&histograms).zip(&palette)
+        {
+            chart
+                .draw_series(counts.iter().enumerate().map(|(bin, &count)| {
+                    let start = min + bin as f64 * width;
+                    Rectangle::new(
+                        [(start, 0.0), (start + width, count)],
+                        color.mix(0.4).filled(),
+                    )
+                }))?
+                .label(name.as_str())
+                .legend(move |(x, y)| {
+                    Rectangle::new(
+                        [(x, y - 5), (x + 20, y + 5)],
+                        color.mix(0.4).filled(),
+                    )
+                });
+        }
+        chart
+            .configure_series_labels()
+            .background_style(WHITE.mix(0.8))
+            .border_style(BLACK)
+            .label_font((font.as_str(), 12))
+            .draw()?;
+
+        let mut chart = ChartBuilder::on(&right)
+            .margin(15)
+            .x_label_area_size(40)
+            .y_label_area_size(50)
+            .build_cartesian_2d(min..max, 0.0..1.0)?;
+        chart
+            .configure_mesh()
+            .x_desc("Time")
+            .y_desc("Fraction of calls at most as slow")
+            .x_labels(5)
+            .x_label_formatter(&|x| util::format_seconds(*x))
+            .label_style((font.as_str(), 12))
+            .draw()?;
+        for (times, &color) in self.samples.iter().zip(&palette) {
+            let mut sorted: Vec<f64> =
+                times.iter().copied().filter(|t| t.is_finite()).collect();
+            sorted.sort_by(f64::total_cmp);
+            let n = sorted.len() as f64;
+            let steps = sorted.iter().enumerate().flat_map(|(i, &time)| {
+                [(time, i as f64 / n), (time, (i + 1) as f64 / n)]
+            });
+            chart.draw_series(LineSeries::new(steps, color.stroke_width(2)))?;
+        }
+
+        super::plot::present(&root, path)
+    }
+}
+
+impl<
+        'a,
+        T: Clone + Send + Sync + 'static,
+        R: Send + Debug + PartialEq + 'static,
+        M: Threading,
+    > Bench<'a, T, R, M>
+{
+    /// Times the functions named `a` and `b` `samples` times each at the
+    /// single input size `size`, for a focused comparison of their
+    /// distributions rather than of their benchmark values.
+    ///
+    /// The calls of the two functions alternate in rounds, so that slow
+    /// drifts in the state of the machine affect both alike. The cache mode,
+    /// prefaulting, timer, and load pause of the benchmark apply, but the
+    /// timings are not recorded in [`Bench::data`], and the functions need
+    /// not be benchmarked at `size` otherwise.
+    ///
+    /// ```
+    /// use benchplot::{BenchBuilder, BenchFnArg};
+    ///
+    /// let argfunc: BenchFnArg<Vec<u32>> = Box::new(|n| (0..n as u32).collect());
+    /// let bench = BenchBuilder::new(Vec::new(), argfunc, vec![1000])
+    ///     .add_function(|v: Vec<u32>| v.iter().sum::<u32>(), "Iterator")
+    ///     .add_function(
+    ///         |v: Vec<u32>| {
+    ///             let mut sum = 0;
+    ///             for x in v {
+    ///                 sum += x;
+    ///             }
+    ///             sum
+    ///         },
+    ///         "Loop",
+    ///     )
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let comparison =
+    ///     bench.compare_at("Iterator", "Loop", 1000, 200).unwrap();
+    /// println!("{}", comparison.summary());
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `samples` is zero.
+    pub fn compare_at(
+        &self,
+        a: &str,
+        b: &str,
+        size: usize,
+        samples: usize,
+    ) -> Result<AbComparison, RunError> {
+        assert!(samples > 0, "an A/B comparison needs at least one sample");
+        let index = |name: &str| {
+            self.functions
+                .iter()
+                .position(|&(_, other)| other == name)
+                .ok_or_else(|| RunError::UnknownFunction(name.to_string()))
+        };
+        let indices = [index(a)?, index(b)?];
+        let arg = self.argument(size)?;
+
+        let mut times =
+            [Vec::with_capacity(samples), Vec::with_capacity(samples)];
+        for round in 0..ROUNDS {
+            let repetitions =
+                samples * (round + 1) / ROUNDS - samples * round / ROUNDS;
+            if repetitions == 0 {
+                continue;
+            }
+            let sampling = super::Sampling {
+                repetitions,
+                max_repetitions: repetitions,
+                cv_threshold: None,
+                target_precision: None,
+                strict: false,
+                retain: false,
+                ..self.sampling()
+            };
+            for (times, &func_idx) in times.iter_mut().zip(&indices) {
+                self.pause_while_busy();
+                let (_, nanos, _, _, _) = Self::time_function_multiple_times(
+                    &self.functions[func_idx].0,
+                    size,
+                    arg.clone(),
+                    sampling,
+                    self.prefault.as_ref(),
+                    &*self.timer,
+                    &[],
+                );
+                times.extend(aggregation::to_secs(&nanos));
+            }
+        }
+
+        Ok(AbComparison {
+            size,
+            names: [a.to_string(), b.to_string()],
+            samples: times,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BenchBuilder, BenchFnArg};
+
+    #[test]
+    fn test_statistics() {
+        let comparison = AbComparison {
+            size: 1000,
+            names: ["Merge Sort".to_string(), "Quick Sort".to_string()],
+            samples: [vec![1.0, 2.0, 3.0], vec![2.0, 4.0, 5.0, 6.0]],
+        };
+        assert_eq!(comparison.medians(), [2.0, 4.5]);
+        assert_eq!(comparison.speedup(), 2.25);
+        // A is faster in 10 of the 12 pairs and slower in 1.
+        assert!((comparison.effect_size() - 0.75).abs() < 1e-12);
+        assert!(comparison.p_value() < 0.2);
+        assert!(comparison
+            .summary()
+            .starts_with("n=1000: Merge Sort is 2.25x faster than Quick Sort"));
+
+        let swapped = AbComparison {
+            names: [comparison.names[1].clone(), comparison.names[0].clone()],
+            samples: [
+                comparison.samples[1].clone(),
+                comparison.samples[0].clone(),
+            ],
+            ..comparison.clone()
+        };
+        assert!((swapped.effect_size() + 0.75).abs() < 1e-12);
+        assert_eq!(swapped.summary(), comparison.summary());
+    }
+
+    #[test]
+    fn test_compare_at() {
+        let argfunc: BenchFnArg<usize> = Box::new(|x| x);
+        let bench = BenchBuilder::new(Vec::new(), argfunc, vec![10])
+            .add_function(|x| x, "Identity")
+            .add_function(|x| (0..x).sum::<usize>(), "Sum")
+            .build()
+            .unwrap();
+
+        let comparison = bench.compare_at("Identity", "Sum", 1000, 25).unwrap();
+        assert_eq!(comparison.size, 1000);
+        assert_eq!(comparison.samples[0].len(), 25);
+        assert_eq!(comparison.samples[1].len(), 25);
+        assert!(bench.data().is_empty());
+        assert!(matches!(
+            bench.compare_at("Identity", "Product", 1000, 25),
+            Err(RunError::UnknownFunction(name)) if name == "Product"
+        ));
+
+        #[cfg(feature = "plot")]
+        {
+            let dir = tempfile::tempdir().unwrap();
+            let path = dir.path().join("ab.svg");
+            comparison.plot(&path).unwrap();
+            let svg = std::fs::read_to_string(&path).unwrap();
+            assert!(svg.contains("Identity") && svg.contains("Sum"));
+            assert!(comparison.plot(dir.path().join("ab.pdf")).is_err());
+        }
+    }
+}
@@ -0,0 +1,220 @@
+/*
+Copyright 2024 Owain Davies
+SPDX-License-Identifier: Apache-2.0 OR MIT
+*/
+
+use std::time::Duration;
+
+/// How the timings of the repetitions of an `(input size, function)` pair are
+/// combined into the single value that is plotted.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Aggregation {
+    /// The arithmetic mean.
+    #[default]
+    Mean,
+
+    /// The median, which is robust to occasional outliers such as timings
+    /// interrupted by the operating system.
+    Median,
+
+    /// The geometric mean, suited to quantities that are ratios.
+    ///
+    /// It is zero if any timing is zero, e.g., with a coarse timer.
+    GeometricMean,
+
+    /// The harmonic mean, suited to quantities that are rates, such as
+    /// throughputs.
+    ///
+    /// It is zero if any timing is zero, e.g., with a coarse timer.
+    HarmonicMean,
+
+    /// The arithmetic mean of the timings left after dropping the given
+    /// fraction of the lowest timings and the same fraction of the highest
+    /// ones, e.g., `TrimmedMean(0.1)` drops the bottom and top 10%.
+    ///
+    /// This is a simple robust alternative to the median. The fraction must
+    /// be at least 0 and less than 0.5.
+    TrimmedMean(f64),
+}
+
+impl Aggregation {
+    /// Returns `true` if the parameter of the aggregation, if any, is valid.
+    pub(crate) fn is_valid(self) -> bool {
+        match self {
+            Aggregation::TrimmedMean(fraction) => {
+                (0.0..0.5).contains(&fraction)
+            }
+            _ => true,
+        }
+    }
+
+    /// Combines `times` into a single value, or `NaN` if `times` is empty.
+    ///
+    /// This is only for metric values, which are not durations. Timings are
+    /// kept in integer nanoseconds and combined with
+    /// [`aggregate_nanos`](Self::aggregate_nanos) instead, which converts
+    /// them to seconds only for the aggregations that need no exact sums.
+    pub(crate) fn aggregate(self, times: &[f64]) -> f64 {
+        if times.is_empty() {
+            return f64::NAN;
+        }
+        let n = times.len() as f64;
+        match self {
+            Aggregation::Mean => times.iter().sum::<f64>() / n,
+            Aggregation::Median => {
+                let sorted = sorted(times);
+                let mid = sorted.len() / 2;
+                if sorted.len() % 2 == 1 {
+                    sorted[mid]
+                } else {
+                    (sorted[mid - 1] + sorted[mid]) / 2.0
+                }
+            }
+            Aggregation::GeometricMean => {
+                (times.iter().map(|t| t.ln()).sum::<f64>() / n).exp()
+            }
+            Aggregation::HarmonicMean => {
+                n / times.iter().map(|t| t.recip()).sum::<f64>()
+            }
+            Aggregation::TrimmedMean(fraction) => {
+                let sorted = sorted(times);
+                let trimmed = (n * fraction) as usize;
+                let kept = &sorted[trimmed..sorted.len() - trimmed];
+                kept.iter().sum::<f64>() / kept.len() as f64
+            }
+        }
+    }
+
+    /// Combines `nanos`, timings in integer nanoseconds, into a single value
+    /// in seconds, or `NaN` if `nanos` is empty.
+    ///
+    /// Means are summed exactly on the integers, so that no precision is lost
+    /// when combining many tiny timings.
+    pub(crate) fn aggregate_nanos(self, nanos: &[u128]) -> f64 {
+        match self {
+            Aggregation::Mean => mean_nanos(nanos),
+            Aggregation::TrimmedMean(fraction) => {
+                let mut sorted = nanos.to_vec();
+                sorted.sort_unstable();
+                let trimmed = (nanos.len() as f64 * fraction) as usize;
+                mean_nanos(&sorted[trimmed..sorted.len() - trimmed])
+            }
+            _ => self.aggregate(&to_secs(nanos)),
+        }
+    }
+}
+
+/// Converts a timing in integer nanoseconds to seconds.
+pub(crate) fn nanos_to_secs(nanos: u128) -> f64 {
+    nanos as f64 / 1e9
+}
+
+/// Converts a timing in integer nanoseconds to a [`Duration`], exactly.
+pub(crate) fn nanos_to_duration(nanos: u128) -> Duration {
+    Duration::new(
+        (nanos / 1_000_000_000) as u64,
+        (nanos % 1_000_000_000) as u32,
+    )
+}
+
+/// Converts timings in integer nanoseconds to seconds, for the statistics of
+/// the repetitions.
+pub(crate) fn to_secs(nanos: &[u128]) -> Vec<f64> {
+    nanos.iter().map(|&nanos| nanos_to_secs(nanos)).collect()
+}
+
+/// Converts timings in seconds, e.g., read back from a results file, to the
+/// integer nanoseconds they were converted from with [`to_secs`].
+pub(crate) fn to_nanos(secs: &[f64]) -> Vec<u128> {
+    secs.iter()
+        .map(|&secs| (secs * 1e9).round() as u128)
+        .collect()
+}
+
+/// Returns the arithmetic mean of `nanos` in seconds, or `NaN` if `nanos` is
+/// empty.
+fn mean_nanos(nanos: &[u128]) -> f64 {
+    if nanos.is_empty() {
+        return f64::NAN;
+    }
+    let n = nanos.len() as u128;
+    let sum: u128 = nanos.iter().sum();
+    ((sum / n) as f64 + (sum % n) as f64 / n as f64) / 1e9
+}
+
+/// Returns a sorted copy of `times`.
+fn sorted(times: &[f64]) -> Vec<f64> {
+    let mut sorted = times.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    sorted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aggregate() {
+        let times = [1.0, 2.0, 4.0, 8.0];
+        assert_eq!(Aggregation::Mean.aggregate(&times), 3.75);
+        assert_eq!(Aggregation::Median.aggregate(&times), 3.0);
+        assert_eq!(Aggregation::Median.aggregate(&times[..3]), 2.0);
+        assert!(
+            (Aggregation::GeometricMean.aggregate(&times) - 8f64.sqrt()).abs()
+                < 1e-12
+        );
+        assert_eq!(Aggregation::HarmonicMean.aggregate(&times), 32.0 / 15.0);
+
+        assert_eq!(Aggregation::GeometricMean.aggregate(&[0.0, 1.0]), 0.0);
+        assert_eq!(Aggregation::HarmonicMean.aggregate(&[0.0, 1.0]), 0.0);
+        assert!(Aggregation::Mean.aggregate(&[]).is_nan());
+    }
+
+    #[test]
+    fn test_aggregate_nanos() {
+        let nanos = [1_000, 2_000, 4_000, 8_000];
+        assert_eq!(Aggregation::Mean.aggregate_nanos(&nanos), 3.75e-6);
+        assert_eq!(Aggregation::Median.aggregate_nanos(&nanos), 3e-6);
+        assert_eq!(
+            Aggregation::TrimmedMean(0.25).aggregate_nanos(&nanos),
+            3e-6
+        );
+        assert!(Aggregation::Mean.aggregate_nanos(&[]).is_nan());
+
+        // Summing the timings as seconds would lose the odd nanoseconds.
+        let nanos = vec![(1 << 60) + 1; 1000];
+        assert_eq!(
+            Aggregation::Mean.aggregate_nanos(&nanos),
+            nanos_to_secs((1 << 60) + 1)
+        );
+        assert_eq!(Aggregation::Mean.aggregate_nanos(&[1, 2]), 1.5e-9);
+    }
+
+    #[test]
+    fn test_to_nanos() {
+        let nanos = [0, 1, 999, 1_000_000_007, (1 << 52) + 1];
+        assert_eq!(to_nanos(&to_secs(&nanos)), nanos);
+
+        // Timings read back from their seconds aggregate as the originals.
+        let nanos = [1, 2, 2, 7];
+        for aggregation in [Aggregation::Mean, Aggregation::Median] {
+            assert_eq!(
+                aggregation.aggregate_nanos(&to_nanos(&to_secs(&nanos))),
+                aggregation.aggregate_nanos(&nanos)
+            );
+        }
+    }
+
+    #[test]
+    fn test_trimmed_mean() {
+        let times = [100.0, 2.0, 1.0, 3.0, 0.0, 4.0, 5.0, 6.0, 7.0, 8.0];
+        assert_eq!(Aggregation::TrimmedMean(0.1).aggregate(&times), 4.5);
+        assert_eq!(Aggregation::TrimmedMean(0.0).aggregate(&times), 13.6);
+        assert_eq!(Aggregation::TrimmedMean(0.49).aggregate(&[1.0, 3.0]), 2.0);
+
+        assert!(Aggregation::TrimmedMean(0.25).is_valid());
+        assert!(!Aggregation::TrimmedMean(0.5).is_valid());
+        assert!(!Aggregation::TrimmedMean(-0.1).is_valid());
+        assert!(!Aggregation::TrimmedMean(f64::NAN).is_valid());
+    }
+}
@@ -0,0 +1,823 @@
+/*
+Copyright 2024 Owain Davies
+SPDX-License-Identifier: Apache-2.0 OR MIT
+*/
+
+use super::isolation;
+use super::json::{self, Value};
+use crate::{Bench, BenchData, Threading, TimerCalibration};
+#[cfg(feature = "plot")]
+use crate::{
+    BenchBuilder, BenchFn, BenchFnNamed, PlotBuilder, PlotBuilderError,
+};
+use std::io::{self, ErrorKind};
+use std::path::Path;
+use std::time::Duration;
+
+/// Saved benchmark results, used to compare a fresh run against an earlier
+/// one.
+///
+/// A baseline may carry a label identifying the run, such as a release
+/// version, which is used to name its series when it is overlaid on a plot.
+///
+/// Baselines are saved as tab-separated values: a header line with `n`
+/// followed by the function names, and a line per input size with the size
+/// followed by the average timing in seconds of each function. If the
+/// baseline is labeled, the header is preceded by a line with `label` and the
+/// label, and if it records the calibration of the timer, by a line with
+/// `timer`, the overhead and the granularity in seconds.
+///
+/// ```no_run
+/// # use benchplot::{Baseline, Bench};
+/// # fn example(bench: &mut Bench<usize, usize>) -> std::io::Result<()> {
+/// // Before a change:
+/// bench.run().baseline().save("baseline.tsv")?;
+///
+/// // After the change:
+/// let baseline = Baseline::load("baseline.tsv")?;
+/// bench
+///     .run()
+///     .plot("comparison.svg")
+///     .overlay(&baseline)
+///     .build()
+///     .expect("Plotting failed");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "BaselineFields", try_from = "BaselineFields")
+)]
+pub struct Baseline {
+    label: Option<String>,
+    timer: Option<TimerCalibration>,
+    functions: Vec<String>,
+    data: BenchData,
+}
+
+/// Fields of a [`Baseline`] as (de)serialized, in the format of
+/// [`Baseline::to_json`], with a row of timings per input size.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct BaselineFields {
+    label: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    timer: Option<TimerCalibration>,
+    functions: Vec<String>,
+    results: Vec<BaselineRow>,
+}
+
+/// Timings of a [`Baseline`] at an input size, where missing timings are
+/// `null`.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct BaselineRow {
+    size: usize,
+    times: Vec<Option<f64>>,
+}
+
+#[cfg(feature = "serde")]
+impl From<Baseline> for BaselineFields {
+    fn from(baseline: Baseline) -> Self {
+        let results = baseline
+            .data
+            .iter()
+            .map(|(size, times)| BaselineRow {
+                size,
+                times: times
+                    .iter()
+                    .map(|&time| Some(time).filter(|time| time.is_finite()))
+                    .collect(),
+            })
+            .collect();
+        Self {
+            label: baseline.label,
+            timer: baseline.timer,
+            functions: baseline.functions,
+            results,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<BaselineFields> for Baseline {
+    type Error = &'static str;
+
+    fn try_from(fields: BaselineFields) -> Result<Self, Self::Error> {
+        let mut data = BenchData::new(fields.functions.len());
+        for row in fields.results {
+            if row.times.len() != fields.functions.len() {
+                return Err("baseline results must have a timing per function");
+            }
+            let times: Vec<f64> = row
+                .times
+                .iter()
+                .map(|time| time.unwrap_or(f64::NAN))
+                .collect();
+            data.push_row(row.size, &times);
+        }
+        Ok(Self {
+            label: fields.label,
+            timer: fields.timer,
+            functions: fields.functions,
+            data,
+        })
+    }
+}
+
+impl Baseline {
+    /// Creates a baseline from function names and timings, where the columns
+    /// of `data` are in the order of `functions`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data` does not have a column per function.
+    pub fn new(functions: Vec<String>, data: BenchData) -> Self {
+        assert_eq!(
+            functions.len(),
+            data.num_functions(),
+            "baseline data must have a column per function"
+        );
+        Self {
+            label: None,
+            timer: None,
+            functions,
+            data,
+        }
+    }
+
+    /// Sets the label identifying the run, e.g., `"v0.3"`.
+    pub fn with_label(mut self, label: &str) -> Self {
+        self.label = Some(label.to_string());
+        self
+    }
+
+    /// Returns the label identifying the run, if any.
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    /// Sets the calibration of the timer of the run.
+    pub fn with_timer_calibration(mut self, timer: TimerCalibration) -> Self {
+        self.timer = Some(timer);
+        self
+    }
+
+    /// Returns the calibration of the timer of the run, if recorded.
+    pub fn timer_calibration(&self) -> Option<TimerCalibration> {
+        self.timer
+    }
+
+    /// Returns the names of the functions.
+    pub fn functions(&self) -> &[String] {
+        &self.functions
+    }
+
+    /// Returns the timings in seconds, with a row per input size and a column
+    /// per function.
+    pub fn data(&self) -> &BenchData {
+        &self.data
+    }
+
+    /// Renders the baseline as a JSON object, e.g., to send it to a
+    /// dashboard:
+    ///
+    /// ```text
+    /// {"label":"v0.3","functions":["Merge Sort"],"results":[{"size":1024,"times":[0.0000151]}]}
+    /// ```
+    ///
+    /// The label is `null` if the baseline is not labeled, and so is any
+    /// timing that is not finite. The calibration of the timer, if recorded,
+    /// follows the label as `"timer"`, with the overhead and the granularity
+    /// as objects of whole seconds and nanoseconds, e.g.,
+    /// `{"overhead":{"secs":0,"nanos":25},"granularity":{"secs":0,"nanos":1}}`.
+    ///
+    /// This is also the representation of baselines with the `serde`
+    /// feature.
+    pub fn to_json(&self) -> String {
+        let label = self
+            .label
+            .as_deref()
+            .map_or("null".to_string(), json::string);
+        let timer = self.timer.map_or(String::new(), |timer| {
+            format!(
+                ",\"timer\":{{\"overhead\":{},\"granularity\":{}}}",
+                duration_json(timer.overhead),
+                duration_json(timer.granularity)
+            )
+        });
+        let functions: Vec<String> = self
+            .functions
+            .iter()
+            .map(|name| json::string(name))
+            .collect();
+        let results: Vec<String> = self
+            .data
+            .iter()
+            .map(|(size, times)| {
+                let times: Vec<String> =
+                    times.iter().map(|&time| json::number(time)).collect();
+                format!("{{\"size\":{},\"times\":[{}]}}", size, times.join(","))
+            })
+            .collect();
+        format!(
+            "{{\"label\":{}{},\"functions\":[{}],\"results\":[{}]}}",
+            label,
+            timer,
+            functions.join(","),
+            results.join(",")
+        )
+    }
+
+    /// Saves the baseline to a file.
+    ///
+    /// Nothing is saved from a child process of an isolated benchmark, so
+    /// that the file holds the results of the parent.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        if isolation::child_cell().is_some() {
+            return Ok(());
+        }
+        std::fs::write(path, self.to_tsv())
+    }
+
+    /// Loads a baseline previously saved with [`Baseline::save`], or written
+    /// to a file as rendered by [`Baseline::to_json`].
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::read(std::fs::File::open(path)?)
+    }
+
+    /// Reads a baseline in either format of [`Baseline::load`] from
+    /// `reader`, e.g., from standard input.
+    pub fn read<R: io::Read>(mut reader: R) -> io::Result<Self> {
+        let mut contents = String::new();
+        reader.read_to_string(&mut contents)?;
+        if contents.trim_start().starts_with('{') {
+            Self::from_json(&contents)
+        } else {
+            Self::from_tsv(&contents)
+        }
+    }
+
+    /// Parses a baseline rendered by [`Baseline::to_json`].
+    pub fn from_json(json: &str) -> io::Result<Self> {
+        Self::parse_json(json.trim()).ok_or_else(|| {
+            io::Error::new(ErrorKind::InvalidData, "invalid baseline JSON")
+        })
+    }
+
+    fn parse_json(json: &str) -> Option<Self> {
+        let json = json::parse(json)?;
+        let label = match json.get("label") {
+            None | Some(Value::Null) => None,
+            Some(label) => Some(label.as_str()?.to_string()),
+        };
+        let timer = match json.get("timer") {
+            None | Some(Value::Null) => None,
+            Some(timer) => Some(TimerCalibration {
+                overhead: parse_duration(timer.get("overhead")?)?,
+                granularity: parse_duration(timer.get("granularity")?)?,
+            }),
+        };
+        let functions = json
+            .get("functions")?
+            .as_array()?
+            .iter()
+            .map(|name| name.as_str().map(str::to_string))
+            .collect::<Option<Vec<_>>>()?;
+
+        let mut data = BenchData::new(functions.len());
+        for row in json.get("results")?.as_array()? {
+            let times = row
+                .get("times")?
+                .as_array()?
+                .iter()
+                .map(Value::as_f64)
+                .collect::<Option<Vec<_>>>()?;
+            if times.len() != functions.len() {
+                return None;
+            }
+            data.push_row(row.get("size")?.as_integer()?, &times);
+        }
+
+        Some(Self {
+            label,
+            timer,
+            functions,
+            data,
+        })
+    }
+
+    /// Merges these results with `other`, e.g., results of the same
+    /// benchmarks measured for different input sizes in another session.
+    ///
+    /// The merged results have the functions of both, in the order of these
+    /// results followed by the functions only in `other`, and the input
+    /// sizes of both, in increasing order. A timing measured in both is taken
+    /// from `other`, unless it is missing there. Timings measured in neither
+    /// are missing, i.e., `NaN`. The label and timer calibration are those of
+    /// these results, if any, and those of `other` otherwise.
+    pub fn merge(&self, other: &Baseline) -> Baseline {
+        let mut functions = self.functions.clone();
+        for name in &other.functions {
+            if !functions.contains(name) {
+                functions.push(name.clone());
+            }
+        }
+        let mut sizes: Vec<usize> = self
+            .data
+            .sizes()
+            .iter()
+            .chain(other.data.sizes())
+            .copied()
+            .collect();
+        sizes.sort_unstable();
+        sizes.dedup();
+
+        let time = |results: &Baseline, name: &String, size: usize| {
+            let fn_idx = results.functions.iter().position(|n| n == name)?;
+            let size_idx =
+                results.data.sizes().iter().position(|&s| s == size)?;
+            Some(results.data.time(fn_idx, size_idx))
+                .filter(|time| !time.is_nan())
+        };
+        let mut data = BenchData::new(functions.len());
+        for size in sizes {
+            let row: Vec<f64> = functions
+                .iter()
+                .map(|name| {
+                    time(other, name, size)
+                        .or_else(|| time(self, name, size))
+                        .unwrap_or(f64::NAN)
+                })
+                .collect();
+            data.push_row(size, &row);
+        }
+
+        Baseline {
+            label: self.label.clone().or_else(|| other.label.clone()),
+            timer: self.timer.or(other.timer),
+            functions,
+            data,
+        }
+    }
+
+    /// Plots the results of the baseline, like the plot of the run they were
+    /// taken from, with the plot configured by `configure`, e.g., to compare
+    /// them with an older baseline:
+    ///
+    /// ```no_run
+    /// # use benchplot::Baseline;
+    /// # fn example() -> std::io::Result<()> {
+    /// let old = Baseline::load("old.json")?;
+    /// let new = Baseline::load("new.json")?;
+    /// new.plot("comparison.svg", |plot| plot.overlay(&old))
+    ///     .expect("Plotting failed");
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "plot")]
+    pub fn plot<P, F>(
+        &self,
+        filename: P,
+        configure: F,
+    ) -> Result<(), PlotBuilderError>
+    where
+        P: AsRef<Path>,
+        F: for<'b> FnOnce(PlotBuilder<'b, (), ()>) -> PlotBuilder<'b, (), ()>,
+    {
+        let bench = self.bench().ok_or(PlotBuilderError::NoData)?;
+        configure(bench.plot(filename)).build()
+    }
+
+    /// Plots the results of the baseline like [`Baseline::plot`], but returns
+    /// the plot as an SVG document instead of saving it, as with
+    /// [`PlotBuilder::build_svg`].
+    #[cfg(feature = "plot")]
+    pub fn plot_svg<F>(&self, configure: F) -> Result<String, PlotBuilderError>
+    where
+        F: for<'b> FnOnce(PlotBuilder<'b, (), ()>) -> PlotBuilder<'b, (), ()>,
+    {
+        let bench = self.bench().ok_or(PlotBuilderError::NoData)?;
+        configure(bench.plot("")).build_svg()
+    }
+
+    /// Returns a benchmark holding the results of the baseline, which can be
+    /// plotted but not run, or `None` if there are no functions or sizes.
+    #[cfg(feature = "plot")]
+    fn bench(&self) -> Option<Bench<'_, (), ()>> {
+        let functions: Vec<BenchFnNamed<(), ()>> = self
+            .functions
+            .iter()
+            .map(|name| (Box::new(|()| ()) as BenchFn<(), ()>, name.as_str()))
+            .collect();
+        let mut bench = BenchBuilder::new(
+            functions,
+            Box::new(|_| ()),
+            self.data.sizes().to_vec(),
+        )
+        .build()
+        .ok()?;
+        bench.data = self.data.clone();
+        bench.label = self.label.clone();
+        bench.timer_calibration = self.timer;
+        Some(bench)
+    }
+
+    fn to_tsv(&self) -> String {
+        let mut tsv = String::new();
+        if let Some(label) = &self.label {
+            tsv.push_str("label\t");
+            tsv.push_str(&sanitize(label));
+            tsv.push('\n');
+        }
+        if let Some(timer) = &self.timer {
+            tsv.push_str(&format!(
+                "timer\t{}\t{}\n",
+                timer.overhead.as_secs_f64(),
+                timer.granularity.as_secs_f64()
+            ));
+        }
+        tsv.push('n');
+        for name in &self.functions {
+            tsv.push('\t');
+            tsv.push_str(&sanitize(name));
+        }
+        tsv.push('\n');
+        self.data.write_tsv(&mut tsv);
+        tsv
+    }
+
+    fn from_tsv(tsv: &str) -> io::Result<Self> {
+        let invalid = |msg: &str| io::Error::new(ErrorKind::InvalidData, msg);
+
+        let mut lines = tsv.lines().peekable();
+        let label = lines
+            .next_if(|line| line.starts_with("label\t"))
+            .map(|line| line["label\t".len()..].to_string());
+        let timer = lines
+            .next_if(|line| line.starts_with("timer\t"))
+            .map(|line| {
+                let mut fields = line.split('\t').skip(1).map(|field| {
+                    field
+                        .parse()
+                        .ok()
+                        .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+                });
+                match (fields.next().flatten(), fields.next().flatten()) {
+                    (Some(overhead), Some(granularity)) => {
+                        Ok(TimerCalibration {
+                            overhead,
+                            granularity,
+                        })
+                    }
+                    _ => Err(invalid("invalid timer calibration")),
+                }
+            })
+            .transpose()?;
+        let header = lines.next().ok_or_else(|| invalid("empty baseline"))?;
+        let mut columns = header.split('\t');
+        if columns.next() != Some("n") {
+            return Err(invalid("baseline header must start with \"n\""));
+        }
+        let functions: Vec<String> = columns.map(String::from).collect();
+        let data = BenchData::parse_tsv(lines, functions.len())?;
+        Ok(Self {
+            label,
+            timer,
+            functions,
+            data,
+        })
+    }
+}
+
+/// Replaces the characters separating fields and lines of a saved baseline.
+fn sanitize(field: &str) -> String {
+    field.replace(['\t', '\n'], " ")
+}
+
+/// Renders `duration` as a JSON object of whole seconds and nanoseconds, like
+/// `serde` does.
+fn duration_json(duration: Duration) -> String {
+    format!(
+        "{{\"secs\":{},\"nanos\":{}}}",
+        duration.as_secs(),
+        duration.subsec_nanos()
+    )
+}
+
+/// Parses a duration rendered by [`duration_json`].
+fn parse_duration(json: &Value) -> Option<Duration> {
+    let secs = json.get("secs")?.as_integer()?;
+    let nanos: u32 = json.get("nanos")?.as_integer()?;
+    (nanos < 1_000_000_000).then(|| Duration::new(secs, nanos))
+}
+
+impl<T, R, M: Threading> Bench<'_, T, R, M> {
+    /// Returns the results of the last run as a [`Baseline`], to be saved and
+    /// compared against later runs.
+    ///
+    /// The baseline is labeled with the label of the benchmark, if any.
+    pub fn baseline(&self) -> Baseline {
+        Baseline {
+            label: self.label.clone(),
+            timer: self.timer_calibration,
+            functions: self
+                .function_names()
+                .into_iter()
+                .map(String::from)
+                .collect(),
+            data: self.data.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("baseline.tsv");
+
+        let baseline = Baseline::new(
+            vec!["Merge Sort".to_string(), "Bubble Sort".to_string()],
+            BenchData::from_rows(
+                2,
+                [(10, vec![1e-6, 2.5e-6]), (100, vec![1.5e-5, 0.25])],
+            )
+            .unwrap(),
+        );
+        baseline.save(&path).unwrap();
+
+        assert_eq!(Baseline::load(&path).unwrap(), baseline);
+
+        let labeled = baseline.with_label("v0.3");
+        labeled.save(&path).unwrap();
+
+        let loaded = Baseline::load(&path).unwrap();
+        assert_eq!(loaded.label(), Some("v0.3"));
+        assert_eq!(loaded, labeled);
+        assert_eq!(
+            Baseline::read(labeled.to_tsv().as_bytes()).unwrap(),
+            labeled
+        );
+    }
+
+    #[test]
+    fn test_to_json() {
+        let baseline = Baseline::new(
+            vec!["Merge \"Sort\"".to_string(), "Bubble Sort".to_string()],
+            BenchData::from_rows(
+                2,
+                [(10, vec![1e-6, 2.5]), (100, vec![1.5e-5, f64::NAN])],
+            )
+            .unwrap(),
+        );
+
+        assert_eq!(
+            baseline.to_json(),
+            r#"{"label":null,"functions":["Merge \"Sort\"","Bubble Sort"],"#
+                .to_string()
+                + r#""results":[{"size":10,"times":[0.000001,2.5]},"#
+                + r#"{"size":100,"times":[0.000015,null]}]}"#
+        );
+        assert!(baseline
+            .with_label("v0.3")
+            .to_json()
+            .starts_with(r#"{"label":"v0.3","#));
+    }
+
+    #[test]
+    fn test_timer_calibration_round_trip() {
+        let calibration = TimerCalibration {
+            overhead: Duration::from_nanos(25),
+            granularity: Duration::from_nanos(1),
+        };
+        let baseline = Baseline::new(
+            vec!["A".to_string()],
+            BenchData::from_rows(1, [(10, vec![1e-6])]).unwrap(),
+        )
+        .with_label("v0.3")
+        .with_timer_calibration(calibration);
+
+        let tsv = baseline.to_tsv();
+        assert!(
+            tsv.starts_with("label\tv0.3\ntimer\t0.000000025\t0.000000001\n")
+        );
+        let loaded = Baseline::from_tsv(&tsv).unwrap();
+        assert_eq!(loaded.timer_calibration(), Some(calibration));
+        assert_eq!(loaded, baseline);
+
+        assert!(Baseline::from_tsv("timer\tx\t1\nn\tA\n").is_err());
+
+        let json = baseline.to_json();
+        assert!(json.starts_with(
+            r#"{"label":"v0.3","timer":{"overhead":{"secs":0,"nanos":25},"#
+        ));
+        assert_eq!(Baseline::from_json(&json).unwrap(), baseline);
+    }
+
+    #[test]
+    fn test_from_json_formatting() {
+        let json = r#"
+            {
+                "results": [
+                    { "times": [ 1e-6, 2.5 ], "size": 10 },
+                    { "size": 100, "times": [ 1.5E-5, 3 ] }
+                ],
+                "functions": [ "A", "B\u00e9\/" ],
+                "timer": null,
+                "label": "v0.3"
+            }
+        "#;
+        let expected = Baseline::new(
+            vec!["A".to_string(), "Bé/".to_string()],
+            BenchData::from_rows(
+                2,
+                [(10, vec![1e-6, 2.5]), (100, vec![1.5e-5, 3.0])],
+            )
+            .unwrap(),
+        )
+        .with_label("v0.3");
+        assert_eq!(Baseline::from_json(json).unwrap(), expected);
+        assert_eq!(Baseline::read(json.as_bytes()).unwrap(), expected);
+
+        // The label may be omitted, as with `serde`.
+        assert_eq!(
+            Baseline::from_json(r#"{"functions":[],"results":[]}"#)
+                .unwrap()
+                .label(),
+            None
+        );
+        assert!(
+            Baseline::from_json(r#"{"functions":[],"results":[]} x"#).is_err()
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_matches_json() {
+        let baseline = Baseline::new(
+            vec!["Merge \"Sort\"".to_string(), "Bubble Sort".to_string()],
+            BenchData::from_rows(
+                2,
+                [(10, vec![1e-6, 2.5]), (100, vec![1.5e-5, f64::NAN])],
+            )
+            .unwrap(),
+        )
+        .with_label("v0.3")
+        .with_timer_calibration(TimerCalibration {
+            overhead: Duration::from_nanos(25),
+            granularity: Duration::from_nanos(1),
+        });
+
+        for baseline in [
+            baseline.clone(),
+            Baseline {
+                timer: None,
+                ..baseline
+            },
+        ] {
+            let serialized = serde_json::to_string(&baseline).unwrap();
+            assert_eq!(
+                serde_json::from_str::<serde_json::Value>(&serialized).unwrap(),
+                serde_json::from_str::<serde_json::Value>(&baseline.to_json())
+                    .unwrap()
+            );
+            let from_serde = Baseline::from_json(&serialized).unwrap();
+            assert_eq!(from_serde.to_json(), baseline.to_json());
+            let from_json: Baseline =
+                serde_json::from_str(&baseline.to_json()).unwrap();
+            assert_eq!(from_json.to_json(), baseline.to_json());
+        }
+
+        assert!(serde_json::from_str::<Baseline>(
+            r#"{"label":null,"functions":["A"],"results":[{"size":1,"times":[1,2]}]}"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("baseline.json");
+
+        let baseline = Baseline::new(
+            vec!["Merge \"Sort\"".to_string(), "Bubble Sort".to_string()],
+            BenchData::from_rows(
+                2,
+                [(10, vec![1e-6, 2.5]), (100, vec![1.5e-5, f64::NAN])],
+            )
+            .unwrap(),
+        )
+        .with_label("v0.3");
+        std::fs::write(&path, baseline.to_json() + "\n").unwrap();
+
+        let loaded = Baseline::load(&path).unwrap();
+        assert_eq!(loaded.label(), Some("v0.3"));
+        assert_eq!(loaded.functions(), baseline.functions());
+        assert_eq!(loaded.data().sizes(), [10, 100]);
+        assert_eq!(loaded.data().row(0), [1e-6, 2.5]);
+        assert!(loaded.data().time(1, 1).is_nan());
+
+        let unlabeled = Baseline::new(
+            vec!["A".to_string()],
+            BenchData::from_rows(1, [(10, vec![1e-6])]).unwrap(),
+        );
+        assert_eq!(
+            Baseline::from_json(&unlabeled.to_json()).unwrap(),
+            unlabeled
+        );
+        assert_eq!(
+            Baseline::read(unlabeled.to_json().as_bytes()).unwrap(),
+            unlabeled
+        );
+
+        assert!(Baseline::from_json("{}").is_err());
+        assert!(Baseline::from_json(
+            r#"{"label":null,"functions":["A"],"results":[{"size":1,"times":[1,2]}]}"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_merge() {
+        let a = Baseline::new(
+            vec!["A".to_string(), "B".to_string()],
+            BenchData::from_rows(
+                2,
+                [(10, vec![1.0, 2.0]), (1000, vec![3.0, f64::NAN])],
+            )
+            .unwrap(),
+        )
+        .with_label("laptop");
+        let b = Baseline::new(
+            vec!["C".to_string(), "B".to_string()],
+            BenchData::from_rows(
+                2,
+                [(100, vec![5.0, 6.0]), (1000, vec![7.0, 8.0])],
+            )
+            .unwrap(),
+        );
+
+        let merged = a.merge(&b);
+        assert_eq!(merged.label(), Some("laptop"));
+        assert_eq!(merged.functions(), ["A", "B", "C"]);
+        assert_eq!(merged.data().sizes(), [10, 100, 1000]);
+        let row = |idx| {
+            merged
+                .data()
+                .row(idx)
+                .iter()
+                .map(|t| t.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(row(0), ["1", "2", "NaN"]);
+        assert_eq!(row(1), ["NaN", "6", "5"]);
+        assert_eq!(row(2), ["3", "8", "7"]);
+
+        // Timings missing from the later results are kept.
+        assert_eq!(b.merge(&a).data().row(2), [7.0, 8.0, 3.0]);
+    }
+
+    #[cfg(feature = "plot")]
+    #[test]
+    fn test_plot() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("baseline.svg");
+        let old = Baseline::new(
+            vec!["Merge Sort".to_string()],
+            BenchData::from_rows(1, [(10, vec![1e-6]), (100, vec![1e-5])])
+                .unwrap(),
+        )
+        .with_label("v0.3");
+        let new = Baseline::new(
+            vec!["Merge Sort".to_string()],
+            BenchData::from_rows(1, [(10, vec![2e-6]), (100, vec![3e-5])])
+                .unwrap(),
+        )
+        .with_label("v0.4");
+
+        new.plot(&path, |plot| plot.overlay(&old)).unwrap();
+        let svg = std::fs::read_to_string(&path).unwrap();
+        assert!(svg.contains("Merge Sort (v0.4)"));
+        assert!(svg.contains("Merge Sort (v0.3)"));
+
+        let empty = Baseline::new(vec![], BenchData::new(0));
+        assert!(matches!(
+            empty.plot(&path, |plot| plot),
+            Err(PlotBuilderError::NoData)
+        ));
+    }
+
+    #[test]
+    fn test_load_invalid() {
+        assert!(Baseline::from_tsv("").is_err());
+        assert!(Baseline::from_tsv("size\tA\n").is_err());
+        assert!(Baseline::from_tsv("n\tA\nx\t1.0\n").is_err());
+        assert!(Baseline::from_tsv("n\tA\n10\t1.0\t2.0\n").is_err());
+    }
+}
@@ -0,0 +1,127 @@
+/*
+Copyright 2024 Owain Davies
+SPDX-License-Identifier: Apache-2.0 OR MIT
+*/
+
+use super::Aggregation;
+use crate::util;
+
+/// Number of resamples drawn to estimate each confidence interval.
+const RESAMPLES: usize = 1000;
+
+/// A bootstrap confidence interval of the benchmark value of an
+/// `(input size, function)` pair, in seconds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ConfidenceInterval {
+    /// The lower bound of the interval.
+    pub lower: f64,
+
+    /// The upper bound of the interval.
+    pub upper: f64,
+}
+
+impl ConfidenceInterval {
+    /// Returns `true` if `value` lies within the interval, bounds included.
+    pub fn contains(&self, value: f64) -> bool {
+        self.lower <= value && value <= self.upper
+    }
+}
+
+/// Estimates a confidence interval at `level` (e.g., `0.95`) of the value
+/// `aggregation` gives for `nanos`, timings in integer nanoseconds, using the
+/// percentile bootstrap.
+///
+/// `nanos` is resampled with replacement and aggregated `RESAMPLES` times, and
+/// the interval spans the central `level` fraction of the resampled values, so
+/// that no assumption is made about the distribution of the timings. The
+/// resampling is seeded from the timings themselves, so the same timings
+/// always give the same interval.
+pub(crate) fn confidence_interval(
+    nanos: &[u128],
+    aggregation: Aggregation,
+    level: f64,
+) -> ConfidenceInterval {
+    if nanos.is_empty() {
+        return ConfidenceInterval {
+            lower: f64::NAN,
+            upper: f64::NAN,
+        };
+    }
+
+    let bytes: Vec<u8> = nanos.iter().flat_map(|t| t.to_le_bytes()).collect();
+    let mut rng = SplitMix64(util::fnv1a(&bytes));
+    let mut resample = vec![0; nanos.len()];
+    let mut values: Vec<f64> = (0..RESAMPLES)
+        .map(|_| {
+            for slot in resample.iter_mut() {
+                *slot = nanos[rng.below(nanos.len())];
+            }
+            aggregation.aggregate_nanos(&resample)
+        })
+        .collect();
+    values.sort_by(f64::total_cmp);
+
+    let tail = (1.0 - level) / 2.0;
+    let last = (RESAMPLES - 1) as f64;
+    ConfidenceInterval {
+        lower: values[(tail * last).round() as usize],
+        upper: values[((1.0 - tail) * last).round() as usize],
+    }
+}
+
+/// The SplitMix64 pseudorandom number generator, which is small and fast and
+/// more than random enough for resampling.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a number in `0..n`, for `n > 0`.
+    ///
+    /// The modulo bias is negligible for the number of repetitions of a
+    /// benchmark.
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_confidence_interval() {
+        let nanos: Vec<u128> = (1..=50).map(|t| t * 1_000).collect();
+        let mean = Aggregation::Mean.aggregate_nanos(&nanos);
+        let interval = confidence_interval(&nanos, Aggregation::Mean, 0.95);
+        assert!(interval.contains(mean));
+        assert!(interval.lower > 20e-6 && interval.upper < 31e-6);
+        assert_eq!(
+            interval,
+            confidence_interval(&nanos, Aggregation::Mean, 0.95)
+        );
+
+        let narrower = confidence_interval(&nanos, Aggregation::Mean, 0.5);
+        assert!(narrower.lower > interval.lower);
+        assert!(narrower.upper < interval.upper);
+
+        let constant = confidence_interval(&[2; 5], Aggregation::Median, 0.9);
+        assert_eq!(
+            constant,
+            ConfidenceInterval {
+                lower: 2e-9,
+                upper: 2e-9
+            }
+        );
+        assert!(confidence_interval(&[], Aggregation::Mean, 0.9)
+            .lower
+            .is_nan());
+    }
+}
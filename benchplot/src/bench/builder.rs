@@ -3,8 +3,23 @@ Copyright 2024 Owain Davies
 SPDX-License-Identifier: Apache-2.0 OR MIT
 */
 
-use crate::{Bench, BenchFnArg, BenchFnNamed};
+use crate::bench::load::LoadPause;
+use crate::bench::local;
+use crate::bench::metric::{Metric, MetricSource};
+use crate::bench::profile::ProfiledCell;
+use crate::bench::{
+    factory_from_fn, factory_from_fn_mut, factory_from_sized_fn, InputWriterFn,
+    PrefaultFn,
+};
+use crate::{
+    Aggregation, Bench, BenchData, BenchFnArg, BenchFnNamed, CacheMode,
+    FallibleBenchFnArg, InstantTimer, Local, LocalBenchFnArg,
+    LocalBenchFnNamed, Prefault, Priority, Profiler, Reporter, Shared,
+    Threading, Timer, Verbosity,
+};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 
 /// Error type for `BenchBuilder`.
 #[derive(Debug, PartialEq, thiserror::Error)]
@@ -13,6 +28,11 @@ pub enum BenchBuilderError {
     #[error("Repetitions must be greater than 0.")]
     ZeroRepetitions,
 
+    /// Indicates that the maximum number of repetitions is less than the
+    /// number of repetitions.
+    #[error("Maximum repetitions must not be less than repetitions.")]
+    MaxRepetitionsTooSmall,
+
     /// Indicates that the sizes vector is empty.
     #[error("The sizes vector must not be empty.")]
     NoSizes,
@@ -20,46 +40,375 @@ pub enum BenchBuilderError {
     /// Indicates that the functions vector is empty.
     #[error("The functions vector must not be empty.")]
     NoFunctions,
+
+    /// Indicates that the reference function name does not match the name of
+    /// any function in the functions vector.
+    #[error("No function named \"{0}\" to use as the reference.")]
+    UnknownReference(String),
+
+    /// Indicates that return values are to be compared while timing in child
+    /// processes, which cannot return values to the parent process.
+    #[error(
+        "Return values cannot be compared when timing in child processes."
+    )]
+    IsolatedAssertEqual,
+
+    /// Indicates that the name of a profiled function does not match the
+    /// name of any function in the functions vector.
+    #[error("No function named \"{0}\" to profile.")]
+    UnknownProfiledFunction(String),
+
+    /// Indicates that the input size of a profiled pair is not one of the
+    /// benchmarked sizes.
+    #[error("No input size {0} to profile.")]
+    UnknownProfiledSize(usize),
+
+    /// Indicates that the name of a function with a maximum input size does
+    /// not match the name of any function in the functions vector.
+    #[error("No function named \"{0}\" to set a maximum input size for.")]
+    UnknownCappedFunction(String),
+
+    /// Indicates that the name of a function with its own input sizes does
+    /// not match the name of any function in the functions vector.
+    #[error("No function named \"{0}\" to set input sizes for.")]
+    UnknownSizedFunction(String),
+
+    /// Indicates that the fraction of timings dropped by a trimmed mean is
+    /// not at least 0 and less than 0.5.
+    #[error("Trimmed fraction {0} must be at least 0 and less than 0.5.")]
+    InvalidTrimFraction(f64),
+
+    /// Indicates that the confidence level of the confidence intervals is not
+    /// strictly between 0 and 1.
+    #[error("Confidence level {0} must be greater than 0 and less than 1.")]
+    InvalidConfidenceLevel(f64),
+
+    /// Indicates that the target precision is not a positive number.
+    #[error("Target precision {0} must be greater than 0.")]
+    InvalidTargetPrecision(f64),
+
+    /// Indicates that a target precision is set without a maximum number of
+    /// repetitions, or with fewer than 2 repetitions to estimate it from.
+    #[error(
+        "A target precision requires max_repetitions and at least 2 \
+         repetitions."
+    )]
+    TargetPrecisionWithoutRepetitions,
+
+    /// Indicates that the maximum system load to measure under is not a
+    /// positive number.
+    #[error("Maximum system load {0} must be greater than 0.")]
+    InvalidMaxLoad(f64),
+
+    /// Indicates that several metrics have the same name.
+    #[error("More than one metric is named \"{0}\".")]
+    DuplicateMetric(String),
 }
 
 /// Builder for creating a `Bench` instance.
-pub struct BenchBuilder<'a, T, R> {
-    functions: Vec<BenchFnNamed<'a, T, R>>,
-    argfunc: BenchFnArg<T>,
+///
+/// Builders created with [`BenchBuilder::new_local`] are marked [`Local`],
+/// and the others [`Shared`].
+pub struct BenchBuilder<'a, T, R, M: Threading = Shared> {
+    functions: Vec<(M::Factory<T, R>, &'a str)>,
+    argfunc: M::Argfunc<T>,
     sizes: Vec<usize>,
     repetitions: usize,
+    max_repetitions: Option<usize>,
     parallel: bool,
+    isolated: bool,
+    cachegrind: bool,
     assert_equal: bool,
+    strict_equal: bool,
+    reference: Option<&'a str>,
+    mismatch_report: Option<PathBuf>,
+    mismatch_inputs: Option<(PathBuf, InputWriterFn<T>)>,
+    inputs: Option<(PathBuf, InputWriterFn<T>)>,
+    results_file: Option<PathBuf>,
+    live_plot: Option<PathBuf>,
+    results_database: Option<PathBuf>,
+    cv_threshold: Option<f64>,
+    target_precision: Option<f64>,
+    priority: Priority,
+    cache_mode: CacheMode,
+    aggregation: Aggregation,
+    confidence_level: Option<f64>,
+    load_pause: Option<LoadPause>,
+    keep_samples: bool,
+    print_verdict: bool,
+    catch_panics: bool,
+    verbosity: Verbosity,
+    max_sizes: Vec<(&'a str, usize)>,
+    function_sizes: Vec<(&'a str, Vec<usize>)>,
+    prefault: Option<PrefaultFn<T>>,
+    timer: Arc<dyn Timer>,
+    label: Option<String>,
+    reporters: Vec<Arc<dyn Reporter>>,
+    profiled: Vec<(&'a str, usize, Arc<dyn Profiler>)>,
+    metrics: Vec<Metric<R>>,
 }
 
-impl<'a, T, R> BenchBuilder<'a, T, R> {
+impl<'a, T: 'static, R: 'static> BenchBuilder<'a, T, R> {
     /// Creates a new `BenchBuilder` with required parameters.
     ///
     /// Mandatory parameters are required upfront and optional parameters are
     /// configured through method chaining.
     ///
-    /// By default, `repetitions` is set to 1, `parallel` to false, and
-    /// `assert_equal` to false.
+    /// By default, `repetitions` is set to 1, `parallel` to false,
+    /// `assert_equal` to false, and `strict_equal` to false.
     pub fn new(
         functions: Vec<BenchFnNamed<'a, T, R>>,
         argfunc: BenchFnArg<T>,
         sizes: Vec<usize>,
+    ) -> Self {
+        Self::new_fallible(functions, Box::new(move |n| Ok(argfunc(n))), sizes)
+    }
+
+    /// Creates a new `BenchBuilder` like [`BenchBuilder::new`], with an
+    /// argument function that may fail, e.g., because inputs are loaded from
+    /// files or a network.
+    ///
+    /// Failures are returned by [`Bench::try_run`](crate::Bench::try_run).
+    ///
+    /// ```
+    /// use benchplot::{BenchBuilder, BenchFnNamed, FallibleBenchFnArg};
+    ///
+    /// let functions: Vec<BenchFnNamed<Vec<u8>, usize>> =
+    ///     vec![(Box::new(|v: Vec<u8>| v.len()), "Length")];
+    /// let argfunc: FallibleBenchFnArg<Vec<u8>> =
+    ///     Box::new(|n| Ok(std::fs::read(format!("input_{}.bin", n))?));
+    /// let mut bench = BenchBuilder::new_fallible(functions, argfunc, vec![10])
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// assert!(bench.try_run().is_err());
+    /// ```
+    pub fn new_fallible(
+        functions: Vec<BenchFnNamed<'a, T, R>>,
+        argfunc: FallibleBenchFnArg<T>,
+        sizes: Vec<usize>,
+    ) -> Self {
+        Self::with_functions(
+            functions
+                .into_iter()
+                .map(|(func, name)| (factory_from_fn(func), name))
+                .collect(),
+            argfunc,
+            sizes,
+        )
+    }
+
+    /// Sets whether to run (input size, function) pair benchmarks in parallel.
+    ///
+    /// Pairs run on the rayon thread pool with the `parallel` feature, which
+    /// is enabled by default, and on scoped threads without it.
+    ///
+    /// **Default**: `false`.
+    pub fn parallel(mut self, parallel: bool) -> Self {
+        self.parallel = parallel;
+        self
+    }
+}
+
+impl<'a, T: 'static, R: 'static> BenchBuilder<'a, T, R, Local> {
+    /// Creates a new `BenchBuilder` like [`BenchBuilder::new`], with functions
+    /// to benchmark and an argument function that need not be thread-safe,
+    /// e.g., closures capturing an `Rc` or a `RefCell`.
+    ///
+    /// The builder and the benchmark it builds are marked [`Local`]: they
+    /// cannot run in `parallel`, and are neither `Send` nor `Sync`, so they
+    /// stay on the thread that created them.
+    ///
+    /// ```
+    /// use benchplot::{BenchBuilder, LocalBenchFnArg, LocalBenchFnNamed};
+    /// use std::cell::RefCell;
+    /// use std::rc::Rc;
+    ///
+    /// let calls = Rc::new(RefCell::new(0));
+    /// let counted = Rc::clone(&calls);
+    /// let functions: Vec<LocalBenchFnNamed<usize, usize>> = vec![(
+    ///     Box::new(move |n| {
+    ///         *counted.borrow_mut() += 1;
+    ///         n
+    ///     }),
+    ///     "Counted",
+    /// )];
+    /// let argfunc: LocalBenchFnArg<usize> = Box::new(|n| n);
+    /// let mut bench = BenchBuilder::new_local(functions, argfunc, vec![10, 20])
+    ///     .build()
+    ///     .unwrap();
+    /// bench.run();
+    ///
+    /// assert_eq!(*calls.borrow(), 2);
+    /// ```
+    pub fn new_local(
+        functions: Vec<LocalBenchFnNamed<'a, T, R>>,
+        argfunc: LocalBenchFnArg<T>,
+        sizes: Vec<usize>,
+    ) -> Self {
+        Self::with_functions(
+            functions
+                .into_iter()
+                .map(|(func, name)| (local::factory_from_local_fn(func), name))
+                .collect(),
+            local::argfunc_from_local(argfunc),
+            sizes,
+        )
+    }
+
+    /// Adds a function to benchmark with the given name that need not be
+    /// thread-safe, like the functions passed to [`BenchBuilder::new_local`].
+    pub fn add_local_function<F>(mut self, func: F, name: &'a str) -> Self
+    where
+        F: Fn(T) -> R + 'static,
+    {
+        self.functions
+            .push((local::factory_from_local_fn(Box::new(func)), name));
+        self
+    }
+}
+
+impl<'a, T: 'static, R: 'static, M: Threading> BenchBuilder<'a, T, R, M> {
+    /// Creates a new `BenchBuilder` with the given functions and argument
+    /// function, and the default options.
+    fn with_functions(
+        functions: Vec<(M::Factory<T, R>, &'a str)>,
+        argfunc: M::Argfunc<T>,
+        sizes: Vec<usize>,
     ) -> Self {
         Self {
             functions,
             argfunc,
             sizes,
             repetitions: 1,
+            max_repetitions: None,
             parallel: false,
+            isolated: false,
+            cachegrind: false,
             assert_equal: false,
+            strict_equal: false,
+            reference: None,
+            mismatch_report: None,
+            mismatch_inputs: None,
+            inputs: None,
+            results_file: None,
+            live_plot: None,
+            results_database: None,
+            cv_threshold: None,
+            target_precision: None,
+            priority: Priority::Normal,
+            cache_mode: CacheMode::Warm,
+            aggregation: Aggregation::Mean,
+            confidence_level: None,
+            load_pause: None,
+            keep_samples: false,
+            print_verdict: false,
+            catch_panics: false,
+            verbosity: Verbosity::Normal,
+            max_sizes: Vec::new(),
+            function_sizes: Vec::new(),
+            prefault: None,
+            timer: Arc::new(InstantTimer::new()),
+            label: None,
+            reporters: Vec::new(),
+            profiled: Vec::new(),
+            metrics: Vec::new(),
         }
     }
 
+    /// Adds a function to benchmark with the given name.
+    ///
+    /// Unlike the functions vector passed to [`BenchBuilder::new`], plain
+    /// functions and closures are accepted without boxing them first.
+    ///
+    /// ```
+    /// use benchplot::{BenchBuilder, BenchFnArg};
+    ///
+    /// let argfunc: BenchFnArg<Vec<u32>> = Box::new(|n| (0..n as u32).collect());
+    /// let bench = BenchBuilder::new(Vec::new(), argfunc, vec![10, 100])
+    ///     .add_function(|v: Vec<u32>| v.iter().sum::<u32>(), "Sum")
+    ///     .add_function(|v: Vec<u32>| v.iter().fold(0, |a, b| a + b), "Fold")
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn add_function<F>(mut self, func: F, name: &'a str) -> Self
+    where
+        F: Fn(T) -> R + Send + Sync + 'static,
+    {
+        self.functions
+            .push((M::factory(factory_from_fn(Box::new(func))), name));
+        self
+    }
+
+    /// Adds a function to benchmark with the given name that takes the input
+    /// size along with the argument, e.g., to preallocate a buffer or compute
+    /// a parameter derived from the size without recovering it from the
+    /// argument.
+    ///
+    /// ```
+    /// use benchplot::{BenchBuilder, BenchFnArg};
+    ///
+    /// let argfunc: BenchFnArg<Vec<u32>> = Box::new(|n| (0..n as u32).collect());
+    /// let bench = BenchBuilder::new(Vec::new(), argfunc, vec![10, 100])
+    ///     .add_sized_function(
+    ///         |n, v: Vec<u32>| {
+    ///             let mut doubled = Vec::with_capacity(n);
+    ///             doubled.extend(v.iter().map(|x| x * 2));
+    ///             doubled
+    ///         },
+    ///         "Double Into Preallocated",
+    ///     )
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn add_sized_function<F>(mut self, func: F, name: &'a str) -> Self
+    where
+        F: Fn(usize, T) -> R + Send + Sync + 'static,
+    {
+        self.functions
+            .push((M::factory(factory_from_sized_fn(Box::new(func))), name));
+        self
+    }
+
+    /// Adds a stateful function to benchmark with the given name.
+    ///
+    /// Stateful functions may mutate the state they capture, such as a
+    /// reusable scratch buffer. Each (input size, function) pair is timed
+    /// with its own clone of `func`, which is reused across the repetitions of
+    /// that pair. In parallel mode, pairs timed concurrently therefore never
+    /// share state.
+    ///
+    /// ```
+    /// use benchplot::{BenchBuilder, BenchFnArg};
+    ///
+    /// let argfunc: BenchFnArg<Vec<u32>> = Box::new(|n| (0..n as u32).collect());
+    /// let mut scratch: Vec<u32> = Vec::new();
+    /// let bench = BenchBuilder::new(Vec::new(), argfunc, vec![10, 100])
+    ///     .add_function_mut(
+    ///         move |v: Vec<u32>| {
+    ///             scratch.clear();
+    ///             scratch.extend(v.iter().rev());
+    ///             scratch.len()
+    ///         },
+    ///         "Reverse Into Scratch",
+    ///     )
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn add_function_mut<F>(mut self, func: F, name: &'a str) -> Self
+    where
+        F: FnMut(T) -> R + Clone + Send + Sync + 'static,
+    {
+        self.functions
+            .push((M::factory(factory_from_fn_mut(func)), name));
+        self
+    }
+
     /// Sets the number of times to time each (input size, function) pair.
     ///
     /// For each (input size, function) pair, the function is timed
-    /// `repetitions` times and the average over the repetitions is used as the
-    /// benchmark value.
+    /// `repetitions` times and the timings are combined as set by
+    /// `aggregation`, by default their average, into the benchmark value.
     ///
     /// **Default**: `1`.
     pub fn repetitions(mut self, repetitions: usize) -> Self {
@@ -67,11 +416,59 @@ impl<'a, T, R> BenchBuilder<'a, T, R> {
         self
     }
 
-    /// Sets whether to run (input size, function) pair benchmarks in parallel.
+    /// Sets the maximum number of times to time each (input size, function)
+    /// pair.
+    ///
+    /// If a `cv_threshold` is set and the timings of an (input size, function)
+    /// pair are noisy after `repetitions` repetitions, the pair is timed again
+    /// until its coefficient of variation drops below the threshold or it has
+    /// been timed `max_repetitions` times. Likewise, if a `target_precision`
+    /// is set, the pair is timed again until its mean is precise enough. The
+    /// timings of all repetitions are then combined as set by `aggregation`
+    /// into the benchmark value.
+    ///
+    /// **Default**: the value of `repetitions` (no additional repetitions).
+    pub fn max_repetitions(mut self, max_repetitions: usize) -> Self {
+        self.max_repetitions = Some(max_repetitions);
+        self
+    }
+
+    /// Sets whether to time each (input size, function) pair in its own child
+    /// process.
+    ///
+    /// Isolating pairs prevents allocator state, caches, and memory
+    /// fragmentation caused by one function from affecting the timing of the
+    /// next. Pairs are timed one at a time, regardless of `parallel`.
+    ///
+    /// Each child process re-executes the current executable with the same
+    /// arguments, so the program must be deterministic up to the call to
+    /// `run()`, and any side effects before it are repeated in each child.
+    /// Within a program, isolated benchmarks are identified by the order in
+    /// which they are run. Return values cannot be compared in this mode, so
+    /// it cannot be combined with `assert_equal`.
     ///
     /// **Default**: `false`.
-    pub fn parallel(mut self, parallel: bool) -> Self {
-        self.parallel = parallel;
+    pub fn isolated(mut self, isolated: bool) -> Self {
+        self.isolated = isolated;
+        self
+    }
+
+    /// Sets whether to also count the instructions executed and the cache
+    /// references made by a single call of each function at each input size,
+    /// by running it under Valgrind's Cachegrind.
+    ///
+    /// Unlike timings, the counts do not depend on the load of the machine,
+    /// which makes them suited to detecting regressions in CI. Each pair is
+    /// run in two child processes under `valgrind`, which must be installed:
+    /// one calling the function and one calibrating for everything else, such
+    /// as generating the argument. The counts are available from
+    /// [`Bench::instruction_counts`](crate::Bench::instruction_counts).
+    ///
+    /// This implies `isolated(true)`, with the same restrictions.
+    ///
+    /// **Default**: `false`.
+    pub fn cachegrind(mut self, cachegrind: bool) -> Self {
+        self.cachegrind = cachegrind;
         self
     }
 
@@ -81,7 +478,8 @@ impl<'a, T, R> BenchBuilder<'a, T, R> {
     /// return values are not equal, then the program panics.
     ///
     /// If `repetitions` is greater than 1, then for each input size, only the
-    /// function return values from the last repetition are compared.
+    /// function return values from the last repetition are compared, unless
+    /// `strict_equal` is set.
     ///
     /// **Default**: `false`.
     pub fn assert_equal(mut self, assert_equal: bool) -> Self {
@@ -89,33 +487,674 @@ impl<'a, T, R> BenchBuilder<'a, T, R> {
         self
     }
 
+    /// Sets whether to assert that each function returns equal values across
+    /// all repetitions.
+    ///
+    /// Setting `strict_equal` to `true` implies `assert_equal(true)`. Together,
+    /// they ensure that the return values of every repetition are compared,
+    /// catching functions that are nondeterministically wrong. When a function
+    /// returns different values across repetitions for some input size, the
+    /// program panics.
+    ///
+    /// Each return value is compared with the return value of the previous
+    /// repetition, so at most two return values per function are held at a
+    /// time.
+    ///
+    /// **Default**: `false`.
+    pub fn strict_equal(mut self, strict_equal: bool) -> Self {
+        self.strict_equal = strict_equal;
+        self
+    }
+
+    /// Sets the name of the function whose return values the return values of
+    /// all other functions are compared against.
+    ///
+    /// Setting a reference function implies `assert_equal(true)`. When a
+    /// function's return value differs from that of the reference function,
+    /// the program panics with a message naming both functions and the input
+    /// size, e.g. `Bubble Sort disagreed with Merge Sort at n=4096`.
+    ///
+    /// **Default**: the first function in the functions vector.
+    pub fn reference(mut self, name: &'a str) -> Self {
+        self.reference = Some(name);
+        self
+    }
+
+    /// Sets the path of a file to write a mismatch report to when function
+    /// return values are not equal.
+    ///
+    /// The report lists the functions that disagreed with the reference
+    /// function, the input size, and a truncated `Debug` rendering of the
+    /// values. It is written before the program panics with the same report.
+    ///
+    /// **Default**: no report file is written.
+    pub fn mismatch_report<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.mismatch_report = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Sets a directory to write the input that function return values
+    /// disagreed on to, with `write`, so that the mismatch can be reproduced,
+    /// e.g., in a unit test.
+    ///
+    /// When the return values are not equal at the input size `n`, the
+    /// argument is written to the file `mismatch_<n>_input` of the directory,
+    /// which is created if needed, and the untruncated `Debug` renderings of
+    /// the return values of the reference function and of each function that
+    /// disagreed with it to the file `mismatch_<n>_outputs.txt`. The files are
+    /// written before the program panics with the mismatch report.
+    ///
+    /// This has no effect unless [`assert_equal`](Self::assert_equal) is
+    /// enabled.
+    ///
+    /// **Default**: mismatching inputs are not written.
+    pub fn mismatch_inputs<P, W>(mut self, dir: P, write: W) -> Self
+    where
+        P: AsRef<Path>,
+        W: Fn(&T, &mut dyn std::io::Write) -> std::io::Result<()>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.mismatch_inputs =
+            Some((dir.as_ref().to_path_buf(), Arc::new(write)));
+        self
+    }
+
+    /// Sets a directory to write the argument of each input size to, with
+    /// `write`, so that a surprising data point can be investigated later with
+    /// exactly the same input.
+    ///
+    /// The argument of the input size `n` is written to the file `input_<n>`
+    /// of the directory, which is created if needed, as soon as it is
+    /// generated. Its path is returned by
+    /// [`Bench::input_path`](crate::Bench::input_path).
+    ///
+    /// ```
+    /// use benchplot::{BenchBuilder, BenchFnArg, BenchFnNamed};
+    ///
+    /// let functions: Vec<BenchFnNamed<Vec<u32>, usize>> =
+    ///     vec![(Box::new(|v: Vec<u32>| v.len()), "Length")];
+    /// let argfunc: BenchFnArg<Vec<u32>> = Box::new(|n| (0..n as u32).collect());
+    /// let dir = std::env::temp_dir().join("benchplot_inputs");
+    /// let mut bench = BenchBuilder::new(functions, argfunc, vec![3])
+    ///     .persist_inputs(&dir, |arg, writer| write!(writer, "{:?}", arg))
+    ///     .build()
+    ///     .unwrap();
+    /// bench.run();
+    ///
+    /// let path = bench.input_path(3).unwrap();
+    /// assert_eq!(std::fs::read_to_string(path).unwrap(), "[0, 1, 2]");
+    /// ```
+    ///
+    /// **Default**: inputs are not written.
+    pub fn persist_inputs<P, W>(mut self, dir: P, write: W) -> Self
+    where
+        P: AsRef<Path>,
+        W: Fn(&T, &mut dyn std::io::Write) -> std::io::Result<()>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.inputs = Some((dir.as_ref().to_path_buf(), Arc::new(write)));
+        self
+    }
+
+    /// Sets the path of a file to stream results to during a run.
+    ///
+    /// The timings of each (input size, function) pair are appended to the
+    /// file, in the JSON Lines format, as soon as the pair has been timed, so
+    /// that the timings of long sweeps are kept on disk rather than in memory
+    /// and survive an interrupted run. The file is truncated at the start of
+    /// each run, and the average timings are read back from it at the end.
+    ///
+    /// **Default**: results are not streamed.
+    pub fn stream_results<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.results_file = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Sets the path of an SVG file to re-render with the results measured so
+    /// far after each input size completes, so that the plot of a long sweep
+    /// can be watched in a browser while it runs.
+    ///
+    /// The plot has the default options of [`Bench::plot`], with the label of
+    /// the benchmark as its title. When `parallel` is enabled, input sizes
+    /// complete in no particular order, so the plot is only rendered once all
+    /// of them have been timed. A failure to render the plot is reported with
+    /// a warning on standard error rather than interrupting the run.
+    ///
+    /// **Default**: no plot is rendered during the run.
+    #[cfg(feature = "plot")]
+    pub fn live_plot<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.live_plot = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Sets the path of a SQLite database to append the results of every run
+    /// to, giving a queryable history of the benchmark.
+    ///
+    /// The database and its `runs`, `cells`, and `samples` tables are created
+    /// if they do not exist. Each run adds a row to `runs` with the label of
+    /// the benchmark, a row to `cells` with the mean timing of each
+    /// (input size, function) pair, and a row to `samples` with each timing
+    /// of the pair, as soon as the pair has been timed.
+    ///
+    /// **Default**: results are not recorded in a database.
+    #[cfg(feature = "sqlite")]
+    pub fn results_database<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.results_database = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Sets the coefficient of variation above which an (input size, function)
+    /// pair is considered noisy.
+    ///
+    /// The coefficient of variation is the standard deviation of the timings
+    /// over the repetitions divided by their mean, expressed as a fraction
+    /// (e.g., `0.1` for 10%). Noisy pairs are reported with a warning on
+    /// standard error and marked on the plot. Requires `repetitions` to be
+    /// greater than 1 to have any effect. See `max_repetitions` to time noisy
+    /// pairs additional times before accepting them.
+    ///
+    /// **Default**: no threshold (noise is not checked).
+    pub fn cv_threshold(mut self, cv_threshold: f64) -> Self {
+        self.cv_threshold = Some(cv_threshold);
+        self
+    }
+
+    /// Sets the precision to time each (input size, function) pair to, as the
+    /// half-width of the confidence interval of the mean of its timings
+    /// relative to the mean, e.g., `0.02` for ±2%.
+    ///
+    /// After `repetitions` repetitions, the pair is timed again until the
+    /// precision is reached or it has been timed `max_repetitions` times, so
+    /// `max_repetitions` must be set for this to have any effect. The interval
+    /// is estimated with a normal approximation at the `confidence_level`, or
+    /// 95% if none is set. Building fails unless `max_repetitions` is set and
+    /// `repetitions` is greater than 1.
+    ///
+    /// **Default**: no target (pairs are timed `repetitions` times unless
+    /// noisy).
+    pub fn target_precision(mut self, relative_error: f64) -> Self {
+        self.target_precision = Some(relative_error);
+        self
+    }
+
+    /// Sets the scheduling priority of the process while benchmarks are
+    /// running, to reduce scheduler-induced variance.
+    ///
+    /// Raising the priority usually requires elevated privileges. If it fails,
+    /// a warning is printed to standard error and benchmarks run at the current
+    /// priority; whether elevation succeeded can be queried with
+    /// [`Bench::priority_elevated`]. The previous priority is restored when
+    /// `run()` returns.
+    ///
+    /// **Default**: [`Priority::Normal`].
+    pub fn priority(mut self, priority: Priority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Pauses measurement while the system is busy, e.g., on a shared CI
+    /// runner, to avoid timing functions that compete for the CPU.
+    ///
+    /// Before each (input size, function) pair is timed, the 1-minute load
+    /// average divided by the number of logical CPUs is read, and measurement
+    /// waits until it is at most `max_load` (e.g., `0.5` for half of the CPUs
+    /// busy), for at most `max_wait`. If the load is still too high after
+    /// `max_wait`, a warning is printed to standard error and measurement
+    /// resumes. The load includes the benchmark itself, so `max_load` should
+    /// leave room for it.
+    ///
+    /// Measurement is not paused when `parallel` is enabled, or on platforms
+    /// where the load average is unavailable (e.g., Windows).
+    ///
+    /// **Default**: measurement is never paused.
+    pub fn pause_when_busy(
+        mut self,
+        max_load: f64,
+        max_wait: Duration,
+    ) -> Self {
+        self.load_pause = Some(LoadPause { max_load, max_wait });
+        self
+    }
+
+    /// Sets the state of the CPU caches when each repetition is timed.
+    ///
+    /// With [`CacheMode::Cold`], the input is evicted from CPU caches before
+    /// every repetition, measuring performance when the input has to be
+    /// fetched from main memory.
+    ///
+    /// **Default**: [`CacheMode::Warm`].
+    pub fn cache_mode(mut self, cache_mode: CacheMode) -> Self {
+        self.cache_mode = cache_mode;
+        self
+    }
+
+    /// Sets how the timings of the repetitions of each (input size, function)
+    /// pair are combined into its benchmark value.
+    ///
+    /// **Default**: [`Aggregation::Mean`].
+    pub fn aggregation(mut self, aggregation: Aggregation) -> Self {
+        self.aggregation = aggregation;
+        self
+    }
+
+    /// Sets the confidence level, e.g., `0.95`, of confidence intervals to
+    /// estimate for the benchmark value of each (input size, function) pair.
+    ///
+    /// Intervals are estimated by bootstrap resampling of the timings of the
+    /// repetitions, so they make no assumption about how the timings are
+    /// distributed, and are available from
+    /// [`Bench::confidence_intervals`](crate::Bench::confidence_intervals)
+    /// and as error bands with
+    /// [`PlotBuilder::error_bands`](crate::PlotBuilder::error_bands). They
+    /// are only meaningful with several `repetitions`.
+    ///
+    /// **Default**: no confidence intervals are estimated.
+    pub fn confidence_level(mut self, level: f64) -> Self {
+        self.confidence_level = Some(level);
+        self
+    }
+
+    /// Sets whether to keep the timing of every repetition of each
+    /// (input size, function) pair, rather than only their combined value.
+    ///
+    /// The timings are available from
+    /// [`Bench::samples`](crate::Bench::samples) and are needed by
+    /// [`Bench::significance_matrix`](crate::Bench::significance_matrix).
+    /// Memory use grows with the number of repetitions.
+    ///
+    /// **Default**: `false`.
+    pub fn keep_samples(mut self, keep_samples: bool) -> Self {
+        self.keep_samples = keep_samples;
+        self
+    }
+
+    /// Sets whether to print the [`Bench::verdict`](crate::Bench::verdict)
+    /// of each run to standard output once it completes, e.g.,
+    /// `verdict: Merge Sort is fastest at n=1000: 1.25x faster than Quick
+    /// Sort`, so that scripts can extract the conclusion with `grep`.
+    ///
+    /// **Default**: `false`.
+    pub fn print_verdict(mut self, print_verdict: bool) -> Self {
+        self.print_verdict = print_verdict;
+        self
+    }
+
+    /// Sets whether a function that panics at an input size is marked as
+    /// failed at that size rather than aborting the whole benchmark.
+    ///
+    /// The timing of a failed (input size, function) pair is NaN, so it is
+    /// left out of plots and reports like a skipped pair, a warning with the
+    /// panic message is printed, and the failure is listed by
+    /// [`Bench::failures`](crate::Bench::failures). The default panic hook
+    /// still prints the panic to standard error. Panics cannot be caught in
+    /// binaries built with `panic = "abort"`.
+    ///
+    /// **Default**: `false`.
+    pub fn catch_panics(mut self, catch_panics: bool) -> Self {
+        self.catch_panics = catch_panics;
+        self
+    }
+
+    /// Sets how much the benchmark prints while and after it runs, from
+    /// nothing at all with [`Verbosity::Quiet`] to the timing of every
+    /// (input size, function) pair with [`Verbosity::Verbose`].
+    ///
+    /// **Default**: [`Verbosity::Normal`], which only prints warnings.
+    pub fn verbosity(mut self, verbosity: Verbosity) -> Self {
+        self.verbosity = verbosity;
+        self
+    }
+
+    /// Sets the largest input size at which to time the function named
+    /// `name`, e.g., so that a quadratic function stops early while faster
+    /// functions continue to larger input sizes.
+    ///
+    /// The function has no timing at larger input sizes, where its value in
+    /// [`Bench::data`](crate::Bench::data) is `NaN` and its line on the plot
+    /// ends.
+    ///
+    /// **Default**: every function is timed at every input size.
+    pub fn max_size_for(mut self, name: &'a str, max_size: usize) -> Self {
+        self.max_sizes.push((name, max_size));
+        self
+    }
+
+    /// Sets the input sizes at which to time the function named `name`,
+    /// instead of the sizes passed to [`BenchBuilder::new`], e.g., to compare
+    /// algorithms whose practical ranges of input sizes barely overlap.
+    ///
+    /// The benchmark covers the sizes passed to [`BenchBuilder::new`] and
+    /// those of every function, in increasing order, and each function has
+    /// no timing at the sizes it is not timed at, where its value in
+    /// [`Bench::data`](crate::Bench::data) is `NaN` and its line on the plot
+    /// skips them.
+    ///
+    /// **Default**: every function is timed at the sizes passed to
+    /// [`BenchBuilder::new`].
+    pub fn sizes_for(mut self, name: &'a str, sizes: Vec<usize>) -> Self {
+        self.function_sizes.push((name, sizes));
+        self
+    }
+
+    /// Sets the clock used to time functions.
+    ///
+    /// Custom timers allow using alternative clocks, such as cycle counters,
+    /// or mock clocks that make timings deterministic in tests. Besides
+    /// [`InstantTimer`], the crate provides [`CoarseTimer`](crate::CoarseTimer)
+    /// and, with the `tsc` feature, `TscTimer`, which trade resolution for
+    /// overhead.
+    ///
+    /// **Default**: [`InstantTimer`].
+    pub fn timer<C: Timer + 'static>(mut self, timer: C) -> Self {
+        self.timer = Arc::new(timer);
+        self
+    }
+
+    /// Sets a label identifying this run, such as the version of the code
+    /// being benchmarked (e.g., `"v0.4"`).
+    ///
+    /// Plotted series of a labeled run are named `<function> (<label>)`, and
+    /// the label is carried over to its [`Baseline`](crate::Baseline), so
+    /// that labeled runs can be plotted together with
+    /// [`PlotBuilder::overlay`](crate::PlotBuilder::overlay).
+    ///
+    /// **Default**: no label.
+    pub fn label(mut self, label: &str) -> Self {
+        self.label = Some(label.to_string());
+        self
+    }
+
+    /// Adds a metric collected along with the time of every repetition,
+    /// measured as the difference between readings of `counter` before and
+    /// after each call, e.g., the CPU time with
+    /// [`thread_cpu_time`](crate::thread_cpu_time) or the allocations with
+    /// [`CountingAllocator`](crate::CountingAllocator), or a custom counter
+    /// incremented by the functions.
+    ///
+    /// The values of the repetitions of each (input size, function) pair are
+    /// combined as set by `aggregation`, like the timings, and are available
+    /// from [`Bench::metric`](crate::Bench::metric) and plotted with
+    /// [`PlotBuilder::metric`](crate::PlotBuilder::metric). Metrics are not
+    /// collected in `isolated` runs.
+    ///
+    /// **Default**: only the time is measured.
+    pub fn metric<F>(mut self, name: &str, counter: F) -> Self
+    where
+        F: Fn() -> f64 + Send + Sync + 'static,
+    {
+        self.metrics.push(Metric {
+            name: name.to_string(),
+            source: MetricSource::Counter(Box::new(counter)),
+        });
+        self
+    }
+
+    /// Adds a metric computed by `value` from the value returned by every
+    /// repetition, e.g., a count of comparisons returned by the functions
+    /// along with their result. Otherwise like [`BenchBuilder::metric`].
+    ///
+    /// Such auxiliary values, e.g., a compression ratio or a number of
+    /// iterations to converge, can also be plotted along with the timings
+    /// with
+    /// [`PlotBuilder::secondary_metric`](crate::PlotBuilder::secondary_metric).
+    ///
+    /// ```
+    /// use benchplot::{BenchBuilder, BenchFnArg};
+    ///
+    /// let argfunc: BenchFnArg<Vec<u32>> = Box::new(|n| (0..n as u32).collect());
+    /// let bench = BenchBuilder::new(Vec::new(), argfunc, vec![10, 100])
+    ///     .add_function(
+    ///         |v: Vec<u32>| {
+    ///             let steps = v.len().ilog2() as usize + 1;
+    ///             (v.binary_search(&3).is_ok(), steps)
+    ///         },
+    ///         "Binary Search",
+    ///     )
+    ///     .result_metric("Steps", |&(_, steps)| steps as f64)
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    ///
+    /// **Default**: only the time is measured.
+    pub fn result_metric<F>(mut self, name: &str, value: F) -> Self
+    where
+        F: Fn(&R) -> f64 + Send + Sync + 'static,
+    {
+        self.metrics.push(Metric {
+            name: name.to_string(),
+            source: MetricSource::Result(Box::new(value)),
+        });
+        self
+    }
+
+    /// Adds a reporter, invoked with the results at the end of every run,
+    /// e.g., to upload them to a performance dashboard.
+    ///
+    /// Reporters are invoked in the order they are added. A reporter that
+    /// fails produces a warning rather than a panic.
+    ///
+    /// **Default**: no reporters.
+    pub fn reporter<P: Reporter + 'static>(mut self, reporter: P) -> Self {
+        self.reporters.push(Arc::new(reporter));
+        self
+    }
+
+    /// Sets a profiler to invoke around the timing of the function named
+    /// `name` at the input size `size`, e.g., to capture a flamegraph of a
+    /// slow point of the plot from the same harness.
+    ///
+    /// The profiler is started right before the first repetition of the pair
+    /// and stopped right after the last one. Other pairs are timed
+    /// concurrently in parallel mode, so profiles are cleanest in sequential
+    /// or isolated mode. Profiling may perturb the timings of the pair.
+    ///
+    /// **Default**: no pair is profiled.
+    pub fn profile<P: Profiler + 'static>(
+        mut self,
+        name: &'a str,
+        size: usize,
+        profiler: P,
+    ) -> Self {
+        self.profiled.push((name, size, Arc::new(profiler)));
+        self
+    }
+
     /// Validates the configuration and builds a `Bench` instance.
-    pub fn build(self) -> Result<Bench<'a, T, R>, BenchBuilderError> {
+    pub fn build(self) -> Result<Bench<'a, T, R, M>, BenchBuilderError> {
         if self.repetitions == 0 {
             return Err(BenchBuilderError::ZeroRepetitions);
         }
-        if self.sizes.is_empty() {
+        let max_repetitions = self.max_repetitions.unwrap_or(self.repetitions);
+        if max_repetitions < self.repetitions {
+            return Err(BenchBuilderError::MaxRepetitionsTooSmall);
+        }
+        let sizes = if self.function_sizes.is_empty() {
+            self.sizes.clone()
+        } else {
+            let mut sizes = self.sizes.clone();
+            sizes.extend(
+                self.function_sizes
+                    .iter()
+                    .flat_map(|(_, sizes)| sizes.iter().copied()),
+            );
+            sizes.sort_unstable();
+            sizes.dedup();
+            sizes
+        };
+        if sizes.is_empty() {
             return Err(BenchBuilderError::NoSizes);
         }
         if self.functions.is_empty() {
             return Err(BenchBuilderError::NoFunctions);
         }
-        Ok(Bench {
-            functions: self
+        if let Aggregation::TrimmedMean(fraction) = self.aggregation {
+            if !self.aggregation.is_valid() {
+                return Err(BenchBuilderError::InvalidTrimFraction(fraction));
+            }
+        }
+        if let Some(level) = self.confidence_level {
+            if !(level > 0.0 && level < 1.0) {
+                return Err(BenchBuilderError::InvalidConfidenceLevel(level));
+            }
+        }
+        if let Some(precision) = self.target_precision {
+            if !(precision > 0.0 && precision.is_finite()) {
+                return Err(BenchBuilderError::InvalidTargetPrecision(
+                    precision,
+                ));
+            }
+            if self.max_repetitions.is_none() || self.repetitions < 2 {
+                return Err(
+                    BenchBuilderError::TargetPrecisionWithoutRepetitions,
+                );
+            }
+        }
+        if let Some(LoadPause { max_load, .. }) = self.load_pause {
+            if !(max_load > 0.0 && max_load.is_finite()) {
+                return Err(BenchBuilderError::InvalidMaxLoad(max_load));
+            }
+        }
+        let reference = match self.reference {
+            Some(name) => self
                 .functions
-                .into_iter()
-                .map(|(func, name)| (Arc::new(func), name))
-                .collect(),
+                .iter()
+                .position(|&(_, n)| n == name)
+                .ok_or_else(|| {
+                BenchBuilderError::UnknownReference(name.to_string())
+            })?,
+            None => 0,
+        };
+        let assert_equal =
+            self.assert_equal || self.strict_equal || self.reference.is_some();
+        let isolated = self.isolated || self.cachegrind;
+        if isolated && assert_equal {
+            return Err(BenchBuilderError::IsolatedAssertEqual);
+        }
+        let profiled = self
+            .profiled
+            .into_iter()
+            .map(|(name, size, profiler)| {
+                let func_idx = self
+                    .functions
+                    .iter()
+                    .position(|&(_, n)| n == name)
+                    .ok_or_else(|| {
+                        BenchBuilderError::UnknownProfiledFunction(
+                            name.to_string(),
+                        )
+                    })?;
+                if !sizes.contains(&size) {
+                    return Err(BenchBuilderError::UnknownProfiledSize(size));
+                }
+                Ok(ProfiledCell {
+                    size,
+                    func_idx,
+                    profiler,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        let position =
+            |name: &str| self.functions.iter().position(|&(_, n)| n == name);
+        let mut function_sizes = vec![self.sizes; self.functions.len()];
+        for (name, sizes) in self.function_sizes {
+            let func_idx = position(name).ok_or_else(|| {
+                BenchBuilderError::UnknownSizedFunction(name.to_string())
+            })?;
+            function_sizes[func_idx] = sizes;
+        }
+        let mut max_sizes = vec![None; self.functions.len()];
+        for (name, max_size) in self.max_sizes {
+            let func_idx = position(name).ok_or_else(|| {
+                BenchBuilderError::UnknownCappedFunction(name.to_string())
+            })?;
+            function_sizes[func_idx].retain(|&size| size <= max_size);
+            let cap: &mut Option<usize> = &mut max_sizes[func_idx];
+            *cap = Some(cap.map_or(max_size, |cap| cap.min(max_size)));
+        }
+        for (idx, metric) in self.metrics.iter().enumerate() {
+            if self.metrics[..idx].iter().any(|m| m.name == metric.name) {
+                return Err(BenchBuilderError::DuplicateMetric(
+                    metric.name.clone(),
+                ));
+            }
+        }
+        let data = BenchData::new(self.functions.len());
+        let metric_data =
+            vec![BenchData::new(self.functions.len()); self.metrics.len()];
+        Ok(Bench {
+            functions: self.functions,
             argfunc: Arc::new(self.argfunc),
-            sizes: self.sizes,
+            sizes,
             repetitions: self.repetitions,
+            max_repetitions,
             parallel: self.parallel,
-            assert_equal: self.assert_equal,
-            data: Vec::new(),
+            isolated,
+            cachegrind: self.cachegrind,
+            assert_equal,
+            strict_equal: self.strict_equal,
+            reference,
+            mismatch_report: self.mismatch_report,
+            mismatch_inputs: self.mismatch_inputs,
+            inputs: self.inputs,
+            results_file: self.results_file,
+            live_plot: self.live_plot,
+            results_database: self.results_database,
+            cv_threshold: self.cv_threshold,
+            target_precision: self.target_precision,
+            priority: self.priority,
+            cache_mode: self.cache_mode,
+            aggregation: self.aggregation,
+            confidence_level: self.confidence_level,
+            load_pause: self.load_pause,
+            keep_samples: self.keep_samples,
+            print_verdict: self.print_verdict,
+            catch_panics: self.catch_panics,
+            verbosity: self.verbosity,
+            function_sizes,
+            max_sizes,
+            prefault: self.prefault,
+            timer: self.timer,
+            label: self.label,
+            reporters: self.reporters,
+            profiled,
+            metrics: self.metrics,
+            priority_elevated: None,
+            timer_calibration: None,
+            data,
+            metric_data,
+            clone_times: Vec::new(),
+            instruction_counts: Vec::new(),
+            confidence_intervals: Vec::new(),
+            samples: Vec::new(),
+            noisy: Vec::new(),
+            failures: Vec::new(),
         })
     }
 }
 
+impl<'a, T: Prefault + 'static, R, M: Threading> BenchBuilder<'a, T, R, M> {
+    /// Sets whether to touch every page of the argument before timing each
+    /// repetition.
+    ///
+    /// Pre-faulting ensures that first-touch page faults on the argument are
+    /// not attributed to whichever function happens to be timed first. It is
+    /// available for argument types implementing [`Prefault`].
+    ///
+    /// **Default**: `false`.
+    pub fn prefault(mut self, prefault: bool) -> Self {
+        self.prefault = if prefault {
+            Some(Arc::new(|arg: &T| arg.prefault()))
+        } else {
+            None
+        };
+        self
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -151,6 +1190,40 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_add_function() {
+        let argfunc: BenchFnArg<usize> = Box::new(dummy_arg_fn);
+        let sizes = vec![10, 20, 30];
+
+        let builder = BenchBuilder::new(Vec::new(), argfunc, sizes)
+            .add_function(dummy_bench_fn, "Dummy Function")
+            .add_function(|x| x + 1, "Closure");
+        let bench = builder.build().unwrap();
+
+        assert_eq!(bench.functions.len(), 2);
+        assert_eq!(bench.functions[1].1, "Closure");
+    }
+
+    #[test]
+    fn test_add_function_mut() {
+        let argfunc: BenchFnArg<usize> = Box::new(dummy_arg_fn);
+        let sizes = vec![10, 20, 30];
+
+        let mut calls = 0;
+        let builder = BenchBuilder::new(Vec::new(), argfunc, sizes)
+            .add_function_mut(
+                move |x| {
+                    calls += 1;
+                    x + calls
+                },
+                "Counter",
+            );
+        let bench = builder.build().unwrap();
+
+        assert_eq!(bench.functions.len(), 1);
+        assert_eq!(bench.functions[0].1, "Counter");
+    }
+
     #[test]
     fn test_setting_repetitions() {
         let (functions, argfunc, sizes) = create_mandatory_args();
@@ -162,6 +1235,41 @@ mod tests {
         assert_eq!(bench.repetitions, 8);
     }
 
+    #[test]
+    fn test_setting_max_repetitions() {
+        let (functions, argfunc, sizes) = create_mandatory_args();
+
+        let bench = BenchBuilder::new(functions, argfunc, sizes)
+            .repetitions(4)
+            .build()
+            .unwrap();
+        assert_eq!(bench.max_repetitions, 4);
+
+        let (functions, argfunc, sizes) = create_mandatory_args();
+
+        let bench = BenchBuilder::new(functions, argfunc, sizes)
+            .repetitions(4)
+            .max_repetitions(16)
+            .build()
+            .unwrap();
+        assert_eq!(bench.max_repetitions, 16);
+    }
+
+    #[test]
+    fn test_max_repetitions_too_small() {
+        let (functions, argfunc, sizes) = create_mandatory_args();
+
+        let builder = BenchBuilder::new(functions, argfunc, sizes)
+            .repetitions(4)
+            .max_repetitions(2);
+        let result = builder.build();
+
+        assert!(matches!(
+            result,
+            Err(BenchBuilderError::MaxRepetitionsTooSmall)
+        ));
+    }
+
     #[test]
     fn test_setting_parallel() {
         let (functions, argfunc, sizes) = create_mandatory_args();
@@ -173,6 +1281,44 @@ mod tests {
         assert!(bench.parallel);
     }
 
+    #[test]
+    fn test_setting_isolated() {
+        let (functions, argfunc, sizes) = create_mandatory_args();
+
+        let builder =
+            BenchBuilder::new(functions, argfunc, sizes).isolated(true);
+        let bench = builder.build().unwrap();
+
+        assert!(bench.isolated);
+    }
+
+    #[test]
+    fn test_cachegrind_implies_isolated() {
+        let (functions, argfunc, sizes) = create_mandatory_args();
+
+        let builder =
+            BenchBuilder::new(functions, argfunc, sizes).cachegrind(true);
+        let bench = builder.build().unwrap();
+
+        assert!(bench.cachegrind);
+        assert!(bench.isolated);
+    }
+
+    #[test]
+    fn test_isolated_assert_equal() {
+        let (functions, argfunc, sizes) = create_mandatory_args();
+
+        let builder = BenchBuilder::new(functions, argfunc, sizes)
+            .isolated(true)
+            .assert_equal(true);
+        let result = builder.build();
+
+        assert!(matches!(
+            result,
+            Err(BenchBuilderError::IsolatedAssertEqual)
+        ));
+    }
+
     #[test]
     fn test_assert_equal() {
         let (functions, argfunc, sizes) = create_mandatory_args();
@@ -184,6 +1330,251 @@ mod tests {
         assert!(bench.assert_equal);
     }
 
+    #[test]
+    fn test_strict_equal() {
+        let (functions, argfunc, sizes) = create_mandatory_args();
+
+        let builder =
+            BenchBuilder::new(functions, argfunc, sizes).strict_equal(true);
+        let bench = builder.build().unwrap();
+
+        assert!(bench.strict_equal);
+        assert!(bench.assert_equal);
+    }
+
+    #[test]
+    fn test_cv_threshold() {
+        let (functions, argfunc, sizes) = create_mandatory_args();
+
+        let builder =
+            BenchBuilder::new(functions, argfunc, sizes).cv_threshold(0.05);
+        let bench = builder.build().unwrap();
+
+        assert_eq!(bench.cv_threshold, Some(0.05));
+    }
+
+    #[test]
+    fn test_setting_priority() {
+        let (functions, argfunc, sizes) = create_mandatory_args();
+
+        let builder = BenchBuilder::new(functions, argfunc, sizes)
+            .priority(Priority::High);
+        let bench = builder.build().unwrap();
+
+        assert_eq!(bench.priority, Priority::High);
+    }
+
+    #[test]
+    fn test_setting_cache_mode() {
+        let (functions, argfunc, sizes) = create_mandatory_args();
+
+        let builder = BenchBuilder::new(functions, argfunc, sizes)
+            .cache_mode(CacheMode::Cold);
+        let bench = builder.build().unwrap();
+
+        assert_eq!(bench.cache_mode, CacheMode::Cold);
+    }
+
+    #[test]
+    fn test_setting_aggregation() {
+        let (functions, argfunc, sizes) = create_mandatory_args();
+
+        let builder = BenchBuilder::new(functions, argfunc, sizes)
+            .aggregation(Aggregation::Median);
+        let bench = builder.build().unwrap();
+
+        assert_eq!(bench.aggregation, Aggregation::Median);
+    }
+
+    #[test]
+    fn test_invalid_trim_fraction() {
+        let (functions, argfunc, sizes) = create_mandatory_args();
+
+        let builder = BenchBuilder::new(functions, argfunc, sizes)
+            .aggregation(Aggregation::TrimmedMean(0.5));
+
+        assert_eq!(
+            builder.build().err(),
+            Some(BenchBuilderError::InvalidTrimFraction(0.5))
+        );
+    }
+
+    #[test]
+    fn test_invalid_confidence_level() {
+        let (functions, argfunc, sizes) = create_mandatory_args();
+
+        let builder =
+            BenchBuilder::new(functions, argfunc, sizes).confidence_level(1.0);
+
+        assert_eq!(
+            builder.build().err(),
+            Some(BenchBuilderError::InvalidConfidenceLevel(1.0))
+        );
+    }
+
+    #[test]
+    fn test_local_functions() {
+        let calls = std::rc::Rc::new(std::cell::Cell::new(0));
+        let counted = std::rc::Rc::clone(&calls);
+        let functions: Vec<LocalBenchFnNamed<'static, usize, usize>> =
+            vec![(Box::new(|x| x), "Identity")];
+        let mut bench =
+            BenchBuilder::new_local(functions, Box::new(|x| x), vec![10, 20])
+                .add_function(|x| x, "Shared")
+                .add_local_function(
+                    move |x| {
+                        counted.set(counted.get() + 1);
+                        x
+                    },
+                    "Counted",
+                )
+                .assert_equal(true)
+                .build()
+                .unwrap();
+        bench.run();
+        assert_eq!(calls.get(), 2);
+        assert_eq!(bench.function_names(), ["Identity", "Shared", "Counted"]);
+    }
+
+    #[test]
+    fn test_invalid_target_precision() {
+        let (functions, argfunc, sizes) = create_mandatory_args();
+
+        let builder =
+            BenchBuilder::new(functions, argfunc, sizes).target_precision(0.0);
+
+        assert_eq!(
+            builder.build().err(),
+            Some(BenchBuilderError::InvalidTargetPrecision(0.0))
+        );
+    }
+
+    #[test]
+    fn test_target_precision_without_repetitions() {
+        let build = |repetitions: usize, max_repetitions: Option<usize>| {
+            let (functions, argfunc, sizes) = create_mandatory_args();
+            let mut builder = BenchBuilder::new(functions, argfunc, sizes)
+                .repetitions(repetitions)
+                .target_precision(0.05);
+            if let Some(max_repetitions) = max_repetitions {
+                builder = builder.max_repetitions(max_repetitions);
+            }
+            builder.build().err()
+        };
+
+        assert_eq!(
+            build(2, None),
+            Some(BenchBuilderError::TargetPrecisionWithoutRepetitions)
+        );
+        assert_eq!(
+            build(1, Some(10)),
+            Some(BenchBuilderError::TargetPrecisionWithoutRepetitions)
+        );
+        assert_eq!(build(2, Some(10)), None);
+    }
+
+    #[test]
+    fn test_invalid_max_load() {
+        for max_load in [0.0, f64::NAN] {
+            let (functions, argfunc, sizes) = create_mandatory_args();
+
+            let builder = BenchBuilder::new(functions, argfunc, sizes)
+                .pause_when_busy(max_load, Duration::from_secs(1));
+
+            assert!(matches!(
+                builder.build().err(),
+                Some(BenchBuilderError::InvalidMaxLoad(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_unknown_capped_function() {
+        let (functions, argfunc, sizes) = create_mandatory_args();
+
+        let builder = BenchBuilder::new(functions, argfunc, sizes)
+            .max_size_for("Bubble Sort", 100);
+
+        assert_eq!(
+            builder.build().err(),
+            Some(BenchBuilderError::UnknownCappedFunction(
+                "Bubble Sort".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_setting_sizes_for() {
+        let functions: Vec<BenchFnNamed<'static, usize, usize>> = vec![
+            (Box::new(|x| x), "Insertion Sort"),
+            (Box::new(|x| x), "Merge Sort"),
+        ];
+        let argfunc: BenchFnArg<usize> = Box::new(|x| x);
+
+        let builder = BenchBuilder::new(functions, argfunc, vec![100, 10])
+            .sizes_for("Merge Sort", vec![1000, 100])
+            .max_size_for("Merge Sort", 500);
+        let bench = builder.build().unwrap();
+
+        assert_eq!(bench.sizes, [10, 100, 1000]);
+        assert_eq!(bench.function_sizes, [vec![100, 10], vec![100]]);
+    }
+
+    #[test]
+    fn test_setting_prefault() {
+        let functions: Vec<BenchFnNamed<'static, Vec<u8>, usize>> =
+            vec![(Box::new(|v: Vec<u8>| v.len()), "Length")];
+        let argfunc: BenchFnArg<Vec<u8>> = Box::new(|n| vec![0; n]);
+        let sizes = vec![10, 20, 30];
+
+        let builder =
+            BenchBuilder::new(functions, argfunc, sizes).prefault(true);
+        let bench = builder.build().unwrap();
+
+        assert!(bench.prefault.is_some());
+    }
+
+    #[test]
+    fn test_setting_label() {
+        let (functions, argfunc, sizes) = create_mandatory_args();
+
+        let builder = BenchBuilder::new(functions, argfunc, sizes).label("v1");
+        let bench = builder.build().unwrap();
+
+        assert_eq!(bench.label(), Some("v1"));
+    }
+
+    #[test]
+    fn test_reference() {
+        let functions: Vec<BenchFnNamed<'static, usize, usize>> = vec![
+            (Box::new(dummy_bench_fn), "First"),
+            (Box::new(dummy_bench_fn), "Second"),
+        ];
+        let argfunc: BenchFnArg<usize> = Box::new(dummy_arg_fn);
+        let sizes = vec![10, 20, 30];
+
+        let builder =
+            BenchBuilder::new(functions, argfunc, sizes).reference("Second");
+        let bench = builder.build().unwrap();
+
+        assert!(bench.assert_equal);
+        assert_eq!(bench.reference, 1);
+    }
+
+    #[test]
+    fn test_unknown_reference() {
+        let (functions, argfunc, sizes) = create_mandatory_args();
+
+        let builder =
+            BenchBuilder::new(functions, argfunc, sizes).reference("Missing");
+        let result = builder.build();
+
+        assert!(matches!(
+            result,
+            Err(BenchBuilderError::UnknownReference(name)) if name == "Missing"
+        ));
+    }
+
     #[test]
     fn test_zero_repetitions() {
         let (functions, argfunc, sizes) = create_mandatory_args();
@@ -207,6 +1598,21 @@ mod tests {
         assert!(matches!(result, Err(BenchBuilderError::NoSizes)));
     }
 
+    #[test]
+    fn test_duplicate_metric() {
+        let argfunc: BenchFnArg<usize> = Box::new(dummy_arg_fn);
+        let result = BenchBuilder::new(Vec::new(), argfunc, vec![10])
+            .add_function(dummy_bench_fn, "Dummy Function")
+            .result_metric("Result", |&result| result as f64)
+            .metric("Result", || 0.0)
+            .build();
+
+        assert!(matches!(
+            result,
+            Err(BenchBuilderError::DuplicateMetric(name)) if name == "Result"
+        ));
+    }
+
     #[test]
     fn test_no_functions() {
         let functions: Vec<BenchFnNamed<'static, usize, usize>> = Vec::new();
@@ -0,0 +1,201 @@
+/*
+Copyright 2024 Owain Davies
+SPDX-License-Identifier: Apache-2.0 OR MIT
+*/
+
+use std::hint::black_box;
+#[cfg(feature = "plot")]
+use std::path::Path;
+
+/// Size of the buffer traversed to evict data from CPU caches. Chosen to
+/// exceed the last-level cache of most processors.
+const EVICTION_BUFFER_SIZE: usize = 64 * 1024 * 1024;
+
+/// Distance between consecutive bytes touched while evicting, matching the
+/// common cache line size.
+const CACHE_LINE_SIZE: usize = 64;
+
+/// Directory describing the caches of the first CPU on Linux.
+#[cfg(feature = "plot")]
+const CPU_CACHE_DIR: &str = "/sys/devices/system/cpu/cpu0/cache";
+
+/// State of the CPU caches when each repetition is timed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CacheMode {
+    /// Repetitions run back to back, so the input is typically cached by the
+    /// time a function is timed (except for the first repetition).
+    #[default]
+    Warm,
+
+    /// Before every repetition, a buffer larger than the last-level cache is
+    /// traversed to evict the input from CPU caches.
+    ///
+    /// In parallel mode, the eviction performed by one thread also affects
+    /// functions being timed concurrently on other cores, so cold-cache
+    /// timings are most meaningful when benchmarks run sequentially.
+    Cold,
+}
+
+/// Evicts data from CPU caches by traversing a large buffer.
+pub(crate) struct CacheEvictor {
+    buffer: Vec<u8>,
+}
+
+impl CacheEvictor {
+    /// Creates an evictor for `mode`, or `None` if no eviction is needed.
+    pub(crate) fn new(mode: CacheMode) -> Option<Self> {
+        match mode {
+            CacheMode::Warm => None,
+            CacheMode::Cold => Some(Self {
+                buffer: vec![0; EVICTION_BUFFER_SIZE],
+            }),
+        }
+    }
+
+    /// Writes to every cache line of the buffer, displacing other data.
+    pub(crate) fn evict(&mut self) {
+        for byte in self.buffer.iter_mut().step_by(CACHE_LINE_SIZE) {
+            *byte = byte.wrapping_add(1);
+        }
+        black_box(&mut self.buffer);
+    }
+}
+
+/// A data or unified CPU cache.
+#[cfg(feature = "plot")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct CpuCache {
+    /// Level of the cache, e.g., 1 for L1.
+    pub(crate) level: u8,
+    /// Capacity of the cache in bytes.
+    pub(crate) size: usize,
+}
+
+#[cfg(feature = "plot")]
+impl CpuCache {
+    /// Returns a label such as `L2 (2 MiB)`.
+    pub(crate) fn label(&self) -> String {
+        if self.size >= 1024 * 1024 && self.size.is_multiple_of(1024 * 1024) {
+            format!("L{} ({} MiB)", self.level, self.size / (1024 * 1024))
+        } else {
+            format!("L{} ({} KiB)", self.level, self.size / 1024)
+        }
+    }
+}
+
+/// Returns the data and unified caches of the first CPU, ordered by level,
+/// one per level.
+///
+/// Returns an empty vector if the caches cannot be read, which is currently
+/// the case on platforms other than Linux.
+#[cfg(feature = "plot")]
+pub(crate) fn cpu_caches() -> Vec<CpuCache> {
+    read_cpu_caches(Path::new(CPU_CACHE_DIR))
+}
+
+/// Reads the caches described by the `index*` subdirectories of `dir`, in the
+/// layout of Linux sysfs.
+#[cfg(feature = "plot")]
+fn read_cpu_caches(dir: &Path) -> Vec<CpuCache> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let read = |path: &Path, name: &str| {
+        std::fs::read_to_string(path.join(name))
+            .map(|value| value.trim().to_string())
+            .ok()
+    };
+    let mut caches: Vec<CpuCache> = entries
+        .flatten()
+        .filter(|entry| {
+            entry.file_name().to_string_lossy().starts_with("index")
+        })
+        .filter_map(|entry| {
+            let path = entry.path();
+            if read(&path, "type")? == "Instruction" {
+                return None;
+            }
+            Some(CpuCache {
+                level: read(&path, "level")?.parse().ok()?,
+                size: parse_cache_size(&read(&path, "size")?)?,
+            })
+        })
+        .collect();
+    caches.sort_by_key(|cache| cache.level);
+    caches.dedup_by_key(|cache| cache.level);
+    caches
+}
+
+/// Parses a cache size such as `48K` or `32M` into bytes.
+#[cfg(feature = "plot")]
+fn parse_cache_size(size: &str) -> Option<usize> {
+    let (digits, multiplier) = match size.as_bytes().last()? {
+        b'K' => (&size[..size.len() - 1], 1024),
+        b'M' => (&size[..size.len() - 1], 1024 * 1024),
+        b'G' => (&size[..size.len() - 1], 1024 * 1024 * 1024),
+        _ => (size, 1),
+    };
+    digits.parse::<usize>().ok()?.checked_mul(multiplier)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evictor_only_for_cold_mode() {
+        assert!(CacheEvictor::new(CacheMode::Warm).is_none());
+
+        let mut evictor = CacheEvictor::new(CacheMode::Cold).unwrap();
+        evictor.evict();
+        assert_eq!(evictor.buffer[0], 1);
+        assert_eq!(evictor.buffer[1], 0);
+    }
+
+    #[cfg(feature = "plot")]
+    #[test]
+    fn test_read_cpu_caches() {
+        let dir = tempfile::tempdir().unwrap();
+        for (index, level, kind, size) in [
+            (0, "1", "Data", "48K"),
+            (1, "1", "Instruction", "32K"),
+            (2, "2", "Unified", "2048K"),
+            (3, "3", "Unified", "32M"),
+        ] {
+            let index_dir = dir.path().join(format!("index{}", index));
+            std::fs::create_dir(&index_dir).unwrap();
+            std::fs::write(index_dir.join("level"), format!("{}\n", level))
+                .unwrap();
+            std::fs::write(index_dir.join("type"), format!("{}\n", kind))
+                .unwrap();
+            std::fs::write(index_dir.join("size"), format!("{}\n", size))
+                .unwrap();
+        }
+        std::fs::write(dir.path().join("uevent"), "").unwrap();
+
+        let caches = read_cpu_caches(dir.path());
+        assert_eq!(
+            caches,
+            [
+                CpuCache {
+                    level: 1,
+                    size: 48 * 1024
+                },
+                CpuCache {
+                    level: 2,
+                    size: 2048 * 1024
+                },
+                CpuCache {
+                    level: 3,
+                    size: 32 * 1024 * 1024
+                },
+            ]
+        );
+        assert_eq!(caches[0].label(), "L1 (48 KiB)");
+        assert_eq!(caches[1].label(), "L2 (2 MiB)");
+
+        assert!(read_cpu_caches(&dir.path().join("missing")).is_empty());
+        assert_eq!(parse_cache_size("512"), Some(512));
+        assert_eq!(parse_cache_size("abcK"), None);
+    }
+}
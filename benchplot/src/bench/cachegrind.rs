@@ -0,0 +1,197 @@
+/*
+Copyright 2024 Owain Davies
+SPDX-License-Identifier: Apache-2.0 OR MIT
+*/
+
+//! Support for counting the instructions executed and the cache references
+//! made by each `(input size, function)` pair, by running it in a child
+//! process under Valgrind's Cachegrind.
+//!
+//! Each pair is run twice: once calling the function a single time, and once
+//! going through the same steps without calling it, to calibrate for the
+//! start-up of the process and the generation of the argument. The counts of
+//! the pair are the differences between the two runs, which do not depend on
+//! the load of the machine.
+
+use super::isolation::{self, Cell};
+use std::path::Path;
+use std::process::Command;
+
+/// Environment variable telling a child process whether to call the function
+/// of its pair, `call`, or only to go through the same steps, `calibrate`.
+const MODE_ENV_VAR: &str = "BENCHPLOT_CACHEGRIND";
+
+/// Counts of the instructions executed and the cache references made by a
+/// single call of a function, as simulated by Cachegrind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InstructionCounts {
+    /// Number of instructions executed.
+    pub instructions: u64,
+
+    /// Number of data reads and writes.
+    pub data_references: u64,
+
+    /// Number of misses of the first-level instruction and data caches.
+    pub l1_misses: u64,
+
+    /// Number of misses of the last-level cache.
+    pub ll_misses: u64,
+}
+
+/// What a child process running under Cachegrind does with its pair.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Mode {
+    /// Calls the function once.
+    Call,
+
+    /// Goes through the same steps without calling the function.
+    Calibrate,
+}
+
+/// Returns what to do with the pair if this process runs under Cachegrind.
+pub(crate) fn child_mode() -> Option<Mode> {
+    match std::env::var(MODE_ENV_VAR).ok()?.as_str() {
+        "call" => Some(Mode::Call),
+        "calibrate" => Some(Mode::Calibrate),
+        _ => None,
+    }
+}
+
+/// Counts the instructions and cache references of a single call of the
+/// function of the pair `cell`.
+pub(crate) fn count_cell(cell: Cell) -> Result<InstructionCounts, String> {
+    let call = run_under_cachegrind(cell, Mode::Call)?;
+    let calibration = run_under_cachegrind(cell, Mode::Calibrate)?;
+    Ok(InstructionCounts {
+        instructions: call
+            .instructions
+            .saturating_sub(calibration.instructions),
+        data_references: call
+            .data_references
+            .saturating_sub(calibration.data_references),
+        l1_misses: call.l1_misses.saturating_sub(calibration.l1_misses),
+        ll_misses: call.ll_misses.saturating_sub(calibration.ll_misses),
+    })
+}
+
+/// Runs the pair `cell` in a child process under Cachegrind, returning the
+/// counts of the whole process.
+fn run_under_cachegrind(
+    cell: Cell,
+    mode: Mode,
+) -> Result<InstructionCounts, String> {
+    let exe = std::env::current_exe().map_err(|err| err.to_string())?;
+    let out_file = std::env::temp_dir().join(format!(
+        "benchplot-cachegrind-{}-{}-{}-{}-{:?}.out",
+        std::process::id(),
+        cell.run_idx,
+        cell.size_idx,
+        cell.func_idx,
+        mode
+    ));
+    let (cell_var, cell_value) = isolation::cell_env(cell);
+    let output = Command::new("valgrind")
+        .arg("--tool=cachegrind")
+        .arg("--cache-sim=yes")
+        .arg(format!("--cachegrind-out-file={}", out_file.display()))
+        .arg(exe)
+        .args(std::env::args_os().skip(1))
+        .env(cell_var, cell_value)
+        .env(
+            MODE_ENV_VAR,
+            match mode {
+                Mode::Call => "call",
+                Mode::Calibrate => "calibrate",
+            },
+        )
+        .output()
+        .map_err(|err| format!("failed to run valgrind: {}", err))?;
+
+    let counts = read_counts(&out_file);
+    let _ = std::fs::remove_file(&out_file);
+    if !output.status.success() {
+        return Err(format!(
+            "valgrind exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    counts
+}
+
+/// Reads the total counts from a Cachegrind output file.
+fn read_counts(path: &Path) -> Result<InstructionCounts, String> {
+    let output = std::fs::read_to_string(path)
+        .map_err(|err| format!("failed to read {}: {}", path.display(), err))?;
+    parse_counts(&output).ok_or_else(|| {
+        format!("invalid cachegrind output file {}", path.display())
+    })
+}
+
+/// Parses the `events:` and `summary:` lines of a Cachegrind output file.
+fn parse_counts(output: &str) -> Option<InstructionCounts> {
+    let line = |prefix: &str| {
+        output
+            .lines()
+            .find_map(|line| line.strip_prefix(prefix))
+            .map(str::split_whitespace)
+    };
+    let events: Vec<&str> = line("events:")?.collect();
+    let totals = line("summary:")?
+        .map(|total| total.parse().ok())
+        .collect::<Option<Vec<u64>>>()?;
+    if totals.len() != events.len() {
+        return None;
+    }
+    let total = |names: &[&str]| -> u64 {
+        events
+            .iter()
+            .zip(&totals)
+            .filter(|(event, _)| names.contains(event))
+            .map(|(_, &total)| total)
+            .sum()
+    };
+    Some(InstructionCounts {
+        instructions: total(&["Ir"]),
+        data_references: total(&["Dr", "Dw"]),
+        l1_misses: total(&["I1mr", "D1mr", "D1mw"]),
+        ll_misses: total(&["ILmr", "DLmr", "DLmw"]),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_counts() {
+        let output = "desc: I1 cache: 32768 B, 64 B, 8-way associative\n\
+                      cmd: ./bench\n\
+                      events: Ir I1mr ILmr Dr D1mr DLmr Dw D1mw DLmw\n\
+                      fl=bench.rs\n\
+                      fn=main\n\
+                      1 10 1 1 4 0 0 2 0 0\n\
+                      summary: 1000 10 5 300 20 8 200 6 2\n";
+        assert_eq!(
+            parse_counts(output),
+            Some(InstructionCounts {
+                instructions: 1000,
+                data_references: 500,
+                l1_misses: 36,
+                ll_misses: 15,
+            })
+        );
+
+        // Without cache simulation, only instructions are counted.
+        assert_eq!(
+            parse_counts("events: Ir\nsummary: 42\n"),
+            Some(InstructionCounts {
+                instructions: 42,
+                ..Default::default()
+            })
+        );
+        assert_eq!(parse_counts("events: Ir Dr\nsummary: 42\n"), None);
+        assert_eq!(parse_counts("summary: 42\n"), None);
+    }
+}
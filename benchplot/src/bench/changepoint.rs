@@ -0,0 +1,158 @@
+/*
+Copyright 2024 Owain Davies
+SPDX-License-Identifier: Apache-2.0 OR MIT
+*/
+
+use crate::util;
+use crate::{Bench, Threading};
+
+/// Minimum number of input sizes on either side of a change point, so that
+/// the slopes on both sides are estimated from more than two timings.
+const MIN_SEGMENT_LEN: usize = 3;
+
+/// Largest fraction of the squared error of a single line fit that a fit of
+/// two lines may leave for the split between them to be a change point.
+const MAX_ERROR_FRACTION: f64 = 0.5;
+
+/// An input size at which the growth of the timings of a function changes
+/// abruptly, e.g., because its data no longer fits in a cache.
+///
+/// Slopes are those of the timings against the input sizes on log-log scales,
+/// i.e., the exponents `k` of the growth `n^k` of the timings: about 1 for
+/// linear growth and 2 for quadratic growth.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChangePoint {
+    /// The index of the function, in the order of
+    /// [`Bench::function_names`].
+    pub function: usize,
+
+    /// The input size at which the growth changes.
+    pub size: usize,
+
+    /// The slope of the timings up to `size`.
+    pub slope_before: f64,
+
+    /// The slope of the timings from `size` on.
+    pub slope_after: f64,
+}
+
+impl<T, R, M: Threading> Bench<'_, T, R, M> {
+    /// Returns the input sizes at which the slope of the timings of a
+    /// function changes by at least `min_slope_change`, e.g., `0.5`, ordered
+    /// by function and then by input size.
+    ///
+    /// The timings of each function are split recursively at the input size
+    /// where fitting a separate line on each side best reduces the error of
+    /// fitting a single line on log-log scales. Each side must span at least
+    /// three input sizes, and non-positive timings are ignored.
+    pub fn change_points(&self, min_slope_change: f64) -> Vec<ChangePoint> {
+        let mut change_points = Vec::new();
+        for function in 0..self.functions.len() {
+            let mut points: Vec<(usize, f64)> = self
+                .data
+                .iter()
+                .map(|(size, timings)| (size, timings[function]))
+                .filter(|&(size, timing)| size > 0 && timing > 0.0)
+                .collect();
+            points.sort_by_key(|&(size, _)| size);
+            let log_points: Vec<(f64, f64)> = points
+                .iter()
+                .map(|&(size, timing)| ((size as f64).ln(), timing.ln()))
+                .collect();
+
+            let mut splits = Vec::new();
+            split(&log_points, 0, min_slope_change, &mut splits);
+            splits.sort_unstable();
+
+            let mut bounds = vec![0];
+            bounds.extend(&splits);
+            bounds.push(log_points.len().saturating_sub(1));
+            for (i, &split) in splits.iter().enumerate() {
+                let slope_of = |start: usize, end: usize| {
+                    util::linear_fit(&log_points[start..=end]).0
+                };
+                change_points.push(ChangePoint {
+                    function,
+                    size: points[split].0,
+                    slope_before: slope_of(bounds[i], split),
+                    slope_after: slope_of(split, bounds[i + 2]),
+                });
+            }
+        }
+        change_points
+    }
+}
+
+/// Pushes to `splits` the indices, offset by `offset`, of the change points of
+/// `points`, found by binary segmentation.
+///
+/// The point at a split belongs to the segments on both of its sides.
+fn split(
+    points: &[(f64, f64)],
+    offset: usize,
+    min_slope_change: f64,
+    splits: &mut Vec<usize>,
+) {
+    if points.len() < 2 * MIN_SEGMENT_LEN - 1 {
+        return;
+    }
+    let best = (MIN_SEGMENT_LEN - 1..=points.len() - MIN_SEGMENT_LEN)
+        .map(|k| {
+            let error =
+                squared_error(&points[..=k]) + squared_error(&points[k..]);
+            (k, error)
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1));
+    let Some((k, error)) = best else {
+        return;
+    };
+
+    let slope_change =
+        util::linear_fit(&points[k..]).0 - util::linear_fit(&points[..=k]).0;
+    if slope_change.abs() < min_slope_change
+        || error > MAX_ERROR_FRACTION * squared_error(points)
+    {
+        return;
+    }
+    splits.push(offset + k);
+    split(&points[..=k], offset, min_slope_change, splits);
+    split(&points[k..], offset + k, min_slope_change, splits);
+}
+
+/// Returns the sum of the squared residuals of the least-squares line through
+/// `points`.
+fn squared_error(points: &[(f64, f64)]) -> f64 {
+    let (slope, intercept) = util::linear_fit(points);
+    points
+        .iter()
+        .map(|&(x, y)| (y - (slope * x + intercept)).powi(2))
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split() {
+        // Linear growth up to x = 4, then quadratic growth.
+        let points: Vec<(f64, f64)> = (0..10)
+            .map(f64::from)
+            .map(|x| (x, if x <= 4.0 { x } else { 4.0 + 2.0 * (x - 4.0) }))
+            .collect();
+        let mut splits = Vec::new();
+        split(&points, 0, 0.5, &mut splits);
+        assert_eq!(splits, vec![4]);
+
+        let mut splits = Vec::new();
+        split(&points, 0, 1.5, &mut splits);
+        assert!(splits.is_empty());
+
+        let line: Vec<(f64, f64)> =
+            (0..10).map(f64::from).map(|x| (x, 3.0 * x)).collect();
+        let mut splits = Vec::new();
+        split(&line, 0, 0.5, &mut splits);
+        assert!(splits.is_empty());
+    }
+}
@@ -0,0 +1,218 @@
+/*
+Copyright 2024 Owain Davies
+SPDX-License-Identifier: Apache-2.0 OR MIT
+*/
+
+use crate::{util, Baseline, BenchData};
+use std::fmt;
+
+/// The timing of a function at an input size in two sets of results, e.g.,
+/// before and after a change, as returned by [`Baseline::changes`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CellChange {
+    /// The name of the function.
+    pub function: String,
+
+    /// The input size.
+    pub size: usize,
+
+    /// The timing in seconds in the older results.
+    pub old: f64,
+
+    /// The timing in seconds in the newer results.
+    pub new: f64,
+}
+
+impl CellChange {
+    /// Returns the newer timing divided by the older one, e.g., 1.2 if the
+    /// function got 20% slower.
+    pub fn ratio(&self) -> f64 {
+        self.new / self.old
+    }
+
+    /// Returns whether the function got slower.
+    pub fn is_regression(&self) -> bool {
+        self.new > self.old
+    }
+}
+
+impl fmt::Display for CellChange {
+    /// Formats the change as, e.g.,
+    /// `Merge Sort, n = 1024: 15.10 µs -> 18.20 µs (+20.5%)`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}, n = {}: {} -> {} ({:+.1}%)",
+            self.function,
+            self.size,
+            util::format_seconds(self.old),
+            util::format_seconds(self.new),
+            (self.ratio() - 1.0) * 100.0
+        )
+    }
+}
+
+impl Baseline {
+    /// Returns the timings that changed by more than the fraction
+    /// `threshold` from these results to `newer`, e.g., by more than 5% for
+    /// a threshold of 0.05.
+    ///
+    /// Only the functions and input sizes present in both results with
+    /// positive timings are compared. The changes are in the order of the
+    /// functions and input sizes of `newer`.
+    pub fn changes(&self, newer: &Baseline, threshold: f64) -> Vec<CellChange> {
+        let mut changes = Vec::new();
+        for (new_fn, function) in newer.functions().iter().enumerate() {
+            let Some(old_fn) =
+                self.functions().iter().position(|name| name == function)
+            else {
+                continue;
+            };
+            for (new_size, &size) in newer.data().sizes().iter().enumerate() {
+                let Some(old_size) =
+                    self.data().sizes().iter().position(|&s| s == size)
+                else {
+                    continue;
+                };
+                let change = CellChange {
+                    function: function.clone(),
+                    size,
+                    old: self.data().time(old_fn, old_size),
+                    new: newer.data().time(new_fn, new_size),
+                };
+                let valid = change.old > 0.0 && change.new > 0.0;
+                if valid && (change.ratio() - 1.0).abs() > threshold {
+                    changes.push(change);
+                }
+            }
+        }
+        changes
+    }
+
+    /// Returns the timings of `newer` divided by these, e.g., 1.2 where a
+    /// function got 20% slower, as results to plot the changes with.
+    ///
+    /// The ratios are those of the functions and input sizes present in both
+    /// results, in the order of `newer`, and are `NaN` unless both timings
+    /// are positive. The label of `newer` is kept.
+    pub fn ratios(&self, newer: &Baseline) -> Baseline {
+        let functions: Vec<(usize, usize)> = newer
+            .functions()
+            .iter()
+            .enumerate()
+            .filter_map(|(new_fn, function)| {
+                let old_fn = self
+                    .functions()
+                    .iter()
+                    .position(|name| name == function)?;
+                Some((new_fn, old_fn))
+            })
+            .collect();
+        let mut data = BenchData::new(functions.len());
+        for (new_size, &size) in newer.data().sizes().iter().enumerate() {
+            let Some(old_size) =
+                self.data().sizes().iter().position(|&s| s == size)
+            else {
+                continue;
+            };
+            let ratios: Vec<f64> = functions
+                .iter()
+                .map(|&(new_fn, old_fn)| {
+                    let old = self.data().time(old_fn, old_size);
+                    let new = newer.data().time(new_fn, new_size);
+                    if old > 0.0 && new > 0.0 {
+                        new / old
+                    } else {
+                        f64::NAN
+                    }
+                })
+                .collect();
+            data.push_row(size, &ratios);
+        }
+        let names = functions
+            .iter()
+            .map(|&(new_fn, _)| newer.functions()[new_fn].clone())
+            .collect();
+        let ratios = Baseline::new(names, data);
+        match newer.label() {
+            Some(label) => ratios.with_label(label),
+            None => ratios,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn old_and_new() -> (Baseline, Baseline) {
+        let old = Baseline::new(
+            vec!["A".to_string(), "B".to_string(), "Removed".to_string()],
+            BenchData::from_rows(
+                3,
+                [(10, vec![1e-6, 1e-6, 1.0]), (100, vec![1e-5, 1e-5, 1.0])],
+            )
+            .unwrap(),
+        );
+        let new = Baseline::new(
+            vec!["B".to_string(), "A".to_string(), "Added".to_string()],
+            BenchData::from_rows(
+                3,
+                [
+                    (10, vec![1.02e-6, 1.5e-6, 1.0]),
+                    (100, vec![0.5e-5, f64::NAN, 1.0]),
+                    (1000, vec![1.0, 1.0, 1.0]),
+                ],
+            )
+            .unwrap(),
+        )
+        .with_label("new");
+        (old, new)
+    }
+
+    #[test]
+    fn test_changes() {
+        let (old, new) = old_and_new();
+        let changes = old.changes(&new, 0.05);
+        assert_eq!(
+            changes,
+            [
+                CellChange {
+                    function: "B".to_string(),
+                    size: 100,
+                    old: 1e-5,
+                    new: 0.5e-5,
+                },
+                CellChange {
+                    function: "A".to_string(),
+                    size: 10,
+                    old: 1e-6,
+                    new: 1.5e-6,
+                },
+            ]
+        );
+        assert!(!changes[0].is_regression());
+        assert!(changes[1].is_regression());
+        assert_eq!(
+            changes[0].to_string(),
+            "B, n = 100: 10.00 µs -> 5.00 µs (-50.0%)"
+        );
+        assert_eq!(old.changes(&new, 0.0).len(), 3);
+    }
+
+    #[test]
+    fn test_ratios() {
+        let (old, new) = old_and_new();
+        let ratios = old.ratios(&new);
+        assert_eq!(ratios.functions(), ["B", "A"]);
+        assert_eq!(ratios.label(), Some("new"));
+
+        let data = ratios.data();
+        assert_eq!(data.sizes(), [10, 100]);
+        assert!((data.time(0, 0) - 1.02).abs() < 1e-12);
+        assert!((data.time(1, 0) - 1.5).abs() < 1e-12);
+        assert!((data.time(0, 1) - 0.5).abs() < 1e-12);
+        assert!(data.time(1, 1).is_nan());
+    }
+}
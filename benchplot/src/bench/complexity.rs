@@ -0,0 +1,225 @@
+/*
+Copyright 2024 Owain Davies
+SPDX-License-Identifier: Apache-2.0 OR MIT
+*/
+
+use crate::util;
+use crate::{Bench, Threading};
+
+/// An asymptotic complexity class, used to normalize timings by the expected
+/// growth of a function's running time.
+///
+/// When timings are divided by the growth function of the complexity class an
+/// implementation belongs to, its curve becomes flat, so that deviations from
+/// the expected complexity and constant-factor differences between
+/// implementations stand out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Complexity {
+    /// Linear growth, `n`.
+    Linear,
+
+    /// Linearithmic growth, `n log₂ n`.
+    Linearithmic,
+
+    /// Quadratic growth, `n²`.
+    Quadratic,
+}
+
+impl Complexity {
+    /// Every complexity class, from the slowest growing.
+    pub const ALL: [Complexity; 3] = [
+        Complexity::Linear,
+        Complexity::Linearithmic,
+        Complexity::Quadratic,
+    ];
+
+    /// Returns the value of the growth function at the input size `n`.
+    ///
+    /// To keep normalized timings finite, `n` is treated as at least 1 and,
+    /// for [`Complexity::Linearithmic`], `log₂ n` as at least 1.
+    pub fn growth(self, n: usize) -> f64 {
+        let n = n.max(1) as f64;
+        match self {
+            Complexity::Linear => n,
+            Complexity::Linearithmic => n * n.log2().max(1.0),
+            Complexity::Quadratic => n * n,
+        }
+    }
+
+    /// Returns the growth function as written in an axis description, e.g.,
+    /// `n log n`.
+    #[cfg(feature = "plot")]
+    pub(crate) fn notation(self) -> &'static str {
+        match self {
+            Complexity::Linear => "n",
+            Complexity::Linearithmic => "(n log n)",
+            Complexity::Quadratic => "n²",
+        }
+    }
+}
+
+/// A fit of the timings of a function to a complexity class, modeling the
+/// timing at the input size `n` as `intercept + coefficient * growth(n)`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ComplexityFit {
+    /// The complexity class fitted.
+    pub complexity: Complexity,
+
+    /// The time in seconds per unit of growth.
+    pub coefficient: f64,
+
+    /// The constant overhead in seconds.
+    pub intercept: f64,
+
+    /// The coefficient of determination, the fraction of the variance of the
+    /// timings that the fit explains: 1 for a perfect fit and 0 or less for
+    /// a fit no better than the average timing.
+    #[cfg_attr(
+        feature = "serde",
+        serde(deserialize_with = "crate::util::deserialize_nan")
+    )]
+    pub r_squared: f64,
+
+    /// The difference in seconds between the measured and the fitted timing
+    /// at each input size, as `(input size, residual)` in the order of
+    /// [`Bench::data`].
+    pub residuals: Vec<(usize, f64)>,
+}
+
+impl<T, R, M: Threading> Bench<'_, T, R, M> {
+    /// Fits the timings of each function to every [`Complexity`] class by
+    /// least squares, returning the fits of each function, in the order of
+    /// [`Bench::function_names`], from the best to the worst.
+    ///
+    /// Fits are only meaningful with at least three input sizes, and a low
+    /// `r_squared` of the best fit of a function may indicate noisy or
+    /// bimodal timings rather than a different complexity class.
+    pub fn complexity_fits(&self) -> Vec<Vec<ComplexityFit>> {
+        (0..self.functions.len())
+            .map(|function| {
+                let points: Vec<(usize, f64)> = self
+                    .data
+                    .iter()
+                    .map(|(size, timings)| (size, timings[function]))
+                    .filter(|(_, timing)| !timing.is_nan())
+                    .collect();
+                let mut fits: Vec<ComplexityFit> = Complexity::ALL
+                    .iter()
+                    .map(|&complexity| fit(complexity, &points))
+                    .collect();
+                fits.sort_by(|a, b| b.r_squared.total_cmp(&a.r_squared));
+                fits
+            })
+            .collect()
+    }
+
+    /// Returns the indices of the functions whose timings fit no complexity
+    /// class with an `r_squared` of at least `min_r_squared`, e.g., `0.9`.
+    pub fn poorly_fit_functions(&self, min_r_squared: f64) -> Vec<usize> {
+        self.complexity_fits()
+            .iter()
+            .enumerate()
+            .filter(|(_, fits)| {
+                let best = fits[0].r_squared;
+                best.is_nan() || best < min_r_squared
+            })
+            .map(|(function, _)| function)
+            .collect()
+    }
+}
+
+/// Fits the `(input size, timing)` points to `complexity`.
+fn fit(complexity: Complexity, points: &[(usize, f64)]) -> ComplexityFit {
+    let growth_points: Vec<(f64, f64)> = points
+        .iter()
+        .map(|&(size, timing)| (complexity.growth(size), timing))
+        .collect();
+    let (coefficient, intercept) = util::linear_fit(&growth_points);
+    let residuals: Vec<(usize, f64)> = points
+        .iter()
+        .zip(&growth_points)
+        .map(|(&(size, timing), &(growth, _))| {
+            (size, timing - (intercept + coefficient * growth))
+        })
+        .collect();
+
+    let mean =
+        points.iter().map(|&(_, t)| t).sum::<f64>() / points.len() as f64;
+    let total: f64 = points.iter().map(|&(_, t)| (t - mean).powi(2)).sum();
+    let residual: f64 = residuals.iter().map(|&(_, r)| r * r).sum();
+    let r_squared = if total > 0.0 {
+        1.0 - residual / total
+    } else if residual == 0.0 {
+        1.0
+    } else {
+        f64::NAN
+    };
+    ComplexityFit {
+        complexity,
+        coefficient,
+        intercept,
+        r_squared,
+        residuals,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_growth() {
+        assert_eq!(Complexity::Linear.growth(8), 8.0);
+        assert_eq!(Complexity::Linearithmic.growth(8), 24.0);
+        assert_eq!(Complexity::Quadratic.growth(8), 64.0);
+
+        assert_eq!(Complexity::Linear.growth(0), 1.0);
+        assert_eq!(Complexity::Linearithmic.growth(1), 1.0);
+        assert_eq!(Complexity::Linearithmic.growth(2), 2.0);
+    }
+
+    #[test]
+    fn test_fit() {
+        let points: Vec<(usize, f64)> = [10, 100, 1000, 10000]
+            .iter()
+            .map(|&n| (n, 1e-3 + 2e-9 * (n * n) as f64))
+            .collect();
+
+        let quadratic = fit(Complexity::Quadratic, &points);
+        assert!((quadratic.r_squared - 1.0).abs() < 1e-9);
+        assert!((quadratic.coefficient - 2e-9).abs() < 1e-15);
+        assert!((quadratic.intercept - 1e-3).abs() < 1e-9);
+        assert!(quadratic.residuals.iter().all(|&(_, r)| r.abs() < 1e-9));
+
+        let linear = fit(Complexity::Linear, &points);
+        assert!(linear.r_squared < quadratic.r_squared);
+        assert_eq!(linear.residuals.len(), 4);
+    }
+
+    #[test]
+    fn test_poorly_fit_functions() {
+        use crate::{BenchBuilder, BenchData, BenchFnArg, BenchFnNamed};
+
+        let functions: Vec<BenchFnNamed<'static, usize, usize>> =
+            vec![(Box::new(|x| x), "Linear"), (Box::new(|x| x), "Bimodal")];
+        let argfunc: BenchFnArg<usize> = Box::new(|x| x);
+        let sizes = vec![1, 2, 3, 4, 5, 6];
+        let mut bench = BenchBuilder::new(functions, argfunc, sizes.clone())
+            .build()
+            .unwrap();
+        bench.data = BenchData::from_rows(
+            2,
+            sizes.iter().map(|&n| {
+                let bimodal = if n % 2 == 1 { 1.0 } else { 100.0 };
+                (n, vec![n as f64, bimodal])
+            }),
+        )
+        .unwrap();
+
+        let fits = bench.complexity_fits();
+        assert_eq!(fits[0][0].complexity, Complexity::Linear);
+        assert_eq!(bench.poorly_fit_functions(0.9), vec![1]);
+    }
+}
@@ -0,0 +1,365 @@
+/*
+Copyright 2024 Owain Davies
+SPDX-License-Identifier: Apache-2.0 OR MIT
+*/
+
+use std::io::{self, ErrorKind};
+use std::time::Duration;
+
+/// Average timings in seconds of a benchmark, as a matrix with a row per input
+/// size and a column per function.
+///
+/// Every row has exactly one timing per function.
+///
+/// ```
+/// use benchplot::BenchData;
+///
+/// let mut data = BenchData::new(2);
+/// data.push_row(10, &[1e-6, 2e-6]);
+/// data.push_row(100, &[1e-5, 4e-5]);
+///
+/// assert_eq!(data.sizes(), [10, 100]);
+/// assert_eq!(data.time(1, 0), 2e-6);
+/// assert_eq!(data.column(0).collect::<Vec<_>>(), [1e-6, 1e-5]);
+/// ```
+///
+/// Timings are stored as seconds in `f64` rather than as [`Duration`]s, with
+/// `NaN` marking a missing timing, e.g., of a function skipped at an input
+/// size, since the same matrix holds the values of other metrics, e.g.,
+/// allocated bytes, returned by [`Bench::metric`](crate::Bench::metric). The
+/// `duration` accessors return timings as [`Duration`]s, so that units
+/// cannot be confused when post-processing the results:
+///
+/// ```
+/// use benchplot::BenchData;
+/// use std::time::Duration;
+///
+/// let data = BenchData::from_rows(2, [(10, vec![1e-6, f64::NAN])]).unwrap();
+///
+/// assert_eq!(data.duration(0, 0), Some(Duration::from_micros(1)));
+/// assert_eq!(data.duration(1, 0), None);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "BenchDataFields")
+)]
+pub struct BenchData {
+    num_functions: usize,
+    sizes: Vec<usize>,
+    times: Vec<f64>,
+}
+
+/// Fields of a deserialized [`BenchData`], which are checked to form a matrix
+/// before they are accepted.
+///
+/// Missing timings are serialized as `null` by formats without `NaN`, such as
+/// JSON, and are read back as `NaN`.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct BenchDataFields {
+    num_functions: usize,
+    sizes: Vec<usize>,
+    times: Vec<Option<f64>>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<BenchDataFields> for BenchData {
+    type Error = &'static str;
+
+    fn try_from(fields: BenchDataFields) -> Result<Self, Self::Error> {
+        if fields.times.len() != fields.sizes.len() * fields.num_functions {
+            return Err("rows must have one timing per function");
+        }
+        Ok(Self {
+            num_functions: fields.num_functions,
+            sizes: fields.sizes,
+            times: fields
+                .times
+                .into_iter()
+                .map(|time| time.unwrap_or(f64::NAN))
+                .collect(),
+        })
+    }
+}
+
+impl BenchData {
+    /// Creates an empty matrix with a column per function.
+    pub fn new(num_functions: usize) -> Self {
+        Self {
+            num_functions,
+            sizes: Vec::new(),
+            times: Vec::new(),
+        }
+    }
+
+    /// Creates a matrix from rows pairing an input size with the timing of
+    /// each function, returning `None` if a row does not have exactly
+    /// `num_functions` timings.
+    pub fn from_rows<I>(num_functions: usize, rows: I) -> Option<Self>
+    where
+        I: IntoIterator<Item = (usize, Vec<f64>)>,
+    {
+        let mut data = Self::new(num_functions);
+        for (size, times) in rows {
+            if times.len() != num_functions {
+                return None;
+            }
+            data.push_row(size, &times);
+        }
+        Some(data)
+    }
+
+    /// Appends a row with the timing of each function at the input size
+    /// `size`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `times` does not have one timing per function.
+    pub fn push_row(&mut self, size: usize, times: &[f64]) {
+        assert_eq!(
+            times.len(),
+            self.num_functions,
+            "row of {} timings in data of {} functions",
+            times.len(),
+            self.num_functions
+        );
+        self.sizes.push(size);
+        self.times.extend_from_slice(times);
+    }
+
+    /// Returns the number of functions, i.e., the number of columns.
+    pub fn num_functions(&self) -> usize {
+        self.num_functions
+    }
+
+    /// Returns the number of input sizes, i.e., the number of rows.
+    pub fn num_sizes(&self) -> usize {
+        self.sizes.len()
+    }
+
+    /// Returns `true` if there are no rows.
+    pub fn is_empty(&self) -> bool {
+        self.sizes.is_empty()
+    }
+
+    /// Returns the input sizes, in the order of the rows.
+    pub fn sizes(&self) -> &[usize] {
+        &self.sizes
+    }
+
+    /// Returns the timing of the function at index `fn_idx` at the input size
+    /// at index `size_idx`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either index is out of bounds.
+    pub fn time(&self, fn_idx: usize, size_idx: usize) -> f64 {
+        self.row(size_idx)[fn_idx]
+    }
+
+    /// Returns the timing of the function at index `fn_idx` at the input size
+    /// at index `size_idx` as a [`Duration`], or `None` if it is missing.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either index is out of bounds.
+    pub fn duration(&self, fn_idx: usize, size_idx: usize) -> Option<Duration> {
+        to_duration(self.time(fn_idx, size_idx))
+    }
+
+    /// Returns an iterator over the timings of the function at index `fn_idx`
+    /// as [`Duration`]s, in the order of the rows, with `None` for missing
+    /// timings.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fn_idx` is out of bounds.
+    pub fn durations(
+        &self,
+        fn_idx: usize,
+    ) -> impl Iterator<Item = Option<Duration>> + '_ {
+        self.column(fn_idx).map(to_duration)
+    }
+
+    /// Returns the timing of each function at the input size at index
+    /// `size_idx`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size_idx` is out of bounds.
+    pub fn row(&self, size_idx: usize) -> &[f64] {
+        assert!(size_idx < self.sizes.len(), "size index out of bounds");
+        let start = size_idx * self.num_functions;
+        &self.times[start..start + self.num_functions]
+    }
+
+    /// Returns an iterator over the timings of the function at index `fn_idx`,
+    /// in the order of the rows.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fn_idx` is out of bounds.
+    pub fn column(&self, fn_idx: usize) -> impl Iterator<Item = f64> + '_ {
+        assert!(fn_idx < self.num_functions, "function index out of bounds");
+        (0..self.sizes.len()).map(move |size_idx| self.time(fn_idx, size_idx))
+    }
+
+    /// Returns an iterator over the rows, each pairing an input size with the
+    /// timing of each function.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &[f64])> + '_ {
+        (0..self.sizes.len())
+            .map(|size_idx| (self.sizes[size_idx], self.row(size_idx)))
+    }
+
+    /// Shortens the matrix to its first `num_sizes` rows.
+    pub(crate) fn truncate(&mut self, num_sizes: usize) {
+        self.sizes.truncate(num_sizes);
+        self.times.truncate(self.sizes.len() * self.num_functions);
+    }
+
+    /// Appends a tab-separated line per row, with the input size followed by
+    /// the timing of each function.
+    pub(crate) fn write_tsv(&self, tsv: &mut String) {
+        for (size, times) in self.iter() {
+            tsv.push_str(&size.to_string());
+            for time in times {
+                tsv.push('\t');
+                tsv.push_str(&time.to_string());
+            }
+            tsv.push('\n');
+        }
+    }
+
+    /// Parses lines written by [`BenchData::write_tsv`], skipping empty lines.
+    pub(crate) fn parse_tsv<'l, I>(
+        lines: I,
+        num_functions: usize,
+    ) -> io::Result<Self>
+    where
+        I: IntoIterator<Item = &'l str>,
+    {
+        let invalid = |msg: &str| io::Error::new(ErrorKind::InvalidData, msg);
+
+        let mut data = Self::new(num_functions);
+        for line in lines.into_iter().filter(|line| !line.is_empty()) {
+            let mut fields = line.split('\t');
+            let size = fields
+                .next()
+                .and_then(|field| field.parse().ok())
+                .ok_or_else(|| invalid("invalid input size"))?;
+            let times = fields
+                .map(|field| field.parse().ok())
+                .collect::<Option<Vec<f64>>>()
+                .ok_or_else(|| invalid("invalid timing"))?;
+            if times.len() != num_functions {
+                return Err(invalid("wrong number of timings"));
+            }
+            data.push_row(size, &times);
+        }
+        Ok(data)
+    }
+}
+
+/// Converts a timing in seconds to a [`Duration`], returning `None` if it is
+/// missing, i.e., `NaN`, or otherwise not a valid duration.
+pub(crate) fn to_duration(seconds: f64) -> Option<Duration> {
+    Duration::try_from_secs_f64(seconds).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accessors() {
+        let data = BenchData::from_rows(
+            2,
+            [(10, vec![1.0, 2.0]), (20, vec![3.0, 4.0])],
+        )
+        .unwrap();
+
+        assert_eq!(data.num_functions(), 2);
+        assert_eq!(data.num_sizes(), 2);
+        assert_eq!(data.sizes(), [10, 20]);
+        assert_eq!(data.time(0, 1), 3.0);
+        assert_eq!(data.row(1), [3.0, 4.0]);
+        assert_eq!(data.column(1).collect::<Vec<_>>(), [2.0, 4.0]);
+        assert_eq!(
+            data.iter().collect::<Vec<_>>(),
+            [(10, &[1.0, 2.0][..]), (20, &[3.0, 4.0][..])]
+        );
+    }
+
+    #[test]
+    fn test_durations() {
+        let data = BenchData::from_rows(
+            2,
+            [(10, vec![0.5, f64::NAN]), (20, vec![2.0, -1.0])],
+        )
+        .unwrap();
+
+        assert_eq!(data.duration(0, 0), Some(Duration::from_millis(500)));
+        assert_eq!(data.duration(1, 0), None);
+        assert_eq!(
+            data.durations(0).collect::<Vec<_>>(),
+            [
+                Some(Duration::from_millis(500)),
+                Some(Duration::from_secs(2))
+            ]
+        );
+        assert_eq!(data.durations(1).collect::<Vec<_>>(), [None, None]);
+    }
+
+    #[test]
+    fn test_rows_have_one_timing_per_function() {
+        assert!(BenchData::from_rows(2, [(10, vec![1.0])]).is_none());
+
+        let result = std::panic::catch_unwind(|| {
+            BenchData::new(2).push_row(10, &[1.0, 2.0, 3.0]);
+        });
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_tsv_round_trip() {
+        let data = BenchData::from_rows(
+            2,
+            [(10, vec![1e-6, 2.5e-6]), (100, vec![1.5e-5, 0.25])],
+        )
+        .unwrap();
+        let mut tsv = String::new();
+        data.write_tsv(&mut tsv);
+
+        assert_eq!(BenchData::parse_tsv(tsv.lines(), 2).unwrap(), data);
+        assert!(BenchData::parse_tsv(["x\t1.0"], 1).is_err());
+        assert!(BenchData::parse_tsv(["10\t1.0\t2.0"], 1).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let data = BenchData::from_rows(2, [(10, vec![1e-6, 2.5e-6])]).unwrap();
+        let json = serde_json::to_string(&data).unwrap();
+
+        assert_eq!(serde_json::from_str::<BenchData>(&json).unwrap(), data);
+
+        // A missing timing round-trips through `null`.
+        let data =
+            BenchData::from_rows(2, [(10, vec![1e-6, f64::NAN])]).unwrap();
+        let json = serde_json::to_string(&data).unwrap();
+        assert_eq!(
+            json,
+            r#"{"num_functions":2,"sizes":[10],"times":[1e-6,null]}"#
+        );
+        let loaded = serde_json::from_str::<BenchData>(&json).unwrap();
+        assert_eq!(loaded.sizes(), [10]);
+        assert_eq!(loaded.time(0, 0), 1e-6);
+        assert!(loaded.time(1, 0).is_nan());
+        assert!(serde_json::from_str::<BenchData>(
+            r#"{"num_functions":2,"sizes":[10],"times":[1.0]}"#
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,145 @@
+/*
+Copyright 2024 Owain Davies
+SPDX-License-Identifier: Apache-2.0 OR MIT
+*/
+
+//! Support for appending the results of every run to a SQLite database, for a
+//! queryable history of a benchmark.
+//!
+//! The database has three tables:
+//!
+//! - `runs`: one row per run, with its `id`, its `label`, if any, and the
+//!   time it started at, `started_at`, in seconds since the Unix epoch.
+//! - `cells`: one row per `(input size, function)` pair of a run, with its
+//!   `id`, the `run_id`, the `size`, the `function` name, and the `mean`
+//!   timing in seconds.
+//! - `samples`: one row per repetition of a pair, with the `cell_id`, the
+//!   0-based `repetition`, and the timing in `seconds`.
+
+use super::aggregation::to_nanos;
+use crate::Aggregation;
+use rusqlite::{params, Connection};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS runs (
+        id INTEGER PRIMARY KEY,
+        label TEXT,
+        started_at INTEGER NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS cells (
+        id INTEGER PRIMARY KEY,
+        run_id INTEGER NOT NULL REFERENCES runs (id),
+        size INTEGER NOT NULL,
+        function TEXT NOT NULL,
+        mean REAL NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS samples (
+        cell_id INTEGER NOT NULL REFERENCES cells (id),
+        repetition INTEGER NOT NULL,
+        seconds REAL NOT NULL
+    );
+";
+
+/// A run recorded in a results database.
+pub(crate) struct ResultsDatabase {
+    connection: Connection,
+    run_id: i64,
+}
+
+impl ResultsDatabase {
+    /// Opens the database, creating it and its tables if they do not exist,
+    /// and records the start of a run labeled `label`.
+    pub(crate) fn open(
+        path: &Path,
+        label: Option<&str>,
+    ) -> rusqlite::Result<Self> {
+        let connection = Connection::open(path)?;
+        connection.execute_batch(SCHEMA)?;
+        let started_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs() as i64);
+        connection.execute(
+            "INSERT INTO runs (label, started_at) VALUES (?1, ?2)",
+            params![label, started_at],
+        )?;
+        let run_id = connection.last_insert_rowid();
+        Ok(Self { connection, run_id })
+    }
+
+    /// Records the timings of a pair, in a single transaction so that a pair
+    /// is recorded either with all of its samples or not at all.
+    pub(crate) fn insert_cell(
+        &mut self,
+        size: usize,
+        function: &str,
+        times: &[f64],
+    ) -> rusqlite::Result<()> {
+        let mean = Aggregation::Mean.aggregate_nanos(&to_nanos(times));
+        let transaction = self.connection.transaction()?;
+        transaction.execute(
+            "INSERT INTO cells (run_id, size, function, mean)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![self.run_id, size as i64, function, mean],
+        )?;
+        let cell_id = transaction.last_insert_rowid();
+        {
+            let mut insert_sample = transaction.prepare(
+                "INSERT INTO samples (cell_id, repetition, seconds)
+                 VALUES (?1, ?2, ?3)",
+            )?;
+            for (repetition, &seconds) in times.iter().enumerate() {
+                insert_sample.execute(params![
+                    cell_id,
+                    repetition as i64,
+                    seconds
+                ])?;
+            }
+        }
+        transaction.commit()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BenchBuilder, BenchFnArg, BenchFnNamed};
+    use rusqlite::Connection;
+
+    #[test]
+    fn test_runs_are_appended() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("results.sqlite");
+
+        for parallel in [false, true] {
+            let functions: Vec<BenchFnNamed<'static, usize, usize>> = vec![
+                (Box::new(|x| x * 2), "Double"),
+                (Box::new(|x| x * x), "Square"),
+            ];
+            let argfunc: BenchFnArg<usize> = Box::new(|x| x);
+            BenchBuilder::new(functions, argfunc, vec![10, 100])
+                .repetitions(3)
+                .parallel(parallel)
+                .label("v0.1")
+                .results_database(&path)
+                .build()
+                .unwrap()
+                .run();
+        }
+
+        let connection = Connection::open(&path).unwrap();
+        let count = |query: &str| -> i64 {
+            connection.query_row(query, [], |row| row.get(0)).unwrap()
+        };
+        assert_eq!(count("SELECT COUNT(*) FROM runs WHERE label = 'v0.1'"), 2);
+        assert_eq!(count("SELECT COUNT(*) FROM cells"), 8);
+        assert_eq!(count("SELECT COUNT(*) FROM samples"), 24);
+        assert_eq!(
+            count(
+                "SELECT COUNT(*) FROM cells
+                 WHERE run_id = 2 AND function = 'Square' AND size = 100"
+            ),
+            1
+        );
+    }
+}
@@ -0,0 +1,85 @@
+/*
+Copyright 2024 Owain Davies
+SPDX-License-Identifier: Apache-2.0 OR MIT
+*/
+
+//! Conversion of benchmark results to [polars](https://pola.rs) data frames.
+
+use crate::{Baseline, Bench, BenchData, Threading};
+use polars::prelude::{Column, DataFrame, PolarsResult};
+
+impl<T, R, M: Threading> Bench<'_, T, R, M> {
+    /// Returns the measured average timings as a data frame, e.g., to join
+    /// them with other datasets.
+    ///
+    /// The data frame has an `n` column with the input sizes, followed by a
+    /// column per function, named after the function, with its timings in
+    /// seconds. It has no rows until `run()` is called.
+    ///
+    /// Returns an error if two functions have the same name.
+    pub fn to_dataframe(&self) -> PolarsResult<DataFrame> {
+        dataframe(&self.function_names(), &self.data)
+    }
+}
+
+impl Baseline {
+    /// Returns the timings as a data frame, laid out as by
+    /// [`Bench::to_dataframe`].
+    ///
+    /// Returns an error if two functions have the same name.
+    pub fn to_dataframe(&self) -> PolarsResult<DataFrame> {
+        let names: Vec<&str> =
+            self.functions().iter().map(String::as_str).collect();
+        dataframe(&names, self.data())
+    }
+}
+
+/// Returns a data frame with an `n` column with the input sizes of `data`,
+/// followed by a column of timings per function, named `names`.
+fn dataframe(names: &[&str], data: &BenchData) -> PolarsResult<DataFrame> {
+    let sizes: Vec<u64> =
+        data.sizes().iter().map(|&size| size as u64).collect();
+    let mut columns = vec![Column::new("n".into(), sizes)];
+    for (fn_idx, &name) in names.iter().enumerate() {
+        let times: Vec<f64> = data.column(fn_idx).collect();
+        columns.push(Column::new(name.into(), times));
+    }
+    DataFrame::new(columns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_baseline_to_dataframe() {
+        let baseline = Baseline::new(
+            vec!["Merge Sort".to_string(), "Bubble Sort".to_string()],
+            BenchData::from_rows(
+                2,
+                [(10, vec![1.0, 2.0]), (100, vec![3.0, 4.0])],
+            )
+            .unwrap(),
+        );
+        let df = baseline.to_dataframe().unwrap();
+
+        assert_eq!(df.get_column_names(), ["n", "Merge Sort", "Bubble Sort"]);
+        assert_eq!(
+            df.column("n").unwrap().u64().unwrap().to_vec(),
+            [Some(10), Some(100)]
+        );
+        assert_eq!(
+            df.column("Bubble Sort").unwrap().f64().unwrap().to_vec(),
+            [Some(2.0), Some(4.0)]
+        );
+    }
+
+    #[test]
+    fn test_duplicate_names() {
+        let baseline = Baseline::new(
+            vec!["A".to_string(), "A".to_string()],
+            BenchData::from_rows(2, [(10, vec![1.0, 2.0])]).unwrap(),
+        );
+        assert!(baseline.to_dataframe().is_err());
+    }
+}
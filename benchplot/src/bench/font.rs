@@ -0,0 +1,47 @@
+/*
+Copyright 2024 Owain Davies
+SPDX-License-Identifier: Apache-2.0 OR MIT
+*/
+
+use crate::util;
+use font_kit::family_name::FamilyName;
+use font_kit::properties::Properties;
+use font_kit::source::SystemSource;
+
+/// Name under which the embedded font is declared in the SVG file.
+const EMBEDDED_FAMILY: &str = "benchplot-embedded";
+
+/// Returns a `<style>` element declaring the font that `family` resolves to
+/// on this system as a data URL and applying it to all text, or `None` if no
+/// font can be found.
+///
+/// The font is resolved as by [`plotters`], which measured the text with it,
+/// and style rules take precedence over the `font-family` attributes written
+/// by the SVG backend.
+pub(crate) fn font_face_style(family: &str) -> Option<String> {
+    let family = match family {
+        "serif" => FamilyName::Serif,
+        "sans-serif" => FamilyName::SansSerif,
+        "monospace" => FamilyName::Monospace,
+        name => FamilyName::Title(name.to_string()),
+    };
+    let font = SystemSource::new()
+        .select_best_match(&[family, FamilyName::SansSerif], &Properties::new())
+        .ok()?
+        .load()
+        .ok()?;
+    let data = font.copy_font_data()?;
+    let mime = match data.get(..4) {
+        Some(b"OTTO") => "font/otf",
+        Some(b"ttcf") => "font/collection",
+        _ => "font/ttf",
+    };
+    Some(format!(
+        "<style>@font-face {{ font-family: \"{}\"; src: url(data:{};base64,{}); \
+         }} text {{ font-family: \"{}\"; }}</style>\n",
+        EMBEDDED_FAMILY,
+        mime,
+        util::base64(&data),
+        EMBEDDED_FAMILY
+    ))
+}
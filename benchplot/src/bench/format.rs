@@ -0,0 +1,109 @@
+/*
+Copyright 2024 Owain Davies
+SPDX-License-Identifier: Apache-2.0 OR MIT
+*/
+
+/// How numbers in axis labels are written, for plots destined for reports in
+/// languages other than English.
+///
+/// ```
+/// use benchplot::NumberFormat;
+///
+/// // German: 1.234.567,5
+/// let german = NumberFormat::new(',').with_thousands_separator('.');
+///
+/// // French: 1 234 567,5
+/// let french = NumberFormat::new(',').with_thousands_separator('\u{202F}');
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberFormat {
+    decimal_separator: char,
+    thousands_separator: Option<char>,
+}
+
+impl NumberFormat {
+    /// Creates a format with the given decimal separator and no thousands
+    /// separator.
+    pub fn new(decimal_separator: char) -> Self {
+        Self {
+            decimal_separator,
+            thousands_separator: None,
+        }
+    }
+
+    /// Sets the separator inserted between groups of three digits of the
+    /// integer part of a number.
+    pub fn with_thousands_separator(mut self, separator: char) -> Self {
+        self.thousands_separator = Some(separator);
+        self
+    }
+
+    /// Rewrites a number formatted by Rust, e.g., `1234.5` or `1.50e-3`,
+    /// with the separators of this format.
+    ///
+    /// Labels that are not plain numbers, such as powers written with
+    /// superscripts, are returned unchanged.
+    pub(crate) fn apply(&self, number: &str) -> String {
+        if number.is_empty()
+            || !number.chars().all(|c| "0123456789.-+e".contains(c))
+        {
+            return number.to_string();
+        }
+
+        let (mantissa, exponent) = match number.find('e') {
+            Some(idx) => number.split_at(idx),
+            None => (number, ""),
+        };
+        let (integer, fraction) = match mantissa.find('.') {
+            Some(idx) => (&mantissa[..idx], Some(&mantissa[idx + 1..])),
+            None => (mantissa, None),
+        };
+        let (sign, digits) = match integer.strip_prefix('-') {
+            Some(digits) => ("-", digits),
+            None => ("", integer),
+        };
+
+        let mut formatted = String::from(sign);
+        for (idx, digit) in digits.chars().enumerate() {
+            if idx > 0 && (digits.len() - idx) % 3 == 0 {
+                if let Some(separator) = self.thousands_separator {
+                    formatted.push(separator);
+                }
+            }
+            formatted.push(digit);
+        }
+        if let Some(fraction) = fraction {
+            formatted.push(self.decimal_separator);
+            formatted.push_str(fraction);
+        }
+        formatted.push_str(exponent);
+        formatted
+    }
+}
+
+impl Default for NumberFormat {
+    /// Returns the English format, with a decimal point and no thousands
+    /// separator.
+    fn default() -> Self {
+        Self::new('.')
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply() {
+        let german = NumberFormat::new(',').with_thousands_separator('.');
+        assert_eq!(german.apply("1234567.5"), "1.234.567,5");
+        assert_eq!(german.apply("-1024"), "-1.024");
+        assert_eq!(german.apply("512"), "512");
+        assert_eq!(german.apply("1.50e-3"), "1,50e-3");
+        assert_eq!(german.apply("10³"), "10³");
+        assert_eq!(german.apply(""), "");
+
+        let default = NumberFormat::default();
+        assert_eq!(default.apply("1234567.5"), "1234567.5");
+    }
+}
@@ -0,0 +1,312 @@
+/*
+Copyright 2024 Owain Davies
+SPDX-License-Identifier: Apache-2.0 OR MIT
+*/
+
+use crate::{util, Baseline, BenchSuite};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+usage: <benchmark> [options]
+
+options:
+  --filter <text>       Runs only the benchmarks whose name contains the text.
+  --sizes <n,n,...>     Benchmarks the given input sizes instead.
+  --reps <n>            Times each (input size, function) pair n times.
+  --output <dir>        Saves the plots to the directory (default:
+                        target/benchplot).
+  --json                Also saves the results of each benchmark as JSON.
+  --baseline <dir>      Overlays the results saved with --json in the
+                        directory.
+
+The --bench flag passed by `cargo bench` is accepted and ignored.";
+
+/// Options of the command line generated by [`main!`](crate::main).
+#[derive(Debug, Clone, PartialEq)]
+struct Options {
+    filter: Option<String>,
+    sizes: Option<Vec<usize>>,
+    repetitions: Option<usize>,
+    output: PathBuf,
+    json: bool,
+    baseline: Option<PathBuf>,
+    help: bool,
+}
+
+impl Options {
+    fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Self, String> {
+        let mut options = Self {
+            filter: None,
+            sizes: None,
+            repetitions: None,
+            output: PathBuf::from("target/benchplot"),
+            json: false,
+            baseline: None,
+            help: false,
+        };
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| format!("missing value for `{}`", arg))
+            };
+            match arg.as_str() {
+                "--filter" => options.filter = Some(value()?),
+                "--sizes" => {
+                    let list = value()?;
+                    let mut sizes = list
+                        .split(',')
+                        .map(|size| size.trim().parse())
+                        .collect::<Result<Vec<usize>, _>>()
+                        .ok()
+                        .filter(|sizes| !sizes.is_empty())
+                        .ok_or_else(|| format!("invalid sizes `{}`", list))?;
+                    sizes.sort_unstable();
+                    sizes.dedup();
+                    options.sizes = Some(sizes);
+                }
+                "--reps" => {
+                    let reps = value()?;
+                    options.repetitions = Some(
+                        reps.parse().ok().filter(|&reps| reps > 0).ok_or_else(
+                            || format!("invalid repetitions `{}`", reps),
+                        )?,
+                    );
+                }
+                "--output" => options.output = PathBuf::from(value()?),
+                "--json" => options.json = true,
+                "--baseline" => {
+                    options.baseline = Some(PathBuf::from(value()?))
+                }
+                "-h" | "--help" => options.help = true,
+                // Passed by `cargo bench` to benchmarks without a harness.
+                "--bench" => {}
+                _ => return Err(format!("unknown argument `{}`", arg)),
+            }
+        }
+        Ok(options)
+    }
+}
+
+impl BenchSuite<'_> {
+    /// Runs the suite as the `main` function of a benchmark binary, with the
+    /// command line described in [`main!`](crate::main).
+    ///
+    /// Invalid arguments exit with status 2 after printing the usage.
+    /// Options a benchmark cannot run with, such as `--reps 1` with a target
+    /// precision, and failures to save the results exit with status 1.
+    pub fn main(mut self) -> ExitCode {
+        let options = match Options::parse(std::env::args().skip(1)) {
+            Ok(options) if options.help => {
+                println!("{}", USAGE);
+                return ExitCode::SUCCESS;
+            }
+            Ok(options) => options,
+            Err(msg) => {
+                eprintln!("error: {}\n\n{}", msg, USAGE);
+                return ExitCode::from(2);
+            }
+        };
+        match self.run_with(&options) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(msg) => {
+                eprintln!("error: {}", msg);
+                ExitCode::FAILURE
+            }
+        }
+    }
+
+    /// Runs the benchmarks selected by `options` and saves their results.
+    fn run_with(&mut self, options: &Options) -> Result<(), String> {
+        if let Some(filter) = &options.filter {
+            self.benches
+                .retain(|(_, name)| name.contains(filter.as_str()));
+            if self.benches.is_empty() {
+                return Err(format!("no benchmark matches `{}`", filter));
+            }
+        }
+        for (bench, name) in &mut self.benches {
+            let invalid =
+                |err| format!("invalid options for `{}`: {}", name, err);
+            if let Some(sizes) = &options.sizes {
+                bench.set_sizes(sizes.clone()).map_err(invalid)?;
+            }
+            if let Some(repetitions) = options.repetitions {
+                bench.set_repetitions(repetitions).map_err(invalid)?;
+            }
+        }
+        self.run();
+
+        let dir = &options.output;
+        let cannot_write =
+            |path: &Path, err: &dyn std::fmt::Display| -> String {
+                format!("cannot write {}: {}", path.display(), err)
+            };
+        std::fs::create_dir_all(dir).map_err(|err| cannot_write(dir, &err))?;
+        let overlays: Vec<Vec<Baseline>> = self
+            .benches
+            .iter()
+            .map(|(_, name)| {
+                let Some(baseline_dir) = &options.baseline else {
+                    return Vec::new();
+                };
+                let path = baseline_dir.join(json_file_name(name));
+                match Baseline::load(&path) {
+                    Ok(baseline) => vec![baseline],
+                    Err(err) => {
+                        eprintln!(
+                            "warning: no baseline for `{}` ({}: {})",
+                            name,
+                            path.display(),
+                            err
+                        );
+                        Vec::new()
+                    }
+                }
+            })
+            .collect();
+        for path in self
+            .plot_each_overlaid(dir, &overlays)
+            .map_err(|err| cannot_write(dir, &err))?
+        {
+            println!("plotted {}", path.display());
+        }
+        if options.json {
+            for (bench, name) in &self.benches {
+                let path = dir.join(json_file_name(name));
+                std::fs::write(&path, bench.baseline().to_json() + "\n")
+                    .map_err(|err| cannot_write(&path, &err))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the name of the file the results of the benchmark named `name`
+/// are saved to with `--json`, e.g., `merge_sort.json` for `Merge Sort`.
+fn json_file_name(name: &str) -> String {
+    format!("{}.json", util::file_stem(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BenchBuilder, BenchFnArg};
+    use tempfile::tempdir;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_options() {
+        let options = Options::parse(args(&[
+            "--bench",
+            "--filter",
+            "Sort",
+            "--sizes",
+            "100,10,100",
+            "--reps",
+            "3",
+            "--json",
+        ]))
+        .unwrap();
+        assert_eq!(options.filter.as_deref(), Some("Sort"));
+        assert_eq!(options.sizes, Some(vec![10, 100]));
+        assert_eq!(options.repetitions, Some(3));
+        assert_eq!(options.output, PathBuf::from("target/benchplot"));
+        assert!(options.json);
+        assert_eq!(options.baseline, None);
+
+        for invalid in [
+            &["--reps", "0"][..],
+            &["--sizes", "10,x"],
+            &["--output"],
+            &["--verbose"],
+        ] {
+            assert!(Options::parse(args(invalid)).is_err(), "{:?}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_run_with() {
+        let dir = tempdir().unwrap();
+        let numbers: BenchFnArg<usize> = Box::new(|x| x);
+        let strings: BenchFnArg<String> = Box::new(|n| "ab".repeat(n));
+        let mut suite = BenchSuite::new()
+            .add(
+                "Arithmetic",
+                BenchBuilder::new(Vec::new(), numbers, vec![10, 100])
+                    .add_function(|x: usize| x * 2, "Double")
+                    .build()
+                    .unwrap(),
+            )
+            .add(
+                "String Length",
+                BenchBuilder::new(Vec::new(), strings, vec![10, 100])
+                    .add_function(|s: String| s.len(), "len")
+                    .build()
+                    .unwrap(),
+            );
+
+        let output = dir.path().join("out").display().to_string();
+        let options = Options::parse(args(&[
+            "--filter", "String", "--sizes", "5,50,500", "--reps", "2",
+            "--output", &output, "--json",
+        ]))
+        .unwrap();
+        suite.run_with(&options).unwrap();
+
+        assert_eq!(suite.names(), ["String Length"]);
+        let out = dir.path().join("out");
+        assert!(out.join("string_length.svg").exists());
+        assert!(!out.join("arithmetic.svg").exists());
+        let results = Baseline::load(out.join("string_length.json")).unwrap();
+        assert_eq!(results.data().sizes(), [5, 50, 500]);
+
+        let options = Options {
+            filter: Some("Sorting".to_string()),
+            ..options
+        };
+        assert!(suite.run_with(&options).is_err());
+    }
+
+    #[test]
+    fn test_run_with_checks_bench_options() {
+        let dir = tempdir().unwrap();
+        let argfunc: BenchFnArg<usize> = Box::new(|x| x);
+        let mut suite = BenchSuite::new().add(
+            "Arithmetic",
+            BenchBuilder::new(Vec::new(), argfunc, vec![10, 100])
+                .add_function(|x: usize| x * 2, "Double")
+                .add_function(|x: usize| x * x, "Square")
+                .max_size_for("Square", 50)
+                .repetitions(2)
+                .max_repetitions(4)
+                .target_precision(0.5)
+                .build()
+                .unwrap(),
+        );
+
+        let output = dir.path().display().to_string();
+        let options = Options::parse(args(&[
+            "--sizes", "5,50,500", "--output", &output, "--json",
+        ]))
+        .unwrap();
+        suite.run_with(&options).unwrap();
+        let results = Baseline::load(dir.path().join("arithmetic.json"));
+        let data = results.unwrap().data().clone();
+        assert_eq!(data.sizes(), [5, 50, 500]);
+        assert!(!data.time(1, 1).is_nan());
+        assert!(data.time(1, 2).is_nan());
+        assert!(!data.time(0, 2).is_nan());
+
+        let options = Options {
+            repetitions: Some(1),
+            ..options
+        };
+        let err = suite.run_with(&options).unwrap_err();
+        assert!(err.contains("target precision"), "{}", err);
+    }
+}
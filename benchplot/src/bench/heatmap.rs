@@ -0,0 +1,233 @@
+/*
+Copyright 2024 Owain Davies
+SPDX-License-Identifier: Apache-2.0 OR MIT
+*/
+
+use super::plot::OutputFormat;
+use crate::{Bench, DefaultTheme, PlotBuilderError, Theme, Threading};
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use plotters::style::text_anchor::{HPos, Pos, VPos};
+use std::path::Path;
+
+/// Side of a cell of the heatmap of [`Bench::plot_speedups`] in pixels.
+const CELL_SIZE: u32 = 80;
+
+/// Color of the cells of speedups above 1, i.e., of rows faster than their
+/// column, at full intensity, which is kept light enough for the labels of
+/// the cells to stay readable.
+const FASTER: RGBColor = RGBColor(120, 205, 130);
+
+/// Color of the cells of speedups below 1 at full intensity.
+const SLOWER: RGBColor = RGBColor(235, 120, 115);
+
+impl<T, R, M: Threading> Bench<'_, T, R, M> {
+    /// Saves a heatmap of the [`Bench::speedup_matrix`] to an SVG or bitmap
+    /// file, summarizing how every function compares with every other over
+    /// all input sizes, which reads better than many overlapping lines once
+    /// a benchmark has more than a few functions.
+    ///
+    /// The cell in the row of function `a` and the column of function `b`
+    /// is labeled with the speedup of `a` over `b`, e.g., `2.00x`, and is
+    /// green if `a` is faster and red if it is slower, the more intense the
+    /// larger the logarithm of the speedup. The functions are in the order
+    /// of [`Bench::ranking`], from the fastest at the top and at the left.
+    pub fn plot_speedups<P: AsRef<Path>>(
+        &self,
+        path: P,
+    ) -> Result<(), PlotBuilderError> {
+        let path = path.as_ref();
+        let Some(format) = OutputFormat::of(path) else {
+            return Err(PlotBuilderError::UnsupportedFormat {
+                path: path.to_path_buf(),
+            });
+        };
+        if self.data.is_empty() {
+            return Err(PlotBuilderError::NoData);
+        }
+
+        let label_width = self
+            .function_names()
+            .iter()
+            .map(|name| name.chars().count() as u32 * 8)
+            .max()
+            .unwrap_or(0)
+            + 20;
+        let side = self.functions.len() as u32 * CELL_SIZE;
+        let dimensions = (label_width + side + 40, label_width + side + 100);
+        match format {
+            OutputFormat::Svg => {
+                let root =
+                    SVGBackend::new(path, dimensions).into_drawing_area();
+                self.draw_speedups(&root, label_width)?;
+                super::plot::present(&root, path)
+            }
+            OutputFormat::Bitmap => {
+                let root =
+                    BitMapBackend::new(path, dimensions).into_drawing_area();
+                root.fill(&WHITE)?;
+                self.draw_speedups(&root, label_width)?;
+                root.present()?;
+                Ok(())
+            }
+        }
+    }
+
+    /// Draws the heatmap of [`Bench::plot_speedups`] onto `root`, leaving
+    /// `label_width` pixels for the function names left of and below the
+    /// cells.
+    fn draw_speedups<DB>(
+        &self,
+        root: &DrawingArea<DB, Shift>,
+        label_width: u32,
+    ) -> Result<(), PlotBuilderError>
+    where
+        DB: DrawingBackend,
+        PlotBuilderError: From<DrawingAreaErrorKind<DB::ErrorType>>,
+    {
+        let names = self.function_names();
+        let order: Vec<usize> = self
+            .ranking()
+            .into_iter()
+            .map(|(function, _)| function)
+            .collect();
+        let speedups = self.speedup_matrix();
+        let max_log = speedups
+            .iter()
+            .flatten()
+            .filter(|speedup| speedup.is_finite())
+            .map(|speedup| speedup.ln().abs())
+            .fold(0.0, f64::max);
+
+        let font = DefaultTheme.font_family();
+        let text = DefaultTheme.text();
+        let label_font = (font.as_str(), 14).into_font().color(&text);
+        let cell_font = (font.as_str(), 14).into_font().color(&BLACK);
+        root.fill(&DefaultTheme.background())?;
+        let area = root.titled(
+            "Speedup of each row over each column",
+            (font.as_str(), 18).into_font().color(&text),
+        )?;
+
+        let num_functions = order.len() as i32;
+        let chart = ChartBuilder::on(&area)
+            .margin(20)
+            .x_label_area_size(label_width)
+            .y_label_area_size(label_width)
+            .build_cartesian_2d(0..num_functions, 0..num_functions)?;
+        // The center in pixels of the cell in the given row and column, the
+        // first row being at the top.
+        let center = |row: usize, column: usize| {
+            let y = num_functions - 1 - row as i32;
+            let (left, top) = chart.backend_coord(&(column as i32, y + 1));
+            let (right, bottom) = chart.backend_coord(&(column as i32 + 1, y));
+            ((left + right) / 2, (top + bottom) / 2)
+        };
+
+        for (row, &a) in order.iter().enumerate() {
+            for (column, &b) in order.iter().enumerate() {
+                let speedup = speedups[a][b];
+                let color = if !speedup.is_finite() {
+                    RGBColor(230, 230, 230)
+                } else if max_log == 0.0 {
+                    WHITE
+                } else {
+                    let intensity = speedup.ln() / max_log;
+                    let end = if intensity > 0.0 { FASTER } else { SLOWER };
+                    mix(WHITE, end, intensity.abs())
+                };
+                let (x, y) = (column as i32, num_functions - 1 - row as i32);
+                chart.plotting_area().draw(&Rectangle::new(
+                    [(x, y), (x + 1, y + 1)],
+                    color.filled(),
+                ))?;
+                let label = if speedup.is_finite() {
+                    format!("{:.2}x", speedup)
+                } else {
+                    "-".to_string()
+                };
+                area.draw(&Text::new(
+                    label,
+                    center(row, column),
+                    cell_font.pos(Pos::new(HPos::Center, VPos::Center)),
+                ))?;
+            }
+        }
+
+        let (left, bottom) = chart.backend_coord(&(0, 0));
+        for (idx, &function) in order.iter().enumerate() {
+            let (x, y) = center(idx, idx);
+            area.draw(&Text::new(
+                names[function],
+                (left - 10, y),
+                label_font.pos(Pos::new(HPos::Right, VPos::Center)),
+            ))?;
+            area.draw(&Text::new(
+                names[function],
+                (x, bottom + 10),
+                label_font
+                    .transform(FontTransform::Rotate90)
+                    .pos(Pos::new(HPos::Left, VPos::Center)),
+            ))?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the color `t` of the way from `start` to `end`, for `t` between
+/// 0 and 1.
+fn mix(start: RGBColor, end: RGBColor, t: f64) -> RGBColor {
+    let channel =
+        |from: u8, to: u8| (from as f64 + (to as f64 - from as f64) * t) as u8;
+    RGBColor(
+        channel(start.0, end.0),
+        channel(start.1, end.1),
+        channel(start.2, end.2),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        BenchBuilder, BenchData, BenchFnArg, BenchFnNamed, PlotBuilderError,
+    };
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_plot_speedups() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("speedups.svg");
+
+        let functions: Vec<BenchFnNamed<'static, usize, usize>> = vec![
+            (Box::new(|x| x), "Merge Sort"),
+            (Box::new(|x| x), "Quick Sort"),
+            (Box::new(|x| x), "Bubble Sort"),
+        ];
+        let argfunc: BenchFnArg<usize> = Box::new(|x| x);
+        let mut bench = BenchBuilder::new(functions, argfunc, vec![10, 1000])
+            .build()
+            .unwrap();
+        assert!(matches!(
+            bench.plot_speedups(&path),
+            Err(PlotBuilderError::NoData)
+        ));
+
+        bench.data = BenchData::from_rows(
+            3,
+            [(10, vec![2.0, 1.0, 4.0]), (1000, vec![4.0, 4.0, 100.0])],
+        )
+        .unwrap();
+        bench.plot_speedups(&path).unwrap();
+        let svg = std::fs::read_to_string(&path).unwrap();
+        assert!(svg.contains("Bubble Sort"));
+        assert!(svg.contains("1.00x"));
+        assert!(svg.contains("1.41x"));
+        assert!(svg.contains("10.00x"));
+        assert!(svg.contains("0.10x"));
+
+        assert!(matches!(
+            bench.plot_speedups(dir.path().join("speedups.pdf")),
+            Err(PlotBuilderError::UnsupportedFormat { .. })
+        ));
+    }
+}
@@ -0,0 +1,139 @@
+/*
+Copyright 2024 Owain Davies
+SPDX-License-Identifier: Apache-2.0 OR MIT
+*/
+
+use super::isolation;
+use super::plot::xml_escape;
+use crate::{util, Baseline};
+use std::fmt::Write;
+use std::io;
+use std::path::Path;
+
+impl Baseline {
+    /// Writes an HTML report of the results to the directory `dir`, which is
+    /// created if needed: an `index.html` page showing the plot of the
+    /// results, saved next to it as `plot.svg`, a table of the timings, and
+    /// the label and timer calibration of the run.
+    ///
+    /// Missing timings are shown as `-`. Nothing is written from a child
+    /// process of an isolated benchmark.
+    ///
+    /// ```no_run
+    /// # use benchplot::Baseline;
+    /// # fn example() -> std::io::Result<()> {
+    /// Baseline::load("results.json")?.write_report("report")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn write_report<P: AsRef<Path>>(&self, dir: P) -> io::Result<()> {
+        if isolation::child_cell().is_some() {
+            return Ok(());
+        }
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+        self.plot(dir.join("plot.svg"), |plot| plot)
+            .map_err(io::Error::other)?;
+        std::fs::write(dir.join("index.html"), self.to_html())
+    }
+
+    /// Renders the report page of [`Baseline::write_report`].
+    fn to_html(&self) -> String {
+        let title = self.label().unwrap_or("Benchmark Results");
+        let mut html = String::new();
+        let _ = writeln!(
+            html,
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n\
+             <meta charset=\"utf-8\">\n<title>{}</title>\n<style>\n\
+             body {{ font-family: sans-serif; margin: 2em; }}\n\
+             table {{ border-collapse: collapse; margin-bottom: 2em; }}\n\
+             th, td {{ border: 1px solid #ccc; padding: 0.3em 0.8em; }}\n\
+             td {{ text-align: right; font-variant-numeric: tabular-nums; }}\n\
+             </style>\n</head>\n<body>\n<h1>{}</h1>\n\
+             <img src=\"plot.svg\" alt=\"Plot of the results\">",
+            xml_escape(title),
+            xml_escape(title)
+        );
+
+        html.push_str("<h2>Timings</h2>\n<table>\n<tr><th>Size</th>");
+        for function in self.functions() {
+            let _ = write!(html, "<th>{}</th>", xml_escape(function));
+        }
+        html.push_str("</tr>\n");
+        for (size_idx, size) in self.data().sizes().iter().enumerate() {
+            let _ = write!(html, "<tr><th>{}</th>", size);
+            for fn_idx in 0..self.functions().len() {
+                let time = self.data().time(fn_idx, size_idx);
+                let cell = if time.is_nan() {
+                    "-".to_string()
+                } else {
+                    util::format_seconds(time)
+                };
+                let _ = write!(html, "<td>{}</td>", cell);
+            }
+            html.push_str("</tr>\n");
+        }
+        html.push_str("</table>\n");
+
+        html.push_str("<h2>Metadata</h2>\n<table>\n");
+        let mut metadata = vec![
+            ("Label", self.label().unwrap_or("-").to_string()),
+            ("Functions", self.functions().len().to_string()),
+            ("Input sizes", self.data().sizes().len().to_string()),
+        ];
+        if let Some(timer) = self.timer_calibration() {
+            metadata.push((
+                "Timer overhead",
+                util::format_seconds(timer.overhead.as_secs_f64()),
+            ));
+            metadata.push((
+                "Timer granularity",
+                util::format_seconds(timer.granularity.as_secs_f64()),
+            ));
+        }
+        for (key, value) in metadata {
+            let _ = writeln!(
+                html,
+                "<tr><th>{}</th><td>{}</td></tr>",
+                key,
+                xml_escape(&value)
+            );
+        }
+        html.push_str("</table>\n</body>\n</html>\n");
+        html
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BenchData;
+
+    #[test]
+    fn test_write_report() {
+        let baseline = Baseline::new(
+            vec!["Vec<u8>".to_string(), "B".to_string()],
+            BenchData::from_rows(
+                2,
+                [(10, vec![1.5e-6, f64::NAN]), (100, vec![1.5e-5, 2e-3])],
+            )
+            .unwrap(),
+        )
+        .with_label("v1 & v2");
+
+        let html = baseline.to_html();
+        assert!(html.contains("<title>v1 &amp; v2</title>"));
+        assert!(html.contains("<th>Vec&lt;u8&gt;</th><th>B</th>"));
+        assert!(html.contains("<tr><th>10</th><td>1.50 µs</td><td>-</td>"));
+        assert!(html.contains("<td>2.00 ms</td>"));
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path().join("report");
+        baseline.write_report(&dir).unwrap();
+        assert!(dir.join("plot.svg").exists());
+        assert_eq!(
+            std::fs::read_to_string(dir.join("index.html")).unwrap(),
+            html
+        );
+    }
+}
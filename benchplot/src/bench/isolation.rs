@@ -0,0 +1,172 @@
+/*
+Copyright 2024 Owain Davies
+SPDX-License-Identifier: Apache-2.0 OR MIT
+*/
+
+//! Support for timing each `(input size, function)` pair in a child process.
+//!
+//! The parent process re-executes the current executable with the same
+//! command-line arguments and an environment variable identifying the pair to
+//! time. When the child process reaches the corresponding call to
+//! [`Bench::run`](crate::Bench::run), it times only that pair, prints the
+//! timings to standard output, and exits.
+
+use std::process::Command;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Environment variable identifying the pair a child process should time, in
+/// the form `<run index>:<size index>:<function index>`.
+const CELL_ENV_VAR: &str = "BENCHPLOT_ISOLATED_CELL";
+
+/// Prefix of the line of standard output containing the timings.
+const OUTPUT_PREFIX: &str = "benchplot-cell:";
+
+/// Prefix of the line of standard output containing the error generating the
+/// argument.
+const ARGUMENT_ERROR_PREFIX: &str = "benchplot-argument-error:";
+
+/// Number of isolated benchmarks run so far by this process.
+static RUN_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Identifies an `(input size, function)` pair of a particular isolated
+/// benchmark run.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Cell {
+    pub(crate) run_idx: usize,
+    pub(crate) size_idx: usize,
+    pub(crate) func_idx: usize,
+}
+
+/// What a child process reported about the pair it was to time.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum CellOutput {
+    /// The timings in integer nanoseconds and whether the function returned
+    /// equal values across all repetitions.
+    Timed(Vec<u128>, bool),
+
+    /// The error generating the argument, which prevented timing.
+    ArgumentError(String),
+}
+
+/// Returns the index of the next isolated benchmark run by this process.
+///
+/// Runs are identified by the order in which they are reached, which is the
+/// same in the parent and child processes for deterministic programs.
+pub(crate) fn next_run_index() -> usize {
+    RUN_COUNTER.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Returns the environment variable, and its value, identifying the pair
+/// `cell` to a child process.
+pub(crate) fn cell_env(cell: Cell) -> (&'static str, String) {
+    (
+        CELL_ENV_VAR,
+        format!("{}:{}:{}", cell.run_idx, cell.size_idx, cell.func_idx),
+    )
+}
+
+/// Returns the pair to time if this process is a child process.
+pub(crate) fn child_cell() -> Option<Cell> {
+    std::env::var(CELL_ENV_VAR)
+        .ok()
+        .and_then(|value| parse_cell(&value))
+}
+
+/// Prints the timings of the pair, in integer nanoseconds, in the format
+/// expected by the parent.
+pub(crate) fn report_cell(nanos: &[u128], consistent: bool) {
+    let times: Vec<String> = nanos.iter().map(|t| t.to_string()).collect();
+    println!("{} {} {}", OUTPUT_PREFIX, consistent as u8, times.join(" "));
+}
+
+/// Prints the error generating the argument of the pair in the format
+/// expected by the parent.
+pub(crate) fn report_argument_error(err: &dyn std::error::Error) {
+    let message = err.to_string().replace('\n', " ");
+    println!("{} {}", ARGUMENT_ERROR_PREFIX, message);
+}
+
+/// Times the pair `cell` in a child process, returning what it reported.
+pub(crate) fn time_cell(cell: Cell) -> Result<CellOutput, String> {
+    let exe = std::env::current_exe().map_err(|err| err.to_string())?;
+    let (cell_var, cell_value) = cell_env(cell);
+    let output = Command::new(exe)
+        .args(std::env::args_os().skip(1))
+        .env(cell_var, cell_value)
+        .output()
+        .map_err(|err| err.to_string())?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "child process exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(parse_output)
+        .ok_or_else(|| "child process did not report timings".to_string())
+}
+
+fn parse_cell(value: &str) -> Option<Cell> {
+    let mut parts = value.split(':').map(|part| part.parse().ok());
+    let cell = Cell {
+        run_idx: parts.next()??,
+        size_idx: parts.next()??,
+        func_idx: parts.next()??,
+    };
+    parts.next().is_none().then_some(cell)
+}
+
+fn parse_output(line: &str) -> Option<CellOutput> {
+    if let Some(message) = line.strip_prefix(ARGUMENT_ERROR_PREFIX) {
+        return Some(CellOutput::ArgumentError(message.trim().to_string()));
+    }
+    let mut fields = line.strip_prefix(OUTPUT_PREFIX)?.split_whitespace();
+    let consistent = fields.next()? == "1";
+    let times = fields
+        .map(|field| field.parse().ok())
+        .collect::<Option<Vec<u128>>>()?;
+    (!times.is_empty()).then_some(CellOutput::Timed(times, consistent))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cell() {
+        assert_eq!(
+            parse_cell("1:2:3"),
+            Some(Cell {
+                run_idx: 1,
+                size_idx: 2,
+                func_idx: 3
+            })
+        );
+        assert_eq!(parse_cell("1:2"), None);
+        assert_eq!(parse_cell("1:2:3:4"), None);
+        assert_eq!(parse_cell("1:x:3"), None);
+    }
+
+    #[test]
+    fn test_parse_output() {
+        assert_eq!(
+            parse_output("benchplot-cell: 1 500 250"),
+            Some(CellOutput::Timed(vec![500, 250], true))
+        );
+        assert_eq!(
+            parse_output("benchplot-cell: 0 1"),
+            Some(CellOutput::Timed(vec![1], false))
+        );
+        assert_eq!(parse_output("benchplot-cell: 1 0.5"), None);
+        assert_eq!(parse_output("benchplot-cell: 1"), None);
+        assert_eq!(parse_output("unrelated output"), None);
+        assert_eq!(
+            parse_output("benchplot-argument-error: file not found"),
+            Some(CellOutput::ArgumentError("file not found".to_string()))
+        );
+    }
+}
@@ -0,0 +1,360 @@
+/*
+Copyright 2024 Owain Davies
+SPDX-License-Identifier: Apache-2.0 OR MIT
+*/
+
+//! Reading and writing of the JSON formats of the crate, i.e., baselines
+//! rendered by `Baseline::to_json` and the lines of results files, without
+//! depending on the `serde` feature.
+
+/// Maximum nesting depth of arrays and objects accepted by [`parse`].
+const MAX_DEPTH: usize = 64;
+
+/// A parsed JSON value.
+///
+/// Numbers keep their text, so that integers such as input sizes are parsed
+/// exactly rather than through `f64`.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Value {
+    Null,
+    Bool(bool),
+    Number(String),
+    String(String),
+    Array(Vec<Value>),
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    /// Returns the value of the member `key` if this is an object with such a
+    /// member, taking the last one if the key is repeated.
+    pub(crate) fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Object(members) => members
+                .iter()
+                .rev()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    /// Returns the string if this is a string.
+    pub(crate) fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Returns the elements if this is an array.
+    pub(crate) fn as_array(&self) -> Option<&[Value]> {
+        match self {
+            Value::Array(elements) => Some(elements),
+            _ => None,
+        }
+    }
+
+    /// Returns the number if this is a number, or `NaN` if this is `null`,
+    /// which stands for a missing timing.
+    pub(crate) fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Number(n) => n.parse().ok(),
+            Value::Null => Some(f64::NAN),
+            _ => None,
+        }
+    }
+
+    /// Returns the number if this is a number that fits in a `T` exactly,
+    /// e.g., an input size.
+    pub(crate) fn as_integer<T: std::str::FromStr>(&self) -> Option<T> {
+        match self {
+            Value::Number(n) => n.parse().ok(),
+            _ => None,
+        }
+    }
+}
+
+/// Parses the JSON text `json`, which may be surrounded by whitespace.
+pub(crate) fn parse(json: &str) -> Option<Value> {
+    let mut parser = Parser { json, pos: 0 };
+    let value = parser.value(0)?;
+    parser.skip_whitespace();
+    (parser.pos == json.len()).then_some(value)
+}
+
+/// Renders `s` as a JSON string literal.
+pub(crate) fn string(s: &str) -> String {
+    let mut json = String::with_capacity(s.len() + 2);
+    json.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\t' => json.push_str("\\t"),
+            c if c.is_control() => {
+                json.push_str(&format!("\\u{:04x}", c as u32))
+            }
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+/// Renders `x` as a JSON number, or as `null` if it is not finite, e.g., a
+/// missing timing.
+pub(crate) fn number(x: f64) -> String {
+    if x.is_finite() {
+        x.to_string()
+    } else {
+        "null".to_string()
+    }
+}
+
+/// Recursive descent parser over the text of a JSON value.
+struct Parser<'s> {
+    json: &'s str,
+    pos: usize,
+}
+
+impl<'s> Parser<'s> {
+    fn rest(&self) -> &'s str {
+        &self.json[self.pos..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        let trimmed = rest.trim_start_matches([' ', '\t', '\n', '\r']);
+        self.pos += rest.len() - trimmed.len();
+    }
+
+    /// Consumes `token` after any whitespace, returning whether it was there.
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_whitespace();
+        let found = self.rest().starts_with(token);
+        if found {
+            self.pos += token.len();
+        }
+        found
+    }
+
+    fn value(&mut self, depth: usize) -> Option<Value> {
+        self.skip_whitespace();
+        match self.rest().bytes().next()? {
+            b'n' => self.eat("null").then_some(Value::Null),
+            b't' => self.eat("true").then_some(Value::Bool(true)),
+            b'f' => self.eat("false").then_some(Value::Bool(false)),
+            b'"' => self.string().map(Value::String),
+            b'[' if depth < MAX_DEPTH => self.array(depth + 1),
+            b'{' if depth < MAX_DEPTH => self.object(depth + 1),
+            b'-' | b'0'..=b'9' => self.number(),
+            _ => None,
+        }
+    }
+
+    fn number(&mut self) -> Option<Value> {
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| {
+                !matches!(c, '0'..='9' | '-' | '+' | '.' | 'e' | 'E')
+            })
+            .unwrap_or(rest.len());
+        let number = &rest[..len];
+        number.parse::<f64>().ok()?;
+        self.pos += len;
+        Some(Value::Number(number.to_string()))
+    }
+
+    fn string(&mut self) -> Option<String> {
+        let mut chars = self.rest().strip_prefix('"')?.char_indices();
+        let mut string = String::new();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.pos += i + 2;
+                    return Some(string);
+                }
+                '\\' => match chars.next()?.1 {
+                    '"' => string.push('"'),
+                    '\\' => string.push('\\'),
+                    '/' => string.push('/'),
+                    'b' => string.push('\u{8}'),
+                    'f' => string.push('\u{c}'),
+                    'n' => string.push('\n'),
+                    'r' => string.push('\r'),
+                    't' => string.push('\t'),
+                    'u' => {
+                        let mut code = hex_code(&mut chars)?;
+                        if (0xd800..0xdc00).contains(&code) {
+                            // A high surrogate must be followed by an escaped
+                            // low surrogate, with which it encodes the char.
+                            if chars.next()?.1 != '\\' || chars.next()?.1 != 'u'
+                            {
+                                return None;
+                            }
+                            let low = hex_code(&mut chars)?;
+                            if !(0xdc00..0xe000).contains(&low) {
+                                return None;
+                            }
+                            code = 0x10000
+                                + ((code - 0xd800) << 10)
+                                + (low - 0xdc00);
+                        }
+                        string.push(char::from_u32(code)?);
+                    }
+                    _ => return None,
+                },
+                c if c < ' ' => return None,
+                c => string.push(c),
+            }
+        }
+        None
+    }
+
+    fn array(&mut self, depth: usize) -> Option<Value> {
+        self.eat("[");
+        let mut elements = Vec::new();
+        if self.eat("]") {
+            return Some(Value::Array(elements));
+        }
+        loop {
+            elements.push(self.value(depth)?);
+            if self.eat("]") {
+                return Some(Value::Array(elements));
+            }
+            if !self.eat(",") {
+                return None;
+            }
+        }
+    }
+
+    fn object(&mut self, depth: usize) -> Option<Value> {
+        self.eat("{");
+        let mut members = Vec::new();
+        if self.eat("}") {
+            return Some(Value::Object(members));
+        }
+        loop {
+            self.skip_whitespace();
+            let name = self.string()?;
+            if !self.eat(":") {
+                return None;
+            }
+            members.push((name, self.value(depth)?));
+            if self.eat("}") {
+                return Some(Value::Object(members));
+            }
+            if !self.eat(",") {
+                return None;
+            }
+        }
+    }
+}
+
+/// Reads the four hexadecimal digits of a `\u` escape from `chars`.
+fn hex_code(chars: &mut std::str::CharIndices<'_>) -> Option<u32> {
+    let hex: String = chars.take(4).map(|(_, c)| c).collect();
+    if hex.len() != 4 {
+        return None;
+    }
+    u32::from_str_radix(&hex, 16).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value_of(s: &str) -> Value {
+        Value::String(s.to_string())
+    }
+
+    #[test]
+    fn test_parse() {
+        let value = parse(
+            " {\"b\" : [1, -2.5e-3 , null,true],\n\t\"a\":{\"c\":\"d\"}} \n",
+        )
+        .unwrap();
+        assert_eq!(
+            value,
+            Value::Object(vec![
+                (
+                    "b".to_string(),
+                    Value::Array(vec![
+                        Value::Number("1".to_string()),
+                        Value::Number("-2.5e-3".to_string()),
+                        Value::Null,
+                        Value::Bool(true),
+                    ])
+                ),
+                (
+                    "a".to_string(),
+                    Value::Object(vec![(
+                        "c".to_string(),
+                        Value::String("d".to_string())
+                    )])
+                ),
+            ])
+        );
+        assert_eq!(
+            value.get("a").and_then(|a| a.get("c")),
+            Some(&value_of("d"))
+        );
+        assert_eq!(value.get("missing"), None);
+        assert_eq!(parse("[]"), Some(Value::Array(Vec::new())));
+        assert_eq!(parse("{}"), Some(Value::Object(Vec::new())));
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        for json in [
+            "",
+            "{",
+            "[1,]",
+            "[1 2]",
+            "{\"a\"}",
+            "{\"a\":1,}",
+            "{a:1}",
+            "nul",
+            "1 2",
+            "\"unterminated",
+            "\"\\x\"",
+            "\"\\ud800\"",
+            "\"\u{1}\"",
+            "--1",
+        ] {
+            assert_eq!(parse(json), None, "{:?}", json);
+        }
+        let nested = "[".repeat(MAX_DEPTH + 1) + &"]".repeat(MAX_DEPTH + 1);
+        assert_eq!(parse(&nested), None);
+    }
+
+    #[test]
+    fn test_string_round_trip() {
+        for s in [
+            "plain",
+            "quote \" and \\ slash",
+            "tab\tnew\nline\u{1}",
+            "é🦀",
+        ] {
+            assert_eq!(parse(&string(s)), Some(value_of(s)));
+        }
+        assert_eq!(
+            parse(r#""\u00e9\ud83e\udd80\/\b\f\r""#),
+            Some(value_of("é🦀/\u{8}\u{c}\r"))
+        );
+    }
+
+    #[test]
+    fn test_numbers() {
+        let value = parse("[10, 18446744073709551615, 1.5, null]").unwrap();
+        let elements = value.as_array().unwrap();
+        assert_eq!(elements[0].as_integer::<usize>(), Some(10));
+        assert_eq!(elements[1].as_integer::<u64>(), Some(u64::MAX));
+        assert_eq!(elements[2].as_integer::<usize>(), None);
+        assert_eq!(elements[2].as_f64(), Some(1.5));
+        assert!(elements[3].as_f64().unwrap().is_nan());
+        assert_eq!(number(f64::INFINITY), "null");
+        assert_eq!(number(2.5), "2.5");
+    }
+}
@@ -0,0 +1,166 @@
+/*
+Copyright 2024 Owain Davies
+SPDX-License-Identifier: Apache-2.0 OR MIT
+*/
+
+use super::plot::COLORS;
+use crate::util;
+use crate::{Bench, CellEvent, Threading};
+use egui::{Color32, Response, Ui, Widget};
+use egui_plot::{Legend, Line, Plot, PlotPoints, Points};
+
+/// An [egui](https://docs.rs/egui) widget drawing the results of a benchmark
+/// received so far, e.g., from [`Bench::run_streaming`], as a live-updating
+/// chart.
+///
+/// Timings are plotted against input sizes on logarithmic axes, with one line
+/// per function in the colors of the SVG plot. Record each [`CellEvent`] as it
+/// is received and add the chart to a `Ui` on every frame:
+///
+/// ```no_run
+/// # fn show(ui: &mut egui::Ui, chart: &mut benchplot::LiveChart,
+/// #         receiver: &std::sync::mpsc::Receiver<benchplot::CellEvent>) {
+/// for event in receiver.try_iter() {
+///     chart.record(&event);
+/// }
+/// ui.add(&*chart);
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct LiveChart {
+    names: Vec<String>,
+    points: Vec<Vec<(usize, f64)>>,
+}
+
+impl LiveChart {
+    /// Creates an empty chart for functions named `names`, in the order of
+    /// the functions vector.
+    pub fn new<I, S>(names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let names: Vec<String> = names.into_iter().map(Into::into).collect();
+        let points = vec![Vec::new(); names.len()];
+        Self { names, points }
+    }
+
+    /// Creates an empty chart for the functions of `bench`.
+    pub fn for_bench<T, R, M: Threading>(bench: &Bench<'_, T, R, M>) -> Self {
+        Self::new(bench.function_names())
+    }
+
+    /// Adds the benchmark value of the pair of `event` to the chart, replacing
+    /// any earlier value of the same pair.
+    ///
+    /// Events of functions the chart was not created for are ignored.
+    pub fn record(&mut self, event: &CellEvent) {
+        let Some(points) = self.points.get_mut(event.function) else {
+            return;
+        };
+        match points.binary_search_by_key(&event.size, |&(size, _)| size) {
+            Ok(idx) => points[idx].1 = event.value.as_secs_f64(),
+            Err(idx) => {
+                points.insert(idx, (event.size, event.value.as_secs_f64()))
+            }
+        }
+    }
+
+    /// Removes every recorded value, e.g., before another run.
+    pub fn clear(&mut self) {
+        self.points.iter_mut().for_each(Vec::clear);
+    }
+
+    /// Returns the `(input size, value)` points recorded for the function at
+    /// index `function`, in increasing order of input size.
+    pub fn points(&self, function: usize) -> &[(usize, f64)] {
+        &self.points[function]
+    }
+
+    /// Returns the recorded points of the function at index `function` in
+    /// plot coordinates, i.e., the base-10 logarithms of the input sizes and
+    /// values.
+    ///
+    /// Points that cannot be drawn on logarithmic axes are skipped.
+    fn plot_points(&self, function: usize) -> Vec<[f64; 2]> {
+        self.points[function]
+            .iter()
+            .filter(|&&(size, value)| size > 0 && value > 0.0)
+            .map(|&(size, value)| [(size as f64).log10(), value.log10()])
+            .collect()
+    }
+}
+
+impl Widget for &LiveChart {
+    fn ui(self, ui: &mut Ui) -> Response {
+        Plot::new("benchplot_live_chart")
+            .legend(Legend::default())
+            .x_axis_label("Input size")
+            .y_axis_label("Time")
+            .x_axis_formatter(|mark, _| {
+                format!("{}", 10f64.powf(mark.value).round())
+            })
+            .y_axis_formatter(|mark, _| {
+                util::format_seconds(10f64.powf(mark.value))
+            })
+            .label_formatter(|name, point| {
+                format!(
+                    "{}\nn={}: {}",
+                    name,
+                    10f64.powf(point.x).round(),
+                    util::format_seconds(10f64.powf(point.y))
+                )
+            })
+            .show(ui, |plot_ui| {
+                for (func_idx, name) in self.names.iter().enumerate() {
+                    let color = COLORS[func_idx % COLORS.len()];
+                    let color = Color32::from_rgb(color.0, color.1, color.2);
+                    let points = self.plot_points(func_idx);
+                    plot_ui.line(
+                        Line::new(name, PlotPoints::from(points.clone()))
+                            .color(color),
+                    );
+                    plot_ui.points(
+                        Points::new(name, PlotPoints::from(points))
+                            .color(color)
+                            .radius(3.0),
+                    );
+                }
+            })
+            .response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn event(size: usize, function: usize, value: f64) -> CellEvent {
+        let value = Duration::from_secs_f64(value);
+        CellEvent {
+            size,
+            function,
+            value,
+            times: vec![value],
+        }
+    }
+
+    #[test]
+    fn test_record() {
+        let mut chart = LiveChart::new(["A", "B"]);
+        chart.record(&event(100, 0, 2.0));
+        chart.record(&event(10, 0, 1.0));
+        chart.record(&event(100, 0, 3.0));
+        chart.record(&event(10, 1, 0.0));
+        chart.record(&event(10, 2, 1.0));
+
+        assert_eq!(chart.points(0), [(10, 1.0), (100, 3.0)]);
+        assert_eq!(chart.points(1), [(10, 0.0)]);
+        assert_eq!(chart.plot_points(0), [[1.0, 0.0], [2.0, 3f64.log10()]]);
+        assert!(chart.plot_points(1).is_empty());
+
+        chart.clear();
+        assert!(chart.points(0).is_empty());
+    }
+}
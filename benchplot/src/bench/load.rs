@@ -0,0 +1,127 @@
+/*
+Copyright 2024 Owain Davies
+SPDX-License-Identifier: Apache-2.0 OR MIT
+*/
+
+use std::time::{Duration, Instant};
+
+/// Interval between two readings of the system load while waiting for it to
+/// drop.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Pauses measurement while the system is busy.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct LoadPause {
+    pub(crate) max_load: f64,
+    pub(crate) max_wait: Duration,
+}
+
+impl LoadPause {
+    /// Waits until the system load is at most `max_load`, or for at most
+    /// `max_wait`, returning whether the load dropped below the threshold.
+    ///
+    /// Returns `true` immediately if the load cannot be read on this platform.
+    /// Unless `quiet` is `true`, a warning is printed if the load does not
+    /// drop in time.
+    pub(crate) fn wait_until_idle(&self, quiet: bool) -> bool {
+        self.wait_with(load_per_cpu, POLL_INTERVAL, quiet)
+    }
+
+    fn wait_with(
+        &self,
+        mut load: impl FnMut() -> Option<f64>,
+        poll_interval: Duration,
+        quiet: bool,
+    ) -> bool {
+        let start = Instant::now();
+        loop {
+            let Some(current) = load() else {
+                return true;
+            };
+            if current <= self.max_load {
+                return true;
+            }
+            let waited = start.elapsed();
+            if waited >= self.max_wait {
+                if quiet {
+                    return false;
+                }
+                eprintln!(
+                    "warning: system load of {:.2} per CPU still above {:.2} \
+                     after waiting {:.1?}; resuming measurement",
+                    current, self.max_load, waited
+                );
+                return false;
+            }
+            std::thread::sleep(poll_interval.min(self.max_wait - waited));
+        }
+    }
+}
+
+/// Returns the 1-minute load average divided by the number of logical CPUs,
+/// or `None` if it is unavailable.
+fn load_per_cpu() -> Option<f64> {
+    let cpus = std::thread::available_parallelism().ok()?.get();
+    Some(load_average()? / cpus as f64)
+}
+
+#[cfg(unix)]
+fn load_average() -> Option<f64> {
+    let mut loads = [0.0; 1];
+    // SAFETY: `loads` is a valid buffer of the one sample requested.
+    let count = unsafe { libc::getloadavg(loads.as_mut_ptr(), 1) };
+    (count == 1).then_some(loads[0])
+}
+
+#[cfg(not(unix))]
+fn load_average() -> Option<f64> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pause(max_wait: Duration) -> LoadPause {
+        LoadPause {
+            max_load: 0.5,
+            max_wait,
+        }
+    }
+
+    #[test]
+    fn test_waits_until_load_drops() {
+        let mut loads = vec![0.9, 0.7, 0.4].into_iter();
+        let idle = pause(Duration::from_secs(60)).wait_with(
+            || loads.next(),
+            Duration::ZERO,
+            false,
+        );
+        assert!(idle);
+        assert_eq!(loads.next(), None);
+    }
+
+    #[test]
+    fn test_gives_up_after_max_wait() {
+        let mut readings = 0;
+        let idle = pause(Duration::from_millis(20)).wait_with(
+            || {
+                readings += 1;
+                Some(1.0)
+            },
+            Duration::from_millis(5),
+            true,
+        );
+        assert!(!idle);
+        assert!(readings >= 2);
+    }
+
+    #[test]
+    fn test_unavailable_load() {
+        assert!(pause(Duration::ZERO).wait_with(
+            || None,
+            Duration::ZERO,
+            false
+        ));
+    }
+}
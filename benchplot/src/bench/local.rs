@@ -0,0 +1,176 @@
+/*
+Copyright 2024 Owain Davies
+SPDX-License-Identifier: Apache-2.0 OR MIT
+*/
+
+//! Support for benchmarking functions that are not thread-safe, e.g.,
+//! closures capturing an `Rc`, a `RefCell`, or a raw FFI handle, when input
+//! sizes and functions are timed sequentially.
+
+use super::{Bench, BenchFnFactory, BenchFnInstance, FallibleBenchFnArg};
+use std::error::Error;
+use std::rc::Rc;
+
+/// Type alias for a function to benchmark that takes an argument of type `T`
+/// and returns a result of type `R`, and need not be thread-safe.
+pub type LocalBenchFn<T, R> = Box<dyn Fn(T) -> R>;
+
+/// Type alias for a tuple containing a `LocalBenchFn` and a name.
+pub type LocalBenchFnNamed<'a, T, R> = (LocalBenchFn<T, R>, &'a str);
+
+/// Type alias for a function accepting a positive integer size and returning
+/// input for the benchmarking functions, which need not be thread-safe.
+pub type LocalBenchFnArg<T> = Box<dyn Fn(usize) -> T>;
+
+/// Type alias for a factory of instances of a function to benchmark that need
+/// not be thread-safe.
+type LocalBenchFnFactory<T, R> = Box<dyn Fn(usize) -> BenchFnInstance<T, R>>;
+
+/// Type alias for an argument function that need not be thread-safe.
+type LocalFallibleBenchFnArg<T> =
+    Box<dyn Fn(usize) -> Result<T, Box<dyn Error + Send + Sync>>>;
+
+/// Whether the functions of a [`Bench`] or [`BenchBuilder`] are thread-safe,
+/// i.e., [`Shared`] or [`Local`].
+///
+/// [`BenchBuilder`]: crate::BenchBuilder
+pub trait Threading: sealed::Threading {}
+
+/// Marks a benchmark of thread-safe functions, which can run in `parallel`.
+///
+/// This is the default, for benchmarks created with [`BenchBuilder::new`].
+///
+/// [`BenchBuilder::new`]: crate::BenchBuilder::new
+pub enum Shared {}
+
+/// Marks a benchmark of functions that need not be thread-safe, created with
+/// [`BenchBuilder::new_local`].
+///
+/// Such a benchmark is neither `Send` nor `Sync`, so it is run on the thread
+/// that created it, and it cannot run in `parallel`:
+///
+/// ```compile_fail
+/// use benchplot::{BenchBuilder, LocalBenchFnArg, LocalBenchFnNamed};
+///
+/// let functions: Vec<LocalBenchFnNamed<usize, usize>> =
+///     vec![(Box::new(|n| n), "Identity")];
+/// let argfunc: LocalBenchFnArg<usize> = Box::new(|n| n);
+/// let builder = BenchBuilder::new_local(functions, argfunc, vec![10])
+///     .parallel(true);
+/// ```
+///
+/// ```compile_fail
+/// use benchplot::{BenchBuilder, LocalBenchFnArg, LocalBenchFnNamed};
+///
+/// let functions: Vec<LocalBenchFnNamed<usize, usize>> =
+///     vec![(Box::new(|n| n), "Identity")];
+/// let argfunc: LocalBenchFnArg<usize> = Box::new(|n| n);
+/// let mut bench = BenchBuilder::new_local(functions, argfunc, vec![10])
+///     .build()
+///     .unwrap();
+/// std::thread::spawn(move || {
+///     bench.run();
+/// });
+/// ```
+///
+/// [`BenchBuilder::new_local`]: crate::BenchBuilder::new_local
+pub enum Local {}
+
+impl Threading for Shared {}
+impl Threading for Local {}
+
+mod sealed {
+    use super::*;
+
+    pub trait Threading: Sized + 'static {
+        /// Type of the factories of the functions to benchmark.
+        type Factory<T, R>: Fn(usize) -> BenchFnInstance<T, R>;
+
+        /// Type of the argument function.
+        type Argfunc<T>: Fn(usize) -> Result<T, Box<dyn Error + Send + Sync>>;
+
+        /// Converts a factory of thread-safe instances.
+        fn factory<T, R>(factory: BenchFnFactory<T, R>) -> Self::Factory<T, R>;
+
+        /// Returns `bench` if its functions are thread-safe.
+        fn shared<'b, 'a, T, R>(
+            bench: &'b mut Bench<'a, T, R, Self>,
+        ) -> Option<&'b mut Bench<'a, T, R, Shared>>
+        where
+            Self: super::Threading;
+    }
+
+    impl Threading for Shared {
+        type Factory<T, R> = BenchFnFactory<T, R>;
+        type Argfunc<T> = FallibleBenchFnArg<T>;
+
+        fn factory<T, R>(factory: BenchFnFactory<T, R>) -> Self::Factory<T, R> {
+            factory
+        }
+
+        fn shared<'b, 'a, T, R>(
+            bench: &'b mut Bench<'a, T, R, Self>,
+        ) -> Option<&'b mut Bench<'a, T, R, Shared>> {
+            Some(bench)
+        }
+    }
+
+    impl Threading for Local {
+        type Factory<T, R> = LocalBenchFnFactory<T, R>;
+        type Argfunc<T> = LocalFallibleBenchFnArg<T>;
+
+        fn factory<T, R>(factory: BenchFnFactory<T, R>) -> Self::Factory<T, R> {
+            factory
+        }
+
+        fn shared<'b, 'a, T, R>(
+            _: &'b mut Bench<'a, T, R, Self>,
+        ) -> Option<&'b mut Bench<'a, T, R, Shared>> {
+            None
+        }
+    }
+}
+
+/// Returns a factory sharing a function to benchmark that is not thread-safe
+/// between all of its instances.
+pub(crate) fn factory_from_local_fn<T: 'static, R: 'static>(
+    func: LocalBenchFn<T, R>,
+) -> LocalBenchFnFactory<T, R> {
+    let func = Rc::new(func);
+    Box::new(move |_| {
+        let func = Rc::clone(&func);
+        Box::new(move |arg| func(arg))
+    })
+}
+
+/// Returns an argument function calling `argfunc`, which is not thread-safe.
+pub(crate) fn argfunc_from_local<T: 'static>(
+    argfunc: LocalBenchFnArg<T>,
+) -> LocalFallibleBenchFnArg<T> {
+    Box::new(move |n| Ok(argfunc(n)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn test_local_factory() {
+        let calls = Rc::new(Cell::new(0));
+        let counted = Rc::clone(&calls);
+        let factory =
+            factory_from_local_fn::<usize, usize>(Box::new(move |x| {
+                counted.set(counted.get() + 1);
+                x
+            }));
+        let mut first = factory(10);
+        let mut second = factory(20);
+        assert_eq!(first(3), 3);
+        assert_eq!(second(4), 4);
+        assert_eq!(calls.get(), 2);
+
+        drop((first, second, factory));
+        assert_eq!(Rc::strong_count(&calls), 1);
+    }
+}
@@ -0,0 +1,146 @@
+/*
+Copyright 2024 Owain Davies
+SPDX-License-Identifier: Apache-2.0 OR MIT
+*/
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Number of allocations made through [`CountingAllocator`].
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+/// A named quantity measured along with the time of every repetition, set
+/// with [`BenchBuilder::metric`](crate::BenchBuilder::metric) or
+/// [`BenchBuilder::result_metric`](crate::BenchBuilder::result_metric).
+pub(crate) struct Metric<R> {
+    pub(crate) name: String,
+    pub(crate) source: MetricSource<R>,
+}
+
+/// Where the value of a metric comes from.
+pub(crate) enum MetricSource<R> {
+    /// A counter read before and after each call, whose difference is the
+    /// value of the call.
+    Counter(Box<dyn Fn() -> f64 + Send + Sync>),
+
+    /// A function of the value returned by each call.
+    Result(Box<dyn Fn(&R) -> f64 + Send + Sync>),
+}
+
+impl<R> Metric<R> {
+    /// Reads the counter of the metric before a call, or returns zero if the
+    /// metric is not a counter.
+    pub(crate) fn start(&self) -> f64 {
+        match &self.source {
+            MetricSource::Counter(read) => read(),
+            MetricSource::Result(_) => 0.0,
+        }
+    }
+
+    /// Returns the value of the metric for a call that returned `result`,
+    /// given the reading of [`Metric::start`] before the call.
+    pub(crate) fn value(&self, start: f64, result: &R) -> f64 {
+        match &self.source {
+            MetricSource::Counter(read) => read() - start,
+            MetricSource::Result(value) => value(result),
+        }
+    }
+}
+
+/// Returns the CPU time consumed by the calling thread so far, in seconds, to
+/// be used as the counter of a metric:
+///
+/// ```
+/// use benchplot::{thread_cpu_time, BenchBuilder, BenchFnArg};
+///
+/// let argfunc: BenchFnArg<Vec<u64>> = Box::new(|n| (0..n as u64).collect());
+/// let bench = BenchBuilder::new(Vec::new(), argfunc, vec![10, 100])
+///     .add_function(|v: Vec<u64>| v.iter().sum::<u64>(), "Sum")
+///     .metric("CPU time (s)", thread_cpu_time)
+///     .build()
+///     .unwrap();
+/// ```
+///
+/// Returns NaN on platforms other than Unix.
+pub fn thread_cpu_time() -> f64 {
+    cpu_time()
+}
+
+#[cfg(unix)]
+fn cpu_time() -> f64 {
+    let mut time = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    // SAFETY: `time` is a valid timespec for clock_gettime to write to.
+    let result = unsafe {
+        libc::clock_gettime(libc::CLOCK_THREAD_CPUTIME_ID, &mut time)
+    };
+    if result != 0 {
+        return f64::NAN;
+    }
+    time.tv_sec as f64 + time.tv_nsec as f64 * 1e-9
+}
+
+#[cfg(not(unix))]
+fn cpu_time() -> f64 {
+    f64::NAN
+}
+
+/// A global allocator wrapping the system allocator that counts allocations,
+/// so that they can be collected as a metric:
+///
+/// ```
+/// use benchplot::{BenchBuilder, BenchFnArg, CountingAllocator};
+///
+/// #[global_allocator]
+/// static ALLOCATOR: CountingAllocator = CountingAllocator;
+///
+/// let argfunc: BenchFnArg<usize> = Box::new(|n| n);
+/// let bench = BenchBuilder::new(Vec::new(), argfunc, vec![10, 100])
+///     .add_function(|n| vec![0u8; n].len(), "vec!")
+///     .metric("Allocations", CountingAllocator::allocations)
+///     .build()
+///     .unwrap();
+/// ```
+///
+/// Allocations are counted across all threads, so the counts of functions
+/// timed in `parallel` include those of the functions running concurrently.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CountingAllocator;
+
+impl CountingAllocator {
+    /// Returns the number of allocations and reallocations made through the
+    /// allocator so far.
+    pub fn allocations() -> f64 {
+        ALLOCATIONS.load(Ordering::Relaxed) as f64
+    }
+}
+
+// SAFETY: Every method forwards to the system allocator, which upholds the
+// contract of `GlobalAlloc`.
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(
+        &self,
+        ptr: *mut u8,
+        layout: Layout,
+        new_size: usize,
+    ) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
@@ -0,0 +1,152 @@
+/*
+Copyright 2024 Owain Davies
+SPDX-License-Identifier: Apache-2.0 OR MIT
+*/
+
+use crate::util;
+use std::fmt::{self, Debug, Display};
+
+/// Maximum number of characters of a `Debug` rendering included in a report.
+const MAX_VALUE_CHARS: usize = 200;
+
+/// A function whose return value disagreed with that of the reference
+/// function.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Mismatch {
+    function: String,
+    value: String,
+}
+
+/// Report listing the functions whose return values disagreed with that of the
+/// reference function for a single input size.
+///
+/// Values are rendered with their `Debug` implementation and truncated to keep
+/// the report readable for large return values.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct MismatchReport {
+    size: usize,
+    reference: String,
+    expected: String,
+    mismatches: Vec<Mismatch>,
+}
+
+impl MismatchReport {
+    /// Creates a report comparing each of `results`, ordered as `names`,
+    /// against the result at index `reference`.
+    ///
+    /// Returns `None` if all results agree with the reference result.
+    pub(crate) fn new<R: Debug + PartialEq>(
+        size: usize,
+        names: &[&str],
+        results: &[&R],
+        reference: usize,
+    ) -> Option<Self> {
+        let expected = results[reference];
+        let mismatches: Vec<Mismatch> = names
+            .iter()
+            .zip(results)
+            .filter(|(_, &result)| result != expected)
+            .map(|(&name, result)| Mismatch {
+                function: name.to_string(),
+                value: render(result),
+            })
+            .collect();
+
+        if mismatches.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            size,
+            reference: names[reference].to_string(),
+            expected: render(expected),
+            mismatches,
+        })
+    }
+}
+
+impl Display for MismatchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for mismatch in &self.mismatches {
+            writeln!(
+                f,
+                "{} disagreed with {} at n={}",
+                mismatch.function, self.reference, self.size
+            )?;
+        }
+        writeln!(f, "  {}: {}", self.reference, self.expected)?;
+        for mismatch in &self.mismatches {
+            writeln!(f, "  {}: {}", mismatch.function, mismatch.value)?;
+        }
+        Ok(())
+    }
+}
+
+/// Renders the result at index `reference` and each of `results` disagreeing
+/// with it, ordered as `names`, with their untruncated `Debug`
+/// implementation.
+pub(crate) fn render_outputs<R: Debug + PartialEq>(
+    names: &[&str],
+    results: &[&R],
+    reference: usize,
+) -> String {
+    let expected = results[reference];
+    names
+        .iter()
+        .zip(results)
+        .enumerate()
+        .filter(|&(idx, (_, &result))| idx == reference || result != expected)
+        .map(|(_, (name, result))| format!("{}:\n{:#?}\n", name, result))
+        .collect()
+}
+
+fn render<R: Debug>(value: &R) -> String {
+    util::truncate(&format!("{:?}", value), MAX_VALUE_CHARS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_mismatch() {
+        let report = MismatchReport::new(8, &["A", "B"], &[&1, &1], 0);
+        assert!(report.is_none());
+    }
+
+    #[test]
+    fn test_report_lists_all_mismatches() {
+        let report =
+            MismatchReport::new(8, &["A", "B", "C"], &[&1, &2, &3], 0).unwrap();
+
+        assert_eq!(
+            report.to_string(),
+            "B disagreed with A at n=8\n\
+             C disagreed with A at n=8\n  \
+             A: 1\n  \
+             B: 2\n  \
+             C: 3\n"
+        );
+    }
+
+    #[test]
+    fn test_render_outputs() {
+        let outputs = render_outputs(&["A", "B", "C"], &[&1, &1, &3], 1);
+        assert_eq!(outputs, "B:\n1\nC:\n3\n");
+
+        let outputs = render_outputs(&["A", "B", "C"], &[&1, &2, &1], 0);
+        assert_eq!(outputs, "A:\n1\nB:\n2\n");
+    }
+
+    #[test]
+    fn test_report_truncates_values() {
+        let long = vec![0; 1000];
+        let short = vec![0; 2];
+        let report =
+            MismatchReport::new(8, &["A", "B"], &[&long, &short], 1).unwrap();
+
+        assert!(report.expected.len() < 10);
+        assert!(report.mismatches[0].value.chars().count() <= MAX_VALUE_CHARS);
+        assert!(report.mismatches[0].value.ends_with('…'));
+    }
+}
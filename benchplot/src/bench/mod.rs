@@ -3,17 +3,121 @@ Copyright 2024 Owain Davies
 SPDX-License-Identifier: Apache-2.0 OR MIT
 */
 
+mod ab;
+mod aggregation;
+mod baseline;
+mod bootstrap;
 mod builder;
+mod cache;
+mod cachegrind;
+mod changepoint;
+mod compare;
+mod complexity;
+mod data;
+#[cfg(feature = "sqlite")]
+mod database;
+#[cfg(feature = "polars")]
+mod dataframe;
+#[cfg(feature = "plot")]
+mod font;
+#[cfg(feature = "plot")]
+mod format;
+#[cfg(feature = "plot")]
+mod harness;
+#[cfg(feature = "plot")]
+mod heatmap;
+#[cfg(feature = "plot")]
+mod html;
+mod isolation;
+mod json;
+#[cfg(feature = "egui")]
+mod live;
+mod load;
+mod local;
+mod metric;
+mod mismatch;
+mod parallel;
+mod pgfplots;
+#[cfg(feature = "plot")]
 mod plot;
+mod prefault;
+mod priority;
+mod profile;
+mod ranking;
+mod report;
+mod significance;
+#[cfg(feature = "plot")]
+mod smoothing;
+mod stream;
+#[cfg(feature = "plot")]
+mod style;
+mod suite;
+mod summary;
+#[cfg(feature = "plot")]
+mod theme;
+mod timer;
+mod verbosity;
 
+pub use ab::AbComparison;
+pub use aggregation::Aggregation;
+pub use baseline::Baseline;
+pub use bootstrap::ConfidenceInterval;
 pub use builder::{BenchBuilder, BenchBuilderError};
-pub use plot::{PlotBuilder, PlotBuilderError};
+pub use cache::CacheMode;
+pub use cachegrind::InstructionCounts;
+pub use changepoint::ChangePoint;
+pub use compare::CellChange;
+pub use complexity::{Complexity, ComplexityFit};
+pub use data::BenchData;
+#[cfg(feature = "plot")]
+pub use format::NumberFormat;
+#[cfg(feature = "egui")]
+pub use live::LiveChart;
+pub use local::{
+    Local, LocalBenchFn, LocalBenchFnArg, LocalBenchFnNamed, Shared, Threading,
+};
+pub use metric::{thread_cpu_time, CountingAllocator};
+#[cfg(feature = "plot")]
+pub use plot::{
+    LegendPosition, LowerPanel, Marker, PlotBuilder, PlotBuilderError,
+    SeriesStyle, TitleAlignment, TitleStyle,
+};
+pub use prefault::Prefault;
+pub use priority::Priority;
+#[cfg(feature = "dhat")]
+pub use profile::DhatProfiler;
+pub use profile::Profiler;
+pub use ranking::Winner;
+#[cfg(feature = "upload")]
+pub use report::HttpReporter;
+pub use report::Reporter;
+pub use significance::SignificanceMatrix;
+#[cfg(feature = "plot")]
+pub use smoothing::Smoothing;
+pub use stream::CellEvent;
+#[cfg(feature = "plot")]
+pub use style::PlotStyle;
+pub use suite::{BenchSuite, Runnable};
+#[cfg(feature = "plot")]
+pub use theme::{DarkTheme, DefaultTheme, Theme};
+#[cfg(all(
+    feature = "tsc",
+    any(target_arch = "x86", target_arch = "x86_64")
+))]
+pub use timer::TscTimer;
+pub use timer::{CoarseTimer, InstantTimer, Timer, TimerCalibration};
+pub use verbosity::Verbosity;
 
 use crate::util;
-use std::collections::HashMap;
+use cache::CacheEvictor;
+use load::LoadPause;
+use metric::Metric;
+use mismatch::MismatchReport;
+use profile::ProfiledCell;
 use std::fmt::Debug;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::time::Instant;
+use stream::ResultStream;
 
 /// Type alias for a function to benchmark that takes an argument of type `T`
 /// and returns a result of type `R`.
@@ -22,203 +126,2543 @@ pub type BenchFn<T, R> = Box<dyn Fn(T) -> R + Send + Sync>;
 /// Type alias for a tuple containing a `BenchFn` and a name.
 pub type BenchFnNamed<'a, T, R> = (BenchFn<T, R>, &'a str);
 
+/// Type alias for a function to benchmark that takes the input size along
+/// with an argument of type `T`, e.g., to preallocate a buffer of that size,
+/// and returns a result of type `R`.
+pub type SizedBenchFn<T, R> = Box<dyn Fn(usize, T) -> R + Send + Sync>;
+
 /// Type alias for a function accepting a positive integer size and returning
 /// input for the benchmarking functions.
 pub type BenchFnArg<T> = Box<dyn Fn(usize) -> T + Send + Sync>;
 
+/// Type alias for a function accepting a positive integer size and returning
+/// input for the benchmarking functions, or an error if the input cannot be
+/// generated, e.g., because it is loaded from a file that is missing.
+pub type FallibleBenchFnArg<T> = Box<
+    dyn Fn(usize) -> Result<T, Box<dyn std::error::Error + Send + Sync>>
+        + Send
+        + Sync,
+>;
+
+/// Error type for [`Bench::try_run`].
+#[derive(Debug, thiserror::Error)]
+pub enum RunError {
+    /// Indicates that the argument of an input size could not be generated.
+    #[error("Failed to generate the argument of size {size}: {source}")]
+    Argument {
+        /// The input size.
+        size: usize,
+
+        /// The error returned by the argument function.
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+
+    /// Indicates that the argument of an input size could not be written to
+    /// its file.
+    #[error(
+        "Failed to write the input of size {size} to {}: {source}",
+        .path.display()
+    )]
+    Input {
+        /// The input size.
+        size: usize,
+
+        /// The path of the file.
+        path: PathBuf,
+
+        /// The I/O error.
+        source: std::io::Error,
+    },
+
+    /// Indicates that the run was aborted because the receiver of its events
+    /// was dropped.
+    #[error("The run was aborted by the receiver of its events.")]
+    Aborted,
+
+    /// Indicates that no function to benchmark has the given name.
+    #[error("Unknown function \"{0}\".")]
+    UnknownFunction(String),
+}
+
+/// Type alias for an instance of a function to benchmark, used for all
+/// repetitions of a single `(input size, function)` pair.
+type BenchFnInstance<T, R> = Box<dyn FnMut(T) -> R>;
+
+/// Type alias for a function creating a fresh instance of a function to
+/// benchmark for each `(input size, function)` pair, given the input size.
+type BenchFnFactory<T, R> =
+    Box<dyn Fn(usize) -> BenchFnInstance<T, R> + Send + Sync>;
+
+/// Type alias for a function writing an argument, to persist the inputs the
+/// functions are timed on.
+type InputWriterFn<T> = Arc<
+    dyn Fn(&T, &mut dyn std::io::Write) -> std::io::Result<()> + Send + Sync,
+>;
+
+/// Type alias for a function touching every page of an argument before it is
+/// passed to a function to benchmark.
+type PrefaultFn<T> = Arc<dyn Fn(&T) + Send + Sync>;
+
+/// Returns a factory sharing a stateless function to benchmark between all of
+/// its instances.
+fn factory_from_fn<T: 'static, R: 'static>(
+    func: BenchFn<T, R>,
+) -> BenchFnFactory<T, R> {
+    let func = Arc::new(func);
+    Box::new(move |_| {
+        let func = Arc::clone(&func);
+        Box::new(move |arg| func(arg))
+    })
+}
+
+/// Returns a factory sharing a function to benchmark that also takes the
+/// input size between all of its instances, each bound to its input size.
+fn factory_from_sized_fn<T: 'static, R: 'static>(
+    func: SizedBenchFn<T, R>,
+) -> BenchFnFactory<T, R> {
+    let func = Arc::new(func);
+    Box::new(move |size| {
+        let func = Arc::clone(&func);
+        Box::new(move |arg| func(size, arg))
+    })
+}
+
+/// Returns a factory giving each instance its own clone of a stateful function
+/// to benchmark.
+fn factory_from_fn_mut<T: 'static, R: 'static, F>(
+    func: F,
+) -> BenchFnFactory<T, R>
+where
+    F: FnMut(T) -> R + Clone + Send + Sync + 'static,
+{
+    Box::new(move |_| Box::new(func.clone()))
+}
+
 /// A structure for benchmarking functions over various input sizes and plotting
 /// the results.
-pub struct Bench<'a, T, R> {
-    functions: Vec<(Arc<BenchFn<T, R>>, &'a str)>,
-    argfunc: Arc<BenchFnArg<T>>,
+///
+/// Benchmarks created with [`BenchBuilder::new_local`] are marked [`Local`],
+/// and the others [`Shared`].
+pub struct Bench<'a, T, R, M: Threading = Shared> {
+    functions: Vec<(M::Factory<T, R>, &'a str)>,
+    argfunc: Arc<M::Argfunc<T>>,
     sizes: Vec<usize>,
     repetitions: usize,
+    max_repetitions: usize,
     parallel: bool,
+    isolated: bool,
+    cachegrind: bool,
     assert_equal: bool,
+    strict_equal: bool,
+    reference: usize,
+    mismatch_report: Option<PathBuf>,
+    mismatch_inputs: Option<(PathBuf, InputWriterFn<T>)>,
+    inputs: Option<(PathBuf, InputWriterFn<T>)>,
+    results_file: Option<PathBuf>,
+    live_plot: Option<PathBuf>,
+    results_database: Option<PathBuf>,
+    cv_threshold: Option<f64>,
+    target_precision: Option<f64>,
+    priority: Priority,
+    load_pause: Option<LoadPause>,
+    cache_mode: CacheMode,
+    aggregation: Aggregation,
+    confidence_level: Option<f64>,
+    keep_samples: bool,
+    print_verdict: bool,
+    catch_panics: bool,
+    verbosity: Verbosity,
+    function_sizes: Vec<Vec<usize>>,
+    max_sizes: Vec<Option<usize>>,
+    prefault: Option<PrefaultFn<T>>,
+    timer: Arc<dyn Timer>,
+    label: Option<String>,
+    reporters: Vec<Arc<dyn Reporter>>,
+    profiled: Vec<ProfiledCell>,
+    metrics: Vec<Metric<R>>,
+
+    priority_elevated: Option<bool>,
+    timer_calibration: Option<TimerCalibration>,
+    data: BenchData,
+    metric_data: Vec<BenchData>,
+    clone_times: Vec<(usize, f64)>,
+    instruction_counts: Vec<(usize, usize, InstructionCounts)>,
+    confidence_intervals: Vec<(usize, usize, ConfidenceInterval)>,
+    samples: Vec<(usize, usize, Vec<f64>)>,
+    noisy: Vec<(usize, usize)>,
+    failures: Vec<(usize, usize, String)>,
+}
+
+impl<'a, T, R, M: Threading> Bench<'a, T, R, M> {
+    /// Returns the input sizes, in the order they are benchmarked.
+    pub fn sizes(&self) -> &[usize] {
+        &self.sizes
+    }
+
+    /// Returns the names of the functions to benchmark, in the order of the
+    /// columns of [`Bench::data`].
+    pub fn function_names(&self) -> Vec<&'a str> {
+        self.functions.iter().map(|&(_, name)| name).collect()
+    }
+
+    /// Returns the label identifying this run, if any.
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    /// Returns the minimum number of times each `(input size, function)` pair
+    /// is timed.
+    pub fn repetitions(&self) -> usize {
+        self.repetitions
+    }
+
+    /// Returns the measured average timings in seconds.
+    ///
+    /// The data has a row per input size and a column per function, in the
+    /// order of [`Bench::function_names`]. It is empty until `run()` is
+    /// called.
+    pub fn data(&self) -> &BenchData {
+        &self.data
+    }
+
+    /// Returns the values of the metric named `name`, combined like the
+    /// timings, with a row per input size and a column per function as in
+    /// [`Bench::data`], or `None` if there is no such metric.
+    ///
+    /// Metrics are set with
+    /// [`BenchBuilder::metric`](crate::BenchBuilder::metric) and
+    /// [`BenchBuilder::result_metric`](crate::BenchBuilder::result_metric).
+    /// Their values are NaN for isolated benchmarks.
+    pub fn metric(&self, name: &str) -> Option<&BenchData> {
+        let idx = self.metrics.iter().position(|m| m.name == name)?;
+        Some(&self.metric_data[idx])
+    }
+
+    /// Returns the names of the metrics collected along with the timings, in
+    /// the order they were added.
+    pub fn metric_names(&self) -> Vec<&str> {
+        self.metrics.iter().map(|m| m.name.as_str()).collect()
+    }
+
+    /// Returns the average time in seconds taken to clone the argument of
+    /// each input size, in the order of [`Bench::sizes`].
+    ///
+    /// The argument is cloned before every repetition, outside of the timed
+    /// region, so this cost is not included in [`Bench::data`]. It is measured
+    /// separately to show the overhead of the harness, which for large
+    /// arguments may also perturb caches. Clone times are not measured for
+    /// isolated benchmarks.
+    pub fn clone_times(&self) -> &[(usize, f64)] {
+        &self.clone_times
+    }
+
+    /// Returns the instruction and cache counts of a single call of each
+    /// function at each input size, as `(input size, function index, counts)`
+    /// in the order the pairs were counted.
+    ///
+    /// Counts are only recorded if
+    /// [`BenchBuilder::cachegrind`](crate::BenchBuilder::cachegrind) is set.
+    pub fn instruction_counts(&self) -> &[(usize, usize, InstructionCounts)] {
+        &self.instruction_counts
+    }
+
+    /// Returns the path of the file the input of size `size` is written to,
+    /// if inputs are persisted with
+    /// [`BenchBuilder::persist_inputs`](crate::BenchBuilder::persist_inputs).
+    pub fn input_path(&self, size: usize) -> Option<PathBuf> {
+        let (dir, _) = self.inputs.as_ref()?;
+        Some(dir.join(format!("input_{}", size)))
+    }
+
+    /// Returns the bootstrap confidence interval of the benchmark value of
+    /// each function at each input size, as
+    /// `(input size, function index, interval)` in the order the pairs
+    /// completed.
+    ///
+    /// Intervals are only estimated if
+    /// [`BenchBuilder::confidence_level`](crate::BenchBuilder::confidence_level)
+    /// is set.
+    pub fn confidence_intervals(
+        &self,
+    ) -> &[(usize, usize, ConfidenceInterval)] {
+        &self.confidence_intervals
+    }
+
+    /// Returns the confidence interval of the function at `func_idx` at the
+    /// input size `size`, if one was estimated.
+    #[cfg(feature = "plot")]
+    pub(crate) fn confidence_interval(
+        &self,
+        size: usize,
+        func_idx: usize,
+    ) -> Option<ConfidenceInterval> {
+        self.confidence_intervals
+            .iter()
+            .rev()
+            .find(|&&(s, i, _)| s == size && i == func_idx)
+            .map(|&(_, _, interval)| interval)
+    }
 
-    data: Vec<(usize, Vec<f64>)>,
+    /// Returns the timings in seconds of every repetition of each function at
+    /// each input size, as `(input size, function index, timings)` in the
+    /// order the pairs completed.
+    ///
+    /// Timings are measured in integer nanoseconds, which the benchmark values
+    /// are aggregated from, and converted to seconds for these samples, like
+    /// for the coefficients of variation and confidence intervals.
+    ///
+    /// Timings are only kept if
+    /// [`BenchBuilder::keep_samples`](crate::BenchBuilder::keep_samples) is
+    /// set.
+    pub fn samples(&self) -> &[(usize, usize, Vec<f64>)] {
+        &self.samples
+    }
+
+    /// Returns the (input size, function) pairs at which the function
+    /// panicked, as `(input size, function index, panic message)` in the
+    /// order the pairs completed.
+    ///
+    /// Panics are only caught if
+    /// [`BenchBuilder::catch_panics`](crate::BenchBuilder::catch_panics) is
+    /// set.
+    pub fn failures(&self) -> &[(usize, usize, String)] {
+        &self.failures
+    }
+
+    /// Returns the timings of the function at `func_idx` at the input size
+    /// `size`, if they were kept.
+    pub(crate) fn samples_of(
+        &self,
+        size: usize,
+        func_idx: usize,
+    ) -> Option<&[f64]> {
+        self.samples
+            .iter()
+            .rev()
+            .find(|&&(s, i, _)| s == size && i == func_idx)
+            .map(|(_, _, times)| times.as_slice())
+    }
 }
 
-type FunctionResult<R> = (R, f64);
-type FunctionMultipleResult<R> = (R, Vec<f64>, f64);
+type FunctionResult<R> = (R, u128);
+type FunctionMultipleResult<R> = (Option<R>, Vec<u128>, f64, bool, Vec<f64>);
+
+/// Parameters controlling how many times and how each `(input size, function)`
+/// pair is timed, whether its return values are compared across repetitions,
+/// and whether its last return value is retained for comparison with other
+/// functions.
+#[derive(Clone, Copy)]
+struct Sampling {
+    repetitions: usize,
+    max_repetitions: usize,
+    cv_threshold: Option<f64>,
+    target_precision: Option<f64>,
+    critical_value: f64,
+    strict: bool,
+    retain: bool,
+    cache_mode: CacheMode,
+    aggregation: Aggregation,
+}
+
+impl Sampling {
+    /// Returns whether another repetition should be timed given the timings so
+    /// far, in integer nanoseconds.
+    ///
+    /// At least `repetitions` timings are always taken. Beyond that, timings
+    /// are only added while their coefficient of variation exceeds the
+    /// threshold, or the half-width of the confidence interval of their mean
+    /// relative to the mean exceeds the target precision, up to a total of
+    /// `max_repetitions`.
+    fn needs_more(&self, nanos: &[u128]) -> bool {
+        if nanos.len() < self.repetitions {
+            return true;
+        }
+        if nanos.len() >= self.max_repetitions {
+            return false;
+        }
+        let times = aggregation::to_secs(nanos);
+        let Some(cv) = util::coefficient_of_variation(&times) else {
+            return false;
+        };
+        let noisy = self.cv_threshold.is_some_and(|threshold| cv > threshold);
+        let imprecise = self.target_precision.is_some_and(|target| {
+            self.critical_value * cv / (times.len() as f64).sqrt() > target
+        });
+        noisy || imprecise
+    }
+}
 
 impl<
         'a,
         T: Clone + Send + Sync + 'static,
-        R: Clone + Send + Debug + PartialEq + 'static,
-    > Bench<'a, T, R>
+        R: Send + Debug + PartialEq + 'static,
+        M: Threading,
+    > Bench<'a, T, R, M>
 {
-    #[allow(dead_code)]
-    fn new(
-        functions: Vec<(Arc<BenchFn<T, R>>, &'a str)>,
-        argfunc: Arc<BenchFnArg<T>>,
-        sizes: Vec<usize>,
-        repetitions: usize,
-        parallel: bool,
-        assert_equal: bool,
-    ) -> Self {
-        Self {
-            functions,
-            argfunc,
-            sizes,
-            repetitions,
-            parallel,
-            assert_equal,
-            data: Vec::new(),
-        }
-    }
-
     /// Executes all benchmarks.
     ///
     /// The function either runs benchmarks sequentially or in parallel based on
-    /// the `parallel` flag.
+    /// the `parallel` flag, or in child processes if the `isolated` flag is
+    /// set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the argument of an input size cannot be generated. See
+    /// [`Bench::try_run`] to handle this as an error instead.
     pub fn run(&mut self) -> &mut Self {
-        if self.parallel {
-            self.run_parallel();
-        } else {
-            self.run_sequential();
+        if let Err(err) = self.try_run() {
+            panic!("{}", err);
         }
         self
     }
 
-    /// Times each `(input size, function)` pair sequentially.
-    fn run_sequential(&mut self) {
-        for &size in &self.sizes {
-            let arg = (self.argfunc)(size);
-            let results: Vec<FunctionMultipleResult<R>> =
-                Self::time_functions(arg, &self.functions, self.repetitions);
+    /// Executes all benchmarks like [`Bench::run`] unless they have already
+    /// been run, so that results can be plotted or reported without
+    /// tracking whether the benchmark was run:
+    ///
+    /// ```
+    /// use benchplot::{BenchBuilder, BenchFnArg};
+    ///
+    /// let argfunc: BenchFnArg<usize> = Box::new(|n| n);
+    /// let mut bench = BenchBuilder::new(Vec::new(), argfunc, vec![10, 100])
+    ///     .add_function(|n| n * 2, "Double")
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(bench.run_if_needed().data().num_sizes(), 2);
+    /// // The results of the first run are kept.
+    /// assert_eq!(bench.run_if_needed().data().num_sizes(), 2);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics like [`Bench::run`].
+    pub fn run_if_needed(&mut self) -> &mut Self {
+        if self.data.is_empty() {
+            self.run();
+        }
+        self
+    }
 
-            if self.assert_equal {
-                assert!(util::all_items_equal(
-                    results.iter().map(|(result, _, _)| result)
+    /// Executes all benchmarks like [`Bench::run`], returning an error if the
+    /// argument of an input size cannot be generated, e.g., with an argument
+    /// function passed to
+    /// [`BenchBuilder::new_fallible`](crate::BenchBuilder::new_fallible).
+    ///
+    /// On error, the results of the input sizes timed before the failure may
+    /// have been recorded, and the results are not reported.
+    pub fn try_run(&mut self) -> Result<&mut Self, RunError> {
+        self.run_with_events(None)
+    }
+
+    /// Executes all benchmarks like [`Bench::try_run`], sending a
+    /// [`CellEvent`] to `events` as soon as each `(input size, function)` pair
+    /// has been timed, so that results can be consumed incrementally, e.g., to
+    /// update a user interface.
+    ///
+    /// Dropping the receiver aborts the run once the pairs being timed
+    /// complete, and [`RunError::Aborted`] is returned. Events are sent from
+    /// the thread running the benchmarks, or from the worker threads with
+    /// `parallel`, so the receiver is usually consumed on another thread:
+    ///
+    /// ```
+    /// use benchplot::{BenchBuilder, BenchFnArg, BenchFnNamed, CellEvent};
+    /// use std::sync::mpsc;
+    ///
+    /// let functions: Vec<BenchFnNamed<usize, usize>> =
+    ///     vec![(Box::new(|n: usize| (0..n).sum()), "Sum")];
+    /// let argfunc: BenchFnArg<usize> = Box::new(|n| n);
+    /// let mut bench = BenchBuilder::new(functions, argfunc, vec![10, 100])
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let (sender, receiver) = mpsc::channel::<CellEvent>();
+    /// std::thread::scope(|scope| {
+    ///     scope.spawn(move || {
+    ///         for event in receiver {
+    ///             println!("n={}: {:?}", event.size, event.value);
+    ///         }
+    ///     });
+    ///     bench.run_streaming(sender).unwrap();
+    /// });
+    /// ```
+    pub fn run_streaming(
+        &mut self,
+        events: std::sync::mpsc::Sender<CellEvent>,
+    ) -> Result<&mut Self, RunError> {
+        self.run_with_events(Some(events))
+    }
+
+    fn run_with_events(
+        &mut self,
+        events: Option<std::sync::mpsc::Sender<CellEvent>>,
+    ) -> Result<&mut Self, RunError> {
+        let _priority_guard = self.elevate_priority();
+        if isolation::child_cell().is_none() {
+            self.timer_calibration =
+                Some(TimerCalibration::measure(&*self.timer));
+        }
+        let stream = self.create_results_stream(events);
+        let stream = stream.as_ref();
+        let measured = self.data.num_sizes();
+
+        if self.isolated {
+            self.run_isolated(stream)?;
+        } else if let Some(bench) = M::shared(self).filter(|b| b.parallel) {
+            bench.run_parallel(stream)?;
+        } else {
+            self.run_sequential(stream)?;
+        }
+
+        if stream.is_some() && self.results_file.is_some() {
+            self.data.truncate(measured);
+            self.load_results_file();
+        }
+        self.check_granularity();
+        self.report();
+        self.announce_verdict();
+        Ok(self)
+    }
+
+    /// Warns about the timings of the last run that are within an order of
+    /// magnitude of the granularity of the timer, and so dominated by it.
+    fn check_granularity(&self) {
+        let Some(calibration) = self.timer_calibration else {
+            return;
+        };
+        let threshold = 10.0 * calibration.granularity.as_secs_f64();
+        let coarse = self
+            .data
+            .iter()
+            .flat_map(|(_, times)| times)
+            .filter(|&&time| time < threshold)
+            .count();
+        if coarse > 0 {
+            self.warn(format_args!(
+                "{} timing(s) are within an order of magnitude of the timer \
+                 granularity of {}; increase the input sizes or use a finer \
+                 timer",
+                coarse,
+                util::format_seconds(calibration.granularity.as_secs_f64())
+            ));
+        }
+    }
+
+    /// Generates the argument of the input size `size`.
+    ///
+    /// The argument is also written to its file if inputs are persisted,
+    /// except in a child process of an isolated benchmark, where the parent
+    /// writes it instead.
+    fn argument(&self, size: usize) -> Result<T, RunError> {
+        let arg = (self.argfunc)(size)
+            .map_err(|source| RunError::Argument { size, source })?;
+        if isolation::child_cell().is_none() {
+            self.persist_input(size, &arg)?;
+        }
+        Ok(arg)
+    }
+
+    /// Writes `arg`, the argument of the input size `size`, to its file if
+    /// inputs are persisted.
+    fn persist_input(&self, size: usize, arg: &T) -> Result<(), RunError> {
+        let (Some((dir, write)), Some(path)) =
+            (&self.inputs, self.input_path(size))
+        else {
+            return Ok(());
+        };
+        let result = std::fs::create_dir_all(dir)
+            .and_then(|()| std::fs::File::create(&path))
+            .and_then(|file| {
+                let mut writer = std::io::BufWriter::new(file);
+                write(arg, &mut writer)?;
+                std::io::Write::flush(&mut writer)
+            });
+        result.map_err(|source| RunError::Input { size, path, source })
+    }
+
+    /// Passes the results of the run to each reporter, warning about any that
+    /// fails rather than panicking, since the results themselves are intact.
+    ///
+    /// Nothing is reported from a child process of an isolated benchmark.
+    fn report(&self) {
+        if self.reporters.is_empty() || isolation::child_cell().is_some() {
+            return;
+        }
+        let results = self.baseline();
+        for reporter in &self.reporters {
+            if let Err(err) = reporter.report(&results) {
+                self.warn(format_args!("failed to report results: {}", err));
+            }
+        }
+    }
+
+    /// Prints the verdict of the run, if requested, and the table of results
+    /// if the benchmark is verbose.
+    fn announce_verdict(&self) {
+        if isolation::child_cell().is_some() {
+            return;
+        }
+        if self.verbosity == Verbosity::Verbose {
+            println!("{}", self);
+        }
+        if !self.print_verdict || self.verbosity == Verbosity::Quiet {
+            return;
+        }
+        if let Some(verdict) = self.verdict() {
+            println!("verdict: {}", verdict);
+        }
+    }
+
+    /// Prints a warning to standard error, unless the benchmark is quiet.
+    fn warn(&self, message: std::fmt::Arguments<'_>) {
+        if self.verbosity != Verbosity::Quiet {
+            eprintln!("warning: {}", message);
+        }
+    }
+
+    /// Prints the benchmark value of a `(input size, function)` pair to
+    /// standard error as soon as it is measured, if the benchmark is verbose.
+    fn log_cell(
+        verbosity: Verbosity,
+        size: usize,
+        name: &str,
+        times: &[f64],
+        value: f64,
+    ) {
+        if verbosity == Verbosity::Verbose {
+            eprintln!(
+                "n={}: {}: {} ({} repetitions)",
+                size,
+                name,
+                util::format_seconds(value),
+                times.len()
+            );
+        }
+    }
+
+    /// Returns the overhead and granularity of the timer, measured at the
+    /// start of the last run, or `None` before the first run.
+    pub fn timer_calibration(&self) -> Option<TimerCalibration> {
+        self.timer_calibration
+    }
+
+    /// Returns whether the process priority was successfully raised during the
+    /// last run, or `None` if no priority elevation was requested.
+    pub fn priority_elevated(&self) -> Option<bool> {
+        self.priority_elevated
+    }
+
+    /// Raises the process priority if requested, recording and reporting
+    /// whether elevation succeeded.
+    ///
+    /// The previous priority is restored when the returned guard is dropped.
+    fn elevate_priority(&mut self) -> Option<priority::PriorityGuard> {
+        if self.priority == Priority::Normal {
+            return None;
+        }
+        match priority::elevate(self.priority) {
+            Ok(guard) => {
+                self.priority_elevated = Some(true);
+                Some(guard)
+            }
+            Err(err) => {
+                self.warn(format_args!(
+                    "failed to raise process priority to {:?}: {}",
+                    self.priority, err
                 ));
+                self.priority_elevated = Some(false);
+                None
             }
+        }
+    }
 
-            let execution_times: Vec<f64> =
-                results.iter().map(|(_, _, avg)| *avg).collect();
-            self.data.push((size, execution_times));
+    /// Creates the results file and opens the results database, if either was
+    /// requested, for the timings to be streamed to as each
+    /// `(input size, function)` pair completes, along with events sent to
+    /// `events`, if any.
+    ///
+    /// Nothing is streamed from a child process of an isolated benchmark,
+    /// where the timings are reported to the parent instead.
+    fn create_results_stream(
+        &self,
+        events: Option<std::sync::mpsc::Sender<CellEvent>>,
+    ) -> Option<ResultStream> {
+        if isolation::child_cell().is_some() {
+            return None;
         }
+        let mut stream = ResultStream::default();
+        if let Some(events) = events {
+            stream.set_events(events);
+        }
+        if let Some(path) = &self.results_file {
+            stream.create_file(path).unwrap_or_else(|err| {
+                panic!(
+                    "failed to create results file {}: {}",
+                    path.display(),
+                    err
+                )
+            });
+        }
+        #[cfg(feature = "sqlite")]
+        if let Some(path) = &self.results_database {
+            stream
+                .open_database(path, self.label.as_deref())
+                .unwrap_or_else(|err| {
+                    panic!(
+                        "failed to open results database {}: {}",
+                        path.display(),
+                        err
+                    )
+                });
+        }
+        (!stream.is_empty()).then_some(stream)
     }
 
-    /// Times `(input size, function)` pairs in parallel.
-    fn run_parallel(&mut self) {
-        use rayon::prelude::*;
+    /// Appends the timings of a `(input size, function)` pair, in integer
+    /// nanoseconds, to the results file and sends them as an event with the
+    /// benchmark value `value` in seconds, if results are streamed.
+    fn stream_cell(
+        stream: Option<&ResultStream>,
+        size: usize,
+        (func_idx, name): (usize, &str),
+        nanos: &[u128],
+        value: f64,
+    ) {
+        if let Some(stream) = stream {
+            let times = aggregation::to_secs(nanos);
+            if let Err(err) = stream.write_cell(size, name, &times) {
+                panic!(
+                    "failed to write results of {} at n={}: {}",
+                    name, size, err
+                );
+            }
+            stream.send_event(CellEvent {
+                size,
+                function: func_idx,
+                value: data::to_duration(value)
+                    .expect("the timings of a pair have a benchmark value"),
+                times: nanos
+                    .iter()
+                    .map(|&nanos| aggregation::nanos_to_duration(nanos))
+                    .collect(),
+            });
+        }
+    }
 
-        let size_args: Vec<_> = self
-            .sizes
+    /// Returns an error if the run was aborted by the receiver of its events.
+    fn check_aborted(stream: Option<&ResultStream>) -> Result<(), RunError> {
+        match stream {
+            Some(stream) if stream.is_aborted() => Err(RunError::Aborted),
+            _ => Ok(()),
+        }
+    }
+
+    /// Re-renders the live plot, if any, with the results measured so far.
+    ///
+    /// Nothing is rendered until at least one timing has been measured, or
+    /// from a child process of an isolated benchmark.
+    #[cfg(feature = "plot")]
+    fn update_live_plot(&self) {
+        let Some(path) = &self.live_plot else {
+            return;
+        };
+        let measured = self
+            .data
             .iter()
-            .enumerate()
-            .map(|(size_idx, &size)| {
-                let arg = (self.argfunc)(size);
-                (size_idx, size, arg)
-            })
-            .collect();
+            .any(|(_, times)| times.iter().any(|time| time.is_finite()));
+        if !measured || isolation::child_cell().is_some() {
+            return;
+        }
+        let mut plot = self.plot(path);
+        if let Some(label) = &self.label {
+            plot = plot.title(label);
+        }
+        if let Err(err) = plot.build() {
+            self.warn(format_args!(
+                "failed to update live plot {}: {}",
+                path.display(),
+                err
+            ));
+        }
+    }
 
-        let results_and_times: Vec<_> = size_args
-            .par_iter()
-            .flat_map(|&(size_idx, size, ref arg)| {
-                let repetitions = self.repetitions;
-                self.functions.par_iter().enumerate().map_with(
-                    arg.clone(),
-                    move |arg_clone, (func_idx, (func, _))| {
-                        let (last_result, _times, avg_time) =
-                            Self::time_function_multiple_times(
-                                func,
-                                arg_clone.clone(),
-                                repetitions,
-                            );
-
-                        ((size_idx, func_idx), (size, (last_result, avg_time)))
-                    },
-                )
-            })
-            .collect();
+    /// Does nothing, as live plots cannot be set without the `plot` feature.
+    #[cfg(not(feature = "plot"))]
+    fn update_live_plot(&self) {}
 
-        let mut results_by_size: HashMap<usize, Vec<R>> = HashMap::new();
+    /// Reads the average timings of the last run back from the results file,
+    /// in the order of `self.sizes` and `self.functions`.
+    fn load_results_file(&mut self) {
+        let path = self.results_file.as_ref().expect("results are streamed");
+        let cells = stream::read_cells(path).unwrap_or_else(|err| {
+            panic!("failed to read results file {}: {}", path.display(), err)
+        });
 
-        for ((_size_idx, func_idx), (size, (result, avg_time))) in
-            results_and_times
-        {
-            results_by_size.entry(size).or_default().push(result);
+        let names = self.function_names();
+        let mut times = vec![vec![f64::NAN; names.len()]; self.sizes.len()];
+        for cell in cells {
+            let size_idx = self.sizes.iter().position(|&s| s == cell.size);
+            let func_idx = names.iter().position(|&n| n == cell.function);
+            if let (Some(size_idx), Some(func_idx)) = (size_idx, func_idx) {
+                times[size_idx][func_idx] = self
+                    .aggregation
+                    .aggregate_nanos(&aggregation::to_nanos(&cell.times));
+            }
+        }
+        for (&size, times) in self.sizes.iter().zip(&times) {
+            self.data.push_row(size, times);
+        }
+        self.update_live_plot();
+    }
 
-            #[cfg(debug_assertions)]
-            {
-                println!(
-                    "size index: {}, function index: {}",
-                    _size_idx, func_idx
+    /// Times each `(input size, function)` pair sequentially.
+    fn run_sequential(
+        &mut self,
+        stream: Option<&ResultStream>,
+    ) -> Result<(), RunError> {
+        for size_idx in 0..self.sizes.len() {
+            let size = self.sizes[size_idx];
+            if self.all_skipped(size) {
+                self.data
+                    .push_row(size, &vec![f64::NAN; self.functions.len()]);
+                self.push_metric_rows(size, &[]);
+                continue;
+            }
+            let arg = self.argument(size)?;
+            let clone_time =
+                Self::time_clone(&arg, self.repetitions, &*self.timer);
+            self.clone_times.push((size, clone_time));
+            let results: Vec<Option<FunctionMultipleResult<R>>> = self
+                .time_functions(&arg, size)
+                .into_iter()
+                .enumerate()
+                .map(|(func_idx, result)| match result? {
+                    Ok(result) => Some(result),
+                    Err(message) => {
+                        self.record_failure(size, func_idx, message);
+                        None
+                    }
+                })
+                .collect();
+
+            for (func_idx, result) in results.iter().enumerate() {
+                let Some((_, nanos, avg_time, consistent, _)) = result else {
+                    continue;
+                };
+                let times = &aggregation::to_secs(nanos);
+                self.check_repetitions_equal(size, func_idx, *consistent);
+                self.check_noise(
+                    size,
+                    func_idx,
+                    util::coefficient_of_variation(times),
+                );
+                self.estimate_interval(size, func_idx, nanos);
+                self.keep_samples_of(size, func_idx, times);
+                Self::stream_cell(
+                    stream,
+                    size,
+                    (func_idx, self.functions[func_idx].1),
+                    nanos,
+                    *avg_time,
+                );
+                Self::log_cell(
+                    self.verbosity,
+                    size,
+                    self.functions[func_idx].1,
+                    times,
+                    *avg_time,
                 );
             }
+            Self::check_aborted(stream)?;
 
-            if let Some((_, times)) =
-                self.data.iter_mut().find(|(s, _)| *s == size)
-            {
-                times[func_idx] = avg_time;
-            } else {
-                let mut times = vec![0.0; self.functions.len()];
-                times[func_idx] = avg_time;
-                self.data.push((size, times));
+            if self.assert_equal {
+                let results: Vec<Option<&R>> = results
+                    .iter()
+                    .map(|result| {
+                        result.as_ref().map(|(result, _, _, _, _)| {
+                            result
+                                .as_ref()
+                                .expect("compared results are retained")
+                        })
+                    })
+                    .collect();
+                self.check_results_equal(size, &results, Some(&arg));
+            }
+
+            let execution_times: Vec<f64> = results
+                .iter()
+                .map(|result| {
+                    result.as_ref().map_or(f64::NAN, |(_, _, avg, _, _)| *avg)
+                })
+                .collect();
+            self.data.push_row(size, &execution_times);
+            let metrics: Vec<Option<&[f64]>> = results
+                .iter()
+                .map(|result| {
+                    result.as_ref().map(|(_, _, _, _, metrics)| &metrics[..])
+                })
+                .collect();
+            self.push_metric_rows(size, &metrics);
+            self.update_live_plot();
+        }
+        Ok(())
+    }
+
+    /// Times each `(input size, function)` pair sequentially, each in its own
+    /// child process.
+    ///
+    /// In a child process, only the pair identified by the parent is timed and
+    /// the process exits after reporting the timings. Earlier isolated
+    /// benchmarks, whose pairs are timed by other child processes, are not
+    /// timed at all, and their timings are NaN.
+    fn run_isolated(
+        &mut self,
+        stream: Option<&ResultStream>,
+    ) -> Result<(), RunError> {
+        let run_idx = isolation::next_run_index();
+
+        if let Some(cell) = isolation::child_cell() {
+            if cell.run_idx != run_idx {
+                for size_idx in 0..self.sizes.len() {
+                    let size = self.sizes[size_idx];
+                    self.data
+                        .push_row(size, &vec![f64::NAN; self.functions.len()]);
+                    self.push_metric_rows(size, &[]);
+                }
+                return Ok(());
+            }
+            let size = self.sizes[cell.size_idx];
+            let (func, name) = &self.functions[cell.func_idx];
+            let arg = match (self.argfunc)(size) {
+                Ok(arg) => arg,
+                Err(err) => {
+                    isolation::report_argument_error(&*err);
+                    std::process::exit(0);
+                }
+            };
+            if let Some(mode) = cachegrind::child_mode() {
+                let mut func = func(size);
+                if mode == cachegrind::Mode::Call {
+                    std::hint::black_box(func(std::hint::black_box(arg)));
+                } else {
+                    drop(std::hint::black_box(arg));
+                }
+                std::process::exit(0);
+            }
+            let profiler =
+                profile::profiler_for(&self.profiled, size, cell.func_idx);
+            let (_, nanos, _, consistent, _) =
+                profile::profiled(profiler, name, size, || {
+                    Self::time_function_multiple_times(
+                        func,
+                        size,
+                        arg,
+                        self.sampling(),
+                        self.prefault.as_ref(),
+                        &*self.timer,
+                        &[],
+                    )
+                });
+            isolation::report_cell(&nanos, consistent);
+            std::process::exit(0);
+        }
+
+        for size_idx in 0..self.sizes.len() {
+            let size = self.sizes[size_idx];
+            let mut execution_times = Vec::with_capacity(self.functions.len());
+            if self.inputs.is_some() && !self.all_skipped(size) {
+                // The children generate their own arguments, so the parent
+                // generates one only to write it.
+                self.argument(size)?;
+            }
+
+            for func_idx in 0..self.functions.len() {
+                if self.is_skipped(size, func_idx) {
+                    execution_times.push(f64::NAN);
+                    continue;
+                }
+                self.pause_while_busy();
+                let cell = isolation::Cell {
+                    run_idx,
+                    size_idx,
+                    func_idx,
+                };
+                let output = match isolation::time_cell(cell) {
+                    Ok(output) => output,
+                    Err(err) if self.catch_panics => {
+                        self.record_failure(size, func_idx, err);
+                        execution_times.push(f64::NAN);
+                        continue;
+                    }
+                    Err(err) => panic!(
+                        "failed to time {} at n={} in a child process: {}",
+                        self.functions[func_idx].1, size, err
+                    ),
+                };
+                let (nanos, consistent) = match output {
+                    isolation::CellOutput::Timed(nanos, consistent) => {
+                        (nanos, consistent)
+                    }
+                    isolation::CellOutput::ArgumentError(message) => {
+                        return Err(RunError::Argument {
+                            size,
+                            source: message.into(),
+                        });
+                    }
+                };
+
+                let times = aggregation::to_secs(&nanos);
+                self.check_repetitions_equal(size, func_idx, consistent);
+                self.check_noise(
+                    size,
+                    func_idx,
+                    util::coefficient_of_variation(&times),
+                );
+                self.estimate_interval(size, func_idx, &nanos);
+                self.keep_samples_of(size, func_idx, &times);
+                let avg_time = self.aggregation.aggregate_nanos(&nanos);
+                Self::stream_cell(
+                    stream,
+                    size,
+                    (func_idx, self.functions[func_idx].1),
+                    &nanos,
+                    avg_time,
+                );
+                Self::log_cell(
+                    self.verbosity,
+                    size,
+                    self.functions[func_idx].1,
+                    &times,
+                    avg_time,
+                );
+                Self::check_aborted(stream)?;
+                execution_times.push(avg_time);
+
+                if self.cachegrind {
+                    let counts =
+                        cachegrind::count_cell(cell).unwrap_or_else(|err| {
+                            panic!(
+                                "failed to count instructions of {} at n={}: \
+                                 {}",
+                                self.functions[func_idx].1, size, err
+                            )
+                        });
+                    self.instruction_counts.push((size, func_idx, counts));
+                }
             }
+
+            self.data.push_row(size, &execution_times);
+            // Metrics are not collected from the child processes.
+            self.push_metric_rows(size, &[]);
+            self.update_live_plot();
+        }
+        Ok(())
+    }
+
+    /// Appends a row for the input size `size` to the data of every metric,
+    /// given the values of the metrics for each function in the order of
+    /// `self.metrics`, or `None` for a function that was not timed.
+    ///
+    /// The values of the functions missing from `values` are NaN.
+    fn push_metric_rows(&mut self, size: usize, values: &[Option<&[f64]>]) {
+        let num_functions = self.functions.len();
+        for (metric_idx, data) in self.metric_data.iter_mut().enumerate() {
+            let row: Vec<f64> = (0..num_functions)
+                .map(|func_idx| {
+                    values
+                        .get(func_idx)
+                        .copied()
+                        .flatten()
+                        .map_or(f64::NAN, |values| values[metric_idx])
+                })
+                .collect();
+            data.push_row(size, &row);
+        }
+    }
+
+    /// Returns whether the function at `func_idx` is to be skipped at the
+    /// input size `size`, because of its maximum or its own input sizes.
+    fn is_skipped(&self, size: usize, func_idx: usize) -> bool {
+        !self.function_sizes[func_idx].contains(&size)
+    }
+
+    /// Returns whether every function is to be skipped at the input size
+    /// `size`, in which case its argument need not be generated.
+    fn all_skipped(&self, size: usize) -> bool {
+        (0..self.functions.len())
+            .all(|func_idx| self.is_skipped(size, func_idx))
+    }
+
+    /// Returns the sampling parameters of this benchmark.
+    fn sampling(&self) -> Sampling {
+        Sampling {
+            repetitions: self.repetitions,
+            max_repetitions: self.max_repetitions,
+            cv_threshold: self.cv_threshold,
+            target_precision: self.target_precision,
+            critical_value: util::normal_quantile(
+                0.5 + self.confidence_level.unwrap_or(0.95) / 2.0,
+            ),
+            strict: self.strict_equal,
+            retain: self.assert_equal,
+            cache_mode: self.cache_mode,
+            aggregation: self.aggregation,
+        }
+    }
+
+    /// Records a bootstrap confidence interval of the benchmark value of the
+    /// `(input size, function)` pair from its timings in integer nanoseconds,
+    /// if requested.
+    fn estimate_interval(
+        &mut self,
+        size: usize,
+        func_idx: usize,
+        nanos: &[u128],
+    ) {
+        if let Some(level) = self.confidence_level {
+            let interval =
+                bootstrap::confidence_interval(nanos, self.aggregation, level);
+            self.confidence_intervals.push((size, func_idx, interval));
+        }
+    }
+
+    /// Keeps the timings of the `(input size, function)` pair, if requested.
+    fn keep_samples_of(&mut self, size: usize, func_idx: usize, times: &[f64]) {
+        if self.keep_samples {
+            self.samples.push((size, func_idx, times.to_vec()));
         }
+    }
+
+    /// Records the `(input size, function)` pair as noisy and prints a warning
+    /// if the coefficient of variation `cv` of its timings exceeds the
+    /// configured threshold.
+    fn check_noise(&mut self, size: usize, func_idx: usize, cv: Option<f64>) {
+        let (Some(threshold), Some(cv)) = (self.cv_threshold, cv) else {
+            return;
+        };
+        if cv > threshold {
+            self.warn(format_args!(
+                "{} at n={} is noisy (coefficient of variation {:.1}% exceeds \
+                 {:.1}%)",
+                self.functions[func_idx].1,
+                size,
+                cv * 100.0,
+                threshold * 100.0
+            ));
+            self.noisy.push((size, func_idx));
+        }
+    }
+
+    /// Warns that the function at index `func_idx` panicked at the input size
+    /// `size` with `message` and records the failure.
+    fn record_failure(
+        &mut self,
+        size: usize,
+        func_idx: usize,
+        message: String,
+    ) {
+        self.warn(format_args!(
+            "{} panicked at n={}: {}",
+            self.functions[func_idx].1, size, message
+        ));
+        self.failures.push((size, func_idx, message));
+    }
+
+    /// Panics if the function at index `func_idx` did not return equal values
+    /// across all repetitions for the input size `size`.
+    fn check_repetitions_equal(
+        &self,
+        size: usize,
+        func_idx: usize,
+        consistent: bool,
+    ) {
+        if !consistent {
+            panic!(
+                "{} returned different values across repetitions at n={}",
+                self.functions[func_idx].1, size
+            );
+        }
+    }
 
-        // Sort self.data by size_idx
-        // TODO: not needed?
-        self.data.sort_by(|a, b| a.0.cmp(&b.0));
+    /// Panics if the return values for the input size `size`, ordered as
+    /// `self.functions`, are not all equal.
+    ///
+    /// Each return value is compared against the return value of the
+    /// reference function, and the panic message is a report naming every
+    /// function that disagreed with it along with the values involved. If a
+    /// mismatch report path is configured, the report is also written there.
+    ///
+    /// `results` holds the return value of each function, or `None` for the
+    /// functions not timed at the input size `size`. If the reference
+    /// function is not timed, the first function that is serves as the
+    /// reference.
+    fn check_results_equal(
+        &self,
+        size: usize,
+        results: &[Option<&R>],
+        arg: Option<&T>,
+    ) {
+        let (names, results): (Vec<&str>, Vec<&R>) = self
+            .function_names()
+            .into_iter()
+            .zip(results)
+            .filter_map(|(name, result)| Some((name, (*result)?)))
+            .unzip();
+        if util::all_items_equal(&results) {
+            return;
+        }
+
+        let reference_name = self.functions[self.reference].1;
+        let reference = names
+            .iter()
+            .position(|&name| name == reference_name)
+            .unwrap_or(0);
+        let Some(report) =
+            MismatchReport::new(size, &names, &results, reference)
+        else {
+            return;
+        };
 
-        if self.assert_equal {
-            for results in results_by_size.values() {
-                assert!(util::all_items_equal(results));
+        if let Some(path) = &self.mismatch_report {
+            if let Err(err) = std::fs::write(path, report.to_string()) {
+                panic!(
+                    "{}failed to write mismatch report to {}: {}",
+                    report,
+                    path.display(),
+                    err
+                );
             }
         }
+        if let (Some((dir, write)), Some(arg)) = (&self.mismatch_inputs, arg) {
+            let outputs = mismatch::render_outputs(&names, &results, reference);
+            if let Err(err) =
+                Self::dump_mismatch(dir, write, size, arg, &outputs)
+            {
+                panic!(
+                    "{}failed to write mismatching input to {}: {}",
+                    report,
+                    dir.display(),
+                    err
+                );
+            }
+        }
+        panic!("{}", report);
+    }
+
+    /// Writes `arg`, the argument of the input size `size` that function
+    /// return values disagreed on, and `outputs`, the rendered return values,
+    /// to `dir`.
+    fn dump_mismatch(
+        dir: &Path,
+        write: &InputWriterFn<T>,
+        size: usize,
+        arg: &T,
+        outputs: &str,
+    ) -> std::io::Result<()> {
+        std::fs::create_dir_all(dir)?;
+        let file = std::fs::File::create(
+            dir.join(format!("mismatch_{}_input", size)),
+        )?;
+        let mut writer = std::io::BufWriter::new(file);
+        write(arg, &mut writer)?;
+        std::io::Write::flush(&mut writer)?;
+        std::fs::write(
+            dir.join(format!("mismatch_{}_outputs.txt", size)),
+            outputs,
+        )
+    }
+
+    /// Waits for the system load to drop if measurement is paused while the
+    /// system is busy.
+    fn pause_while_busy(&self) {
+        if let Some(load_pause) = &self.load_pause {
+            load_pause.wait_until_idle(self.verbosity == Verbosity::Quiet);
+        }
+    }
+
+    /// Returns the average time in seconds to clone `arg`, over `repetitions`
+    /// clones timed with `timer`.
+    fn time_clone(arg: &T, repetitions: usize, timer: &dyn Timer) -> f64 {
+        let nanos: Vec<u128> = (0..repetitions)
+            .map(|_| {
+                let start = timer.now();
+                let clone = arg.clone();
+                let nanos = timer.now().saturating_sub(start).as_nanos();
+                drop(clone);
+                nanos
+            })
+            .collect();
+        Aggregation::Mean.aggregate_nanos(&nanos)
     }
 
-    /// Times the function once, returning a tuple containing the value returned
-    /// by the function and the timing.
-    fn time_function(func: &Arc<BenchFn<T, R>>, arg: T) -> FunctionResult<R> {
-        let start = Instant::now();
+    /// Times the function once with `timer`, returning a tuple containing the
+    /// value returned by the function and the timing in nanoseconds.
+    fn time_function(
+        func: &mut BenchFnInstance<T, R>,
+        arg: T,
+        timer: &dyn Timer,
+    ) -> FunctionResult<R> {
+        let start = timer.now();
         let result = func(arg);
-        let duration = start.elapsed().as_secs_f64();
-        (result, duration)
+        let nanos = timer.now().saturating_sub(start).as_nanos();
+        (result, nanos)
     }
 
-    /// Times the function `n` times, returning a tuple containing the last
-    /// return value of the function, the timings, and the average time.
+    /// Times a fresh instance of the function created by `factory` for the
+    /// input size `size` repeatedly according to `sampling`, returning a tuple
+    /// containing the last return value of the function, the timings in
+    /// integer nanoseconds, the average time in seconds, and whether the
+    /// function returned equal values across all repetitions.
+    ///
+    /// The last return value is only retained if `sampling.retain` is `true`;
+    /// otherwise, every return value is dropped after it is timed and the first
+    /// element of the tuple is `None`.
+    ///
+    /// Return values are only compared across repetitions if `sampling.strict`
+    /// is `true`, in which case each return value is compared with the
+    /// previous one; otherwise, the last element of the tuple is always
+    /// `true`.
+    ///
+    /// If `prefault` is given, it is applied to the argument of each
+    /// repetition before timing.
+    ///
+    /// The last element of the tuple holds the value of each of `metrics`,
+    /// measured around every repetition outside of the timed region, combined
+    /// like the timings.
+    #[allow(clippy::too_many_arguments)]
     fn time_function_multiple_times(
-        func: &Arc<BenchFn<T, R>>,
+        factory: &dyn Fn(usize) -> BenchFnInstance<T, R>,
+        size: usize,
         arg: T,
-        n: usize,
+        sampling: Sampling,
+        prefault: Option<&PrefaultFn<T>>,
+        timer: &dyn Timer,
+        metrics: &[Metric<R>],
     ) -> FunctionMultipleResult<R> {
-        let mut total_time = 0.0;
-        let mut times = Vec::new();
-        let mut last_result = None;
+        let mut nanos = Vec::new();
+        let mut metric_values = vec![Vec::new(); metrics.len()];
+        let mut last_result: Option<R> = None;
+        let mut consistent = true;
+        let mut evictor = CacheEvictor::new(sampling.cache_mode);
+        let mut func = factory(size);
+
+        while sampling.needs_more(&nanos) {
+            let arg = arg.clone();
+            if let Some(prefault) = prefault {
+                prefault(&arg);
+            }
+            if let Some(evictor) = &mut evictor {
+                evictor.evict();
+            }
+            let starts: Vec<f64> = metrics.iter().map(Metric::start).collect();
+            let (result, time) = Self::time_function(&mut func, arg, timer);
+            for ((metric, start), values) in
+                metrics.iter().zip(starts).zip(&mut metric_values)
+            {
+                values.push(metric.value(start, &result));
+            }
 
-        for _ in 0..n {
-            let (result, time) = Self::time_function(func, arg.clone());
-            last_result = Some(result);
+            if sampling.strict {
+                if let Some(previous) = &last_result {
+                    consistent &= *previous == result;
+                }
+            }
+            if sampling.strict || sampling.retain {
+                last_result = Some(result);
+            }
 
-            total_time += time;
-            times.push(time);
+            nanos.push(time);
         }
 
-        (last_result.unwrap(), times, total_time / n as f64)
+        let avg_time = sampling.aggregation.aggregate_nanos(&nanos);
+        let last_result = last_result.filter(|_| sampling.retain);
+        let metric_values = metric_values
+            .iter()
+            .map(|values| sampling.aggregation.aggregate(values))
+            .collect();
+        (last_result, nanos, avg_time, consistent, metric_values)
     }
 
-    /// Times each function `n` times, returning a vector of tuples containing
-    /// the last return value of the function, the timings, and the average
-    /// time.
+    /// Times each function repeatedly, returning a vector of tuples as
+    /// returned by `time_function_multiple_times`, with `None` for the
+    /// functions skipped at the input size `size` and the panic message for
+    /// the functions that panicked, if panics are caught.
     fn time_functions(
-        arg: T,
-        functions: &[(Arc<BenchFn<T, R>>, &str)],
-        repetitions: usize,
-    ) -> Vec<FunctionMultipleResult<R>> {
-        functions
+        &self,
+        arg: &T,
+        size: usize,
+    ) -> Vec<Option<Result<FunctionMultipleResult<R>, String>>> {
+        self.functions
             .iter()
-            .map(|(func, _name)| {
-                Self::time_function_multiple_times(
-                    func,
-                    arg.clone(),
-                    repetitions,
-                )
+            .enumerate()
+            .map(|(func_idx, (func, name))| {
+                if self.is_skipped(size, func_idx) {
+                    return None;
+                }
+                self.pause_while_busy();
+                let profiler =
+                    profile::profiler_for(&self.profiled, size, func_idx);
+                Some(Self::catching(self.catch_panics, || {
+                    profile::profiled(profiler, name, size, || {
+                        Self::time_function_multiple_times(
+                            func,
+                            size,
+                            arg.clone(),
+                            self.sampling(),
+                            self.prefault.as_ref(),
+                            &*self.timer,
+                            &self.metrics,
+                        )
+                    })
+                }))
             })
             .collect()
     }
+
+    /// Calls `f`, returning the message of its panic as an error instead of
+    /// unwinding if `catch_panics` is set.
+    fn catching<F>(
+        catch_panics: bool,
+        f: F,
+    ) -> Result<FunctionMultipleResult<R>, String>
+    where
+        F: FnOnce() -> FunctionMultipleResult<R>,
+    {
+        if !catch_panics {
+            return Ok(f());
+        }
+        std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)).map_err(
+            |payload| {
+                if let Some(message) = payload.downcast_ref::<&str>() {
+                    message.to_string()
+                } else if let Some(message) = payload.downcast_ref::<String>() {
+                    message.clone()
+                } else {
+                    "unknown panic".to_string()
+                }
+            },
+        )
+    }
+}
+
+impl<
+        T: Clone + Send + Sync + 'static,
+        R: Send + Debug + PartialEq + 'static,
+    > Bench<'_, T, R, Shared>
+{
+    /// Times `(input size, function)` pairs in parallel.
+    ///
+    /// Only the average timing, coefficient of variation and confidence
+    /// interval of each pair are collected from the workers, so that memory
+    /// use does not grow with the number of repetitions, unless the timings
+    /// are to be kept.
+    fn run_parallel(
+        &mut self,
+        stream: Option<&ResultStream>,
+    ) -> Result<(), RunError> {
+        let sizes: Vec<(usize, usize)> = self
+            .sizes
+            .iter()
+            .copied()
+            .enumerate()
+            .filter(|&(_, size)| !self.all_skipped(size))
+            .collect();
+        let results_and_times = parallel::try_map(
+            sizes,
+            |(size_idx, size)| {
+                Self::check_aborted(stream)?;
+                // The argument is generated by the task timing its size rather
+                // than for all sizes up front, and is dropped as soon as every
+                // function has been timed on it and their return values have
+                // been compared.
+                let arg = self.argument(size)?;
+                let sampling = self.sampling();
+                let prefault = self.prefault.as_ref();
+                let timer = &*self.timer;
+                let profiled = &self.profiled;
+                let metrics = &self.metrics;
+                let confidence_level = self.confidence_level;
+                let keep_samples = self.keep_samples;
+                let catch_panics = self.catch_panics;
+                let verbosity = self.verbosity;
+                let function_sizes = &self.function_sizes;
+                let clone_time =
+                    Self::time_clone(&arg, sampling.repetitions, timer);
+                let functions: Vec<_> = self
+                    .functions
+                    .iter()
+                    .enumerate()
+                    .filter(|&(func_idx, _)| {
+                        function_sizes[func_idx].contains(&size)
+                    })
+                    .collect();
+                let mut cells = parallel::map_nested(
+                    functions,
+                    |(func_idx, (func, name))| {
+                        let profiler =
+                            profile::profiler_for(profiled, size, func_idx);
+                        let cell = Self::catching(catch_panics, || {
+                            profile::profiled(profiler, name, size, || {
+                                Self::time_function_multiple_times(
+                                    func,
+                                    size,
+                                    arg.clone(),
+                                    sampling,
+                                    prefault,
+                                    timer,
+                                    metrics,
+                                )
+                            })
+                        });
+                        let (last_result, nanos, avg_time, consistent, metrics) =
+                            match cell {
+                                Ok(cell) => cell,
+                                Err(message) => {
+                                    return (
+                                        (size_idx, func_idx, clone_time),
+                                        Err(message),
+                                    );
+                                }
+                            };
+                        let times = aggregation::to_secs(&nanos);
+                        Self::stream_cell(
+                            stream,
+                            size,
+                            (func_idx, name),
+                            &nanos,
+                            avg_time,
+                        );
+                        Self::log_cell(verbosity, size, name, &times, avg_time);
+                        let cv = util::coefficient_of_variation(&times);
+                        let interval = confidence_level.map(|level| {
+                            bootstrap::confidence_interval(
+                                &nanos,
+                                sampling.aggregation,
+                                level,
+                            )
+                        });
+                        let samples = keep_samples.then_some(times);
+
+                        (
+                            (size_idx, func_idx, clone_time),
+                            Ok((
+                                (
+                                    last_result,
+                                    cv,
+                                    avg_time,
+                                    consistent,
+                                    interval,
+                                    samples,
+                                ),
+                                metrics,
+                            )),
+                        )
+                    },
+                );
+                // The return values are compared by the task, while the argument
+                // is still alive to be dumped on a mismatch, and are dropped
+                // rather than kept until every size has been timed.
+                if self.assert_equal {
+                    let mut results: Vec<Option<&R>> =
+                        vec![None; self.functions.len()];
+                    for ((_, func_idx, _), cell) in &cells {
+                        if let Ok(((result, ..), _)) = cell {
+                            results[*func_idx] = result.as_ref();
+                        }
+                    }
+                    self.check_results_equal(size, &results, Some(&arg));
+                }
+                for (_, cell) in &mut cells {
+                    if let Ok(((result, ..), _)) = cell {
+                        *result = None;
+                    }
+                }
+                Ok(cells)
+            },
+        )?;
+
+        // Results are indexed by `(size_idx, func_idx)`, so that they are
+        // stored in the order of `self.sizes` whatever order the pairs
+        // complete in.
+        let mut times =
+            vec![vec![f64::NAN; self.functions.len()]; self.sizes.len()];
+        let mut metrics: Vec<Vec<Option<Vec<f64>>>> = (0..self.sizes.len())
+            .map(|_| vec![None; self.functions.len()])
+            .collect();
+        let mut clone_times = vec![None; self.sizes.len()];
+
+        for ((size_idx, func_idx, clone_time), cell) in
+            results_and_times.into_iter().flatten()
+        {
+            let size = self.sizes[size_idx];
+            clone_times[size_idx] = Some(clone_time);
+            let (
+                (_, cv, avg_time, consistent, interval, samples),
+                cell_metrics,
+            ) = match cell {
+                Ok(cell) => cell,
+                Err(message) => {
+                    self.record_failure(size, func_idx, message);
+                    continue;
+                }
+            };
+            self.check_repetitions_equal(size, func_idx, consistent);
+            self.check_noise(size, func_idx, cv);
+            if let Some(interval) = interval {
+                self.confidence_intervals.push((size, func_idx, interval));
+            }
+            if let Some(samples) = samples {
+                self.samples.push((size, func_idx, samples));
+            }
+            times[size_idx][func_idx] = avg_time;
+            metrics[size_idx][func_idx] = Some(cell_metrics);
+        }
+
+        for (size_idx, times) in times.iter().enumerate() {
+            let size = self.sizes[size_idx];
+            self.data.push_row(size, times);
+            let metrics: Vec<Option<&[f64]>> =
+                metrics[size_idx].iter().map(Option::as_deref).collect();
+            self.push_metric_rows(size, &metrics);
+        }
+        self.update_live_plot();
+        self.clone_times.extend(
+            self.sizes
+                .iter()
+                .zip(clone_times)
+                .filter_map(|(&size, clone_time)| Some((size, clone_time?))),
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_disagreeing_bench(parallel: bool) -> Bench<'static, usize, usize> {
+        let functions: Vec<BenchFnNamed<'static, usize, usize>> = vec![
+            (Box::new(|x| x), "Identity"),
+            (Box::new(|x| if x > 50 { x + 1 } else { x }), "Off By One"),
+        ];
+        let argfunc: BenchFnArg<usize> = Box::new(|x| x);
+        let sizes = vec![10, 100];
+        BenchBuilder::new(functions, argfunc, sizes)
+            .parallel(parallel)
+            .reference("Identity")
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    #[should_panic(expected = "Off By One disagreed with Identity at n=100")]
+    fn test_reference_mismatch_sequential() {
+        setup_disagreeing_bench(false).run();
+    }
+
+    #[test]
+    #[should_panic(expected = "Off By One disagreed with Identity at n=100")]
+    fn test_reference_mismatch_parallel() {
+        setup_disagreeing_bench(true).run();
+    }
+
+    #[test]
+    fn test_mismatch_report_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mismatch.txt");
+
+        let functions: Vec<BenchFnNamed<'static, usize, usize>> = vec![
+            (Box::new(|x| x), "Identity"),
+            (Box::new(|x| x + 1), "Off By One"),
+        ];
+        let argfunc: BenchFnArg<usize> = Box::new(|x| x);
+        let mut bench = BenchBuilder::new(functions, argfunc, vec![10])
+            .assert_equal(true)
+            .mismatch_report(&path)
+            .build()
+            .unwrap();
+
+        let result =
+            std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                bench.run();
+            }));
+        assert!(result.is_err());
+
+        let report = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(
+            report,
+            "Off By One disagreed with Identity at n=10\n  \
+             Identity: 10\n  \
+             Off By One: 11\n"
+        );
+    }
+
+    #[test]
+    fn test_stream_results() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("results.jsonl");
+
+        for parallel in [false, true] {
+            let functions: Vec<BenchFnNamed<'static, usize, usize>> = vec![
+                (Box::new(|x| x), "Identity"),
+                (Box::new(|x| x * 2), "Double"),
+            ];
+            let argfunc: BenchFnArg<usize> = Box::new(|x| x);
+            let mut bench =
+                BenchBuilder::new(functions, argfunc, vec![30, 10, 20])
+                    .repetitions(3)
+                    .parallel(parallel)
+                    .stream_results(&path)
+                    .build()
+                    .unwrap();
+            bench.run();
+
+            let cells = stream::read_cells(&path).unwrap();
+            assert_eq!(cells.len(), 6);
+            assert_eq!(bench.sizes(), [30, 10, 20]);
+            assert_eq!(bench.data().sizes(), [30, 10, 20]);
+            for cell in cells {
+                let (_, times) = bench
+                    .data()
+                    .iter()
+                    .find(|&(size, _)| size == cell.size)
+                    .unwrap();
+                let func_idx = bench
+                    .function_names()
+                    .iter()
+                    .position(|&name| name == cell.function)
+                    .unwrap();
+                assert_eq!(times[func_idx], cell.mean);
+            }
+        }
+    }
+
+    #[test]
+    fn test_parallel_generates_each_argument_once() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let generated = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&generated);
+        let functions: Vec<BenchFnNamed<'static, Vec<u8>, usize>> = vec![
+            (Box::new(|v| v.len()), "len"),
+            (Box::new(|v| v.iter().filter(|&&b| b == 0).count()), "zeros"),
+        ];
+        let argfunc: BenchFnArg<Vec<u8>> = Box::new(move |n| {
+            counter.fetch_add(1, Ordering::Relaxed);
+            vec![0; n]
+        });
+        let mut bench = BenchBuilder::new(functions, argfunc, vec![1, 2, 3])
+            .parallel(true)
+            .assert_equal(true)
+            .build()
+            .unwrap();
+        bench.run();
+
+        assert_eq!(generated.load(Ordering::Relaxed), 3);
+        assert_eq!(bench.data().num_sizes(), 3);
+    }
+
+    #[test]
+    fn test_parallel_results_follow_size_order() {
+        let functions: Vec<BenchFnNamed<'static, usize, usize>> =
+            vec![(Box::new(|x| x), "Identity"), (Box::new(|x| x), "Copy")];
+        let argfunc: BenchFnArg<usize> = Box::new(|x| x);
+        let mut bench =
+            BenchBuilder::new(functions, argfunc, vec![300, 10, 200, 20])
+                .parallel(true)
+                .assert_equal(true)
+                .build()
+                .unwrap();
+        bench.run();
+
+        assert_eq!(bench.data().sizes(), [300, 10, 200, 20]);
+    }
+
+    #[test]
+    fn test_try_run_argument_error() {
+        for parallel in [false, true] {
+            let functions: Vec<BenchFnNamed<'static, usize, usize>> =
+                vec![(Box::new(|x| x), "Identity")];
+            let argfunc: FallibleBenchFnArg<usize> = Box::new(|n| {
+                if n > 10 {
+                    Err(format!("no input of size {}", n).into())
+                } else {
+                    Ok(n)
+                }
+            });
+            let mut bench =
+                BenchBuilder::new_fallible(functions, argfunc, vec![10, 20])
+                    .parallel(parallel)
+                    .build()
+                    .unwrap();
+
+            let err = bench.try_run().expect_err("argument generation fails");
+            assert!(matches!(err, RunError::Argument { size: 20, .. }));
+            assert_eq!(
+                err.to_string(),
+                "Failed to generate the argument of size 20: no input of size 20"
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Failed to generate the argument of size 10")]
+    fn test_run_panics_on_argument_error() {
+        let functions: Vec<BenchFnNamed<'static, usize, usize>> =
+            vec![(Box::new(|x| x), "Identity")];
+        let argfunc: FallibleBenchFnArg<usize> =
+            Box::new(|_| Err("unavailable".into()));
+        BenchBuilder::new_fallible(functions, argfunc, vec![10])
+            .build()
+            .unwrap()
+            .run();
+    }
+
+    #[cfg(feature = "plot")]
+    #[test]
+    fn test_live_plot() {
+        for parallel in [false, true] {
+            let dir = tempfile::tempdir().unwrap();
+            let path = dir.path().join("live.svg");
+            let functions: Vec<BenchFnNamed<'static, usize, usize>> =
+                vec![(Box::new(|x| x), "Identity")];
+            let live_path = path.clone();
+            let argfunc: BenchFnArg<usize> = Box::new(move |n| {
+                // Sequentially, the plot is rendered after each size, so only
+                // the first size has been generated before the plot exists.
+                // In parallel, it is rendered once every size is timed.
+                assert_eq!(live_path.exists(), !parallel && n > 10);
+                n
+            });
+            let mut bench =
+                BenchBuilder::new(functions, argfunc, vec![10, 100])
+                    .label("Live")
+                    .live_plot(&path)
+                    .parallel(parallel)
+                    .build()
+                    .unwrap();
+            bench.run();
+
+            let svg = std::fs::read_to_string(&path).unwrap();
+            assert!(svg.contains("Live"));
+        }
+    }
+
+    #[test]
+    fn test_run_streaming() {
+        for parallel in [false, true] {
+            let functions: Vec<BenchFnNamed<'static, usize, usize>> =
+                vec![(Box::new(|x| x), "A"), (Box::new(|x| x + 1), "B")];
+            let argfunc: BenchFnArg<usize> = Box::new(|x| x);
+            let mut bench = BenchBuilder::new(functions, argfunc, vec![10, 20])
+                .parallel(parallel)
+                .repetitions(3)
+                .build()
+                .unwrap();
+
+            let (sender, receiver) = std::sync::mpsc::channel();
+            bench.run_streaming(sender).unwrap();
+
+            let mut events: Vec<CellEvent> = receiver.iter().collect();
+            events.sort_by_key(|event| (event.size, event.function));
+            let cells: Vec<(usize, usize)> = events
+                .iter()
+                .map(|event| (event.size, event.function))
+                .collect();
+            assert_eq!(cells, [(10, 0), (10, 1), (20, 0), (20, 1)]);
+            for event in &events {
+                assert_eq!(event.times.len(), 3);
+                let size_idx = usize::from(event.size == 20);
+                assert_eq!(
+                    Some(event.value),
+                    bench.data().duration(event.function, size_idx)
+                );
+                // The timings are the exact nanoseconds the value was
+                // aggregated from.
+                let nanos: Vec<u128> = event
+                    .times
+                    .iter()
+                    .map(std::time::Duration::as_nanos)
+                    .collect();
+                assert_eq!(
+                    Aggregation::Mean.aggregate_nanos(&nanos),
+                    bench.data().time(event.function, size_idx)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_run_streaming_aborted() {
+        let functions: Vec<BenchFnNamed<'static, usize, usize>> =
+            vec![(Box::new(|x| x), "Identity")];
+        let argfunc: BenchFnArg<usize> = Box::new(|x| {
+            assert!(x < 30, "argument generated after the run was aborted");
+            x
+        });
+        let mut bench = BenchBuilder::new(functions, argfunc, vec![10, 30])
+            .build()
+            .unwrap();
+
+        let (sender, receiver) = std::sync::mpsc::channel();
+        drop(receiver);
+        let err = bench.run_streaming(sender).expect_err("receiver dropped");
+        assert!(matches!(err, RunError::Aborted));
+    }
+
+    #[test]
+    fn test_mismatch_inputs() {
+        for parallel in [false, true] {
+            let dir = tempfile::tempdir().unwrap();
+            let mut bench = BenchBuilder::new(
+                vec![
+                    (Box::new(|x: usize| vec![x; 2]), "Repeat"),
+                    (
+                        Box::new(|x: usize| vec![x + usize::from(x > 50); 2]),
+                        "Off By One",
+                    ),
+                ],
+                Box::new(|x| x * 3),
+                vec![10, 100],
+            )
+            .parallel(parallel)
+            .assert_equal(true)
+            .mismatch_inputs(dir.path(), |arg, writer| {
+                write!(writer, "{}", arg)
+            })
+            .build()
+            .unwrap();
+
+            let result =
+                std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                    bench.run();
+                }));
+            assert!(result.is_err());
+
+            let read = |name: &str| {
+                std::fs::read_to_string(dir.path().join(name)).unwrap()
+            };
+            assert_eq!(read("mismatch_100_input"), "300");
+            assert_eq!(
+                read("mismatch_100_outputs.txt"),
+                "Repeat:\n[\n    300,\n    300,\n]\n\
+                 Off By One:\n[\n    301,\n    301,\n]\n"
+            );
+            assert!(!dir.path().join("mismatch_10_input").exists());
+        }
+    }
+
+    #[test]
+    fn test_persist_inputs() {
+        let dir = tempfile::tempdir().unwrap();
+        for parallel in [false, true] {
+            let functions: Vec<BenchFnNamed<'static, usize, usize>> =
+                vec![(Box::new(|x| x), "Identity")];
+            let argfunc: BenchFnArg<usize> = Box::new(|n| n * 2);
+            let mut bench = BenchBuilder::new(functions, argfunc, vec![10, 20])
+                .parallel(parallel)
+                .persist_inputs(dir.path(), |arg, writer| {
+                    write!(writer, "{}", arg)
+                })
+                .build()
+                .unwrap();
+            bench.run();
+
+            let path = bench.input_path(20).unwrap();
+            assert_eq!(path, dir.path().join("input_20"));
+            assert_eq!(std::fs::read_to_string(path).unwrap(), "40");
+        }
+
+        let functions: Vec<BenchFnNamed<'static, usize, usize>> =
+            vec![(Box::new(|x| x), "Identity")];
+        let argfunc: BenchFnArg<usize> = Box::new(|n| n);
+        let mut bench = BenchBuilder::new(functions, argfunc, vec![10])
+            .persist_inputs(
+                dir.path().join("input_10").join("nested"),
+                |_, _| Ok(()),
+            )
+            .build()
+            .unwrap();
+        let err = bench.try_run().expect_err("input directory is a file");
+        assert!(matches!(err, RunError::Input { size: 10, .. }));
+    }
+
+    #[test]
+    fn test_max_size_for() {
+        for parallel in [false, true] {
+            let functions: Vec<BenchFnNamed<'static, usize, usize>> =
+                vec![(Box::new(|x| x), "Fast"), (Box::new(|x| x), "Slow")];
+            let argfunc: BenchFnArg<usize> = Box::new(|x| {
+                assert!(x <= 100, "argument generated for a capped size");
+                x
+            });
+            let mut bench =
+                BenchBuilder::new(functions, argfunc, vec![10, 100, 1000])
+                    .max_size_for("Slow", 10)
+                    .max_size_for("Fast", 100)
+                    .parallel(parallel)
+                    .assert_equal(true)
+                    .build()
+                    .unwrap();
+            bench.run();
+
+            assert_eq!(bench.data().sizes(), [10, 100, 1000]);
+            assert!(!bench.data().time(1, 0).is_nan());
+            assert!(!bench.data().time(0, 1).is_nan());
+            assert!(bench.data().time(1, 1).is_nan());
+            assert!(bench.data().row(2).iter().all(|t| t.is_nan()));
+            assert_eq!(bench.clone_times().len(), 2);
+        }
+    }
+
+    #[test]
+    fn test_sizes_for() {
+        let functions: Vec<BenchFnNamed<'static, usize, usize>> =
+            vec![(Box::new(|x| x), "Small"), (Box::new(|x| x), "Large")];
+        let argfunc: BenchFnArg<usize> = Box::new(|x| x);
+        let mut bench = BenchBuilder::new(functions, argfunc, vec![10, 20])
+            .sizes_for("Large", vec![20, 1000])
+            .build()
+            .unwrap();
+        bench.run();
+
+        assert_eq!(bench.sizes(), [10, 20, 1000]);
+        assert!(bench.data().time(1, 0).is_nan());
+        assert!(!bench.data().time(1, 1).is_nan());
+        assert!(bench.data().time(0, 2).is_nan());
+        assert!(!bench.data().time(1, 2).is_nan());
+    }
+
+    #[test]
+    fn test_significance_matrix() {
+        use std::time::Duration;
+
+        let functions: Vec<BenchFnNamed<'static, usize, usize>> = vec![
+            (
+                Box::new(|x| {
+                    std::thread::sleep(Duration::from_millis(2));
+                    x
+                }),
+                "Slow",
+            ),
+            (Box::new(|x| x), "Fast"),
+        ];
+        let argfunc: BenchFnArg<usize> = Box::new(|x| x);
+        let mut bench = BenchBuilder::new(functions, argfunc, vec![10])
+            .repetitions(6)
+            .keep_samples(true)
+            .build()
+            .unwrap();
+        bench.run();
+
+        assert_eq!(bench.samples().len(), 2);
+        assert!(bench.samples().iter().all(|(_, _, t)| t.len() == 6));
+        let matrices = bench.significance_matrix();
+        assert_eq!(matrices.len(), 1);
+        assert_eq!(matrices[0].size, 10);
+        assert_eq!(matrices[0].p_values[0][0], 1.0);
+        assert_eq!(matrices[0].p_values[0][1], matrices[0].p_values[1][0]);
+        assert!(matrices[0].is_significant(0, 1, 0.05));
+    }
+
+    #[test]
+    fn test_cv_threshold_marks_noisy_pairs() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::time::Duration;
+
+        let calls = AtomicUsize::new(0);
+        let functions: Vec<BenchFnNamed<'static, usize, usize>> = vec![
+            (
+                Box::new(move |x| {
+                    if calls.fetch_add(1, Ordering::Relaxed) == 0 {
+                        std::thread::sleep(Duration::from_millis(20));
+                    }
+                    x
+                }),
+                "Noisy",
+            ),
+            (Box::new(|x| x), "Quiet"),
+        ];
+        let argfunc: BenchFnArg<usize> = Box::new(|x| x);
+        let mut bench = BenchBuilder::new(functions, argfunc, vec![10])
+            .repetitions(3)
+            .cv_threshold(0.5)
+            .build()
+            .unwrap();
+        bench.run();
+
+        assert!(bench.noisy.contains(&(10, 0)));
+    }
+
+    #[test]
+    fn test_verbosity_does_not_change_results() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::time::Duration;
+
+        for verbosity in
+            [Verbosity::Quiet, Verbosity::Normal, Verbosity::Verbose]
+        {
+            for parallel in [false, true] {
+                let calls = AtomicUsize::new(0);
+                let argfunc: BenchFnArg<usize> = Box::new(|x| x);
+                let mut bench =
+                    BenchBuilder::new(Vec::new(), argfunc, vec![10, 100])
+                        .add_function(
+                            move |x| {
+                                if calls.fetch_add(1, Ordering::Relaxed) % 2
+                                    == 1
+                                {
+                                    std::thread::sleep(Duration::from_millis(
+                                        1,
+                                    ));
+                                }
+                                x
+                            },
+                            "Noisy",
+                        )
+                        .repetitions(4)
+                        .cv_threshold(0.5)
+                        .print_verdict(true)
+                        .parallel(parallel)
+                        .verbosity(verbosity)
+                        .build()
+                        .unwrap();
+                bench.run();
+
+                // Pairs are still flagged as noisy when warnings are not
+                // printed.
+                assert_eq!(bench.data().num_sizes(), 2);
+                assert!(!bench.noisy.is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn test_max_repetitions_remeasures_noisy_pairs() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::time::Duration;
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&calls);
+        let functions: Vec<BenchFnNamed<'static, usize, usize>> = vec![(
+            Box::new(move |x| {
+                if counter.fetch_add(1, Ordering::Relaxed) == 0 {
+                    std::thread::sleep(Duration::from_millis(20));
+                }
+                x
+            }),
+            "Noisy",
+        )];
+        let argfunc: BenchFnArg<usize> = Box::new(|x| x);
+        let mut bench = BenchBuilder::new(functions, argfunc, vec![10])
+            .repetitions(2)
+            .max_repetitions(5)
+            .cv_threshold(0.01)
+            .build()
+            .unwrap();
+        bench.run();
+
+        let calls = calls.load(Ordering::Relaxed);
+        assert!(calls > 2 && calls <= 5);
+    }
+
+    #[test]
+    fn test_sampling_needs_more() {
+        let sampling = Sampling {
+            repetitions: 2,
+            max_repetitions: 4,
+            cv_threshold: Some(0.1),
+            target_precision: None,
+            critical_value: 1.96,
+            strict: false,
+            retain: false,
+            cache_mode: CacheMode::Warm,
+            aggregation: Aggregation::Mean,
+        };
+
+        assert!(sampling.needs_more(&[]));
+        assert!(sampling.needs_more(&[1]));
+        assert!(!sampling.needs_more(&[1, 1]));
+        assert!(sampling.needs_more(&[1, 2]));
+        assert!(sampling.needs_more(&[1, 2, 1]));
+        assert!(!sampling.needs_more(&[1, 2, 1, 2]));
+
+        // The relative half-width of [9, 11] is 1.96 * 0.141 / 1.414 = 0.196.
+        let sampling = Sampling {
+            cv_threshold: None,
+            target_precision: Some(0.1),
+            max_repetitions: 8,
+            ..sampling
+        };
+        assert!(sampling.needs_more(&[9, 11]));
+        assert!(!sampling.needs_more(&[90, 110, 100, 100, 95, 105]));
+        assert!(!sampling.needs_more(&[1, 9, 1, 9, 1, 9, 1, 9]));
+    }
+
+    #[test]
+    fn test_accessors() {
+        let functions: Vec<BenchFnNamed<'static, usize, usize>> = vec![
+            (Box::new(|x| x), "Identity"),
+            (Box::new(|x| x + 1), "Successor"),
+        ];
+        let argfunc: BenchFnArg<usize> = Box::new(|x| x);
+        let mut bench = BenchBuilder::new(functions, argfunc, vec![10, 20])
+            .repetitions(3)
+            .build()
+            .unwrap();
+
+        assert_eq!(bench.sizes(), [10, 20]);
+        assert_eq!(bench.function_names(), ["Identity", "Successor"]);
+        assert_eq!(bench.repetitions(), 3);
+        assert!(bench.data().is_empty());
+
+        bench.run();
+        assert_eq!(bench.data().sizes(), [10, 20]);
+        assert_eq!(bench.data().num_functions(), 2);
+    }
+
+    #[test]
+    fn test_custom_timer() {
+        use std::cell::Cell;
+        use std::time::Duration;
+
+        thread_local! {
+            static READINGS: Cell<u64> = const { Cell::new(0) };
+        }
+
+        /// Advances by one millisecond on every reading on the same thread,
+        /// so that readings interleaved across parallel workers do not
+        /// lengthen each other's intervals.
+        struct StepTimer;
+
+        impl Timer for StepTimer {
+            fn now(&self) -> Duration {
+                Duration::from_millis(READINGS.replace(READINGS.get() + 1))
+            }
+        }
+
+        for parallel in [false, true] {
+            let functions: Vec<BenchFnNamed<'static, usize, usize>> = vec![
+                (Box::new(|x| x), "Identity"),
+                (Box::new(|x| x + 1), "Successor"),
+            ];
+            let argfunc: BenchFnArg<usize> = Box::new(|x| x);
+            let mut bench = BenchBuilder::new(functions, argfunc, vec![10, 20])
+                .repetitions(3)
+                .parallel(parallel)
+                .timer(StepTimer)
+                .build()
+                .unwrap();
+            bench.run();
+
+            for (_, times) in bench.data().iter() {
+                assert!(times.iter().all(|&t| (t - 1e-3).abs() < 1e-12));
+            }
+            assert_eq!(
+                bench
+                    .clone_times()
+                    .iter()
+                    .map(|&(size, _)| size)
+                    .collect::<Vec<_>>(),
+                [10, 20]
+            );
+            assert!(bench
+                .clone_times()
+                .iter()
+                .all(|&(_, t)| (t - 1e-3).abs() < 1e-12));
+            assert_eq!(
+                bench.timer_calibration().unwrap().granularity,
+                Duration::from_millis(1)
+            );
+            assert_eq!(
+                bench.baseline().timer_calibration(),
+                bench.timer_calibration()
+            );
+        }
+    }
+
+    #[test]
+    fn test_priority_elevated() {
+        let functions: Vec<BenchFnNamed<'static, usize, usize>> =
+            vec![(Box::new(|x| x), "Identity")];
+        let argfunc: BenchFnArg<usize> = Box::new(|x| x);
+        let mut bench = BenchBuilder::new(functions, argfunc, vec![10])
+            .build()
+            .unwrap();
+        bench.run();
+        assert_eq!(bench.priority_elevated(), None);
+
+        let functions: Vec<BenchFnNamed<'static, usize, usize>> =
+            vec![(Box::new(|x| x), "Identity")];
+        let argfunc: BenchFnArg<usize> = Box::new(|x| x);
+        let mut bench = BenchBuilder::new(functions, argfunc, vec![10])
+            .priority(Priority::High)
+            .build()
+            .unwrap();
+        bench.run();
+        assert!(bench.priority_elevated().is_some());
+    }
+
+    #[test]
+    fn test_stateful_function_state_is_per_pair() {
+        for parallel in [false, true] {
+            let argfunc: BenchFnArg<usize> = Box::new(|x| x);
+            let mut calls = 0;
+            let mut bench =
+                BenchBuilder::new(Vec::new(), argfunc, vec![10, 20])
+                    .add_function_mut(
+                        move |_| {
+                            calls += 1;
+                            calls
+                        },
+                        "Counter",
+                    )
+                    .add_function(|_| 3, "Three")
+                    .repetitions(3)
+                    .parallel(parallel)
+                    .strict_equal(false)
+                    .assert_equal(true)
+                    .build()
+                    .unwrap();
+
+            // Each pair starts counting from zero, so the last repetition of
+            // every pair returns 3.
+            bench.run();
+        }
+    }
+
+    #[test]
+    fn test_sized_function_receives_size() {
+        for parallel in [false, true] {
+            let argfunc: BenchFnArg<usize> = Box::new(|x| x * 2);
+            let mut bench =
+                BenchBuilder::new(Vec::new(), argfunc, vec![10, 20])
+                    .add_function(|x| x, "Identity")
+                    .add_sized_function(|n, _| n * 2, "Sized")
+                    .repetitions(2)
+                    .parallel(parallel)
+                    .assert_equal(true)
+                    .build()
+                    .unwrap();
+
+            // Both functions return twice the size, which the sized function
+            // only knows from its first argument.
+            bench.run();
+            assert_eq!(bench.data().num_sizes(), 2);
+        }
+    }
+
+    #[test]
+    fn test_metrics() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+        for parallel in [false, true] {
+            let argfunc: BenchFnArg<usize> = Box::new(|x| x);
+            let mut bench =
+                BenchBuilder::new(Vec::new(), argfunc, vec![10, 20])
+                    .add_function(|x| x, "Identity")
+                    .add_function(
+                        |x| {
+                            CALLS.fetch_add(3, Ordering::Relaxed);
+                            x * 2
+                        },
+                        "Double",
+                    )
+                    .max_size_for("Identity", 10)
+                    .metric("Calls", || CALLS.load(Ordering::Relaxed) as f64)
+                    .result_metric("Result", |&result| result as f64)
+                    .repetitions(3)
+                    .parallel(parallel)
+                    .build()
+                    .unwrap();
+            bench.run();
+
+            assert_eq!(bench.metric_names(), ["Calls", "Result"]);
+            let results = bench.metric("Result").unwrap();
+            assert_eq!(results.sizes(), [10, 20]);
+            assert_eq!(results.time(0, 0), 10.0);
+            assert!(results.time(0, 1).is_nan());
+            assert_eq!(results.time(1, 1), 40.0);
+            if !parallel {
+                let calls = bench.metric("Calls").unwrap();
+                assert_eq!(calls.time(0, 0), 0.0);
+                assert_eq!(calls.time(1, 1), 3.0);
+            }
+            assert!(bench.metric("Allocations").is_none());
+        }
+    }
+
+    #[test]
+    fn test_catch_panics() {
+        for parallel in [false, true] {
+            let functions: Vec<BenchFnNamed<'static, usize, usize>> = vec![
+                (Box::new(|x| x), "Identity"),
+                (
+                    Box::new(|x| {
+                        assert!(x != 100, "no hundreds");
+                        x
+                    }),
+                    "Fragile",
+                ),
+            ];
+            let argfunc: BenchFnArg<usize> = Box::new(|x| x);
+            let mut bench =
+                BenchBuilder::new(functions, argfunc, vec![10, 100])
+                    .parallel(parallel)
+                    .catch_panics(true)
+                    .verbosity(Verbosity::Quiet)
+                    .build()
+                    .unwrap();
+            bench.run();
+
+            let data = bench.data();
+            assert!(data.time(0, 0) > 0.0);
+            assert!(data.time(0, 1) > 0.0);
+            assert!(data.time(1, 0) > 0.0);
+            assert!(data.time(1, 1).is_nan());
+            assert_eq!(bench.failures().len(), 1);
+            let (size, func_idx, message) = &bench.failures()[0];
+            assert_eq!((*size, *func_idx), (100, 1));
+            assert!(message.contains("no hundreds"));
+        }
+    }
+
+    #[test]
+    fn test_results_without_clone() {
+        /// A return value that cannot be cloned.
+        #[derive(Debug, PartialEq)]
+        struct Unique(usize);
+
+        for parallel in [false, true] {
+            let functions: Vec<BenchFnNamed<'static, usize, Unique>> =
+                vec![(Box::new(Unique), "First"), (Box::new(Unique), "Second")];
+            let argfunc: BenchFnArg<usize> = Box::new(|x| x);
+            BenchBuilder::new(functions, argfunc, vec![10, 20])
+                .repetitions(2)
+                .parallel(parallel)
+                .strict_equal(true)
+                .build()
+                .unwrap()
+                .run();
+        }
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Flaky returned different values across repetitions at n=10"
+    )]
+    fn test_strict_equal_nondeterministic() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let calls = AtomicUsize::new(0);
+        let functions: Vec<BenchFnNamed<'static, usize, usize>> = vec![(
+            Box::new(move |x| x + calls.fetch_add(1, Ordering::Relaxed)),
+            "Flaky",
+        )];
+        let argfunc: BenchFnArg<usize> = Box::new(|x| x);
+
+        BenchBuilder::new(functions, argfunc, vec![10])
+            .repetitions(3)
+            .strict_equal(true)
+            .build()
+            .unwrap()
+            .run();
+    }
+
+    /// Environment variable holding the directory `isolated_outputs` writes
+    /// to when run by `test_isolated_outputs_written_by_parent`.
+    #[cfg(feature = "plot")]
+    const ISOLATED_OUTPUTS_DIR: &str = "BENCHPLOT_TEST_ISOLATED_OUTPUTS_DIR";
+
+    /// Runs two isolated benchmarks, plotting each, and logs each argument
+    /// generated. Each plot is renamed after the process that saved it.
+    ///
+    /// This is run in its own process by
+    /// `test_isolated_outputs_written_by_parent`, since the child processes
+    /// re-execute the test binary with the same arguments, which must not
+    /// capture their output nor print the name of the test before it.
+    #[cfg(feature = "plot")]
+    #[test]
+    #[ignore]
+    fn isolated_outputs() {
+        let Some(dir) =
+            std::env::var_os(ISOLATED_OUTPUTS_DIR).map(PathBuf::from)
+        else {
+            return;
+        };
+        for name in ["first", "second"] {
+            let log = dir.join(format!("{}.log", name));
+            let functions: Vec<BenchFnNamed<'static, usize, usize>> = vec![
+                (Box::new(|x| x), "Identity"),
+                (Box::new(|x| x + 1), "Successor"),
+            ];
+            let argfunc: BenchFnArg<usize> = Box::new(move |x| {
+                let mut file = std::fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&log)
+                    .unwrap();
+                std::io::Write::write_all(&mut file, b"argument\n").unwrap();
+                x
+            });
+            let mut bench = BenchBuilder::new(functions, argfunc, vec![10, 20])
+                .isolated(true)
+                .verbosity(Verbosity::Quiet)
+                .build()
+                .unwrap();
+            bench.run();
+
+            let path = dir.join(format!("{}.svg", name));
+            bench.plot(&path).build().unwrap();
+            if path.exists() {
+                let saved_by = format!("{}_{}.svg", name, std::process::id());
+                std::fs::rename(&path, dir.join(saved_by)).unwrap();
+            }
+        }
+    }
+
+    #[cfg(feature = "plot")]
+    #[test]
+    fn test_isolated_outputs_written_by_parent() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut parent =
+            std::process::Command::new(std::env::current_exe().unwrap())
+                .args([
+                    "bench::tests::isolated_outputs",
+                    "--exact",
+                    "--ignored",
+                    "--nocapture",
+                    "--quiet",
+                    "--test-threads=1",
+                ])
+                .env(ISOLATED_OUTPUTS_DIR, dir.path())
+                .stdout(std::process::Stdio::null())
+                .spawn()
+                .unwrap();
+        let pid = parent.id();
+        assert!(parent.wait().unwrap().success());
+
+        let mut files: Vec<String> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        files.sort();
+        assert_eq!(
+            files,
+            [
+                "first.log".to_string(),
+                format!("first_{}.svg", pid),
+                "second.log".to_string(),
+                format!("second_{}.svg", pid),
+            ]
+        );
+        // Each argument is generated once, by the child process timing its
+        // pair, and not again by the children of the second benchmark.
+        for log in ["first.log", "second.log"] {
+            let log = std::fs::read_to_string(dir.path().join(log)).unwrap();
+            assert_eq!(log.lines().count(), 4);
+        }
+    }
 }
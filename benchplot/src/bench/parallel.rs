@@ -0,0 +1,166 @@
+/*
+Copyright 2024 Owain Davies
+SPDX-License-Identifier: Apache-2.0 OR MIT
+*/
+
+/// Applies `f` to every item in parallel on the rayon thread pool, returning
+/// the outputs in the order of the items.
+#[cfg(feature = "parallel")]
+pub(crate) fn map<I, O, F>(items: Vec<I>, f: F) -> Vec<O>
+where
+    I: Send,
+    O: Send,
+    F: Fn(I) -> O + Sync,
+{
+    use rayon::prelude::*;
+
+    items.into_par_iter().map(&f).collect()
+}
+
+/// Applies `f` to every item in parallel on scoped threads, one per available
+/// core at most, returning the outputs in the order of the items.
+///
+/// A panic in `f` is propagated to the caller once every thread has stopped.
+#[cfg(not(feature = "parallel"))]
+pub(crate) fn map<I, O, F>(items: Vec<I>, f: F) -> Vec<O>
+where
+    I: Send,
+    O: Send,
+    F: Fn(I) -> O + Sync,
+{
+    use std::sync::Mutex;
+
+    let threads = std::thread::available_parallelism()
+        .map_or(1, |threads| threads.get())
+        .min(items.len());
+    let mut outputs: Vec<Option<O>> = (0..items.len()).map(|_| None).collect();
+    let queue = Mutex::new(items.into_iter().enumerate());
+    let f = &f;
+    let queue = &queue;
+
+    std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|_| {
+                scope.spawn(move || {
+                    let mut done = Vec::new();
+                    loop {
+                        let next = queue
+                            .lock()
+                            .unwrap_or_else(|err| err.into_inner())
+                            .next();
+                        let Some((idx, item)) = next else {
+                            break;
+                        };
+                        done.push((idx, f(item)));
+                    }
+                    done
+                })
+            })
+            .collect();
+        for worker in workers {
+            let done = worker
+                .join()
+                .unwrap_or_else(|err| std::panic::resume_unwind(err));
+            for (idx, output) in done {
+                outputs[idx] = Some(output);
+            }
+        }
+    });
+    outputs
+        .into_iter()
+        .map(|output| output.expect("every item is mapped"))
+        .collect()
+}
+
+/// Applies `f` to every item within a call of [`map`], returning the outputs
+/// in the order of the items.
+///
+/// Items are mapped in parallel on the rayon thread pool, which shares its
+/// threads between nested calls, and sequentially without it, so that nested
+/// calls do not spawn a thread per core for every item of the outer call.
+pub(crate) fn map_nested<I, O, F>(items: Vec<I>, f: F) -> Vec<O>
+where
+    I: Send,
+    O: Send,
+    F: Fn(I) -> O + Sync,
+{
+    if cfg!(feature = "parallel") {
+        map(items, f)
+    } else {
+        items.into_iter().map(f).collect()
+    }
+}
+
+/// Applies `f` to every item in parallel like [`map`], returning the outputs
+/// in the order of the items, or the first error in that order.
+///
+/// Once `f` has failed, the items that have not started are skipped.
+pub(crate) fn try_map<I, O, E, F>(items: Vec<I>, f: F) -> Result<Vec<O>, E>
+where
+    I: Send,
+    O: Send,
+    E: Send,
+    F: Fn(I) -> Result<O, E> + Sync,
+{
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    let failed = AtomicBool::new(false);
+    map(items, |item| {
+        if failed.load(Ordering::Relaxed) {
+            return None;
+        }
+        let output = f(item);
+        if output.is_err() {
+            failed.store(true, Ordering::Relaxed);
+        }
+        Some(output)
+    })
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    #[test]
+    fn test_map_keeps_order() {
+        let squares = map((0..100u64).collect(), |x| x * x);
+        assert_eq!(squares, (0..100u64).map(|x| x * x).collect::<Vec<_>>());
+        assert!(map(Vec::<u64>::new(), |x| x).is_empty());
+    }
+
+    #[test]
+    fn test_map_nested_keeps_order() {
+        let products = map((0..10u64).collect(), |x| {
+            map_nested((0..10u64).collect(), |y| x * y)
+        });
+        for (x, row) in products.iter().enumerate() {
+            let expected: Vec<u64> = (0..10).map(|y| x as u64 * y).collect();
+            assert_eq!(*row, expected);
+        }
+    }
+
+    #[test]
+    fn test_try_map() {
+        assert_eq!(
+            try_map((0..100u64).collect(), Ok::<_, ()>),
+            Ok((0..100u64).collect())
+        );
+
+        let calls = AtomicUsize::new(0);
+        let result = try_map((0..1000u64).collect(), |x| {
+            calls.fetch_add(1, Ordering::Relaxed);
+            if x == 0 || x == 500 {
+                return Err(x);
+            }
+            std::thread::sleep(Duration::from_millis(1));
+            Ok(x)
+        });
+        assert_eq!(result, Err(0));
+        assert!(calls.load(Ordering::Relaxed) < 1000);
+    }
+}
@@ -0,0 +1,122 @@
+/*
+Copyright 2024 Owain Davies
+SPDX-License-Identifier: Apache-2.0 OR MIT
+*/
+
+use crate::Baseline;
+use std::fmt::Write;
+
+impl Baseline {
+    /// Renders the results as a pgfplots figure, a `tikzpicture` with a
+    /// log-log axis, a series per function, and a legend, for LaTeX documents
+    /// to typeset with their own fonts instead of embedding an image.
+    ///
+    /// The document must load the `pgfplots` package. The label of the run,
+    /// if any, is the title of the axis. Missing timings, and timings at an
+    /// input size of zero, which cannot be placed on the logarithmic axis,
+    /// are omitted.
+    ///
+    /// ```
+    /// use benchplot::{Baseline, BenchData};
+    ///
+    /// let data = BenchData::from_rows(1, [(10, vec![1.5e-6])]).unwrap();
+    /// let baseline = Baseline::new(vec!["Merge Sort".to_string()], data);
+    /// let tex = baseline.to_pgfplots();
+    /// assert!(tex.contains("\\addlegendentry{Merge Sort}"));
+    /// ```
+    pub fn to_pgfplots(&self) -> String {
+        let mut tex = String::from(
+            "% Requires \\usepackage{pgfplots} in the preamble.\n\
+             \\begin{tikzpicture}\n\\begin{loglogaxis}[\n",
+        );
+        if let Some(label) = self.label() {
+            let _ = writeln!(tex, "  title={{{}}},", escape(label));
+        }
+        tex.push_str(
+            "  xlabel={$n$},\n  ylabel={Time (s)},\n  grid=major,\n  \
+             legend pos=north west,\n  legend cell align=left,\n]\n",
+        );
+        for (fn_idx, name) in self.functions().iter().enumerate() {
+            tex.push_str("\\addplot coordinates {\n");
+            for (size, times) in self.data().iter() {
+                let time = times[fn_idx];
+                if size > 0 && !time.is_nan() {
+                    let _ = writeln!(tex, "  ({}, {:e})", size, time);
+                }
+            }
+            let _ = writeln!(tex, "}};\n\\addlegendentry{{{}}}", escape(name));
+        }
+        tex.push_str("\\end{loglogaxis}\n\\end{tikzpicture}\n");
+        tex
+    }
+}
+
+/// Escapes the characters of `text` that are special in LaTeX.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '~' => escaped.push_str("\\textasciitilde{}"),
+            '^' => escaped.push_str("\\textasciicircum{}"),
+            '\\' => escaped.push_str("\\textbackslash{}"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BenchData;
+
+    #[test]
+    fn test_to_pgfplots() {
+        let baseline = Baseline::new(
+            vec!["merge_sort".to_string(), "50% & more".to_string()],
+            BenchData::from_rows(
+                2,
+                [
+                    (0, vec![1e-9, 1e-9]),
+                    (10, vec![1.5e-6, f64::NAN]),
+                    (100, vec![1.5e-5, 2e-3]),
+                ],
+            )
+            .unwrap(),
+        )
+        .with_label("v1.0");
+
+        assert_eq!(
+            baseline.to_pgfplots(),
+            "% Requires \\usepackage{pgfplots} in the preamble.\n\
+             \\begin{tikzpicture}\n\
+             \\begin{loglogaxis}[\n  \
+             title={v1.0},\n  \
+             xlabel={$n$},\n  \
+             ylabel={Time (s)},\n  \
+             grid=major,\n  \
+             legend pos=north west,\n  \
+             legend cell align=left,\n\
+             ]\n\
+             \\addplot coordinates {\n  \
+             (10, 1.5e-6)\n  \
+             (100, 1.5e-5)\n\
+             };\n\
+             \\addlegendentry{merge\\_sort}\n\
+             \\addplot coordinates {\n  \
+             (100, 2e-3)\n\
+             };\n\
+             \\addlegendentry{50\\% \\& more}\n\
+             \\end{loglogaxis}\n\
+             \\end{tikzpicture}\n"
+        );
+        assert_eq!(
+            escape("a~b^c\\d"),
+            "a\\textasciitilde{}b\\textasciicircum{}c\\textbackslash{}d"
+        );
+    }
+}
@@ -3,15 +3,29 @@ Copyright 2024-2025 Owain Davies
 SPDX-License-Identifier: Apache-2.0 OR MIT
 */
 
-use crate::Bench;
+use super::cache::cpu_caches;
+use super::font;
+use super::isolation;
+use crate::util;
+use crate::{
+    Baseline, Bench, BenchData, Complexity, DefaultTheme, NumberFormat,
+    PlotStyle, Shared, Smoothing, Theme, Threading,
+};
+use plotters::coord::ranged1d::{AsRangedCoord, Ranged, ValueFormatter};
+use plotters::coord::Shift;
+use plotters::element::{Drawable, PointCollection};
 use plotters::prelude::full_palette::*;
 use plotters::prelude::*;
-use plotters::style::{Color, IntoFont, ShapeStyle};
+use plotters::style::text_anchor::{HPos, Pos, VPos};
+use plotters::style::{Color, IntoFont, RGBAColor, ShapeStyle};
+use plotters_backend::{BackendCoord, DrawingErrorKind};
+use plotters_bitmap::BitMapBackendError;
+use std::collections::HashMap;
 use std::fmt::Debug;
 use std::path::{Path, PathBuf};
 
 /// Colors for each function line. Wrap around if there are more functions.
-const COLORS: &[RGBColor] = &[
+pub(crate) const COLORS: &[RGBColor] = &[
     RGBColor(121, 192, 255),
     RGBColor(137, 87, 229),
     RGBColor(240, 136, 62),
@@ -25,6 +39,313 @@ const COLORS: &[RGBColor] = &[
     RGBColor(127, 255, 212),
 ];
 
+/// Where the legend is drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LegendPosition {
+    /// In an upper corner of the chart, over the data.
+    #[default]
+    Inside,
+
+    /// In a margin to the right of the chart.
+    Right,
+
+    /// In a margin below the chart.
+    Bottom,
+}
+
+/// What the lower panel of a stacked figure shows, below the plot configured
+/// by the [`PlotBuilder`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LowerPanel {
+    /// The raw timings.
+    Time,
+
+    /// The throughput, as with [`PlotBuilder::throughput`].
+    Throughput,
+
+    /// The timings divided by the given complexity class, as with
+    /// [`PlotBuilder::normalize`].
+    Normalized(Complexity),
+}
+
+/// Horizontal alignment of the title of a plot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TitleAlignment {
+    /// Aligned with the left margin of the plot.
+    Left,
+
+    /// Centered over the plot.
+    #[default]
+    Center,
+
+    /// Aligned with the right margin of the plot.
+    Right,
+}
+
+/// Color, font size, and alignment of the title of a plot.
+///
+/// ```no_run
+/// # use benchplot::{Bench, TitleAlignment, TitleStyle};
+/// # use plotters::style::BLACK;
+/// # fn example(bench: &Bench<usize, usize>) {
+/// bench
+///     .plot("plot.svg")
+///     .title("Sorting Algorithms")
+///     .title_style(TitleStyle {
+///         color: BLACK,
+///         font_size: 28,
+///         alignment: TitleAlignment::Left,
+///     })
+///     .build()
+///     .expect("Plotting failed");
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TitleStyle {
+    /// Color of the text.
+    pub color: RGBColor,
+
+    /// Font size of the text in points.
+    pub font_size: u32,
+
+    /// Horizontal alignment of the text.
+    pub alignment: TitleAlignment,
+}
+
+impl Default for TitleStyle {
+    /// Returns the style of a title without overrides: grey 24pt text,
+    /// centered over the plot.
+    fn default() -> Self {
+        Self {
+            color: GREY,
+            font_size: 24,
+            alignment: TitleAlignment::Center,
+        }
+    }
+}
+
+/// Style of the line of a function, overriding the style it is given by its
+/// position in the list of functions.
+///
+/// ```no_run
+/// # use benchplot::{Bench, Marker, SeriesStyle};
+/// # fn example(bench: &Bench<usize, usize>) {
+/// bench
+///     .plot("plot.svg")
+///     .style_for(
+///         "Merge Sort",
+///         SeriesStyle {
+///             color: Some((218, 54, 51)),
+///             width: 4,
+///             dash: None,
+///             marker: Some(Marker::Circle),
+///         },
+///     )
+///     .build()
+///     .expect("Plotting failed");
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SeriesStyle {
+    /// Color of the line as its red, green, and blue components, or `None`
+    /// for the color given by the position of the function.
+    pub color: Option<(u8, u8, u8)>,
+
+    /// Width of the line in pixels.
+    pub width: u32,
+
+    /// Lengths in pixels of the dashes and of the gaps between them, or
+    /// `None` for a solid line.
+    pub dash: Option<(u32, u32)>,
+
+    /// Marker drawn at each data point, or `None` for no markers.
+    pub marker: Option<Marker>,
+}
+
+impl Default for SeriesStyle {
+    /// Returns the style of a function without overrides: a solid line, 2
+    /// pixels wide, in the color given by its position.
+    fn default() -> Self {
+        Self {
+            color: None,
+            width: 2,
+            dash: None,
+            marker: None,
+        }
+    }
+}
+
+/// Shape of the marker drawn at each data point of a series.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Marker {
+    /// A filled circle.
+    Circle,
+
+    /// A filled square.
+    Square,
+
+    /// A filled triangle.
+    Triangle,
+}
+
+impl Marker {
+    /// Draws the marker centered on `(x, y)`, with its size multiplied by
+    /// `scale`.
+    fn draw<DB: DrawingBackend>(
+        self,
+        backend: &mut DB,
+        (x, y): BackendCoord,
+        style: &ShapeStyle,
+        scale: f64,
+    ) -> Result<(), DrawingErrorKind<DB::ErrorType>> {
+        let r = (4.0 * scale).round() as i32;
+        let t = (5.0 * scale).round() as i32;
+        match self {
+            Marker::Circle => {
+                backend.draw_circle((x, y), r as u32, style, true)
+            }
+            Marker::Square => {
+                backend.draw_rect((x - r, y - r), (x + r, y + r), style, true)
+            }
+            Marker::Triangle => backend.fill_polygon(
+                vec![(x, y - t), (x - t, y + r), (x + t, y + r)],
+                style,
+            ),
+        }
+    }
+}
+
+/// A marker at a data point.
+struct MarkerElement<Coord> {
+    center: Coord,
+    marker: Marker,
+    style: ShapeStyle,
+    scale: f64,
+}
+
+impl<'a, Coord> PointCollection<'a, Coord> for &'a MarkerElement<Coord> {
+    type Point = &'a Coord;
+    type IntoIter = std::iter::Once<&'a Coord>;
+
+    fn point_iter(self) -> Self::IntoIter {
+        std::iter::once(&self.center)
+    }
+}
+
+impl<Coord, DB: DrawingBackend> Drawable<DB> for MarkerElement<Coord> {
+    fn draw<I: Iterator<Item = BackendCoord>>(
+        &self,
+        mut points: I,
+        backend: &mut DB,
+        _: (u32, u32),
+    ) -> Result<(), DrawingErrorKind<DB::ErrorType>> {
+        match points.next() {
+            Some(center) => {
+                self.marker.draw(backend, center, &self.style, self.scale)
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+/// Glyph drawn next to the label of a series in the legend: a short line in
+/// the style of the series, dashed like the series, with the marker of the
+/// series at its center.
+#[derive(Clone, Copy)]
+struct LegendGlyph {
+    style: ShapeStyle,
+    dash: Option<(u32, u32)>,
+    marker: Option<Marker>,
+    scale: f64,
+}
+
+/// A legend glyph whose line starts at `origin`.
+struct LegendGlyphElement {
+    origin: BackendCoord,
+    glyph: LegendGlyph,
+}
+
+impl LegendGlyph {
+    /// Returns the glyph with its line starting at `origin`.
+    fn at(self, origin: BackendCoord) -> LegendGlyphElement {
+        LegendGlyphElement {
+            origin,
+            glyph: self,
+        }
+    }
+}
+
+impl<'a> PointCollection<'a, BackendCoord> for &'a LegendGlyphElement {
+    type Point = &'a BackendCoord;
+    type IntoIter = std::iter::Once<&'a BackendCoord>;
+
+    fn point_iter(self) -> Self::IntoIter {
+        std::iter::once(&self.origin)
+    }
+}
+
+impl<DB: DrawingBackend> Drawable<DB> for LegendGlyphElement {
+    fn draw<I: Iterator<Item = BackendCoord>>(
+        &self,
+        mut points: I,
+        backend: &mut DB,
+        _: (u32, u32),
+    ) -> Result<(), DrawingErrorKind<DB::ErrorType>> {
+        let Some((x, y)) = points.next() else {
+            return Ok(());
+        };
+        let LegendGlyph {
+            style,
+            dash,
+            marker,
+            scale,
+        } = self.glyph;
+        let length = scaled(LEGEND_GLYPH_LENGTH, scale) as i32;
+
+        match dash {
+            Some((dash, gap)) => {
+                let (dash, gap) = (dash.max(1) as i32, gap as i32);
+                let mut start = 0;
+                while start < length {
+                    let end = (start + dash).min(length);
+                    backend.draw_line((x + start, y), (x + end, y), &style)?;
+                    start = end + gap;
+                }
+            }
+            None => backend.draw_line((x, y), (x + length, y), &style)?,
+        }
+        if let Some(marker) = marker {
+            marker.draw(backend, (x + length / 2, y), &style, scale)?;
+        }
+        Ok(())
+    }
+}
+
+/// Font size of the legend labels.
+const LEGEND_FONT_SIZE: u32 = 18;
+
+/// Length in pixels of the line of each legend entry.
+const LEGEND_GLYPH_LENGTH: u32 = 30;
+
+/// Height in pixels of each legend entry.
+const LEGEND_ENTRY_HEIGHT: u32 = 24;
+
+/// Minimum width or height in pixels of the area the series are drawn in,
+/// which an outside legend must leave room for.
+const MIN_CHART_SIZE: u32 = 50;
+
+/// Font size of the tick labels of the axes.
+const TICK_FONT_SIZE: u32 = 24;
+
+/// Returns the length `pixels`, e.g., a font size or a stroke width,
+/// multiplied by `scale` and rounded, keeping nonzero lengths visible.
+fn scaled(pixels: u32, scale: f64) -> u32 {
+    if pixels == 0 {
+        return 0;
+    }
+    ((pixels as f64 * scale).round() as u32).max(1)
+}
+
 /// Error type for `PlotBuilder`.
 #[derive(Debug, thiserror::Error)]
 pub enum PlotBuilderError {
@@ -32,28 +353,238 @@ pub enum PlotBuilderError {
     /// attempting to create a plot.
     #[error("{0}")]
     DrawingError(#[from] DrawingAreaErrorKind<std::io::Error>),
+
+    /// Represents errors originating from the [`plotters`] crate when
+    /// attempting to create a bitmap image of a plot, e.g., a PNG file.
+    #[error("{0}")]
+    BitmapError(#[from] DrawingAreaErrorKind<BitMapBackendError>),
+
+    /// Indicates that a plot cannot be saved in the format of the extension
+    /// of a file.
+    #[error(
+        "Unsupported plot format {}. Use .svg, .png, .jpg, or .bmp.",
+        path.display()
+    )]
+    UnsupportedFormat {
+        /// The path of the output file.
+        path: PathBuf,
+    },
+
+    /// Indicates that writing the plot to its output file failed.
+    #[error("Failed to write plot to {}: {source}", path.display())]
+    Io {
+        /// The path of the output file.
+        path: PathBuf,
+        /// The underlying I/O error.
+        source: std::io::Error,
+    },
+
+    /// Indicates that there is no benchmark data to plot, typically because
+    /// `run()` was not called before plotting.
+    #[error(
+        "No benchmark data to plot. Call run() or run_if_needed() before \
+         plotting."
+    )]
+    NoData,
+
+    /// Indicates that the benchmark was run, but that every plotted value is
+    /// missing, e.g., because every function was skipped at every input
+    /// size, so that the y-axis would have no range.
+    #[error("Every plotted value is missing, so there is nothing to draw.")]
+    NothingToDraw,
+
+    /// Indicates that the benchmark has no input sizes.
+    #[error("The sizes vector must not be empty.")]
+    NoSizes,
+
+    /// Indicates that the width or height of the plot is zero.
+    #[error(
+        "Invalid plot dimensions {width}x{height}. Both must be positive."
+    )]
+    InvalidDimensions {
+        /// The requested width in pixels.
+        width: u32,
+        /// The requested height in pixels.
+        height: u32,
+    },
+
+    /// Indicates that power-of-two x-axis labels were requested, but an input
+    /// size is not a power of two.
+    #[error("Input size {0} is not a power of two.")]
+    NotPowerOfTwo(usize),
+
+    /// Indicates that the base of a logarithmic axis is not greater than 1.
+    #[error("Invalid logarithm base {0}. It must be greater than 1.")]
+    InvalidLogBase(f64),
+
+    /// Indicates that the number of x-axis values or labels does not match
+    /// the number of input sizes.
+    #[error("Expected {expected} x-axis values or labels, got {actual}.")]
+    XAxisLength {
+        /// The number of input sizes.
+        expected: usize,
+        /// The number of x-axis values or labels supplied.
+        actual: usize,
+    },
+
+    /// Indicates that the value of a horizontal line is not positive and
+    /// finite, so it cannot be drawn on the logarithmic y-axis.
+    #[error("Invalid horizontal line value {0}. It must be positive.")]
+    InvalidHline(f64),
+
+    /// Indicates that a vertical line cannot be placed at an input size,
+    /// because it is zero, or because the x-axis does not show the input
+    /// sizes and it is not one of the benchmarked sizes.
+    #[error("Cannot draw a vertical line at input size {0}.")]
+    InvalidVline(usize),
+
+    /// Indicates that CPU cache markers were requested with an element size
+    /// of zero.
+    #[error("The element size for CPU cache markers must be positive.")]
+    ZeroElementSize,
+
+    /// Indicates that the scale factor of the plot is not a positive, finite
+    /// number.
+    #[error("Invalid scale factor {0}. It must be positive and finite.")]
+    InvalidScale(f64),
+
+    /// Indicates that the parameter of the smoothing of the curves is
+    /// invalid, e.g., a LOESS span that is not in `(0, 1]`.
+    #[error("Invalid smoothing {0:?}.")]
+    InvalidSmoothing(Smoothing),
+
+    /// Indicates that no font could be found for the font family of the plot
+    /// to embed in the SVG file.
+    #[error("Cannot find a font for the font family \"{0}\" to embed.")]
+    FontNotFound(String),
+
+    /// Indicates that the metric to plot was not collected by the benchmark.
+    #[error("Unknown metric \"{0}\".")]
+    UnknownMetric(String),
+
+    /// Indicates that a legend drawn outside the chart, with
+    /// [`LegendPosition::Right`] or [`LegendPosition::Bottom`], leaves too
+    /// little room for the chart, e.g., because the plot is small or lists
+    /// many functions.
+    #[error(
+        "The legend leaves too little room for the chart. Enlarge the plot \
+         or draw the legend elsewhere."
+    )]
+    LegendTooLarge,
+
+    /// Indicates that a style was set with [`PlotBuilder::style_for`] for a
+    /// function that neither the benchmark nor an overlaid baseline has.
+    #[error("No function named \"{0}\" to style.")]
+    UnknownFunction(String),
+
+    /// Indicates that the frames of a plot cannot be named after its file,
+    /// which has no name, e.g., because its path is empty or ends in `..`.
+    #[error("Cannot name the frames of {}, which has no file name.", path.display())]
+    NoFileStem {
+        /// The path of the plot file.
+        path: PathBuf,
+    },
 }
 
-impl<'a, T: Clone + Send + 'static, R: Send + 'static> Bench<'a, T, R> {
+impl<'a, T: Clone + Send + 'static, R: Send + 'static, M: Threading>
+    Bench<'a, T, R, M>
+{
     /// Returns a builder for generating a plot of the benchmark results and
     /// saving it to a file.
     pub fn plot<P: AsRef<Path>>(
         &'a self,
         filename: P,
-    ) -> PlotBuilder<'a, T, R> {
+    ) -> PlotBuilder<'a, T, R, M> {
         PlotBuilder::new(self, filename)
     }
 }
 
 /// Builder for generating a plot of the benchmark results and saving it to a
 /// file.
-pub struct PlotBuilder<'a, T, R> {
-    bench: &'a Bench<'a, T, R>,
+pub struct PlotBuilder<'a, T, R, M: Threading = Shared> {
+    bench: &'a Bench<'a, T, R, M>,
     title: String,
+    title_style: TitleStyle,
     filename: PathBuf,
+    x_axis: XAxis,
+    axis_descriptions: bool,
+    y_axis_description: Option<String>,
+    dimensions: (u32, u32),
+    scale: f64,
+    margin: u32,
+    x_label_area_size: u32,
+    y_label_area_size: u32,
+    baselines: Vec<Baseline>,
+    normalize: Option<Complexity>,
+    x_log_base: f64,
+    y_log_base: f64,
+    power_of_two_labels: bool,
+    throughput: bool,
+    metric: Option<String>,
+    secondary_metric: Option<String>,
+    number_format: NumberFormat,
+    legend_position: LegendPosition,
+    rank_legend: bool,
+    styles: HashMap<String, SeriesStyle>,
+    palette: Vec<RGBColor>,
+    font_family: String,
+    background: RGBAColor,
+    grid_color: RGBAColor,
+    text_color: RGBColor,
+    series_metadata: bool,
+    embed_font: bool,
+    error_bands: bool,
+    smoothing: Option<Smoothing>,
+    change_points: Option<f64>,
+    hlines: Vec<(f64, String)>,
+    vlines: Vec<(usize, String)>,
+    cpu_cache_element_size: Option<usize>,
+    lower_panel: Option<LowerPanel>,
+    extra_outputs: Vec<PathBuf>,
+    cache: bool,
+
+    /// Number of measured input sizes whose timings are drawn, or `None` for
+    /// all of them.
+    visible_sizes: Option<usize>,
+}
+
+/// File format of a saved plot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum OutputFormat {
+    Svg,
+    Bitmap,
+}
+
+impl OutputFormat {
+    /// Returns the format of `path` by its extension, or `None` if it is not
+    /// supported.
+    pub(crate) fn of(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "svg" => Some(Self::Svg),
+            "png" | "jpg" | "jpeg" | "bmp" => Some(Self::Bitmap),
+            _ => None,
+        }
+    }
+}
+
+/// Horizontal positions of the benchmarked input sizes.
+#[derive(Debug, Clone)]
+enum XAxis {
+    /// Positions are the input sizes, on a logarithmic axis.
+    Sizes,
+
+    /// Positions are the given values, on a logarithmic axis if all of them
+    /// are positive and on a linear axis otherwise.
+    Values(Vec<f64>),
+
+    /// Positions are evenly spaced categories with the given tick labels.
+    Labels(Vec<String>),
 }
 
-impl<'a, T: Clone + Send + 'static, R: Send + 'static> PlotBuilder<'a, T, R> {
+impl<'a, T: Clone + Send + 'static, R: Send + 'static, M: Threading>
+    PlotBuilder<'a, T, R, M>
+{
     /// Creates a new `PlotBuilder` with required parameters.
     ///
     /// Mandatory parameters are required upfront and optional parameters are
@@ -63,179 +594,3335 @@ impl<'a, T: Clone + Send + 'static, R: Send + 'static> PlotBuilder<'a, T, R> {
     /// - `bench`: Reference to an instance of `Bench`.
     /// - `filename`: Path of the file to save the plot to.
     pub fn new<P: AsRef<Path>>(
-        bench: &'a Bench<'a, T, R>,
+        bench: &'a Bench<'a, T, R, M>,
         filename: P,
     ) -> Self {
         Self {
             bench,
             title: String::new(),
+            title_style: TitleStyle::default(),
             filename: filename.as_ref().to_path_buf(),
+            x_axis: XAxis::Sizes,
+            axis_descriptions: true,
+            y_axis_description: None,
+            dimensions: (800, 600),
+            scale: 1.0,
+            margin: 20,
+            x_label_area_size: 50,
+            y_label_area_size: 70,
+            baselines: Vec::new(),
+            normalize: None,
+            x_log_base: 10.0,
+            y_log_base: 10.0,
+            power_of_two_labels: false,
+            throughput: false,
+            metric: None,
+            secondary_metric: None,
+            number_format: NumberFormat::default(),
+            legend_position: LegendPosition::Inside,
+            rank_legend: false,
+            styles: HashMap::new(),
+            palette: DefaultTheme.palette(),
+            font_family: DefaultTheme.font_family(),
+            background: DefaultTheme.background(),
+            grid_color: DefaultTheme.grid(),
+            text_color: DefaultTheme.text(),
+            series_metadata: false,
+            embed_font: false,
+            error_bands: false,
+            smoothing: None,
+            change_points: None,
+            hlines: Vec::new(),
+            vlines: Vec::new(),
+            cpu_cache_element_size: None,
+            lower_panel: None,
+            extra_outputs: Vec::new(),
+            cache: false,
+            visible_sizes: None,
         }
     }
 
     /// Sets the title of the plot.
     ///
-    /// By default, the `title` is empty.
+    /// Lines longer than 50 characters are wrapped, and `\n` starts a new
+    /// line, e.g., to put a subtitle below the title.
+    ///
+    /// By default, the `title` is empty, in which case no space is reserved
+    /// for it.
     pub fn title(mut self, title: &str) -> Self {
         self.title = title.to_string();
         self
     }
 
-    /// Creates a plot of the benchmark results and saves it to a file.
-    pub fn build(self) -> Result<(), PlotBuilderError> {
-        self.create_plot_and_save()
+    /// Sets the color, font size, and alignment of the title.
+    ///
+    /// By default, the title is grey 24pt text, centered over the plot.
+    pub fn title_style(mut self, style: TitleStyle) -> Self {
+        self.title_style = style;
+        self
     }
 
-    fn create_plot_and_save(self) -> Result<(), PlotBuilderError> {
-        let root =
-            SVGBackend::new(&self.filename, (800, 600)).into_drawing_area();
-        root.fill(&RGBColor(255, 255, 255).mix(0.0))?;
-
-        let (min_timing, max_timing) = self
-            .bench
-            .data
-            .iter()
-            .flat_map(|(_, timings)| timings.iter().cloned())
-            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), timing| {
-                (min.min(timing), max.max(timing))
-            });
+    /// Sets the x-axis position of each input size, in the order of the sizes
+    /// passed to the `BenchBuilder`.
+    ///
+    /// The x-axis is logarithmic if all values are positive and linear
+    /// otherwise. Overrides any labels set with `x_labels`.
+    ///
+    /// By default, each input size is plotted at its own value on a
+    /// logarithmic axis.
+    pub fn x_values<I: IntoIterator<Item = f64>>(mut self, values: I) -> Self {
+        self.x_axis = XAxis::Values(values.into_iter().collect());
+        self
+    }
 
-        let mut chart = ChartBuilder::on(&root)
-            .caption(
-                textwrap::fill(&self.title, 50),
-                ("sans-serif", 24).into_font().color(&GREY.to_rgba()),
-            )
-            .margin(20)
-            .x_label_area_size(50)
-            .y_label_area_size(70)
-            .build_cartesian_2d(
-                (self.bench.sizes[0] as f64
-                    ..self.bench.sizes[self.bench.sizes.len() - 1] as f64)
-                    .log_scale(),
-                (min_timing..max_timing).log_scale(),
-            )?;
+    /// Sets a display label for each input size, in the order of the sizes
+    /// passed to the `BenchBuilder`, e.g., `"1K"`, `"1M"`, or dataset names.
+    ///
+    /// The input sizes are then plotted as evenly spaced categories on a linear
+    /// axis, with a tick labeled by each label. Overrides any values set with
+    /// `x_values`.
+    pub fn x_labels<I, S>(mut self, labels: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.x_axis =
+            XAxis::Labels(labels.into_iter().map(Into::into).collect());
+        self
+    }
 
-        chart
-            .configure_mesh()
-            .light_line_style(TRANSPARENT)
-            .x_desc("n")
-            .y_desc("Time (s)")
-            .x_labels(10)
-            .y_labels(10)
-            .x_label_formatter(&|v| {
-                format!("10{}", superscript(v.log10().round() as i32))
-            })
-            .y_label_formatter(&|v| {
-                format!("10{}", superscript(v.log10().round() as i32))
-            })
-            .axis_style(ShapeStyle {
-                color: GREY.mix(0.3).to_rgba(),
-                filled: true,
-                stroke_width: 1,
-            })
-            .x_label_style(
-                ("sans-serif", 24).into_font().color(&GREY.to_rgba()),
-            )
-            .y_label_style(
-                ("sans-serif", 24).into_font().color(&GREY.to_rgba()),
-            )
-            .draw()?;
+    /// Sets whether to draw the axis descriptions (`n` and `Time (s)`).
+    ///
+    /// By default, `axis_descriptions` is `true`.
+    pub fn axis_descriptions(mut self, axis_descriptions: bool) -> Self {
+        self.axis_descriptions = axis_descriptions;
+        self
+    }
 
-        for (i, &(_, name)) in self.bench.functions.iter().enumerate() {
-            let data_series: Vec<(f64, f64)> = self
-                .bench
-                .data
-                .iter()
-                .map(|(size, timings)| (*size as f64, timings[i]))
-                .collect();
+    /// Sets the description of the y-axis, e.g., `New time / old time` for a
+    /// plot of ratios of timings, instead of the one derived from what is
+    /// plotted, such as `Time (s)`. Has no effect if
+    /// [`axis_descriptions`](Self::axis_descriptions) is `false`, and does
+    /// not apply to the [`lower_panel`](Self::lower_panel).
+    ///
+    /// By default, the y-axis is described by what it shows.
+    pub fn y_axis_description(mut self, description: &str) -> Self {
+        self.y_axis_description = Some(description.to_string());
+        self
+    }
 
-            let style = ShapeStyle {
-                color: COLORS[i % COLORS.len()].into(),
-                filled: false,
-                stroke_width: 2,
-            };
+    /// Sets the base of the logarithmic x-axis, e.g., `2.0` for input sizes
+    /// that are powers of two.
+    ///
+    /// Ticks are placed at powers of the base. With base 10, they are labeled
+    /// as powers of ten (e.g., `10³`); with any other base, they are labeled
+    /// with their values (e.g., `1024`). Has no effect if the x-axis is
+    /// linear.
+    ///
+    /// By default, the base is 10.
+    pub fn x_log_base(mut self, base: f64) -> Self {
+        self.x_log_base = base;
+        self
+    }
 
-            chart
-                .draw_series(LineSeries::new(data_series, style))?
-                .label(name.to_string())
-                .legend(move |(x, y)| {
-                    PathElement::new(vec![(x, y), (x + 20, y)], style)
-                });
-        }
+    /// Sets whether to label the x-axis ticks as powers of two, e.g., `2¹⁰`
+    /// and `2²⁰`, instead of powers of ten.
+    ///
+    /// The x-axis then uses base 2, so that ticks fall on powers of two. All
+    /// input sizes must be powers of two. Has no effect if x-axis values or
+    /// labels are set.
+    ///
+    /// By default, `power_of_two_labels` is `false`.
+    pub fn power_of_two_labels(mut self, power_of_two_labels: bool) -> Self {
+        self.power_of_two_labels = power_of_two_labels;
+        self
+    }
 
-        chart
-            .configure_series_labels()
-            .background_style(RGBColor(255, 255, 255).mix(0.0))
-            .border_style(GREY.to_rgba())
-            .label_font(
-                ("sans-serif", 18)
-                    .into_font()
-                    .color(&RGBColor(128, 128, 128)),
-            )
-            .position(SeriesLabelPosition::UpperLeft)
-            .draw()?;
+    /// Sets the base of the logarithmic y-axis.
+    ///
+    /// Ticks are placed and labeled as for [`PlotBuilder::x_log_base`].
+    ///
+    /// By default, the base is 10.
+    pub fn y_log_base(mut self, base: f64) -> Self {
+        self.y_log_base = base;
+        self
+    }
 
-        root.present()?;
-        Ok(())
+    /// Sets whether to plot throughput, the number of operations per second
+    /// (`1 / t`), instead of time, so that higher is better.
+    ///
+    /// Combined with [`PlotBuilder::normalize`], the y-axis shows, e.g., the
+    /// number of elements processed per second (`n / t`). As throughput
+    /// usually decreases with the input size, the legend is placed in the
+    /// upper right corner instead of the upper left corner, and with
+    /// [`rank_legend`](Self::rank_legend), it lists the functions from the
+    /// highest throughput to the lowest.
+    ///
+    /// By default, `throughput` is `false`.
+    pub fn throughput(mut self, throughput: bool) -> Self {
+        self.throughput = throughput;
+        self
     }
-}
 
-#[cfg(test)]
-mod plot_tests {
-    use super::*;
-    use crate::{BenchBuilder, BenchFnArg, BenchFnNamed};
-    use std::fs;
-    use tempfile::{tempdir, TempDir};
+    /// Sets the name of a metric collected with
+    /// [`BenchBuilder::metric`](crate::BenchBuilder::metric) or
+    /// [`BenchBuilder::result_metric`](crate::BenchBuilder::result_metric)
+    /// to plot instead of the time, e.g., `"Allocations"`.
+    ///
+    /// The y-axis is described by the name of the metric, and values that
+    /// are not positive are not drawn, as the axis is logarithmic.
+    /// [`PlotBuilder::normalize`] still applies, but `throughput`, error
+    /// bands, noisy points, and baselines, which all describe timings, are
+    /// ignored. A lower panel still shows the timings.
+    ///
+    /// By default, the timings are plotted.
+    pub fn metric(mut self, name: &str) -> Self {
+        self.metric = Some(name.to_string());
+        self
+    }
 
-    fn setup_bench_data() -> Bench<'static, usize, usize> {
-        let functions: Vec<BenchFnNamed<'static, usize, usize>> = vec![
-            (Box::new(|x| x * 2), "Double"),
-            (Box::new(|x| x * x), "Square"),
-        ];
-        let argfunc: BenchFnArg<usize> = Box::new(|x| x);
-        let sizes = vec![10, 100, 1000];
-        let bench = BenchBuilder::new(functions, argfunc, sizes)
-            .build()
-            .unwrap();
-        bench
+    /// Sets the name of a metric to plot along with the plotted values, on a
+    /// second logarithmic y-axis at the right, e.g., the compression ratio
+    /// or the number of iterations to converge recorded with
+    /// [`BenchBuilder::result_metric`](crate::BenchBuilder::result_metric).
+    ///
+    /// The metric of each function is drawn as a dotted line in the color of
+    /// the function, labeled `<function> (<metric>)` in the legend. Values
+    /// that are not positive are not drawn, and the metric is not shown in
+    /// the lower panel.
+    ///
+    /// By default, no secondary metric is plotted.
+    pub fn secondary_metric(mut self, name: &str) -> Self {
+        self.secondary_metric = Some(name.to_string());
+        self
     }
 
-    fn get_temp_dir_and_file_path() -> (TempDir, PathBuf) {
-        let dir = tempdir().unwrap();
-        let file_path = dir.path().join("test_plot.svg");
-        assert!(!file_path.exists());
-        (dir, file_path)
+    /// Sets a complexity class to divide each timing by, so that the y-axis
+    /// shows, e.g., `t / (n log n)` instead of the raw time `t`.
+    ///
+    /// An implementation of the given complexity then shows up as a flat
+    /// line, making deviations from the expected growth and constant-factor
+    /// differences between implementations easy to see. See
+    /// [`Complexity::growth`] for how the growth function is evaluated.
+    ///
+    /// By default, raw timings are plotted.
+    pub fn normalize(mut self, complexity: Complexity) -> Self {
+        self.normalize = Some(complexity);
+        self
     }
 
-    #[test]
-    fn test_plot_file_creation() {
-        let (_dir, file_path) = get_temp_dir_and_file_path();
+    /// Sets how numbers in axis labels are written, e.g., with a decimal
+    /// comma and thousands separators for reports in German.
+    ///
+    /// Powers written with superscripts, such as `10³`, are not affected.
+    ///
+    /// By default, numbers are written with a decimal point and no thousands
+    /// separator.
+    pub fn number_format(mut self, format: NumberFormat) -> Self {
+        self.number_format = format;
+        self
+    }
 
-        let mut bench = setup_bench_data();
-        let plot_result =
-            bench.run().plot(&file_path).title("Benchmark Plot").build();
+    /// Sets where the legend is drawn.
+    ///
+    /// With [`LegendPosition::Right`] or [`LegendPosition::Bottom`], the chart
+    /// is shrunk to make room for the legend in a margin, so that the legend
+    /// of many functions does not cover the data. Building the plot fails
+    /// with [`PlotBuilderError::LegendTooLarge`] if the legend leaves too
+    /// little room for the chart.
+    ///
+    /// By default, the legend is drawn inside the chart, in the upper left
+    /// corner, or in the upper right corner in throughput mode.
+    pub fn legend_position(mut self, position: LegendPosition) -> Self {
+        self.legend_position = position;
+        self
+    }
 
-        assert!(plot_result.is_ok());
-        assert!(file_path.exists());
+    /// Sets whether to list the functions in the legend from best to worst,
+    /// by their plotted values at the largest input size at which all of
+    /// them were measured: from lowest to highest, or from highest to lowest
+    /// in [`throughput`](Self::throughput) mode, where higher is better.
+    ///
+    /// The colors of the functions do not change.
+    ///
+    /// By default, `rank_legend` is `false`, so the functions are listed in
+    /// the order in which they were given.
+    pub fn rank_legend(mut self, rank_legend: bool) -> Self {
+        self.rank_legend = rank_legend;
+        self
     }
 
-    #[test]
-    fn test_plot_with_title() {
-        let (_dir, file_path) = get_temp_dir_and_file_path();
+    /// Sets the style of the line of the function named `name`, e.g., to
+    /// emphasize one implementation over the others regardless of its
+    /// position in the list of functions.
+    ///
+    /// The legend entry of the function shows its dash pattern and marker.
+    ///
+    /// The color also applies to the function's overlaid baselines. Calling
+    /// this again for the same function replaces its style. Building the plot
+    /// fails with [`PlotBuilderError::UnknownFunction`] if neither the
+    /// benchmark nor an overlaid baseline has a function named `name`.
+    ///
+    /// By default, each function is drawn as a solid line without markers, 2
+    /// pixels wide, in a color given by its position.
+    pub fn style_for(mut self, name: &str, style: SeriesStyle) -> Self {
+        self.styles.insert(name.to_string(), style);
+        self
+    }
 
-        let mut bench = setup_bench_data();
-        let plot_result = bench
-            .run()
-            .plot(&file_path)
-            .title("Custom Title for Plot")
-            .build();
+    /// Sets the colors of the functions' lines, assigned by position in the
+    /// list of functions and reused from the start if there are more
+    /// functions than colors.
+    ///
+    /// Colors set with [`style_for`](Self::style_for) take precedence. An
+    /// empty palette restores the default one.
+    ///
+    /// By default, a palette of 11 colors chosen to be distinguishable on
+    /// light and dark backgrounds is used.
+    pub fn palette(mut self, colors: Vec<RGBColor>) -> Self {
+        self.palette = colors;
+        self
+    }
 
-        assert!(plot_result.is_ok());
+    /// Sets the font family of all text on the plot, e.g., `"Helvetica"`.
+    ///
+    /// By default, the font family is `"sans-serif"`.
+    pub fn font_family(mut self, family: &str) -> Self {
+        self.font_family = family.to_string();
+        self
+    }
+
+    /// Applies the fields that are set in `style`, e.g., a template of the
+    /// standard chart style of an organization loaded with
+    /// [`PlotStyle::load`], overriding the previous configuration.
+    ///
+    /// Setters called after this one override the style in turn.
+    pub fn style(mut self, style: &PlotStyle) -> Self {
+        if let Some(width) = style.width {
+            self.dimensions.0 = width;
+        }
+        if let Some(height) = style.height {
+            self.dimensions.1 = height;
+        }
+        if let Some(margin) = style.margin {
+            self.margin = margin;
+        }
+        if let Some(family) = &style.font_family {
+            self.font_family = family.clone();
+        }
+        if let Some(color) = style.title_color {
+            self.title_style.color = color;
+        }
+        if let Some(font_size) = style.title_font_size {
+            self.title_style.font_size = font_size;
+        }
+        if let Some(alignment) = style.title_alignment {
+            self.title_style.alignment = alignment;
+        }
+        if let Some(position) = style.legend_position {
+            self.legend_position = position;
+        }
+        if let Some(palette) = &style.palette {
+            self.palette = palette.clone();
+        }
+        self
+    }
+
+    /// Applies the background, palette, grid, text color, and font family of
+    /// `theme`, e.g., [`DarkTheme`] for slides, overriding the previous
+    /// configuration.
+    ///
+    /// The text color of the theme is also applied to the title. Setters
+    /// called after this one override the theme in turn.
+    ///
+    /// By default, the plot has the look of [`DefaultTheme`].
+    pub fn theme(mut self, theme: &dyn Theme) -> Self {
+        self.background = theme.background();
+        self.palette = theme.palette();
+        self.grid_color = theme.grid();
+        self.text_color = theme.text();
+        self.title_style.color = theme.text();
+        self.font_family = theme.font_family();
+        self
+    }
+
+    /// Sets the width and height of the plot in pixels.
+    ///
+    /// By default, the plot is 800 by 600 pixels.
+    pub fn dimensions(mut self, width: u32, height: u32) -> Self {
+        self.dimensions = (width, height);
+        self
+    }
+
+    /// Sets the factor by which everything drawn is scaled: the dimensions
+    /// of the canvas, the margins and label areas, the fonts, the widths of
+    /// the lines, and the sizes of the markers, dashes, and legend entries.
+    ///
+    /// This renders the same chart larger, e.g., at a factor of 3 for a
+    /// poster or a print, without retuning each size, which is what matters
+    /// for bitmaps. The dimensions and margins set on this builder, and the
+    /// font sizes and line widths of styles, are given at a factor of 1.
+    ///
+    /// By default, the factor is 1.
+    pub fn scale(mut self, factor: f64) -> Self {
+        self.scale = factor;
+        self
+    }
+
+    /// Sets the margin in pixels around the chart.
+    ///
+    /// By default, the margin is 20 pixels.
+    pub fn margin(mut self, margin: u32) -> Self {
+        self.margin = margin;
+        self
+    }
+
+    /// Sets the height in pixels of the area below the chart holding the
+    /// x-axis tick labels and description.
+    ///
+    /// By default, the area is 50 pixels high.
+    pub fn x_label_area_size(mut self, size: u32) -> Self {
+        self.x_label_area_size = size;
+        self
+    }
+
+    /// Sets the width in pixels of the area left of the chart holding the
+    /// y-axis tick labels and description, e.g., to make room for long tick
+    /// labels.
+    ///
+    /// By default, the area is 70 pixels wide.
+    pub fn y_label_area_size(mut self, size: u32) -> Self {
+        self.y_label_area_size = size;
+        self
+    }
+
+    /// Adds saved results to draw behind the current results, for before and
+    /// after comparisons.
+    ///
+    /// Each function of the baseline is drawn as a faded, dashed line in the
+    /// color of the current function of the same name if there is one. It is
+    /// labeled `<name> (<label>)` if the baseline has a label, such as a
+    /// release version, and `<name> (baseline)` otherwise. Only the input sizes
+    /// that are also benchmarked in the current run are drawn.
+    ///
+    /// May be called multiple times to compare several runs, e.g., the results
+    /// of successive releases.
+    ///
+    /// By default, no baseline is drawn.
+    pub fn overlay(mut self, baseline: &Baseline) -> Self {
+        self.baselines.push(baseline.clone());
+        self
+    }
+
+    /// Sets whether to embed a `<metadata>` block describing each plotted
+    /// series and its data points in the SVG file.
+    ///
+    /// Each series is written as a `<series>` element with a unique `id`, a
+    /// `class` of `benchplot-series` and either `current` or `baseline`, and
+    /// its function name, legend label, and color. Each of its data points is
+    /// a `<point>` element with the input size `n`, the timing in `seconds`,
+    /// the plotted `value`, and whether it was flagged as `noisy`.
+    ///
+    /// This only exports the plotted data, so that post-processing tools can
+    /// read it from the SVG file alone, e.g., to list the timings in a table.
+    /// The `id` and `class` are those of the metadata elements: the drawn
+    /// lines and markers carry neither, so the metadata cannot be used to
+    /// select or restyle them.
+    ///
+    /// By default, `series_metadata` is `false`.
+    pub fn series_metadata(mut self, series_metadata: bool) -> Self {
+        self.series_metadata = series_metadata;
+        self
+    }
+
+    /// Sets whether to embed the font of the text in SVG files, so that they
+    /// display the same on systems without it, e.g., when shared as design
+    /// assets.
+    ///
+    /// The font that the [font family](Self::font_family) resolves to on
+    /// this system, which is the font the text was measured with, is
+    /// embedded as a data URL, adding the size of the font file, typically a
+    /// few hundred kilobytes, to each SVG file. Building the plot fails with
+    /// [`PlotBuilderError::FontNotFound`] if no font can be found. Bitmaps
+    /// are unaffected, as their text is already rasterized.
+    ///
+    /// By default, `embed_font` is `false`.
+    pub fn embed_font(mut self, embed_font: bool) -> Self {
+        self.embed_font = embed_font;
+        self
+    }
+
+    /// Sets whether to shade the confidence interval of each series around
+    /// its line.
+    ///
+    /// Bands are only drawn for the pairs whose confidence intervals were
+    /// estimated, which requires
+    /// [`BenchBuilder::confidence_level`](crate::BenchBuilder::confidence_level)
+    /// to be set.
+    ///
+    /// By default, `error_bands` is `false`.
+    pub fn error_bands(mut self, error_bands: bool) -> Self {
+        self.error_bands = error_bands;
+        self
+    }
+
+    /// Sets how to smooth the curve of each function, e.g.,
+    /// `Smoothing::Loess(0.3)`, to tame the jitter of plots with densely
+    /// sampled input sizes.
+    ///
+    /// The smoothed curve is drawn in the style of the function and labeled
+    /// in the legend, alongside the raw curve, which is drawn as a thin,
+    /// faded line. Markers and noisy points stay on the raw values, and
+    /// baselines are not smoothed.
+    ///
+    /// By default, curves are not smoothed.
+    pub fn smoothing(mut self, smoothing: Smoothing) -> Self {
+        self.smoothing = Some(smoothing);
+        self
+    }
+
+    /// Sets the minimum change in slope of the change points to mark on the
+    /// plot, each with a dashed vertical line in the color of its series.
+    ///
+    /// See [`Bench::change_points`] for how change points are detected.
+    ///
+    /// By default, no change points are marked.
+    pub fn change_points(mut self, min_slope_change: f64) -> Self {
+        self.change_points = Some(min_slope_change);
+        self
+    }
+
+    /// Adds a dashed horizontal line at `value` with the given label, e.g., a
+    /// latency requirement of 10 ms, so that the plot shows at a glance
+    /// which functions and input sizes meet it.
+    ///
+    /// `value` is in the units of the y-axis, i.e., seconds, or the
+    /// normalized timing if [`normalize`](Self::normalize) is set, or
+    /// operations per second in [`throughput`](Self::throughput) mode. It
+    /// must be positive. Call this method repeatedly to add several lines.
+    ///
+    /// By default, no horizontal lines are drawn.
+    pub fn hline(mut self, value: f64, label: &str) -> Self {
+        self.hlines.push((value, label.to_string()));
+        self
+    }
+
+    /// Adds a dashed vertical line at the input size `n` with the given
+    /// label, e.g., to mark the size of a production dataset or a protocol
+    /// limit.
+    ///
+    /// `n` need not be one of the benchmarked sizes, in which case the
+    /// x-axis extends to it, unless the x-axis shows custom values or labels
+    /// instead of the input sizes (see [`x_values`](Self::x_values)), in
+    /// which case it must be. Call this method repeatedly to add several
+    /// lines.
+    ///
+    /// By default, no vertical lines are drawn.
+    pub fn vline(mut self, n: usize, label: &str) -> Self {
+        self.vlines.push((n, label.to_string()));
+        self
+    }
+
+    /// Marks the input sizes at which the working set outgrows each level of
+    /// the CPU cache, assuming that an input of size `n` occupies
+    /// `n * element_size` bytes, with a labeled dashed vertical line, e.g.,
+    /// `L2 (2 MiB)`.
+    ///
+    /// The knees often seen in these plots tend to line up with these
+    /// markers. Only markers within the range of the benchmarked sizes are
+    /// drawn. The caches are those of the machine creating the plot, which
+    /// are currently only detected on Linux.
+    ///
+    /// By default, no CPU cache markers are drawn.
+    pub fn cpu_cache_markers(mut self, element_size: usize) -> Self {
+        self.cpu_cache_element_size = Some(element_size);
+        self
+    }
+
+    /// Stacks a second panel showing the same results in another way, e.g.,
+    /// throughput below time, under the plot in the same file.
+    ///
+    /// The two panels split the height of the plot evenly and share the
+    /// x-axis, input sizes, styles, baselines, vertical lines, and legend
+    /// position, so that the same input size is at the same horizontal
+    /// position in both. The title and horizontal lines are only drawn on the
+    /// upper panel. Consider increasing the height with
+    /// [`dimensions`](Self::dimensions) to make room for both.
+    ///
+    /// By default, the plot has a single panel.
+    pub fn lower_panel(mut self, panel: LowerPanel) -> Self {
+        self.lower_panel = Some(panel);
+        self
+    }
+
+    /// Also saves the plot to `path` when it is built, e.g., a PNG image to
+    /// share next to the SVG file for a README, with identical content.
+    ///
+    /// The format is chosen by the extension of `path`: `svg`, or `png`,
+    /// `jpg`, `jpeg`, or `bmp` for a bitmap image with a white background.
+    /// Call this method repeatedly to save several copies. No copies of the
+    /// frames created by [`build_frames`](Self::build_frames) are saved.
+    ///
+    /// By default, the plot is only saved to the file given to
+    /// [`PlotBuilder::new`].
+    pub fn also_save<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.extra_outputs.push(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Sets whether to skip rendering the plot if the file already holds a
+    /// plot of the same results with the same configuration, e.g., so that
+    /// scripts generating reports can call `plot()` repeatedly at little
+    /// cost.
+    ///
+    /// A hash of the results and the configuration is embedded in the SVG
+    /// file as a comment and compared with that of the existing file, if
+    /// any.
+    ///
+    /// By default, `cache` is `false`, so the plot is always rendered.
+    pub fn cache(mut self, cache: bool) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// Creates a plot of the benchmark results and saves it to a file.
+    ///
+    /// Nothing is saved from a child process of an isolated benchmark, whose
+    /// results are those of a single pair, so that the plot of the parent is
+    /// not overwritten.
+    pub fn build(self) -> Result<(), PlotBuilderError> {
+        self.create_plot_and_save()
+    }
+
+    /// Creates a plot of the benchmark results and returns it as an SVG
+    /// document instead of saving it to a file, e.g., to write it to
+    /// standard output.
+    ///
+    /// The document is the one [`build`](Self::build) would save to an SVG
+    /// file, except that the file name of the builder is not used, and
+    /// [`also_save`](Self::also_save) and [`cache`](Self::cache) are ignored.
+    pub fn build_svg(self) -> Result<String, PlotBuilderError> {
+        self.validate()?;
+        let font_style = self.font_style()?;
+
+        let mut svg = String::new();
+        let root = SVGBackend::with_string(&mut svg, self.canvas())
+            .into_drawing_area();
+        root.fill(&self.background)?;
+        self.draw_on(&root)?;
+        root.present()?;
+        drop(root);
+
+        let mut embedded = String::new();
+        if let Some(style) = &font_style {
+            embedded.push_str(style);
+        }
+        if self.series_metadata {
+            embedded.push_str(&self.metadata());
+        }
+        if !embedded.is_empty() {
+            svg = insert_after_svg_tag(&svg, &embedded)
+                .expect("plotters renders an <svg> element");
+        }
+        Ok(svg)
+    }
+
+    /// Creates a sequence of plots, one per measured input size, in which
+    /// each plot adds the timings at the next input size to the previous
+    /// one, e.g., to show in a talk how the curves diverge as `n` grows.
+    ///
+    /// The axes of every frame span all of the results, so that the curves
+    /// stay in place from one frame to the next. The frames are saved next
+    /// to the plot file, with the file name of the plot followed by
+    /// `_frame_` and the 1-based, zero-padded frame number, e.g.,
+    /// `plot_frame_001.svg`. They can be assembled into an animation with
+    /// external tools. Building the frames fails with
+    /// [`PlotBuilderError::NoFileStem`] if the plot file has no name to
+    /// derive theirs from.
+    ///
+    /// Frames are only saved as SVG files. The options
+    /// [`series_metadata`](Self::series_metadata),
+    /// [`also_save`](Self::also_save), and [`cache`](Self::cache) only apply
+    /// to [`build`](Self::build) and are ignored here.
+    ///
+    /// Returns the paths of the frames, in order. As with
+    /// [`PlotBuilder::build`], nothing is saved from a child process of an
+    /// isolated benchmark, and no paths are returned.
+    pub fn build_frames(mut self) -> Result<Vec<PathBuf>, PlotBuilderError> {
+        if isolation::child_cell().is_some() {
+            return Ok(Vec::new());
+        }
+        self.validate()?;
+        let font_style = self.font_style()?;
+
+        let stem = self
+            .filename
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .ok_or_else(|| PlotBuilderError::NoFileStem {
+                path: self.filename.clone(),
+            })?;
+        let num_frames = self.bench.data.num_sizes();
+        let mut paths = Vec::with_capacity(num_frames);
+        for frame in 1..=num_frames {
+            let path = self
+                .filename
+                .with_file_name(format!("{}_frame_{:03}.svg", stem, frame));
+            self.visible_sizes = Some(frame);
+
+            let root =
+                SVGBackend::new(&path, self.canvas()).into_drawing_area();
+            root.fill(&self.background)?;
+            self.draw_on(&root)?;
+            present(&root, &path)?;
+            drop(root);
+            if let Some(style) = &font_style {
+                embed(&path, style)?;
+            }
+            paths.push(path);
+        }
+        Ok(paths)
+    }
+
+    /// Returns the x-axis position of each input size, in the order of
+    /// `self.bench.sizes`.
+    fn x_positions(&self) -> Result<Vec<f64>, PlotBuilderError> {
+        let sizes = &self.bench.sizes;
+        let positions: Vec<f64> = match &self.x_axis {
+            XAxis::Sizes => sizes.iter().map(|&size| size as f64).collect(),
+            XAxis::Values(values) => values.clone(),
+            XAxis::Labels(labels) => {
+                (0..labels.len()).map(|i| i as f64).collect()
+            }
+        };
+        if positions.len() != sizes.len() {
+            return Err(PlotBuilderError::XAxisLength {
+                expected: sizes.len(),
+                actual: positions.len(),
+            });
+        }
+        Ok(positions)
+    }
+
+    /// Checks that the benchmark results and configuration can be plotted.
+    pub(crate) fn validate(&self) -> Result<(), PlotBuilderError> {
+        let (width, height) = self.dimensions;
+        if width == 0 || height == 0 {
+            return Err(PlotBuilderError::InvalidDimensions { width, height });
+        }
+        if !(self.scale > 0.0 && self.scale.is_finite()) {
+            return Err(PlotBuilderError::InvalidScale(self.scale));
+        }
+        if let Some(smoothing) =
+            self.smoothing.filter(|smoothing| !smoothing.is_valid())
+        {
+            return Err(PlotBuilderError::InvalidSmoothing(smoothing));
+        }
+        for base in [self.x_log_base, self.y_log_base] {
+            if base.is_nan() || base <= 1.0 {
+                return Err(PlotBuilderError::InvalidLogBase(base));
+            }
+        }
+        if self.bench.sizes.is_empty() {
+            return Err(PlotBuilderError::NoSizes);
+        }
+        if self.bench.data.is_empty() {
+            return Err(PlotBuilderError::NoData);
+        }
+        if let Some(name) = self
+            .metric
+            .iter()
+            .chain(&self.secondary_metric)
+            .find(|name| self.bench.metric(name).is_none())
+        {
+            return Err(PlotBuilderError::UnknownMetric(name.clone()));
+        }
+        let functions = self.bench.function_names();
+        if let Some(name) = self
+            .styles
+            .keys()
+            .filter(|name| {
+                !functions.contains(&name.as_str())
+                    && !self
+                        .baselines
+                        .iter()
+                        .any(|baseline| baseline.functions().contains(name))
+            })
+            .min()
+        {
+            return Err(PlotBuilderError::UnknownFunction(name.clone()));
+        }
+        let drawn = self
+            .values()
+            .iter()
+            .any(|(_, values)| values.iter().any(|&v| self.is_drawn(v)));
+        let baseline_drawn = self.baseline_series().iter().any(|series| {
+            series.points.iter().any(|&(_, timing)| !timing.is_nan())
+        });
+        if !drawn && !baseline_drawn {
+            return Err(PlotBuilderError::NothingToDraw);
+        }
+        if self.power_of_two_labels && matches!(self.x_axis, XAxis::Sizes) {
+            if let Some(&size) =
+                self.bench.sizes.iter().find(|size| !size.is_power_of_two())
+            {
+                return Err(PlotBuilderError::NotPowerOfTwo(size));
+            }
+        }
+        if let Some(&(value, _)) = self
+            .hlines
+            .iter()
+            .find(|(value, _)| !value.is_finite() || *value <= 0.0)
+        {
+            return Err(PlotBuilderError::InvalidHline(value));
+        }
+        if self.cpu_cache_element_size == Some(0) {
+            return Err(PlotBuilderError::ZeroElementSize);
+        }
+        if let Some(path) = self
+            .extra_outputs
+            .iter()
+            .find(|path| OutputFormat::of(path).is_none())
+        {
+            return Err(PlotBuilderError::UnsupportedFormat {
+                path: path.clone(),
+            });
+        }
+        let positions = self.x_positions()?;
+        if let Some(&(n, _)) = self
+            .vlines
+            .iter()
+            .find(|&&(n, _)| self.vline_position(n, &positions).is_none())
+        {
+            return Err(PlotBuilderError::InvalidVline(n));
+        }
+        Ok(())
+    }
+
+    fn create_plot_and_save(self) -> Result<(), PlotBuilderError> {
+        if isolation::child_cell().is_some() {
+            return Ok(());
+        }
+        self.validate()?;
+        let font_style = self.font_style()?;
+
+        let cache_comment =
+            format!("<!-- benchplot-cache-key: {} -->\n", self.cache_key());
+        if self.cache
+            && std::fs::read_to_string(&self.filename)
+                .is_ok_and(|svg| svg.contains(&cache_comment))
+            && self.extra_outputs.iter().all(|path| path.exists())
+        {
+            return Ok(());
+        }
+
+        let root =
+            SVGBackend::new(&self.filename, self.canvas()).into_drawing_area();
+        root.fill(&self.background)?;
+        self.draw_on(&root)?;
+        present(&root, &self.filename)?;
+        drop(root);
+
+        let mut embedded = String::new();
+        if self.cache {
+            embedded.push_str(&cache_comment);
+        }
+        if let Some(style) = &font_style {
+            embedded.push_str(style);
+        }
+        if self.series_metadata {
+            embedded.push_str(&self.metadata());
+        }
+        if !embedded.is_empty() {
+            embed(&self.filename, &embedded)?;
+        }
+
+        for path in &self.extra_outputs {
+            self.save_copy(path, font_style.as_deref())?;
+        }
+        Ok(())
+    }
+
+    /// Draws the plot again and saves it to `path`, in the format of its
+    /// extension, embedding `font_style` in SVG files if any.
+    fn save_copy(
+        &self,
+        path: &Path,
+        font_style: Option<&str>,
+    ) -> Result<(), PlotBuilderError> {
+        match OutputFormat::of(path) {
+            Some(OutputFormat::Svg) => {
+                let root =
+                    SVGBackend::new(path, self.canvas()).into_drawing_area();
+                root.fill(&self.background)?;
+                self.draw_on(&root)?;
+                present(&root, path)?;
+                drop(root);
+                match font_style {
+                    Some(style) => embed(path, style),
+                    None => Ok(()),
+                }
+            }
+            Some(OutputFormat::Bitmap) => {
+                let root =
+                    BitMapBackend::new(path, self.canvas()).into_drawing_area();
+                root.fill(&RGBColor(255, 255, 255))?;
+                root.fill(&self.background)?;
+                self.draw_on(&root)?;
+                root.present()?;
+                Ok(())
+            }
+            None => Err(PlotBuilderError::UnsupportedFormat {
+                path: path.to_path_buf(),
+            }),
+        }
+    }
+
+    /// Returns the `<style>` element embedding the font of the plot, if
+    /// [`embed_font`](Self::embed_font) is set.
+    fn font_style(&self) -> Result<Option<String>, PlotBuilderError> {
+        if !self.embed_font {
+            return Ok(None);
+        }
+        font::font_face_style(&self.font_family)
+            .map(Some)
+            .ok_or_else(|| {
+                PlotBuilderError::FontNotFound(self.font_family.clone())
+            })
+    }
+
+    /// Draws the chart onto `root`, which may be the whole plot or one panel
+    /// of a larger figure, stacking the lower panel below it if any.
+    pub(crate) fn draw_on<DB>(
+        &self,
+        root: &DrawingArea<DB, Shift>,
+    ) -> Result<(), PlotBuilderError>
+    where
+        DB: DrawingBackend,
+        PlotBuilderError: From<DrawingAreaErrorKind<DB::ErrorType>>,
+    {
+        let Some(panel) = self.lower_panel else {
+            return self.draw_panel(root);
+        };
+        let (_, height) = root.dim_in_pixel();
+        let (upper, lower) = root.split_vertically(height / 2);
+        self.draw_panel(&upper)?;
+        self.lower_panel_plot(panel).draw_panel(&lower)
+    }
+
+    /// Returns a plot of the lower panel, sharing the configuration of the
+    /// x-axis and of the series with this plot.
+    fn lower_panel_plot(&self, panel: LowerPanel) -> PlotBuilder<'a, T, R, M> {
+        let (throughput, normalize) = match panel {
+            LowerPanel::Time => (false, None),
+            LowerPanel::Throughput => (true, None),
+            LowerPanel::Normalized(complexity) => (false, Some(complexity)),
+        };
+        PlotBuilder {
+            bench: self.bench,
+            title: String::new(),
+            title_style: self.title_style,
+            filename: self.filename.clone(),
+            x_axis: self.x_axis.clone(),
+            axis_descriptions: self.axis_descriptions,
+            y_axis_description: None,
+            dimensions: self.dimensions,
+            scale: self.scale,
+            margin: self.margin,
+            x_label_area_size: self.x_label_area_size,
+            y_label_area_size: self.y_label_area_size,
+            baselines: self.baselines.clone(),
+            normalize,
+            x_log_base: self.x_log_base,
+            y_log_base: self.y_log_base,
+            power_of_two_labels: self.power_of_two_labels,
+            throughput,
+            metric: None,
+            secondary_metric: None,
+            number_format: self.number_format,
+            legend_position: self.legend_position,
+            rank_legend: self.rank_legend,
+            styles: self.styles.clone(),
+            palette: self.palette.clone(),
+            font_family: self.font_family.clone(),
+            background: self.background,
+            grid_color: self.grid_color,
+            text_color: self.text_color,
+            series_metadata: false,
+            embed_font: false,
+            error_bands: self.error_bands,
+            smoothing: self.smoothing,
+            change_points: self.change_points,
+            hlines: Vec::new(),
+            vlines: self.vlines.clone(),
+            cpu_cache_element_size: self.cpu_cache_element_size,
+            lower_panel: None,
+            extra_outputs: Vec::new(),
+            cache: false,
+            visible_sizes: self.visible_sizes,
+        }
+    }
+
+    /// Draws the chart of a single panel onto `root`.
+    fn draw_panel<DB>(
+        &self,
+        root: &DrawingArea<DB, Shift>,
+    ) -> Result<(), PlotBuilderError>
+    where
+        DB: DrawingBackend,
+        PlotBuilderError: From<DrawingAreaErrorKind<DB::ErrorType>>,
+    {
+        let positions = self.x_positions()?;
+
+        let (min_timing, max_timing) = self
+            .values()
+            .iter()
+            .flat_map(|(size, timings)| {
+                timings
+                    .iter()
+                    .filter(|&&timing| self.is_drawn(timing))
+                    .map(move |&timing| self.y_value(size, timing))
+            })
+            .chain(self.baseline_series().into_iter().flat_map(|series| {
+                series
+                    .points
+                    .into_iter()
+                    .map(|(size, timing)| self.y_value(size, timing))
+            }))
+            .chain(self.band_bounds())
+            .chain(self.hlines.iter().map(|&(value, _)| value))
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), timing| {
+                (min.min(timing), max.max(timing))
+            });
+        let (min_x, max_x) = positions
+            .iter()
+            .copied()
+            .chain(
+                self.vlines
+                    .iter()
+                    .filter_map(|&(n, _)| self.vline_position(n, &positions)),
+            )
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), x| {
+                (min.min(x), max.max(x))
+            });
+        let y_spec = (min_timing..max_timing).log_scale().base(self.y_log_base);
+
+        match &self.x_axis {
+            XAxis::Labels(labels) => self.draw_chart(
+                root,
+                &positions,
+                -0.5..labels.len() as f64 - 0.5,
+                y_spec,
+                labels.len(),
+                &|v| category_label(labels, *v),
+            ),
+            XAxis::Values(_) if min_x <= 0.0 => self.draw_chart(
+                root,
+                &positions,
+                min_x..max_x,
+                y_spec,
+                10,
+                &|v| self.number_format.apply(&format!("{}", v)),
+            ),
+            XAxis::Sizes if self.power_of_two_labels => self.draw_chart(
+                root,
+                &positions,
+                (min_x..max_x).log_scale().base(2.0),
+                y_spec,
+                10,
+                &|v| power_of_two_label(*v),
+            ),
+            _ => self.draw_chart(
+                root,
+                &positions,
+                (min_x..max_x).log_scale().base(self.x_log_base),
+                y_spec,
+                10,
+                &|v| self.number_format.apply(&log_label(self.x_log_base, *v)),
+            ),
+        }
+    }
+
+    /// Draws the axes, the series, and the legend of the chart.
+    ///
+    /// `positions` holds the x-axis position of each input size, in the order
+    /// of `self.bench.sizes`.
+    fn draw_chart<DB, X, Y>(
+        &self,
+        root: &DrawingArea<DB, Shift>,
+        positions: &[f64],
+        x_spec: X,
+        y_spec: Y,
+        x_label_count: usize,
+        x_label_formatter: &dyn Fn(&f64) -> String,
+    ) -> Result<(), PlotBuilderError>
+    where
+        DB: DrawingBackend,
+        PlotBuilderError: From<DrawingAreaErrorKind<DB::ErrorType>>,
+        X: AsRangedCoord<Value = f64> + Clone,
+        Y: AsRangedCoord<Value = f64>,
+        X::CoordDescType: Ranged<ValueType = f64> + ValueFormatter<f64>,
+        Y::CoordDescType: Ranged<ValueType = f64> + ValueFormatter<f64>,
+    {
+        let legend_font =
+            (self.font_family.as_str(), self.px(LEGEND_FONT_SIZE))
+                .into_font()
+                .color(&RGBColor(128, 128, 128));
+        let legend_size = self.legend_size(root);
+        let root = &self.draw_title(root)?;
+
+        // An outside legend is drawn in an area split off the root, so that
+        // the chart shrinks to make room for it, as long as the margins, the
+        // label areas, and a minimal chart still fit.
+        let (width, height) = root.dim_in_pixel();
+        let minimum = 2 * self.px(self.margin) + self.px(MIN_CHART_SIZE);
+        let chart_size = |available: u32, legend: u32, needed: u32| {
+            available
+                .checked_sub(legend)
+                .filter(|&size| size >= needed)
+                .ok_or(PlotBuilderError::LegendTooLarge)
+        };
+        let (chart_area, legend_area) = match self.legend_position {
+            LegendPosition::Inside => (root.clone(), None),
+            LegendPosition::Right => {
+                let mut label_areas = self.px(self.y_label_area_size);
+                if self.secondary_metric.is_some() {
+                    label_areas *= 2;
+                }
+                let (chart_area, legend_area) =
+                    root.split_horizontally(chart_size(
+                        width,
+                        legend_size.0 + self.px(40),
+                        minimum + label_areas,
+                    )?);
+                (chart_area, Some(legend_area))
+            }
+            LegendPosition::Bottom => {
+                let (chart_area, legend_area) =
+                    root.split_vertically(chart_size(
+                        height,
+                        legend_size.1 + self.px(30),
+                        minimum + self.px(self.x_label_area_size),
+                    )?);
+                (chart_area, Some(legend_area))
+            }
+        };
+
+        let mut builder = ChartBuilder::on(&chart_area);
+        builder
+            .margin(self.px(self.margin))
+            .x_label_area_size(self.px(self.x_label_area_size))
+            .y_label_area_size(self.px(self.y_label_area_size));
+        if self.secondary_metric.is_some() {
+            builder.right_y_label_area_size(self.px(self.y_label_area_size));
+        }
+        let (min_secondary, max_secondary) = self.secondary_range();
+        let mut chart = builder
+            .build_cartesian_2d(x_spec.clone(), y_spec)?
+            .set_secondary_coord(
+                x_spec,
+                (min_secondary..max_secondary)
+                    .log_scale()
+                    .base(self.y_log_base),
+            );
+
+        let mut mesh = chart.configure_mesh();
+        if self.axis_descriptions {
+            mesh.x_desc("n").y_desc(self.y_description());
+        }
+        mesh.light_line_style(TRANSPARENT)
+            .x_labels(x_label_count)
+            .y_labels(10)
+            .x_label_formatter(x_label_formatter)
+            .y_label_formatter(&|v| {
+                self.number_format.apply(&log_label(self.y_log_base, *v))
+            })
+            .axis_style(ShapeStyle {
+                color: self.text_color.mix(0.3),
+                filled: true,
+                stroke_width: self.px(1),
+            })
+            .bold_line_style(ShapeStyle {
+                color: self.grid_color,
+                filled: false,
+                stroke_width: self.px(1),
+            })
+            .x_label_style(
+                (self.font_family.as_str(), self.px(TICK_FONT_SIZE))
+                    .into_font()
+                    .color(&self.text_color),
+            )
+            .y_label_style(
+                (self.font_family.as_str(), self.px(TICK_FONT_SIZE))
+                    .into_font()
+                    .color(&self.text_color),
+            )
+            .draw()?;
+        if let Some(name) = &self.secondary_metric {
+            let mut secondary_axes = chart.configure_secondary_axes();
+            if self.axis_descriptions {
+                secondary_axes.y_desc(name.as_str());
+            }
+            secondary_axes
+                .y_labels(10)
+                .y_label_formatter(&|v| {
+                    self.number_format.apply(&log_label(self.y_log_base, *v))
+                })
+                .axis_style(ShapeStyle {
+                    color: self.text_color.mix(0.3),
+                    filled: true,
+                    stroke_width: self.px(1),
+                })
+                .label_style(
+                    (self.font_family.as_str(), self.px(TICK_FONT_SIZE))
+                        .into_font()
+                        .color(&self.text_color),
+                )
+                .draw()?;
+        }
+
+        let x_of =
+            |size: usize| -> f64 {
+                let size_idx =
+                    self.bench.sizes.iter().position(|&s| s == size).expect(
+                        "measured size is one of the benchmarked sizes",
+                    );
+                positions[size_idx]
+            };
+
+        let mut legend_entries: Vec<(String, LegendGlyph)> = Vec::new();
+
+        for series in self.baseline_series() {
+            let style = ShapeStyle {
+                color: series.color.mix(0.4),
+                filled: false,
+                stroke_width: self.px(2),
+            };
+            let dash_size = self.px(series.dash_size);
+            let points: Vec<(f64, f64)> = series
+                .points
+                .into_iter()
+                .map(|(size, timing)| (x_of(size), self.y_value(size, timing)))
+                .collect();
+
+            let glyph = LegendGlyph {
+                style,
+                dash: Some((dash_size, self.px(4))),
+                marker: None,
+                scale: self.scale,
+            };
+            chart
+                .draw_series(DashedLineSeries::new(
+                    points,
+                    dash_size,
+                    self.px(4),
+                    style,
+                ))?
+                .label(series.label.clone())
+                .legend(move |coord| glyph.at(coord));
+            legend_entries.push((series.label, glyph));
+        }
+
+        for i in self.legend_order() {
+            let name = self.bench.functions[i].1;
+            let data_series: Vec<(f64, f64)> = self
+                .values()
+                .iter()
+                .take(self.visible_sizes.unwrap_or(usize::MAX))
+                .filter(|(_, timings)| self.is_drawn(timings[i]))
+                .map(|(size, timings)| {
+                    (x_of(size), self.y_value(size, timings[i]))
+                })
+                .collect();
+
+            let series_style =
+                self.styles.get(name).copied().unwrap_or_default();
+            let style = ShapeStyle {
+                color: self.series_color(name, i).into(),
+                filled: false,
+                stroke_width: self.px(series_style.width),
+            };
+            let dash = series_style
+                .dash
+                .map(|(dash, gap)| (self.px(dash), self.px(gap)));
+
+            if self.error_bands && self.metric.is_none() {
+                let band = self.error_band(i, &x_of);
+                if band.len() > 2 {
+                    chart.draw_series(std::iter::once(Polygon::new(
+                        band,
+                        style.color.mix(0.2).filled(),
+                    )))?;
+                }
+            }
+
+            let label = self.series_label(name);
+
+            let line = match self.smoothing {
+                Some(smoothing) => {
+                    chart.draw_series(LineSeries::new(
+                        data_series.clone(),
+                        ShapeStyle {
+                            color: style.color.mix(0.3),
+                            filled: false,
+                            stroke_width: self.px(1),
+                        },
+                    ))?;
+                    smoothing.smooth(&data_series)
+                }
+                None => data_series.clone(),
+            };
+            let glyph = LegendGlyph {
+                style,
+                dash,
+                marker: series_style.marker,
+                scale: self.scale,
+            };
+            let series = match dash {
+                Some((dash, gap)) => chart.draw_series(
+                    DashedLineSeries::new(line, dash, gap, style),
+                )?,
+                None => chart.draw_series(LineSeries::new(line, style))?,
+            };
+            series
+                .label(label.clone())
+                .legend(move |coord| glyph.at(coord));
+            legend_entries.push((label, glyph));
+
+            if let Some(marker) = series_style.marker {
+                chart.draw_series(data_series.into_iter().map(|center| {
+                    MarkerElement {
+                        center,
+                        marker,
+                        style,
+                        scale: self.scale,
+                    }
+                }))?;
+            }
+
+            if let Some(metric) = &self.secondary_metric {
+                let points: Vec<(f64, f64)> = self
+                    .secondary_values(metric)
+                    .iter()
+                    .take(self.visible_sizes.unwrap_or(usize::MAX))
+                    .filter(|(_, values)| values[i] > 0.0)
+                    .map(|(size, values)| (x_of(size), values[i]))
+                    .collect();
+                let glyph = LegendGlyph {
+                    style,
+                    dash: Some((self.px(2), self.px(4))),
+                    marker: None,
+                    scale: self.scale,
+                };
+                let label = format!("{} ({})", self.series_label(name), metric);
+                chart
+                    .draw_secondary_series(DashedLineSeries::new(
+                        points,
+                        self.px(2),
+                        self.px(4),
+                        style,
+                    ))?
+                    .label(label.clone())
+                    .legend(move |coord| glyph.at(coord));
+                legend_entries.push((label, glyph));
+            }
+
+            // Circle the points whose timings were flagged as noisy.
+            let noisy_points = self
+                .bench
+                .noisy
+                .iter()
+                .filter(|_| self.metric.is_none())
+                .filter(|&&(_, func_idx)| func_idx == i)
+                .filter_map(|&(noisy_size, _)| {
+                    self.bench
+                        .data
+                        .iter()
+                        .find(|&(size, _)| size == noisy_size)
+                        .map(|(size, timings)| {
+                            (x_of(size), self.y_value(size, timings[i]))
+                        })
+                });
+            chart.draw_series(
+                noisy_points.map(|point| Circle::new(point, self.px(6), style)),
+            )?;
+        }
+
+        if let Some(min_slope_change) = self.change_points {
+            let y_range = chart.y_range();
+            let visible: Vec<usize> = self
+                .bench
+                .data
+                .iter()
+                .take(self.visible_sizes.unwrap_or(usize::MAX))
+                .map(|(size, _)| size)
+                .collect();
+            for change_point in self.bench.change_points(min_slope_change) {
+                if !visible.contains(&change_point.size) {
+                    continue;
+                }
+                let (_, name) = self.bench.functions[change_point.function];
+                let style = ShapeStyle {
+                    color: self
+                        .series_color(name, change_point.function)
+                        .mix(0.6),
+                    filled: false,
+                    stroke_width: self.px(1),
+                };
+                let x = x_of(change_point.size);
+                chart.draw_series(DashedLineSeries::new(
+                    [(x, y_range.start), (x, y_range.end)],
+                    self.px(6),
+                    self.px(4),
+                    style,
+                ))?;
+            }
+        }
+
+        let x_range = chart.x_range();
+        for (value, label) in &self.hlines {
+            chart.draw_series(DashedLineSeries::new(
+                [(x_range.start, *value), (x_range.end, *value)],
+                self.px(6),
+                self.px(4),
+                ShapeStyle {
+                    color: RGBColor(128, 128, 128).to_rgba(),
+                    filled: false,
+                    stroke_width: self.px(1),
+                },
+            ))?;
+            chart.draw_series(std::iter::once(Text::new(
+                label.clone(),
+                (x_range.end, *value),
+                (self.font_family.as_str(), self.px(LEGEND_FONT_SIZE))
+                    .into_font()
+                    .color(&RGBColor(128, 128, 128))
+                    .pos(Pos::new(HPos::Right, VPos::Bottom)),
+            )))?;
+        }
+        let y_range = chart.y_range();
+        for (n, label) in self.vlines.iter().chain(&self.cache_marker_lines()) {
+            let Some(x) = self.vline_position(*n, positions) else {
+                continue;
+            };
+            chart.draw_series(DashedLineSeries::new(
+                [(x, y_range.start), (x, y_range.end)],
+                self.px(6),
+                self.px(4),
+                ShapeStyle {
+                    color: RGBColor(128, 128, 128).to_rgba(),
+                    filled: false,
+                    stroke_width: self.px(1),
+                },
+            ))?;
+            chart.draw_series(std::iter::once(Text::new(
+                label.clone(),
+                (x, y_range.end),
+                (self.font_family.as_str(), self.px(LEGEND_FONT_SIZE))
+                    .into_font()
+                    .color(&RGBColor(128, 128, 128))
+                    .pos(Pos::new(HPos::Left, VPos::Top)),
+            )))?;
+        }
+
+        match legend_area {
+            Some(legend_area) => draw_legend(
+                &legend_area,
+                &legend_entries,
+                legend_size,
+                &legend_font,
+                self.scale,
+            )?,
+            None => {
+                chart
+                    .configure_series_labels()
+                    .background_style(RGBColor(255, 255, 255).mix(0.0))
+                    .border_style(GREY.to_rgba())
+                    .label_font(legend_font)
+                    .legend_area_size(self.px(LEGEND_GLYPH_LENGTH + 10))
+                    .position(if self.plots_throughput() {
+                        SeriesLabelPosition::UpperRight
+                    } else {
+                        SeriesLabelPosition::UpperLeft
+                    })
+                    .draw()?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A function of an overlaid baseline, ready to be drawn.
+struct BaselineSeries {
+    /// Name of the function.
+    function: String,
+    /// Color of the line, before fading.
+    color: RGBColor,
+    /// Legend label, e.g., `Merge Sort (v0.3)`.
+    label: String,
+    /// Length of the dashes, distinguishing the baselines from one another.
+    dash_size: u32,
+    /// The `(input size, timing)` points.
+    points: Vec<(usize, f64)>,
+}
+
+impl<T, R, M: Threading> PlotBuilder<'_, T, R, M> {
+    /// Returns the y-axis value of a timing measured at the input size `size`,
+    /// normalized by the configured complexity class, if any, and inverted in
+    /// throughput mode.
+    fn y_value(&self, size: usize, timing: f64) -> f64 {
+        let normalized = match self.normalize {
+            Some(complexity) => timing / complexity.growth(size),
+            None => timing,
+        };
+        if self.plots_throughput() {
+            1.0 / normalized
+        } else {
+            normalized
+        }
+    }
+
+    /// Returns the plotted values: the values of the metric set with
+    /// [`PlotBuilder::metric`], or the timings.
+    fn values(&self) -> &BenchData {
+        self.metric
+            .as_deref()
+            .and_then(|name| self.bench.metric(name))
+            .unwrap_or(&self.bench.data)
+    }
+
+    /// Returns the indices of the functions in the order in which they are
+    /// listed in the legend, which is also the order in which they are drawn.
+    fn legend_order(&self) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.bench.functions.len()).collect();
+        if !self.rank_legend {
+            return order;
+        }
+        let Some((size, values)) = self
+            .values()
+            .iter()
+            .filter(|(_, values)| values.iter().all(|&v| self.is_drawn(v)))
+            .last()
+        else {
+            return order;
+        };
+        let plotted: Vec<f64> =
+            values.iter().map(|&v| self.y_value(size, v)).collect();
+        order.sort_by(|&a, &b| {
+            let ordering = plotted[a].total_cmp(&plotted[b]);
+            // Higher throughput is better.
+            if self.plots_throughput() {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
+        order
+    }
+
+    /// Returns whether a plotted value is drawn: missing values are not, and
+    /// neither are the values of metrics that are not positive.
+    fn is_drawn(&self, value: f64) -> bool {
+        if self.metric.is_some() {
+            value > 0.0
+        } else {
+            !value.is_nan()
+        }
+    }
+
+    /// Returns the values of the secondary metric `name`, which validation
+    /// ensures was collected.
+    fn secondary_values(&self, name: &str) -> &BenchData {
+        self.bench
+            .metric(name)
+            .expect("secondary metric is validated")
+    }
+
+    /// Returns the range of the second y-axis: that of the positive values of
+    /// the secondary metric, if any, and an arbitrary range otherwise.
+    fn secondary_range(&self) -> (f64, f64) {
+        let (min, max) = self
+            .secondary_metric
+            .iter()
+            .flat_map(|name| self.secondary_values(name).iter())
+            .flat_map(|(_, values)| values.iter().copied())
+            .filter(|&value| value > 0.0)
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), value| {
+                (min.min(value), max.max(value))
+            });
+        if min.is_finite() {
+            (min, max)
+        } else {
+            (1.0, 10.0)
+        }
+    }
+
+    /// Returns whether throughput is plotted, which it is not for metrics.
+    fn plots_throughput(&self) -> bool {
+        self.throughput && self.metric.is_none()
+    }
+
+    /// Returns the x-axis position of a vertical line at the input size
+    /// `size`, given the positions of the benchmarked sizes, or `None` if it
+    /// cannot be placed.
+    fn vline_position(&self, size: usize, positions: &[f64]) -> Option<f64> {
+        match self.x_axis {
+            XAxis::Sizes => (size > 0).then_some(size as f64),
+            XAxis::Values(_) | XAxis::Labels(_) => self
+                .bench
+                .sizes
+                .iter()
+                .position(|&s| s == size)
+                .map(|size_idx| positions[size_idx]),
+        }
+    }
+
+    /// Returns the input sizes and labels of the CPU cache markers within the
+    /// range of the benchmarked sizes.
+    fn cache_marker_lines(&self) -> Vec<(usize, String)> {
+        let Some(element_size) = self.cpu_cache_element_size else {
+            return Vec::new();
+        };
+        let min_size = self.bench.sizes.iter().copied().min().unwrap_or(0);
+        let max_size = self.bench.sizes.iter().copied().max().unwrap_or(0);
+        cpu_caches()
+            .into_iter()
+            .map(|cache| (cache.size / element_size.max(1), cache.label()))
+            .filter(|&(n, _)| (min_size..=max_size).contains(&n))
+            .collect()
+    }
+
+    /// Returns the y-axis values of the bounds of the drawn error bands, so
+    /// that the axis spans them.
+    fn band_bounds(&self) -> impl Iterator<Item = f64> + '_ {
+        self.bench
+            .confidence_intervals
+            .iter()
+            .filter(|_| self.error_bands && self.metric.is_none())
+            .flat_map(move |&(size, _, interval)| {
+                [interval.lower, interval.upper]
+                    .map(|bound| self.y_value(size, bound))
+            })
+    }
+
+    /// Returns the outline of the error band of the function at `func_idx`,
+    /// running along the upper bounds of its confidence intervals and back
+    /// along the lower bounds.
+    fn error_band(
+        &self,
+        func_idx: usize,
+        x_of: &dyn Fn(usize) -> f64,
+    ) -> Vec<(f64, f64)> {
+        let bounds: Vec<(f64, f64, f64)> = self
+            .bench
+            .data
+            .iter()
+            .take(self.visible_sizes.unwrap_or(usize::MAX))
+            .filter_map(|(size, _)| {
+                let interval =
+                    self.bench.confidence_interval(size, func_idx)?;
+                Some((
+                    x_of(size),
+                    self.y_value(size, interval.lower),
+                    self.y_value(size, interval.upper),
+                ))
+            })
+            .collect();
+        let upper = bounds.iter().map(|&(x, _, upper)| (x, upper));
+        let lower = bounds.iter().rev().map(|&(x, lower, _)| (x, lower));
+        upper.chain(lower).collect()
+    }
+
+    /// Returns `pixels` multiplied by the scale factor of the plot.
+    fn px(&self, pixels: u32) -> u32 {
+        scaled(pixels, self.scale)
+    }
+
+    /// Returns the dimensions of the image in pixels, i.e., the dimensions of
+    /// the plot multiplied by its scale factor.
+    fn canvas(&self) -> (u32, u32) {
+        (self.px(self.dimensions.0), self.px(self.dimensions.1))
+    }
+
+    /// Returns the description of the y-axis, e.g., `Time / n (s)` or
+    /// `n / s`.
+    fn y_description(&self) -> String {
+        if let Some(description) = &self.y_axis_description {
+            return description.clone();
+        }
+        if let Some(name) = &self.metric {
+            return match self.normalize {
+                Some(complexity) => {
+                    format!("{} / {}", name, complexity.notation())
+                }
+                None => name.clone(),
+            };
+        }
+        match (self.throughput, self.normalize) {
+            (false, Some(complexity)) => {
+                format!("Time / {} (s)", complexity.notation())
+            }
+            (false, None) => "Time (s)".to_string(),
+            (true, Some(complexity)) => {
+                format!("{} / s", complexity.notation())
+            }
+            (true, None) => "Operations / s".to_string(),
+        }
+    }
+
+    /// Draws the title at the top of `root`, if it is not empty, and returns
+    /// the area below it.
+    fn draw_title<DB>(
+        &self,
+        root: &DrawingArea<DB, Shift>,
+    ) -> Result<DrawingArea<DB, Shift>, PlotBuilderError>
+    where
+        DB: DrawingBackend,
+        PlotBuilderError: From<DrawingAreaErrorKind<DB::ErrorType>>,
+    {
+        if self.title.is_empty() {
+            return Ok(root.clone());
+        }
+
+        let style = &self.title_style;
+        let font = (self.font_family.as_str(), self.px(style.font_size))
+            .into_font()
+            .color(&style.color);
+        let lines: Vec<String> = self
+            .title
+            .lines()
+            .flat_map(|line| textwrap::wrap(line, 50))
+            .map(|line| line.into_owned())
+            .collect();
+        let mut line_height = 0;
+        for line in &lines {
+            line_height =
+                line_height.max(root.estimate_text_size(line, &font)?.1);
+        }
+        let text_height = line_height * lines.len() as u32;
+        let padding = (line_height / 2).min(5);
+
+        let (width, _) = root.dim_in_pixel();
+        let margin = self.px(self.margin) as i32;
+        let (x, hpos) = match style.alignment {
+            TitleAlignment::Left => (margin, HPos::Left),
+            TitleAlignment::Center => (width as i32 / 2, HPos::Center),
+            TitleAlignment::Right => (width as i32 - margin, HPos::Right),
+        };
+        for (idx, line) in lines.into_iter().enumerate() {
+            let y = padding + idx as u32 * line_height;
+            root.draw(&Text::new(
+                line,
+                (x, y as i32),
+                font.pos(Pos::new(hpos, VPos::Top)),
+            ))?;
+        }
+
+        Ok(root.split_vertically(text_height + padding * 2).1)
+    }
+
+    /// Returns an estimate of the width and height in pixels of the legend, as
+    /// drawn on `root`.
+    fn legend_size<DB: DrawingBackend>(
+        &self,
+        root: &DrawingArea<DB, Shift>,
+    ) -> (u32, u32) {
+        let font = TextStyle::from(
+            (self.font_family.as_str(), self.px(LEGEND_FONT_SIZE)).into_font(),
+        );
+        let labels: Vec<String> = self
+            .baseline_series()
+            .into_iter()
+            .map(|series| series.label)
+            .chain(
+                self.bench
+                    .function_names()
+                    .into_iter()
+                    .map(|name| self.series_label(name)),
+            )
+            .chain(self.secondary_metric.iter().flat_map(|metric| {
+                self.bench.function_names().into_iter().map(move |name| {
+                    format!("{} ({})", self.series_label(name), metric)
+                })
+            }))
+            .collect();
+        let label_width = labels
+            .iter()
+            .filter_map(|label| root.estimate_text_size(label, &font).ok())
+            .map(|(width, _)| width)
+            .max()
+            .unwrap_or(0);
+        // The legend glyph and margins around the labels.
+        (
+            label_width + self.px(LEGEND_GLYPH_LENGTH + 30),
+            labels.len() as u32 * self.px(LEGEND_ENTRY_HEIGHT) + self.px(10),
+        )
+    }
+
+    /// Returns the color of the series of the function `name`, which is the
+    /// color of its style override if any, and is otherwise given by its
+    /// position `color_idx`.
+    fn series_color(&self, name: &str, color_idx: usize) -> RGBColor {
+        self.styles
+            .get(name)
+            .and_then(|style| style.color)
+            .map(|(r, g, b)| RGBColor(r, g, b))
+            .unwrap_or_else(|| {
+                let palette = if self.palette.is_empty() {
+                    COLORS
+                } else {
+                    &self.palette
+                };
+                palette[color_idx % palette.len()]
+            })
+    }
+
+    /// Returns the legend label of the current series of the function `name`,
+    /// e.g., `Merge Sort (v0.4)` if the run is labeled.
+    fn series_label(&self, name: &str) -> String {
+        match &self.bench.label {
+            Some(run_label) => format!("{} ({})", name, run_label),
+            None => name.to_string(),
+        }
+    }
+
+    /// Returns a `<metadata>` element describing every plotted series and its
+    /// data points, as documented on [`PlotBuilder::series_metadata`].
+    fn metadata(&self) -> String {
+        let mut metadata = String::from("<metadata id=\"benchplot-series\">\n");
+
+        for (i, name) in self.bench.function_names().into_iter().enumerate() {
+            metadata.push_str(&series_tag(
+                &format!("series-{}", i),
+                "current",
+                name,
+                &self.series_label(name),
+                self.series_color(name, i),
+            ));
+            for (size, timings) in self.values().iter() {
+                if !self.is_drawn(timings[i]) {
+                    continue;
+                }
+                let noisy = self.bench.noisy.contains(&(size, i));
+                metadata.push_str(&self.point_tag(size, timings[i], noisy));
+            }
+            metadata.push_str("</series>\n");
+        }
+
+        for (i, series) in self.baseline_series().into_iter().enumerate() {
+            metadata.push_str(&series_tag(
+                &format!("baseline-{}", i),
+                "baseline",
+                &series.function,
+                &series.label,
+                series.color,
+            ));
+            for (size, timing) in series.points {
+                metadata.push_str(&self.point_tag(size, timing, false));
+            }
+            metadata.push_str("</series>\n");
+        }
+
+        metadata.push_str("</metadata>\n");
+        metadata
+    }
+
+    /// Returns a hash of the plotted results and of everything affecting how
+    /// they are drawn, as a hexadecimal string.
+    ///
+    /// The version of this crate is included, since it affects the rendering
+    /// too.
+    fn cache_key(&self) -> String {
+        let mut styles: Vec<_> = self.styles.iter().collect();
+        styles.sort_by(|a, b| a.0.cmp(b.0));
+        let state = format!(
+            "{:?}",
+            (
+                (
+                    env!("CARGO_PKG_VERSION"),
+                    self.bench.function_names(),
+                    self.bench.label(),
+                    self.values(),
+                    &self.metric,
+                    self.secondary_metric
+                        .as_ref()
+                        .map(|name| (name, self.bench.metric(name))),
+                    &self.bench.noisy,
+                    &self.bench.confidence_intervals,
+                    &self.baselines,
+                ),
+                (
+                    &self.title,
+                    self.title_style,
+                    &self.x_axis,
+                    self.axis_descriptions,
+                    &self.y_axis_description,
+                    self.dimensions,
+                    self.margin,
+                    self.x_label_area_size,
+                    self.y_label_area_size,
+                    self.scale,
+                ),
+                (
+                    self.normalize,
+                    self.x_log_base,
+                    self.y_log_base,
+                    self.power_of_two_labels,
+                    self.throughput,
+                    self.number_format,
+                    self.legend_position,
+                    styles,
+                    self.series_metadata,
+                    self.embed_font,
+                    self.error_bands,
+                    self.smoothing,
+                ),
+                (
+                    self.change_points,
+                    self.rank_legend,
+                    &self.hlines,
+                    &self.vlines,
+                    self.cache_marker_lines(),
+                    self.lower_panel,
+                    &self.extra_outputs,
+                    &self.palette,
+                    &self.font_family,
+                    (self.background, self.grid_color, self.text_color),
+                ),
+            )
+        );
+        format!("{:016x}", util::fnv1a(state.as_bytes()))
+    }
+
+    /// Returns a `<point>` element describing the timing of a series at the
+    /// input size `size`.
+    fn point_tag(&self, size: usize, timing: f64, noisy: bool) -> String {
+        format!(
+            "<point n=\"{}\" seconds=\"{}\" value=\"{}\" noisy=\"{}\"/>\n",
+            size,
+            timing,
+            self.y_value(size, timing),
+            noisy
+        )
+    }
+
+    /// Returns the series of every function of every baseline to overlay.
+    ///
+    /// Points at input sizes that are not benchmarked in the current run are
+    /// omitted.
+    fn baseline_series(&self) -> Vec<BaselineSeries> {
+        let names = self.bench.function_names();
+        let mut series = Vec::new();
+        if self.metric.is_some() {
+            return series;
+        }
+
+        for (baseline_idx, baseline) in self.baselines.iter().enumerate() {
+            let run_label = baseline.label().unwrap_or("baseline");
+            for (i, name) in baseline.functions().iter().enumerate() {
+                let color_idx = names
+                    .iter()
+                    .position(|current| current == name)
+                    .unwrap_or(names.len() + i);
+                let points = baseline
+                    .data()
+                    .iter()
+                    .filter(|(size, _)| self.bench.sizes.contains(size))
+                    .map(|(size, timings)| (size, timings[i]))
+                    .collect();
+                series.push(BaselineSeries {
+                    function: name.clone(),
+                    color: self.series_color(name, color_idx),
+                    label: format!("{} ({})", name, run_label),
+                    dash_size: 6 + 4 * baseline_idx as u32,
+                    points,
+                });
+            }
+        }
+        series
+    }
+}
+
+/// Writes the plot drawn on `root` to `path`, reporting I/O failures with the
+/// path of the file.
+pub(crate) fn present(
+    root: &DrawingArea<SVGBackend, Shift>,
+    path: &Path,
+) -> Result<(), PlotBuilderError> {
+    root.present().map_err(|err| match err {
+        DrawingAreaErrorKind::BackendError(DrawingErrorKind::DrawingError(
+            source,
+        )) => PlotBuilderError::Io {
+            path: path.to_path_buf(),
+            source,
+        },
+        err => PlotBuilderError::DrawingError(err),
+    })
+}
+
+/// Inserts `text` right after the opening `<svg>` tag of the SVG file at
+/// `path`.
+fn embed(path: &Path, text: &str) -> Result<(), PlotBuilderError> {
+    let io_error = |source| PlotBuilderError::Io {
+        path: path.to_path_buf(),
+        source,
+    };
+    let svg = std::fs::read_to_string(path).map_err(io_error)?;
+    let output = insert_after_svg_tag(&svg, text).ok_or_else(|| {
+        io_error(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "plot is not an SVG document",
+        ))
+    })?;
+    std::fs::write(path, output).map_err(io_error)
+}
+
+/// Returns the SVG document `svg` with `text` inserted right after its
+/// opening `<svg>` tag, or `None` if it has no such tag.
+fn insert_after_svg_tag(svg: &str, text: &str) -> Option<String> {
+    let insert_at = svg
+        .find("<svg")
+        .and_then(|start| svg[start..].find('>').map(|end| start + end + 1))?;
+    let mut output = String::with_capacity(svg.len() + text.len() + 1);
+    output.push_str(&svg[..insert_at]);
+    output.push('\n');
+    output.push_str(text);
+    output.push_str(&svg[insert_at..]);
+    Some(output)
+}
+
+/// Returns the opening tag of a `<series>` metadata element.
+fn series_tag(
+    id: &str,
+    class: &str,
+    function: &str,
+    label: &str,
+    color: RGBColor,
+) -> String {
+    let RGBColor(r, g, b) = color;
+    format!(
+        "<series id=\"{}\" class=\"benchplot-series {}\" function=\"{}\" \
+         label=\"{}\" color=\"#{:02X}{:02X}{:02X}\">\n",
+        id,
+        class,
+        xml_escape(function),
+        xml_escape(label),
+        r,
+        g,
+        b
+    )
+}
+
+/// Draws a legend of the given size listing `entries`, each a label and the
+/// style of its line, in the upper left corner of `area`, with its offsets and
+/// spacing multiplied by `scale`.
+fn draw_legend<DB>(
+    area: &DrawingArea<DB, Shift>,
+    entries: &[(String, LegendGlyph)],
+    (width, height): (u32, u32),
+    font: &TextStyle,
+    scale: f64,
+) -> Result<(), PlotBuilderError>
+where
+    DB: DrawingBackend,
+    PlotBuilderError: From<DrawingAreaErrorKind<DB::ErrorType>>,
+{
+    let px = |pixels: u32| scaled(pixels, scale) as i32;
+    let (x0, y0) = (px(10), px(20));
+    area.draw(&Rectangle::new(
+        [(x0, y0), (x0 + width as i32, y0 + height as i32)],
+        GREY.to_rgba(),
+    ))?;
+
+    let entry_height = px(LEGEND_ENTRY_HEIGHT);
+    for (idx, (label, glyph)) in entries.iter().enumerate() {
+        let y = y0 + px(5) + idx as i32 * entry_height + entry_height / 2;
+        area.draw(&glyph.at((x0 + px(10), y)))?;
+        area.draw(&Text::new(
+            label.as_str(),
+            (x0 + px(20 + LEGEND_GLYPH_LENGTH), y),
+            font.pos(Pos::new(HPos::Left, VPos::Center)),
+        ))?;
+    }
+    Ok(())
+}
+
+/// Escapes the characters of `s` that are special in XML (and HTML) text and
+/// attribute values.
+pub(crate) fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Formats a value on a logarithmic axis of base `base`: as the nearest power
+/// of ten (e.g., `10³`) if the base is 10, and as a number otherwise.
+fn log_label(base: f64, v: f64) -> String {
+    if base == 10.0 {
+        power_of_ten_label(v)
+    } else if (1.0..1e7).contains(&v) && v.fract() == 0.0 {
+        format!("{}", v)
+    } else {
+        format!("{:.2e}", v)
+    }
+}
+
+/// Formats an axis value as the nearest power of ten, e.g., `10³`.
+fn power_of_ten_label(v: f64) -> String {
+    format!("10{}", superscript(v.log10().round() as i32))
+}
+
+/// Formats an axis value that is a power of two as such, e.g., `2¹⁰`, or
+/// returns an empty string if it is not a power of two.
+fn power_of_two_label(v: f64) -> String {
+    let exponent = v.log2().round();
+    if (v.log2() - exponent).abs() > 1e-9 {
+        return String::new();
+    }
+    format!("2{}", superscript(exponent as i32))
+}
+
+/// Returns the label of the category at the axis value `v`, or an empty string
+/// if `v` is not the position of a category.
+fn category_label(labels: &[String], v: f64) -> String {
+    let idx = v.round();
+    if (v - idx).abs() > 1e-6 || idx < 0.0 {
+        return String::new();
+    }
+    labels.get(idx as usize).cloned().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod plot_tests {
+    use super::*;
+    use crate::{BenchBuilder, BenchFnArg, BenchFnNamed, DarkTheme};
+    use std::fs;
+    use tempfile::{tempdir, TempDir};
+
+    fn setup_bench_data() -> Bench<'static, usize, usize> {
+        let functions: Vec<BenchFnNamed<'static, usize, usize>> = vec![
+            (Box::new(|x| x * 2), "Double"),
+            (Box::new(|x| x * x), "Square"),
+        ];
+        let argfunc: BenchFnArg<usize> = Box::new(|x| x);
+        let sizes = vec![10, 100, 1000];
+        let bench = BenchBuilder::new(functions, argfunc, sizes)
+            .build()
+            .unwrap();
+        bench
+    }
+
+    fn get_temp_dir_and_file_path() -> (TempDir, PathBuf) {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("test_plot.svg");
+        assert!(!file_path.exists());
+        (dir, file_path)
+    }
+
+    #[test]
+    fn test_plot_file_creation() {
+        let (_dir, file_path) = get_temp_dir_and_file_path();
+
+        let mut bench = setup_bench_data();
+        let plot_result =
+            bench.run().plot(&file_path).title("Benchmark Plot").build();
+
+        assert!(plot_result.is_ok());
+        assert!(file_path.exists());
+    }
+
+    #[test]
+    fn test_plot_with_title() {
+        let (_dir, file_path) = get_temp_dir_and_file_path();
+
+        let mut bench = setup_bench_data();
+        let plot_result = bench
+            .run()
+            .plot(&file_path)
+            .title("Custom Title for Plot")
+            .build();
+
+        assert!(plot_result.is_ok());
+
+        let file_content =
+            fs::read_to_string(file_path).expect("Failed to read plot file");
+
+        assert!(file_content.contains("Custom Title for Plot"));
+    }
+
+    #[test]
+    fn test_plot_with_title_style() {
+        let (_dir, file_path) = get_temp_dir_and_file_path();
+
+        let mut bench = setup_bench_data();
+        let plot_result = bench
+            .run()
+            .plot(&file_path)
+            .title("Styled Title")
+            .title_style(TitleStyle {
+                color: RGBColor(1, 2, 3),
+                font_size: 30,
+                alignment: TitleAlignment::Left,
+            })
+            .build();
+        assert!(plot_result.is_ok());
+
+        let file_content =
+            fs::read_to_string(file_path).expect("Failed to read plot file");
+        // The attributes of a text element precede its content.
+        let lines: Vec<&str> = file_content.lines().collect();
+        let title_idx = lines
+            .iter()
+            .position(|line| *line == "Styled Title")
+            .expect("Title not found");
+        let title = lines[title_idx - 1];
+        assert!(title.contains(r#"x="20""#));
+        assert!(title.contains(r#"text-anchor="start""#));
+        assert!(title.contains(r##"fill="#010203""##));
+    }
+
+    #[test]
+    fn test_plot_with_multi_line_title() {
+        let (_dir, file_path) = get_temp_dir_and_file_path();
+
+        let mut bench = setup_bench_data();
+        let plot_result = bench
+            .run()
+            .plot(&file_path)
+            .title("Sorting Algorithms\nRandom input, release build")
+            .build();
+        assert!(plot_result.is_ok());
+
+        let file_content =
+            fs::read_to_string(file_path).expect("Failed to read plot file");
+        let lines: Vec<&str> = file_content.lines().collect();
+        let title_idx = lines
+            .iter()
+            .position(|line| *line == "Sorting Algorithms")
+            .expect("Title not found");
+        let subtitle_idx = lines
+            .iter()
+            .position(|line| *line == "Random input, release build")
+            .expect("Subtitle not found");
+        assert_ne!(lines[title_idx - 1], lines[subtitle_idx - 1]);
+    }
+
+    #[test]
+    fn test_plot_without_title_or_axis_descriptions() {
+        let (_dir, file_path) = get_temp_dir_and_file_path();
+
+        let mut bench = setup_bench_data();
+        let plot_result = bench
+            .run()
+            .plot(&file_path)
+            .axis_descriptions(false)
+            .build();
+
+        assert!(plot_result.is_ok());
+
+        let file_content =
+            fs::read_to_string(file_path).expect("Failed to read plot file");
+
+        assert!(!file_content.contains("Time (s)"));
+    }
+
+    #[test]
+    fn test_plot_normalized() {
+        let (_dir, file_path) = get_temp_dir_and_file_path();
+
+        let mut bench = setup_bench_data();
+        let plot_result = bench
+            .run()
+            .plot(&file_path)
+            .normalize(Complexity::Linearithmic)
+            .build();
+
+        assert!(plot_result.is_ok());
+
+        let file_content =
+            fs::read_to_string(file_path).expect("Failed to read plot file");
+
+        assert!(file_content.contains("Time / (n log n) (s)"));
+    }
+
+    #[test]
+    fn test_plot_metric() {
+        let (_dir, file_path) = get_temp_dir_and_file_path();
+
+        let functions: Vec<BenchFnNamed<'static, usize, usize>> = vec![
+            (Box::new(|x| x * 2), "Double"),
+            (Box::new(|x| x * x), "Square"),
+        ];
+        let argfunc: BenchFnArg<usize> = Box::new(|x| x);
+        let mut bench = BenchBuilder::new(functions, argfunc, vec![10, 100])
+            .result_metric("Result", |&result| result as f64)
+            .build()
+            .unwrap();
+        bench.run();
+
+        let plot = bench.plot(&file_path).metric("Result").throughput(true);
+        assert_eq!(plot.y_description(), "Result");
+        assert!(plot.build().is_ok());
+        let file_content =
+            fs::read_to_string(&file_path).expect("Failed to read plot file");
+        assert!(file_content.contains("Result"));
+
+        let result = bench.plot(&file_path).metric("Allocations").build();
+        assert!(matches!(
+            result,
+            Err(PlotBuilderError::UnknownMetric(name)) if name == "Allocations"
+        ));
+    }
+
+    #[test]
+    fn test_plot_secondary_metric() {
+        let (_dir, file_path) = get_temp_dir_and_file_path();
+
+        let functions: Vec<BenchFnNamed<'static, usize, (usize, f64)>> = vec![
+            (Box::new(|x| (x * 2, 0.5)), "Double"),
+            (Box::new(|x| (x * x, x as f64)), "Square"),
+        ];
+        let argfunc: BenchFnArg<usize> = Box::new(|x| x);
+        let mut bench = BenchBuilder::new(functions, argfunc, vec![10, 100])
+            .result_metric("Ratio", |&(_, ratio)| ratio)
+            .build()
+            .unwrap();
+        bench.run();
+
+        let plot = bench.plot(&file_path).secondary_metric("Ratio");
+        assert_eq!(plot.secondary_range(), (0.5, 100.0));
+        assert!(plot.build().is_ok());
+        let file_content =
+            fs::read_to_string(&file_path).expect("Failed to read plot file");
+        assert!(file_content.contains("Double (Ratio)"));
+        assert!(file_content.contains("Square (Ratio)"));
+
+        let result = bench.plot(&file_path).secondary_metric("Steps").build();
+        assert!(matches!(
+            result,
+            Err(PlotBuilderError::UnknownMetric(name)) if name == "Steps"
+        ));
+    }
+
+    #[test]
+    fn test_rank_legend() {
+        let (_dir, file_path) = get_temp_dir_and_file_path();
+
+        let functions: Vec<BenchFnNamed<usize, usize>> = vec![
+            (Box::new(|x| x), "Slow"),
+            (Box::new(|x| x), "Fast"),
+            (Box::new(|x| x), "Medium"),
+        ];
+        let argfunc: BenchFnArg<usize> = Box::new(|x| x);
+        let mut bench = BenchBuilder::new(functions, argfunc, vec![10, 100])
+            .build()
+            .unwrap();
+        bench.data.push_row(10, &[3.0, 1.0, 2.0]);
+        // Medium was not measured at the largest input size, where Slow is
+        // faster than Fast.
+        bench.data.push_row(100, &[10.0, 20.0, f64::NAN]);
+
+        assert_eq!(bench.plot(&file_path).legend_order(), [0, 1, 2]);
+        assert_eq!(
+            bench.plot(&file_path).rank_legend(true).legend_order(),
+            [1, 2, 0]
+        );
+        // The fastest function has the highest throughput, so it is still
+        // listed first.
+        let plot = bench.plot(&file_path).rank_legend(true).throughput(true);
+        assert_eq!(plot.legend_order(), [1, 2, 0]);
+
+        plot.build().unwrap();
+        let svg = fs::read_to_string(&file_path).unwrap();
+        let position =
+            |label: &str| svg.lines().position(|line| line == label).unwrap();
+        assert!(position("Fast") < position("Medium"));
+        assert!(position("Medium") < position("Slow"));
+    }
+
+    #[test]
+    fn test_plot_throughput() {
+        let (_dir, file_path) = get_temp_dir_and_file_path();
+
+        let mut bench = setup_bench_data();
+        bench.run();
+        let plot = bench.plot(&file_path).throughput(true);
+
+        assert_eq!(plot.y_value(10, 0.25), 4.0);
+        assert_eq!(plot.y_description(), "Operations / s");
+
+        let plot = plot.normalize(Complexity::Linear);
+        assert_eq!(plot.y_value(10, 0.25), 40.0);
+        assert_eq!(plot.y_description(), "n / s");
+
+        assert!(plot.build().is_ok());
+
+        let file_content =
+            fs::read_to_string(file_path).expect("Failed to read plot file");
+
+        assert!(file_content.contains("n / s"));
+    }
+
+    #[test]
+    fn test_y_axis_description() {
+        let (_dir, file_path) = get_temp_dir_and_file_path();
+
+        let mut bench = setup_bench_data();
+        bench.run();
+        let plot = bench
+            .plot(&file_path)
+            .throughput(true)
+            .y_axis_description("Elements / s");
+        assert_eq!(plot.y_description(), "Elements / s");
+
+        plot.build().unwrap();
+        let svg = fs::read_to_string(&file_path).unwrap();
+        assert!(svg.contains("Elements / s"));
+        assert!(!svg.contains("Operations / s"));
+    }
+
+    #[test]
+    fn test_plot_with_x_labels() {
+        let (_dir, file_path) = get_temp_dir_and_file_path();
+
+        let mut bench = setup_bench_data();
+        let plot_result = bench
+            .run()
+            .plot(&file_path)
+            .x_labels(["Small", "Medium", "Large"])
+            .build();
+
+        assert!(plot_result.is_ok());
+
+        let file_content =
+            fs::read_to_string(file_path).expect("Failed to read plot file");
+
+        assert!(file_content.contains("Small"));
+        assert!(file_content.contains("Medium"));
+        assert!(file_content.contains("Large"));
+    }
+
+    #[test]
+    fn test_plot_with_x_values() {
+        let (_dir, file_path) = get_temp_dir_and_file_path();
+
+        let mut bench = setup_bench_data();
+        let plot_result = bench
+            .run()
+            .plot(&file_path)
+            .x_values([-1.0, 0.0, 1.0])
+            .build();
+
+        assert!(plot_result.is_ok());
+        assert!(file_path.exists());
+    }
+
+    #[test]
+    fn test_plot_with_wrong_number_of_x_labels() {
+        let (_dir, file_path) = get_temp_dir_and_file_path();
+
+        let mut bench = setup_bench_data();
+        let plot_result = bench
+            .run()
+            .plot(&file_path)
+            .x_labels(["Small", "Large"])
+            .build();
+
+        assert!(matches!(
+            plot_result,
+            Err(PlotBuilderError::XAxisLength {
+                expected: 3,
+                actual: 2
+            })
+        ));
+    }
+
+    #[test]
+    fn test_plot_without_run() {
+        let (_dir, file_path) = get_temp_dir_and_file_path();
+
+        let bench = setup_bench_data();
+        let plot_result = bench.plot(&file_path).build();
+
+        assert!(matches!(plot_result, Err(PlotBuilderError::NoData)));
+        assert!(!file_path.exists());
+    }
+
+    #[test]
+    fn test_plot_nothing_to_draw() {
+        let (_dir, file_path) = get_temp_dir_and_file_path();
+
+        let mut bench = setup_bench_data();
+        bench.data = BenchData::from_rows(
+            2,
+            [(10, vec![f64::NAN; 2]), (100, vec![f64::NAN; 2])],
+        )
+        .unwrap();
+        let plot_result = bench.plot(&file_path).build();
+
+        assert!(matches!(plot_result, Err(PlotBuilderError::NothingToDraw)));
+        assert!(!file_path.exists());
+    }
+
+    #[test]
+    fn test_plot_after_run_if_needed() {
+        let (_dir, file_path) = get_temp_dir_and_file_path();
+
+        let mut bench = setup_bench_data();
+        assert!(bench.run_if_needed().plot(&file_path).build().is_ok());
+        let data = bench.data().clone();
+        bench.run_if_needed();
+        assert_eq!(bench.data(), &data);
+    }
+
+    #[test]
+    fn test_plot_with_invalid_dimensions() {
+        let (_dir, file_path) = get_temp_dir_and_file_path();
+
+        let mut bench = setup_bench_data();
+        let plot_result =
+            bench.run().plot(&file_path).dimensions(0, 600).build();
+
+        assert!(matches!(
+            plot_result,
+            Err(PlotBuilderError::InvalidDimensions {
+                width: 0,
+                height: 600
+            })
+        ));
+    }
+
+    #[test]
+    fn test_plot_scale() {
+        let (_dir, file_path) = get_temp_dir_and_file_path();
+
+        let mut bench = setup_bench_data();
+        bench.run();
+        bench.plot(&file_path).scale(2.0).build().unwrap();
+        let svg = std::fs::read_to_string(&file_path).unwrap();
+        assert!(svg.contains("width=\"1600\""));
+        assert!(svg.contains("height=\"1200\""));
+
+        for factor in [0.0, -1.0, f64::NAN] {
+            assert!(matches!(
+                bench.plot(&file_path).scale(factor).build(),
+                Err(PlotBuilderError::InvalidScale(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_plot_smoothing() {
+        let (_dir, file_path) = get_temp_dir_and_file_path();
+
+        let mut bench = setup_bench_data();
+        bench.run();
+        for smoothing in [Smoothing::MovingAverage(2), Smoothing::Loess(0.5)] {
+            bench.plot(&file_path).smoothing(smoothing).build().unwrap();
+        }
+        assert!(matches!(
+            bench
+                .plot(&file_path)
+                .smoothing(Smoothing::Loess(0.0))
+                .build(),
+            Err(PlotBuilderError::InvalidSmoothing(Smoothing::Loess(_)))
+        ));
+    }
+
+    #[test]
+    fn test_plot_io_error_includes_path() {
+        let (dir, _) = get_temp_dir_and_file_path();
+        let file_path = dir.path().join("missing").join("test_plot.svg");
+
+        let mut bench = setup_bench_data();
+        let plot_result = bench.run().plot(&file_path).build();
+
+        match plot_result {
+            Err(PlotBuilderError::Io { path, .. }) => {
+                assert_eq!(path, file_path)
+            }
+            other => panic!("expected an I/O error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_plot_with_overlay() {
+        let (_dir, file_path) = get_temp_dir_and_file_path();
+
+        let mut bench = setup_bench_data();
+        let mut baseline = bench.run().baseline();
+        assert_eq!(baseline.functions(), ["Double", "Square"]);
+
+        // Add an input size that is not plotted and a function that no
+        // longer exists.
+        let mut data = baseline.data().clone();
+        data.push_row(5, &[1.0, 1.0]);
+        baseline = Baseline::new(
+            vec!["Double".to_string(), "Removed".to_string()],
+            data,
+        );
+
+        let plot_result = bench.plot(&file_path).overlay(&baseline).build();
+        assert!(plot_result.is_ok());
+
+        let file_content =
+            fs::read_to_string(file_path).expect("Failed to read plot file");
+
+        assert!(file_content.contains("Double (baseline)"));
+        assert!(file_content.contains("Removed (baseline)"));
+    }
+
+    #[test]
+    fn test_embed_font() {
+        let (dir, file_path) = get_temp_dir_and_file_path();
+        let copy_path = dir.path().join("copy.svg");
+
+        let mut bench = setup_bench_data();
+        bench.run();
+        bench
+            .plot(&file_path)
+            .embed_font(true)
+            .also_save(&copy_path)
+            .build()
+            .unwrap();
+        for path in [&file_path, &copy_path] {
+            let svg = fs::read_to_string(path).unwrap();
+            assert!(svg.contains("@font-face"));
+            assert!(svg.contains(";base64,"));
+            assert!(svg.contains("text { font-family: \"benchplot-embedded\""));
+        }
+
+        bench.plot(&file_path).build().unwrap();
+        let svg = fs::read_to_string(&file_path).unwrap();
+        assert!(!svg.contains("@font-face"));
+    }
+
+    #[test]
+    fn test_build_svg() {
+        let (_dir, file_path) = get_temp_dir_and_file_path();
+
+        let mut bench = setup_bench_data();
+        bench.run();
+        bench
+            .plot(&file_path)
+            .title("In Memory")
+            .series_metadata(true)
+            .build()
+            .unwrap();
+        let svg = bench
+            .plot("ignored.png")
+            .title("In Memory")
+            .series_metadata(true)
+            .build_svg()
+            .unwrap();
+
+        assert!(svg.contains("<series"));
+        assert_eq!(svg, fs::read_to_string(&file_path).unwrap());
+        assert!(!Path::new("ignored.png").exists());
+    }
+
+    #[test]
+    fn test_series_metadata() {
+        let (_dir, file_path) = get_temp_dir_and_file_path();
+
+        let functions: Vec<BenchFnNamed<'static, usize, usize>> = vec![
+            (Box::new(|x| x * 2), "Double"),
+            (Box::new(|x| x + x), "Add & Add"),
+        ];
+        let argfunc: BenchFnArg<usize> = Box::new(|x| x);
+        let mut bench = BenchBuilder::new(functions, argfunc, vec![10, 100])
+            .build()
+            .unwrap();
+        bench.run();
+        let baseline = bench.baseline();
+
+        let plot_result = bench
+            .plot(&file_path)
+            .overlay(&baseline)
+            .series_metadata(true)
+            .build();
+        assert!(plot_result.is_ok());
+
+        let file_content =
+            fs::read_to_string(file_path).expect("Failed to read plot file");
+        let metadata_start = file_content.find("<metadata").unwrap();
+        assert!(file_content[..metadata_start].trim_end().ends_with('>'));
+        assert!(file_content.contains(
+            "<series id=\"series-0\" class=\"benchplot-series current\" \
+             function=\"Double\" label=\"Double\" color=\"#79C0FF\">"
+        ));
+        assert!(file_content.contains("function=\"Add &amp; Add\""));
+        assert!(file_content.contains(
+            "<series id=\"baseline-1\" class=\"benchplot-series baseline\""
+        ));
+        assert_eq!(file_content.matches("<point n=\"100\"").count(), 4);
+    }
+
+    #[test]
+    fn test_plot_cache() {
+        let (_dir, file_path) = get_temp_dir_and_file_path();
+        let mut bench = setup_bench_data();
+        bench.run();
+
+        let plot = |title: &str| {
+            bench
+                .plot(&file_path)
+                .title(title)
+                .cache(true)
+                .build()
+                .unwrap();
+        };
+        let mark = || {
+            let mut file_content = fs::read_to_string(&file_path).unwrap();
+            file_content.push_str("<!-- marker -->\n");
+            fs::write(&file_path, file_content).unwrap();
+        };
+        let is_marked = || {
+            fs::read_to_string(&file_path)
+                .unwrap()
+                .contains("<!-- marker -->")
+        };
+
+        plot("Title");
+        assert!(fs::read_to_string(&file_path)
+            .unwrap()
+            .contains("<!-- benchplot-cache-key: "));
+        mark();
+
+        // The same results and configuration are not rendered again.
+        plot("Title");
+        assert!(is_marked());
+
+        // A different configuration is.
+        plot("Other Title");
+        assert!(!is_marked());
+    }
+
+    #[test]
+    fn test_error_bands() {
+        let (_dir, file_path) = get_temp_dir_and_file_path();
+        let functions: Vec<BenchFnNamed<'static, usize, usize>> = vec![
+            (Box::new(|x| x * 2), "Double"),
+            (Box::new(|x| x * x), "Square"),
+        ];
+        let argfunc: BenchFnArg<usize> = Box::new(|x| x);
+        let mut bench = BenchBuilder::new(functions, argfunc, vec![10, 100])
+            .repetitions(5)
+            .confidence_level(0.9)
+            .build()
+            .unwrap();
+        bench.run();
+        assert_eq!(bench.confidence_intervals().len(), 4);
+
+        let polygons = |error_bands: bool| {
+            bench
+                .plot(&file_path)
+                .error_bands(error_bands)
+                .build()
+                .unwrap();
+            fs::read_to_string(&file_path)
+                .unwrap()
+                .matches("<polygon")
+                .count()
+        };
+        assert_eq!(polygons(false), 0);
+        assert_eq!(polygons(true), 2);
+    }
+
+    #[test]
+    fn test_change_points() {
+        let (_dir, file_path) = get_temp_dir_and_file_path();
+        let mut bench = setup_bench_data();
+        let sizes = [1, 2, 4, 8, 16, 32, 64, 128];
+        bench.sizes = sizes.to_vec();
+        bench.data = BenchData::from_rows(
+            2,
+            sizes.iter().map(|&n| {
+                let n = n as f64;
+                // Linear growth, and linear growth turning quadratic at 16.
+                (
+                    n as usize,
+                    vec![n, if n <= 16.0 { n } else { n * n / 16.0 }],
+                )
+            }),
+        )
+        .unwrap();
+
+        let change_points = bench.change_points(0.5);
+        assert_eq!(change_points.len(), 1);
+        assert_eq!(change_points[0].function, 1);
+        assert_eq!(change_points[0].size, 16);
+        assert!((change_points[0].slope_before - 1.0).abs() < 1e-9);
+        assert!((change_points[0].slope_after - 2.0).abs() < 1e-9);
+
+        let marked = |change_points: Option<f64>| {
+            let mut plot = bench.plot(&file_path);
+            if let Some(min_slope_change) = change_points {
+                plot = plot.change_points(min_slope_change);
+            }
+            plot.build().unwrap();
+            fs::read_to_string(&file_path)
+                .unwrap()
+                .lines()
+                .any(|line| line.contains("opacity=\"0.6\" stroke=\"#8957E5\""))
+        };
+        assert!(!marked(None));
+        assert!(marked(Some(0.5)));
+    }
+
+    #[test]
+    fn test_hline() {
+        let (_dir, file_path) = get_temp_dir_and_file_path();
+        let mut bench = setup_bench_data();
+        bench.run();
+
+        bench
+            .plot(&file_path)
+            .hline(10.0, "SLO (10 s)")
+            .build()
+            .unwrap();
+        let file_content = fs::read_to_string(&file_path).unwrap();
+        assert!(file_content.contains("SLO (10 s)"));
+        // The line is drawn in grey at the top of the y-axis, which extends
+        // to it from the timings far below.
+        assert!(file_content
+            .contains("stroke=\"#808080\" stroke-width=\"1\" points=\"90,20 "));
+
+        for value in [0.0, -1.0, f64::NAN] {
+            assert!(matches!(
+                bench.plot(&file_path).hline(value, "SLO").build(),
+                Err(PlotBuilderError::InvalidHline(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_vline() {
+        let (_dir, file_path) = get_temp_dir_and_file_path();
+        let mut bench = setup_bench_data();
+        bench.run();
+
+        bench
+            .plot(&file_path)
+            .vline(1_000_000, "Production")
+            .build()
+            .unwrap();
+        let file_content = fs::read_to_string(&file_path).unwrap();
+        assert!(file_content.contains("Production"));
+        assert!(file_content.contains("stroke=\"#808080\""));
+
+        assert!(matches!(
+            bench.plot(&file_path).vline(0, "Empty").build(),
+            Err(PlotBuilderError::InvalidVline(0))
+        ));
+        // With custom x-axis labels, only benchmarked sizes can be marked.
+        let labels = ["a", "b", "c"];
+        assert!(bench
+            .plot(&file_path)
+            .x_labels(labels)
+            .vline(bench.sizes[1], "Middle")
+            .build()
+            .is_ok());
+        assert!(matches!(
+            bench
+                .plot(&file_path)
+                .x_labels(labels)
+                .vline(1_000_000, "Production")
+                .build(),
+            Err(PlotBuilderError::InvalidVline(1_000_000))
+        ));
+    }
+
+    #[test]
+    fn test_lower_panel() {
+        let (_dir, file_path) = get_temp_dir_and_file_path();
+        let mut bench = setup_bench_data();
+        bench.run();
+
+        bench
+            .plot(&file_path)
+            .title("Stacked")
+            .hline(1.0, "SLO")
+            .lower_panel(LowerPanel::Throughput)
+            .build()
+            .unwrap();
+        let file_content = fs::read_to_string(&file_path).unwrap();
+        assert!(file_content.contains("Time (s)"));
+        assert!(file_content.contains("Operations / s"));
+        // The title and horizontal lines are only drawn once.
+        assert_eq!(file_content.matches("Stacked").count(), 1);
+        assert_eq!(file_content.matches("SLO").count(), 1);
+
+        bench
+            .plot(&file_path)
+            .lower_panel(LowerPanel::Normalized(Complexity::Linear))
+            .build()
+            .unwrap();
+        let file_content = fs::read_to_string(&file_path).unwrap();
+        assert!(file_content.contains("Time (s)"));
+        assert!(file_content.contains("Time / n (s)"));
+    }
+
+    #[test]
+    fn test_also_save() {
+        let (dir, file_path) = get_temp_dir_and_file_path();
+        let mut bench = setup_bench_data();
+        bench.run();
+
+        let png_path = dir.path().join("plot.png");
+        let svg_path = dir.path().join("copy.svg");
+        bench
+            .plot(&file_path)
+            .title("Copies")
+            .also_save(&png_path)
+            .also_save(&svg_path)
+            .build()
+            .unwrap();
+        assert_eq!(fs::read(&file_path).unwrap(), fs::read(&svg_path).unwrap());
+        let png = fs::read(&png_path).unwrap();
+        assert!(png.starts_with(b"\x89PNG"));
+
+        assert!(matches!(
+            bench.plot(&file_path).also_save("plot.pdf").build(),
+            Err(PlotBuilderError::UnsupportedFormat { .. })
+        ));
+    }
+
+    #[test]
+    fn test_style() {
+        let (_dir, file_path) = get_temp_dir_and_file_path();
+        let mut bench = setup_bench_data();
+        bench.run();
+
+        let style = PlotStyle::parse(
+            "width = 1000\n\
+             font_family = \"Helvetica\"\n\
+             palette = [\"#102030\"]\n",
+        )
+        .unwrap();
+        let plot = bench.plot(&file_path).style(&style).margin(5);
+        assert_eq!(plot.dimensions, (1000, 600));
+        assert_eq!(plot.margin, 5);
+        assert_eq!(plot.series_color("Double", 0), RGBColor(16, 32, 48));
+        assert_eq!(plot.series_color("Square", 1), RGBColor(16, 32, 48));
+        plot.build().unwrap();
+
+        let file_content = fs::read_to_string(&file_path).unwrap();
+        assert!(file_content.contains("font-family=\"Helvetica\""));
+        assert!(!file_content.contains("sans-serif"));
+        assert!(file_content.contains("stroke=\"#102030\""));
+    }
+
+    #[test]
+    fn test_theme() {
+        let (_dir, file_path) = get_temp_dir_and_file_path();
+        let mut bench = setup_bench_data();
+        bench.run();
+
+        bench.plot(&file_path).title("Dark").build().unwrap();
+        let default_content = fs::read_to_string(&file_path).unwrap();
+        bench
+            .plot(&file_path)
+            .title("Dark")
+            .theme(&DefaultTheme)
+            .build()
+            .unwrap();
+        assert_eq!(fs::read_to_string(&file_path).unwrap(), default_content);
+
+        bench
+            .plot(&file_path)
+            .title("Dark")
+            .theme(&DarkTheme)
+            .build()
+            .unwrap();
+        let file_content = fs::read_to_string(&file_path).unwrap();
+        assert!(file_content.contains("fill=\"#0D1117\""));
+        assert!(file_content.contains("fill=\"#C9D1D9\""));
+        assert!(file_content.contains("stroke=\"#FFFFFF\""));
+        assert!(!default_content.contains("#0D1117"));
+    }
+
+    #[test]
+    fn test_cpu_cache_markers() {
+        let (_dir, file_path) = get_temp_dir_and_file_path();
+        let mut bench = setup_bench_data();
+        bench.sizes = vec![1, usize::MAX];
+        bench.data = BenchData::from_rows(
+            2,
+            [(1, vec![1.0, 1.0]), (usize::MAX, vec![2.0, 4.0])],
+        )
+        .unwrap();
+
+        // Every detected cache is within the range of sizes.
+        let plot = bench.plot(&file_path).cpu_cache_markers(1);
+        let markers = plot.cache_marker_lines();
+        assert_eq!(markers.len(), cpu_caches().len());
+        plot.build().unwrap();
+        let file_content = fs::read_to_string(&file_path).unwrap();
+        for (_, label) in markers {
+            assert!(file_content.contains(&label));
+        }
+
+        assert!(matches!(
+            bench.plot(&file_path).cpu_cache_markers(0).build(),
+            Err(PlotBuilderError::ZeroElementSize)
+        ));
+    }
+
+    #[test]
+    fn test_plot_frames() {
+        let (dir, file_path) = get_temp_dir_and_file_path();
+
+        let mut bench = setup_bench_data();
+        let frames = bench.run().plot(&file_path).build_frames().unwrap();
+
+        assert_eq!(
+            frames,
+            [
+                dir.path().join("test_plot_frame_001.svg"),
+                dir.path().join("test_plot_frame_002.svg"),
+                dir.path().join("test_plot_frame_003.svg"),
+            ]
+        );
+        assert!(!file_path.exists());
+
+        // Each frame adds a point to the line of each function.
+        for (frame, path) in frames.iter().enumerate() {
+            let file_content = fs::read_to_string(path).unwrap();
+            let lines: Vec<&str> = file_content
+                .lines()
+                .filter(|line| {
+                    line.starts_with("<polyline")
+                        && (line.contains("#79C0FF")
+                            || line.contains("#8957E5"))
+                })
+                .collect();
+            assert_eq!(lines.len(), 2);
+            for line in lines {
+                assert_eq!(line.matches(',').count(), frame + 1);
+            }
+        }
+
+        let result = bench.plot(dir.path().join("..")).build_frames();
+        assert!(matches!(
+            result,
+            Err(PlotBuilderError::NoFileStem { path })
+                if path == dir.path().join("..")
+        ));
+    }
+
+    #[test]
+    fn test_plot_with_labeled_runs() {
+        let (_dir, file_path) = get_temp_dir_and_file_path();
+
+        let functions: Vec<BenchFnNamed<'static, usize, usize>> =
+            vec![(Box::new(|x| x * 2), "Double")];
+        let argfunc: BenchFnArg<usize> = Box::new(|x| x);
+        let mut bench = BenchBuilder::new(functions, argfunc, vec![10, 100])
+            .label("v0.4")
+            .build()
+            .unwrap();
+        bench.run();
+
+        let v2 = Baseline::new(
+            vec!["Double".to_string()],
+            BenchData::from_rows(1, [(10, vec![1e-6]), (100, vec![1e-5])])
+                .unwrap(),
+        )
+        .with_label("v0.2");
+        let v3 = bench.baseline().with_label("v0.3");
+
+        let plot_result =
+            bench.plot(&file_path).overlay(&v2).overlay(&v3).build();
+        assert!(plot_result.is_ok());
 
         let file_content =
             fs::read_to_string(file_path).expect("Failed to read plot file");
 
-        assert!(file_content.contains("Custom Title for Plot"));
+        assert!(file_content.contains("Double (v0.2)"));
+        assert!(file_content.contains("Double (v0.3)"));
+        assert!(file_content.contains("Double (v0.4)"));
+    }
+
+    #[test]
+    fn test_plot_with_log_base_two() {
+        let (_dir, file_path) = get_temp_dir_and_file_path();
+
+        let functions: Vec<BenchFnNamed<'static, usize, usize>> =
+            vec![(Box::new(|x| x * 2), "Double")];
+        let argfunc: BenchFnArg<usize> = Box::new(|x| x);
+        let sizes = vec![16, 256, 4096];
+        let mut bench = BenchBuilder::new(functions, argfunc, sizes)
+            .build()
+            .unwrap();
+        let plot_result = bench.run().plot(&file_path).x_log_base(2.0).build();
+
+        assert!(plot_result.is_ok());
+
+        let file_content =
+            fs::read_to_string(file_path).expect("Failed to read plot file");
+
+        assert!(file_content.contains("\n1024\n"));
+    }
+
+    #[test]
+    fn test_style_for_unknown_function() {
+        let (_dir, file_path) = get_temp_dir_and_file_path();
+
+        let mut bench = setup_bench_data();
+        bench.run();
+        let result = bench
+            .plot(&file_path)
+            .style_for("Square", SeriesStyle::default())
+            .style_for("Cube", SeriesStyle::default())
+            .build();
+        assert!(matches!(
+            result,
+            Err(PlotBuilderError::UnknownFunction(name)) if name == "Cube"
+        ));
+
+        // A function of an overlaid baseline can be styled.
+        let baseline = Baseline::new(
+            vec!["Double".to_string(), "Cube".to_string()],
+            bench.baseline().data().clone(),
+        );
+        let result = bench
+            .plot(&file_path)
+            .overlay(&baseline)
+            .style_for("Cube", SeriesStyle::default())
+            .build();
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_plot_with_style_override() {
+        let (_dir, file_path) = get_temp_dir_and_file_path();
+
+        let mut bench = setup_bench_data();
+        let plot_result = bench
+            .run()
+            .plot(&file_path)
+            .style_for(
+                "Square",
+                SeriesStyle {
+                    color: Some((1, 2, 3)),
+                    width: 5,
+                    dash: Some((8, 4)),
+                    marker: Some(Marker::Square),
+                },
+            )
+            .series_metadata(true)
+            .build();
+        assert!(plot_result.is_ok());
+
+        let file_content =
+            fs::read_to_string(file_path).expect("Failed to read plot file");
+        assert!(file_content.contains(r##"stroke="#010203" stroke-width="5""##));
+        // A square marker at each data point and in the legend.
+        assert_eq!(file_content.matches(r##"fill="#010203""##).count(), 4);
+        assert!(
+            file_content.contains(
+                r##"function="Square" label="Square" color="#010203""##,
+            )
+        );
+    }
+
+    #[test]
+    fn test_plot_with_legend_outside() {
+        for (position, dimensions) in [
+            (LegendPosition::Right, (1200, 600)),
+            (LegendPosition::Bottom, (800, 900)),
+        ] {
+            let (_dir, file_path) = get_temp_dir_and_file_path();
+
+            let functions: Vec<BenchFnNamed<'static, usize, usize>> = (0..10)
+                .map(|i| -> BenchFnNamed<'static, usize, usize> {
+                    (Box::new(move |x| x + i), "Function")
+                })
+                .collect();
+            let argfunc: BenchFnArg<usize> = Box::new(|x| x);
+            let mut bench =
+                BenchBuilder::new(functions, argfunc, vec![10, 100])
+                    .build()
+                    .unwrap();
+            let plot_result = bench
+                .run()
+                .plot(&file_path)
+                .legend_position(position)
+                .dimensions(dimensions.0, dimensions.1)
+                .build();
+            assert!(plot_result.is_ok());
+
+            let file_content = fs::read_to_string(file_path).unwrap();
+            assert_eq!(file_content.matches("Function").count(), 10);
+        }
+    }
+
+    #[test]
+    fn test_legend_too_large() {
+        let functions: Vec<BenchFnNamed<'static, usize, usize>> = (0..10)
+            .map(|i| -> BenchFnNamed<'static, usize, usize> {
+                (Box::new(move |x| x + i), "Function")
+            })
+            .collect();
+        let argfunc: BenchFnArg<usize> = Box::new(|x| x);
+        let mut bench = BenchBuilder::new(functions, argfunc, vec![10, 100])
+            .build()
+            .unwrap();
+        bench.run();
+
+        for (position, dimensions) in [
+            (LegendPosition::Right, (250, 600)),
+            (LegendPosition::Bottom, (800, 300)),
+        ] {
+            let (_dir, file_path) = get_temp_dir_and_file_path();
+            let plot_result = bench
+                .plot(&file_path)
+                .legend_position(position)
+                .dimensions(dimensions.0, dimensions.1)
+                .build();
+            assert!(matches!(
+                plot_result,
+                Err(PlotBuilderError::LegendTooLarge)
+            ));
+
+            // The same plot leaves room for the chart with the legend inside.
+            let plot_result = bench
+                .plot(&file_path)
+                .dimensions(dimensions.0, dimensions.1)
+                .build();
+            assert!(plot_result.is_ok());
+        }
+    }
+
+    #[test]
+    fn test_plot_with_margins_and_label_areas() {
+        let (_dir, file_path) = get_temp_dir_and_file_path();
+
+        let mut bench = setup_bench_data();
+        let plot_result = bench
+            .run()
+            .plot(&file_path)
+            .margin(40)
+            .x_label_area_size(80)
+            .y_label_area_size(120)
+            .build();
+        assert!(plot_result.is_ok());
+
+        // The y-axis is drawn at the right edge of the y-label area.
+        let file_content =
+            fs::read_to_string(file_path).expect("Failed to read plot file");
+        assert!(file_content.contains(r#"x1="160""#));
+    }
+
+    #[test]
+    fn test_plot_with_number_format() {
+        let (_dir, file_path) = get_temp_dir_and_file_path();
+
+        let functions: Vec<BenchFnNamed<'static, usize, usize>> =
+            vec![(Box::new(|x| x * 2), "Double")];
+        let argfunc: BenchFnArg<usize> = Box::new(|x| x);
+        let sizes = vec![16, 256, 4096];
+        let mut bench = BenchBuilder::new(functions, argfunc, sizes)
+            .build()
+            .unwrap();
+        let plot_result = bench
+            .run()
+            .plot(&file_path)
+            .x_log_base(2.0)
+            .number_format(NumberFormat::new(',').with_thousands_separator('.'))
+            .build();
+
+        assert!(plot_result.is_ok());
+
+        let file_content =
+            fs::read_to_string(file_path).expect("Failed to read plot file");
+
+        assert!(file_content.contains("\n1.024\n"));
+        assert!(!file_content.contains("\n1024\n"));
+    }
+
+    #[test]
+    fn test_plot_with_invalid_log_base() {
+        let (_dir, file_path) = get_temp_dir_and_file_path();
+
+        let mut bench = setup_bench_data();
+        let plot_result = bench.run().plot(&file_path).y_log_base(1.0).build();
+
+        assert!(matches!(
+            plot_result,
+            Err(PlotBuilderError::InvalidLogBase(base)) if base == 1.0
+        ));
+    }
+
+    #[test]
+    fn test_plot_with_power_of_two_labels() {
+        let (_dir, file_path) = get_temp_dir_and_file_path();
+
+        let functions: Vec<BenchFnNamed<'static, usize, usize>> =
+            vec![(Box::new(|x| x * 2), "Double")];
+        let argfunc: BenchFnArg<usize> = Box::new(|x| x);
+        let sizes = vec![1 << 4, 1 << 8, 1 << 12];
+        let mut bench = BenchBuilder::new(functions, argfunc, sizes)
+            .build()
+            .unwrap();
+        let plot_result = bench
+            .run()
+            .plot(&file_path)
+            .power_of_two_labels(true)
+            .build();
+
+        assert!(plot_result.is_ok());
+
+        let file_content =
+            fs::read_to_string(file_path).expect("Failed to read plot file");
+
+        assert!(file_content.contains("2¹⁰"));
+    }
+
+    #[test]
+    fn test_plot_with_power_of_two_labels_invalid_sizes() {
+        let (_dir, file_path) = get_temp_dir_and_file_path();
+
+        let mut bench = setup_bench_data();
+        let plot_result = bench
+            .run()
+            .plot(&file_path)
+            .power_of_two_labels(true)
+            .build();
+
+        assert!(matches!(
+            plot_result,
+            Err(PlotBuilderError::NotPowerOfTwo(10))
+        ));
+    }
+
+    #[test]
+    fn test_power_of_two_label() {
+        assert_eq!(power_of_two_label(1024.0), "2¹⁰");
+        assert_eq!(power_of_two_label(1.0), "2⁰");
+        assert_eq!(power_of_two_label(0.25), "2⁻²");
+        assert_eq!(power_of_two_label(1000.0), "");
+    }
+
+    #[test]
+    fn test_log_label() {
+        assert_eq!(log_label(10.0, 1000.0), "10³");
+        assert_eq!(log_label(2.0, 1024.0), "1024");
+        assert_eq!(log_label(2.0, 0.5), "5.00e-1");
+    }
+
+    #[test]
+    fn test_category_label() {
+        let labels = vec!["a".to_string(), "b".to_string()];
+
+        assert_eq!(category_label(&labels, 0.0), "a");
+        assert_eq!(category_label(&labels, 1.0), "b");
+        assert_eq!(category_label(&labels, 0.5), "");
+        assert_eq!(category_label(&labels, 2.0), "");
+        assert_eq!(category_label(&labels, -1.0), "");
     }
 }
 
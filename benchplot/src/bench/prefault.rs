@@ -0,0 +1,85 @@
+/*
+Copyright 2024 Owain Davies
+SPDX-License-Identifier: Apache-2.0 OR MIT
+*/
+
+use std::mem::{size_of, MaybeUninit};
+
+/// Smallest page size in common use. Touching memory at this interval reaches
+/// every page regardless of the actual page size.
+const PAGE_SIZE: usize = 4096;
+
+/// Types whose memory can be touched page by page before timing, so that
+/// first-touch page faults are not attributed to the function being timed.
+///
+/// Implementations are provided for slices, vectors, boxed slices, and
+/// strings. Only the memory directly owned by the value is touched; for
+/// example, the inner vectors of a `Vec<Vec<u8>>` are not.
+pub trait Prefault {
+    /// Reads from every page of memory owned by `self`.
+    fn prefault(&self);
+}
+
+impl<U> Prefault for [U] {
+    fn prefault(&self) {
+        if size_of::<U>() == 0 {
+            return;
+        }
+        let step = (PAGE_SIZE / size_of::<U>()).max(1);
+        let ptr = self.as_ptr() as *const MaybeUninit<U>;
+        for i in (0..self.len()).step_by(step) {
+            // SAFETY: `i` is in bounds, and reading the element as
+            // `MaybeUninit` neither requires initialized padding nor creates a
+            // second owner of the element.
+            unsafe {
+                std::ptr::read_volatile(ptr.add(i));
+            }
+        }
+    }
+}
+
+impl<U> Prefault for Vec<U> {
+    fn prefault(&self) {
+        self.as_slice().prefault();
+    }
+}
+
+impl<U> Prefault for Box<[U]> {
+    fn prefault(&self) {
+        self.as_ref().prefault();
+    }
+}
+
+impl Prefault for str {
+    fn prefault(&self) {
+        self.as_bytes().prefault();
+    }
+}
+
+impl Prefault for String {
+    fn prefault(&self) {
+        self.as_bytes().prefault();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prefault_does_not_modify() {
+        let v: Vec<u64> = (0..10_000).collect();
+        v.prefault();
+        assert!(v.iter().copied().eq(0..10_000));
+
+        let s = "x".repeat(10_000);
+        s.prefault();
+        assert_eq!(s.len(), 10_000);
+
+        let empty: Vec<String> = Vec::new();
+        empty.prefault();
+
+        let zero_sized = vec![(); 100];
+        zero_sized.prefault();
+    }
+}
@@ -0,0 +1,195 @@
+/*
+Copyright 2024 Owain Davies
+SPDX-License-Identifier: Apache-2.0 OR MIT
+*/
+
+/// Scheduling priority of the process while benchmarks are running.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Priority {
+    /// Leaves the process priority unchanged.
+    #[default]
+    Normal,
+
+    /// Raises the process priority: a nice value of -20 on Unix and
+    /// `HIGH_PRIORITY_CLASS` on Windows.
+    High,
+
+    /// Raises the process to real-time priority: the `SCHED_FIFO` scheduling
+    /// policy on Unix and `REALTIME_PRIORITY_CLASS` on Windows.
+    ///
+    /// On Unix, the policy applies to the thread calling `run()` and to the
+    /// threads it subsequently spawns.
+    ///
+    /// Use with care, as a long-running benchmark may starve other processes.
+    Realtime,
+}
+
+/// Restores the previous process priority when dropped.
+pub(crate) struct PriorityGuard {
+    previous: sys::Previous,
+}
+
+impl Drop for PriorityGuard {
+    fn drop(&mut self) {
+        sys::restore(&self.previous);
+    }
+}
+
+/// Raises the process priority, returning a guard restoring the previous
+/// priority on success, or a description of the failure.
+///
+/// Elevation usually requires elevated privileges (e.g., `CAP_SYS_NICE` on
+/// Linux).
+pub(crate) fn elevate(priority: Priority) -> Result<PriorityGuard, String> {
+    sys::elevate(priority).map(|previous| PriorityGuard { previous })
+}
+
+#[cfg(unix)]
+mod sys {
+    use super::Priority;
+    use std::io;
+
+    pub(super) enum Previous {
+        Unchanged,
+        Nice(libc::c_int),
+        Policy(libc::c_int, libc::sched_param),
+    }
+
+    pub(super) fn elevate(priority: Priority) -> Result<Previous, String> {
+        match priority {
+            Priority::Normal => Ok(Previous::Unchanged),
+            Priority::High => {
+                // SAFETY: getpriority and setpriority only read and modify the
+                // scheduling priority of the calling process.
+                unsafe {
+                    let previous = libc::getpriority(libc::PRIO_PROCESS, 0);
+                    if libc::setpriority(libc::PRIO_PROCESS, 0, -20) != 0 {
+                        return Err(io::Error::last_os_error().to_string());
+                    }
+                    Ok(Previous::Nice(previous))
+                }
+            }
+            Priority::Realtime => {
+                // SAFETY: pthread_self always returns a valid handle for the
+                // calling thread, and `previous` and `param` are valid,
+                // initialized sched_param values.
+                unsafe {
+                    let thread = libc::pthread_self();
+                    let mut policy = 0;
+                    let mut previous: libc::sched_param = std::mem::zeroed();
+                    let err = libc::pthread_getschedparam(
+                        thread,
+                        &mut policy,
+                        &mut previous,
+                    );
+                    if err != 0 {
+                        return Err(
+                            io::Error::from_raw_os_error(err).to_string()
+                        );
+                    }
+                    let mut param: libc::sched_param = std::mem::zeroed();
+                    param.sched_priority =
+                        libc::sched_get_priority_max(libc::SCHED_FIFO);
+                    let err = libc::pthread_setschedparam(
+                        thread,
+                        libc::SCHED_FIFO,
+                        &param,
+                    );
+                    if err != 0 {
+                        return Err(
+                            io::Error::from_raw_os_error(err).to_string()
+                        );
+                    }
+                    Ok(Previous::Policy(policy, previous))
+                }
+            }
+        }
+    }
+
+    pub(super) fn restore(previous: &Previous) {
+        // SAFETY: see `elevate`.
+        unsafe {
+            match previous {
+                Previous::Unchanged => {}
+                Previous::Nice(nice) => {
+                    libc::setpriority(libc::PRIO_PROCESS, 0, *nice);
+                }
+                Previous::Policy(policy, param) => {
+                    libc::pthread_setschedparam(
+                        libc::pthread_self(),
+                        *policy,
+                        param,
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[cfg(windows)]
+mod sys {
+    use super::Priority;
+    use std::io;
+    use windows_sys::Win32::System::Threading::{
+        GetCurrentProcess, GetPriorityClass, SetPriorityClass,
+        HIGH_PRIORITY_CLASS, REALTIME_PRIORITY_CLASS,
+    };
+
+    pub(super) enum Previous {
+        Unchanged,
+        Class(u32),
+    }
+
+    pub(super) fn elevate(priority: Priority) -> Result<Previous, String> {
+        let class = match priority {
+            Priority::Normal => return Ok(Previous::Unchanged),
+            Priority::High => HIGH_PRIORITY_CLASS,
+            Priority::Realtime => REALTIME_PRIORITY_CLASS,
+        };
+        // SAFETY: GetCurrentProcess returns a pseudo handle that is always
+        // valid for the calling process.
+        unsafe {
+            let process = GetCurrentProcess();
+            let previous = GetPriorityClass(process);
+            if previous == 0 || SetPriorityClass(process, class) == 0 {
+                return Err(io::Error::last_os_error().to_string());
+            }
+            Ok(Previous::Class(previous))
+        }
+    }
+
+    pub(super) fn restore(previous: &Previous) {
+        if let Previous::Class(class) = previous {
+            // SAFETY: see `elevate`.
+            unsafe {
+                SetPriorityClass(GetCurrentProcess(), *class);
+            }
+        }
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod sys {
+    use super::Priority;
+
+    pub(super) struct Previous;
+
+    pub(super) fn elevate(priority: Priority) -> Result<Previous, String> {
+        match priority {
+            Priority::Normal => Ok(Previous),
+            _ => Err("not supported on this platform".to_string()),
+        }
+    }
+
+    pub(super) fn restore(_previous: &Previous) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normal_priority_always_succeeds() {
+        assert!(elevate(Priority::Normal).is_ok());
+    }
+}
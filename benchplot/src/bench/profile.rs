@@ -0,0 +1,256 @@
+/*
+Copyright 2024 Owain Davies
+SPDX-License-Identifier: Apache-2.0 OR MIT
+*/
+
+#[cfg(feature = "dhat")]
+use crate::util;
+#[cfg(feature = "dhat")]
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+#[cfg(feature = "dhat")]
+use std::sync::Mutex;
+
+/// A hook invoked around the timing of a chosen `(input size, function)`
+/// pair, e.g., to capture a flamegraph of a slow point of a plot with a
+/// sampling profiler such as `pprof`.
+///
+/// ```no_run
+/// use benchplot::{BenchBuilder, BenchFnArg, BenchFnNamed, Profiler};
+///
+/// struct Announce;
+///
+/// impl Profiler for Announce {
+///     fn start(&self, function: &str, size: usize) {
+///         eprintln!("attach a profiler to profile {} at n={}", function, size);
+///         std::thread::sleep(std::time::Duration::from_secs(10));
+///     }
+///
+///     fn stop(&self, _function: &str, _size: usize) {}
+/// }
+///
+/// let functions: Vec<BenchFnNamed<Vec<u32>, Vec<u32>>> = vec![(
+///     Box::new(|mut v| {
+///         v.sort();
+///         v
+///     }),
+///     "Sort",
+/// )];
+/// let argfunc: BenchFnArg<Vec<u32>> =
+///     Box::new(|n| (0..n as u32).rev().collect());
+/// let sizes = vec![1_000, 1_000_000];
+/// let mut bench = BenchBuilder::new(functions, argfunc, sizes)
+///     .profile("Sort", 1_000_000, Announce)
+///     .build()
+///     .unwrap();
+/// bench.run();
+/// ```
+pub trait Profiler: Send + Sync {
+    /// Called right before the first repetition of the pair is timed.
+    fn start(&self, function: &str, size: usize);
+
+    /// Called right after the last repetition of the pair is timed.
+    fn stop(&self, function: &str, size: usize);
+}
+
+/// A [`Profiler`] capturing a heap profile of the pair with
+/// [dhat](https://docs.rs/dhat), for investigations of memory behavior.
+///
+/// The profile is saved as `dhat_<function>_<size>.json` in the given
+/// directory, e.g., the directory of the plot, and can be opened with the
+/// [dhat viewer](https://nnethercote.github.io/dh_view/dh_view.html).
+/// Allocations are only recorded if dhat's allocator is the global
+/// allocator of the benchmark binary:
+///
+/// ```no_run
+/// #[global_allocator]
+/// static ALLOC: dhat::Alloc = dhat::Alloc;
+/// ```
+#[cfg(feature = "dhat")]
+#[derive(Debug)]
+pub struct DhatProfiler {
+    dir: PathBuf,
+    profiler: Mutex<Option<dhat::Profiler>>,
+}
+
+#[cfg(feature = "dhat")]
+impl DhatProfiler {
+    /// Creates a profiler saving profiles in the directory `dir`.
+    pub fn new<P: AsRef<Path>>(dir: P) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+            profiler: Mutex::new(None),
+        }
+    }
+
+    /// Returns the path of the profile of the function `function` at the
+    /// input size `size`.
+    pub fn profile_path(&self, function: &str, size: usize) -> PathBuf {
+        self.dir.join(format!(
+            "dhat_{}_{}.json",
+            util::file_stem(function),
+            size
+        ))
+    }
+}
+
+#[cfg(feature = "dhat")]
+impl Profiler for DhatProfiler {
+    fn start(&self, function: &str, size: usize) {
+        let profiler = dhat::Profiler::builder()
+            .file_name(self.profile_path(function, size))
+            .build();
+        *self.profiler.lock().unwrap_or_else(|err| err.into_inner()) =
+            Some(profiler);
+    }
+
+    /// Saves the profile, which dhat does when its profiler is dropped.
+    fn stop(&self, _function: &str, _size: usize) {
+        drop(
+            self.profiler
+                .lock()
+                .unwrap_or_else(|err| err.into_inner())
+                .take(),
+        );
+    }
+}
+
+/// A profiled `(input size, function)` pair.
+#[derive(Clone)]
+pub(crate) struct ProfiledCell {
+    pub(crate) size: usize,
+    pub(crate) func_idx: usize,
+    pub(crate) profiler: Arc<dyn Profiler>,
+}
+
+/// Returns the profiler of the function at index `func_idx` at the input
+/// size `size`, if the pair is profiled.
+pub(crate) fn profiler_for(
+    profiled: &[ProfiledCell],
+    size: usize,
+    func_idx: usize,
+) -> Option<&dyn Profiler> {
+    profiled
+        .iter()
+        .find(|cell| cell.size == size && cell.func_idx == func_idx)
+        .map(|cell| &*cell.profiler)
+}
+
+/// Calls `timing`, the timing of the function `function` at the input size
+/// `size`, between the start and stop calls of `profiler`, if any.
+pub(crate) fn profiled<X>(
+    profiler: Option<&dyn Profiler>,
+    function: &str,
+    size: usize,
+    timing: impl FnOnce() -> X,
+) -> X {
+    let Some(profiler) = profiler else {
+        return timing();
+    };
+    profiler.start(function, size);
+    let result = timing();
+    profiler.stop(function, size);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BenchBuilder, BenchBuilderError, BenchFnArg, BenchFnNamed};
+    use std::sync::Mutex;
+
+    /// A profiler recording its calls.
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<String>>);
+
+    impl Profiler for Recorder {
+        fn start(&self, function: &str, size: usize) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("start {} {}", function, size));
+        }
+
+        fn stop(&self, function: &str, size: usize) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("stop {} {}", function, size));
+        }
+    }
+
+    impl Profiler for Arc<Recorder> {
+        fn start(&self, function: &str, size: usize) {
+            (**self).start(function, size);
+        }
+
+        fn stop(&self, function: &str, size: usize) {
+            (**self).stop(function, size);
+        }
+    }
+
+    fn setup_builder() -> BenchBuilder<'static, usize, usize> {
+        let functions: Vec<BenchFnNamed<'static, usize, usize>> = vec![
+            (Box::new(|x| x * 2), "Double"),
+            (Box::new(|x| x * x), "Square"),
+        ];
+        let argfunc: BenchFnArg<usize> = Box::new(|x| x);
+        BenchBuilder::new(functions, argfunc, vec![10, 100]).repetitions(3)
+    }
+
+    #[test]
+    fn test_profiler_wraps_chosen_pair() {
+        for parallel in [false, true] {
+            let recorder = Arc::new(Recorder::default());
+            setup_builder()
+                .parallel(parallel)
+                .profile("Square", 100, Arc::clone(&recorder))
+                .build()
+                .unwrap()
+                .run();
+
+            assert_eq!(
+                *recorder.0.lock().unwrap(),
+                ["start Square 100", "stop Square 100"]
+            );
+        }
+    }
+
+    #[cfg(feature = "dhat")]
+    #[test]
+    fn test_dhat_profiler() {
+        let dir = tempfile::tempdir().unwrap();
+        let profiler = DhatProfiler::new(dir.path());
+        let path = profiler.profile_path("Square", 100);
+        assert_eq!(path, dir.path().join("dhat_square_100.json"));
+
+        setup_builder()
+            .profile("Square", 100, profiler)
+            .build()
+            .unwrap()
+            .run();
+
+        let profile = std::fs::read_to_string(path).unwrap();
+        assert!(profile.contains("\"dhatFileVersion\""));
+    }
+
+    #[test]
+    fn test_profile_unknown_pair() {
+        assert_eq!(
+            setup_builder()
+                .profile("Cube", 100, Recorder::default())
+                .build()
+                .err(),
+            Some(BenchBuilderError::UnknownProfiledFunction(
+                "Cube".to_string()
+            ))
+        );
+        assert_eq!(
+            setup_builder()
+                .profile("Square", 50, Recorder::default())
+                .build()
+                .err(),
+            Some(BenchBuilderError::UnknownProfiledSize(50))
+        );
+    }
+}
@@ -0,0 +1,343 @@
+/*
+Copyright 2024 Owain Davies
+SPDX-License-Identifier: Apache-2.0 OR MIT
+*/
+
+use crate::{Bench, Threading};
+use std::fmt::Write;
+
+/// The fastest function at an input size.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Winner {
+    /// The input size.
+    pub size: usize,
+
+    /// The index of the fastest function, in the order of
+    /// [`Bench::function_names`].
+    pub function: usize,
+
+    /// The index of the second fastest function and the ratio of its timing
+    /// to that of the fastest, e.g., `1.25` if the fastest takes 20% less
+    /// time, or `None` if there is no other function to compare with.
+    pub runner_up: Option<(usize, f64)>,
+}
+
+impl<T, R, M: Threading> Bench<'_, T, R, M> {
+    /// Returns the fastest function at each measured input size, in the order
+    /// of [`Bench::data`], along with its margin over the second fastest.
+    ///
+    /// Timings that are `NaN` are ignored, so input sizes at which every
+    /// timing is `NaN` have no winner.
+    pub fn winners(&self) -> Vec<Winner> {
+        self.data
+            .iter()
+            .filter_map(|(size, timings)| {
+                let mut ranked: Vec<(usize, f64)> = timings
+                    .iter()
+                    .copied()
+                    .enumerate()
+                    .filter(|(_, time)| !time.is_nan())
+                    .collect();
+                ranked.sort_by(|a, b| a.1.total_cmp(&b.1));
+                let &(function, fastest) = ranked.first()?;
+                Some(Winner {
+                    size,
+                    function,
+                    runner_up: ranked
+                        .get(1)
+                        .map(|&(runner_up, time)| (runner_up, time / fastest)),
+                })
+            })
+            .collect()
+    }
+
+    /// Ranks the functions over all measured input sizes, returning
+    /// `(function index, ratio)` from the fastest to the slowest.
+    ///
+    /// The ratio of a function is the geometric mean, over the input sizes,
+    /// of its timing divided by the fastest timing at the input size, so that
+    /// every input size weighs the same however long its timings are. A
+    /// ratio of 1 means the function is the fastest at every input size.
+    ///
+    /// Only the input sizes at which every function was timed are compared,
+    /// as returned by [`Bench::ranked_sizes`], so that a function timed at
+    /// fewer sizes, e.g., one capped with
+    /// [`BenchBuilder::max_size_for`](crate::BenchBuilder::max_size_for), is
+    /// not ranked on the sizes it is fastest at alone. Functions without any
+    /// timing are left out of that requirement, and they, or every function
+    /// if no input size was timed for all of them, have a `NaN` ratio and
+    /// are ranked last.
+    pub fn ranking(&self) -> Vec<(usize, f64)> {
+        let mut log_ratios = vec![(0.0, 0); self.functions.len()];
+        for size_idx in self.ranked_sizes() {
+            let timings = self.data.row(size_idx);
+            let fastest = timings
+                .iter()
+                .copied()
+                .filter(|time| !time.is_nan())
+                .fold(f64::INFINITY, f64::min);
+            for (function, &time) in timings.iter().enumerate() {
+                if !time.is_nan() {
+                    log_ratios[function].0 += (time / fastest).ln();
+                    log_ratios[function].1 += 1;
+                }
+            }
+        }
+
+        let mut ranking: Vec<(usize, f64)> = log_ratios
+            .into_iter()
+            .enumerate()
+            .map(|(function, (sum, count))| match count {
+                0 => (function, f64::NAN),
+                _ => (function, (sum / count as f64).exp()),
+            })
+            .collect();
+        ranking.sort_by(|a, b| {
+            a.1.is_nan().cmp(&b.1.is_nan()).then(a.1.total_cmp(&b.1))
+        });
+        ranking
+    }
+
+    /// Returns the indices into [`Bench::data`] of the input sizes compared
+    /// by [`Bench::ranking`]: those at which every function with any timing
+    /// was timed.
+    pub fn ranked_sizes(&self) -> Vec<usize> {
+        let timed: Vec<bool> = (0..self.data.num_functions())
+            .map(|function| self.data.column(function).any(|t| !t.is_nan()))
+            .collect();
+        (0..self.data.num_sizes())
+            .filter(|&size_idx| {
+                let row = self.data.row(size_idx);
+                row.iter().any(|time| !time.is_nan())
+                    && row
+                        .iter()
+                        .zip(&timed)
+                        .all(|(time, &timed)| !timed || !time.is_nan())
+            })
+            .collect()
+    }
+
+    /// Returns a summary of [`Bench::winners`] with a line per input size,
+    /// e.g., `n=1000: Merge Sort (1.25x faster than Quick Sort)`, suited to
+    /// logs and pull request comments.
+    pub fn winners_summary(&self) -> String {
+        let names = self.function_names();
+        let mut summary = String::new();
+        for winner in self.winners() {
+            let _ = write!(
+                summary,
+                "n={}: {}",
+                winner.size, names[winner.function]
+            );
+            if let Some((runner_up, ratio)) = winner.runner_up {
+                let _ = write!(
+                    summary,
+                    " ({:.2}x faster than {})",
+                    ratio, names[runner_up]
+                );
+            }
+            summary.push('\n');
+        }
+        summary
+    }
+
+    /// Returns a one-line conclusion naming the fastest function at the
+    /// largest measured input size and its speedup over every other function
+    /// timed at that size, from the closest to the slowest, e.g.,
+    /// `Merge Sort is fastest at n=1000: 1.25x faster than Quick Sort, 25.00x
+    /// faster than Bubble Sort`.
+    ///
+    /// Returns `None` if no timing has been measured.
+    pub fn verdict(&self) -> Option<String> {
+        let names = self.function_names();
+        let (size, timings) = self
+            .data
+            .iter()
+            .filter(|(_, timings)| timings.iter().any(|time| !time.is_nan()))
+            .max_by_key(|&(size, _)| size)?;
+        let mut ranked: Vec<(usize, f64)> = timings
+            .iter()
+            .copied()
+            .enumerate()
+            .filter(|(_, time)| !time.is_nan())
+            .collect();
+        ranked.sort_by(|a, b| a.1.total_cmp(&b.1));
+        let (fastest, fastest_time) = ranked[0];
+
+        let mut verdict =
+            format!("{} is fastest at n={}", names[fastest], size);
+        for (idx, &(function, time)) in ranked.iter().enumerate().skip(1) {
+            let _ = write!(
+                verdict,
+                "{}{:.2}x faster than {}",
+                if idx == 1 { ": " } else { ", " },
+                time / fastest_time,
+                names[function]
+            );
+        }
+        Some(verdict)
+    }
+
+    /// Returns the speedup of every function over every other, as a matrix
+    /// indexed in the order of [`Bench::function_names`], whose entry
+    /// `[a][b]` is the number of times function `a` is faster than function
+    /// `b`, e.g., `2.0` if it takes half the time.
+    ///
+    /// Like the ratios of [`Bench::ranking`], each speedup is the geometric
+    /// mean, over the input sizes at which both functions were timed, of the
+    /// ratio of their timings, so that `[b][a]` is the inverse of `[a][b]`.
+    /// The speedup of two functions never timed at the same input size is
+    /// `NaN`.
+    pub fn speedup_matrix(&self) -> Vec<Vec<f64>> {
+        let num_functions = self.functions.len();
+        let mut log_speedups =
+            vec![vec![(0.0, 0); num_functions]; num_functions];
+        for (_, timings) in self.data.iter() {
+            for (a, &time_a) in timings.iter().enumerate() {
+                for (b, &time_b) in timings.iter().enumerate() {
+                    if !time_a.is_nan() && !time_b.is_nan() {
+                        log_speedups[a][b].0 += (time_b / time_a).ln();
+                        log_speedups[a][b].1 += 1;
+                    }
+                }
+            }
+        }
+        log_speedups
+            .into_iter()
+            .map(|row| {
+                row.into_iter()
+                    .map(|(sum, count)| match count {
+                        0 => f64::NAN,
+                        _ => (sum / count as f64).exp(),
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BenchBuilder, BenchData, BenchFnArg, BenchFnNamed};
+
+    #[test]
+    fn test_winners() {
+        let functions: Vec<BenchFnNamed<'static, usize, usize>> = vec![
+            (Box::new(|x| x), "Merge Sort"),
+            (Box::new(|x| x), "Quick Sort"),
+            (Box::new(|x| x), "Bubble Sort"),
+        ];
+        let argfunc: BenchFnArg<usize> = Box::new(|x| x);
+        let mut bench = BenchBuilder::new(functions, argfunc, vec![10, 1000])
+            .build()
+            .unwrap();
+        bench.data = BenchData::from_rows(
+            3,
+            [
+                (10, vec![2.0, 1.0, f64::NAN]),
+                (1000, vec![4.0, 5.0, 100.0]),
+            ],
+        )
+        .unwrap();
+
+        let winners = bench.winners();
+        assert_eq!(winners.len(), 2);
+        assert_eq!(winners[0].function, 1);
+        assert_eq!(winners[0].runner_up, Some((0, 2.0)));
+        assert_eq!(winners[1].function, 0);
+        assert_eq!(winners[1].runner_up, Some((1, 1.25)));
+        assert_eq!(
+            bench.winners_summary(),
+            "n=10: Quick Sort (2.00x faster than Merge Sort)\n\
+             n=1000: Merge Sort (1.25x faster than Quick Sort)\n"
+        );
+
+        // Bubble Sort was not timed at n=10, so only n=1000 is ranked.
+        assert_eq!(bench.ranked_sizes(), [1]);
+        let ranking = bench.ranking();
+        assert_eq!(
+            ranking.iter().map(|&(f, _)| f).collect::<Vec<_>>(),
+            [0, 1, 2]
+        );
+        assert_eq!(ranking[0].1, 1.0);
+        assert!((ranking[1].1 - 1.25).abs() < 1e-12);
+        assert!((ranking[2].1 - 25.0).abs() < 1e-12);
+
+        assert_eq!(
+            bench.verdict().unwrap(),
+            "Merge Sort is fastest at n=1000: 1.25x faster than Quick Sort, \
+             25.00x faster than Bubble Sort"
+        );
+        let speedups = bench.speedup_matrix();
+        assert_eq!(speedups[0][0], 1.0);
+        assert!((speedups[1][0] - 1.6f64.sqrt()).abs() < 1e-12);
+        assert!((speedups[0][1] - 1.0 / 1.6f64.sqrt()).abs() < 1e-12);
+        assert!((speedups[0][2] - 25.0).abs() < 1e-12);
+        assert!((speedups[2][1] - 0.05).abs() < 1e-12);
+
+        bench.data = BenchData::from_rows(
+            3,
+            [
+                (10, vec![2.0, f64::NAN, f64::NAN]),
+                (1000, vec![f64::NAN; 3]),
+            ],
+        )
+        .unwrap();
+        assert_eq!(bench.verdict().unwrap(), "Merge Sort is fastest at n=10");
+        assert!(bench.speedup_matrix()[0][1].is_nan());
+        bench.data = BenchData::new(3);
+        assert_eq!(bench.verdict(), None);
+    }
+
+    #[test]
+    fn test_ranking_capped_function() {
+        let functions: Vec<BenchFnNamed<'static, usize, usize>> = vec![
+            (Box::new(|x| x), "Merge Sort"),
+            (Box::new(|x| x), "Quick Sort"),
+            (Box::new(|x| x), "Bubble Sort"),
+            (Box::new(|x| x), "Bogo Sort"),
+        ];
+        let argfunc: BenchFnArg<usize> = Box::new(|x| x);
+        let mut bench =
+            BenchBuilder::new(functions, argfunc, vec![10, 100, 1000])
+                .max_size_for("Bubble Sort", 100)
+                .build()
+                .unwrap();
+        // Bubble Sort is fastest at the smallest size and is not timed at
+        // the largest, where Merge Sort would otherwise gain 1.33x on Quick
+        // Sort; Bogo Sort is never timed.
+        bench.data = BenchData::from_rows(
+            4,
+            [
+                (10, vec![2.0, 4.0, 1.0, f64::NAN]),
+                (100, vec![20.0, 30.0, 50.0, f64::NAN]),
+                (1000, vec![200.0, 150.0, f64::NAN, f64::NAN]),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(bench.ranked_sizes(), [0, 1]);
+        let ranking = bench.ranking();
+        assert_eq!(
+            ranking.iter().map(|&(f, _)| f).collect::<Vec<_>>(),
+            [0, 2, 1, 3]
+        );
+        assert!((ranking[0].1 - 2f64.sqrt()).abs() < 1e-12);
+        assert!((ranking[1].1 - 2.5f64.sqrt()).abs() < 1e-12);
+        assert!((ranking[2].1 - 6f64.sqrt()).abs() < 1e-12);
+        assert!(ranking[3].1.is_nan());
+
+        // Without an input size timed for every function, none is ranked.
+        bench.data = BenchData::from_rows(
+            4,
+            [
+                (10, vec![2.0, f64::NAN, 1.0, f64::NAN]),
+                (100, vec![20.0, 30.0, f64::NAN, f64::NAN]),
+            ],
+        )
+        .unwrap();
+        assert!(bench.ranked_sizes().is_empty());
+        assert!(bench.ranking().iter().all(|(_, ratio)| ratio.is_nan()));
+    }
+}
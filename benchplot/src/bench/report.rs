@@ -0,0 +1,178 @@
+/*
+Copyright 2024 Owain Davies
+SPDX-License-Identifier: Apache-2.0 OR MIT
+*/
+
+use crate::Baseline;
+use std::io;
+
+/// A hook invoked with the results at the end of every run, e.g., to feed a
+/// central performance dashboard directly from a benchmark binary.
+///
+/// Closures taking a [`Baseline`] and returning an [`io::Result`] are
+/// reporters too.
+///
+/// ```
+/// use benchplot::{BenchBuilder, BenchFnArg, BenchFnNamed};
+///
+/// let functions: Vec<BenchFnNamed<usize, usize>> =
+///     vec![(Box::new(|x| x * 2), "Double")];
+/// let argfunc: BenchFnArg<usize> = Box::new(|x| x);
+/// let mut bench = BenchBuilder::new(functions, argfunc, vec![10, 100])
+///     .reporter(|results: &benchplot::Baseline| {
+///         println!("{}", results.to_json());
+///         Ok(())
+///     })
+///     .build()
+///     .unwrap();
+/// bench.run();
+/// ```
+pub trait Reporter: Send + Sync {
+    /// Reports the results of a run, labeled with the label of the
+    /// benchmark, if any.
+    fn report(&self, results: &Baseline) -> io::Result<()>;
+}
+
+impl<F> Reporter for F
+where
+    F: Fn(&Baseline) -> io::Result<()> + Send + Sync,
+{
+    fn report(&self, results: &Baseline) -> io::Result<()> {
+        self(results)
+    }
+}
+
+/// A [`Reporter`] sending the results of every run, rendered by
+/// [`Baseline::to_json`], in the body of a POST request to an HTTP endpoint.
+///
+/// ```no_run
+/// use benchplot::HttpReporter;
+///
+/// let reporter = HttpReporter::new("https://perf.example.com/api/results")
+///     .header("Authorization", "Bearer <token>");
+/// ```
+#[cfg(feature = "upload")]
+#[derive(Debug, Clone)]
+pub struct HttpReporter {
+    url: String,
+    headers: Vec<(String, String)>,
+}
+
+#[cfg(feature = "upload")]
+impl HttpReporter {
+    /// Creates a reporter posting to `url`.
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            headers: Vec::new(),
+        }
+    }
+
+    /// Adds a header to the request, e.g., to authenticate with the
+    /// endpoint.
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+}
+
+#[cfg(feature = "upload")]
+impl Reporter for HttpReporter {
+    /// Posts the results, failing if the request cannot be sent or the
+    /// endpoint responds with an error status.
+    fn report(&self, results: &Baseline) -> io::Result<()> {
+        let mut request = ureq::post(&self.url);
+        for (name, value) in &self.headers {
+            request = request.header(name, value);
+        }
+        request
+            .content_type("application/json")
+            .send(results.to_json())
+            .map(|_| ())
+            .map_err(io::Error::other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BenchBuilder, BenchFnArg, BenchFnNamed};
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_reporters_are_invoked_after_run() {
+        let reported = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&reported);
+
+        let functions: Vec<BenchFnNamed<'static, usize, usize>> =
+            vec![(Box::new(|x| x * 2), "Double")];
+        let argfunc: BenchFnArg<usize> = Box::new(|x| x);
+        let mut bench = BenchBuilder::new(functions, argfunc, vec![10, 100])
+            .label("v0.1")
+            .reporter(move |results: &Baseline| {
+                sink.lock().unwrap().push(results.clone());
+                Ok(())
+            })
+            .reporter(|_: &Baseline| Err(io::Error::other("unreachable")))
+            .build()
+            .unwrap();
+        bench.run();
+        bench.run();
+
+        let reported = reported.lock().unwrap();
+        assert_eq!(reported.len(), 2);
+        assert_eq!(reported[1], bench.baseline());
+        assert_eq!(reported[1].label(), Some("v0.1"));
+    }
+
+    #[cfg(feature = "upload")]
+    #[test]
+    fn test_http_reporter() {
+        use std::io::{BufRead, BufReader, Read, Write};
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/results", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut head = Vec::new();
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line == "\r\n" {
+                    break;
+                }
+                if let Some(length) =
+                    line.to_ascii_lowercase().strip_prefix("content-length:")
+                {
+                    content_length = length.trim().parse().unwrap();
+                }
+                head.push(line);
+            }
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+            reader
+                .get_mut()
+                .write_all(b"HTTP/1.1 204 No Content\r\n\r\n")
+                .unwrap();
+            (head, String::from_utf8(body).unwrap())
+        });
+
+        let results = Baseline::new(
+            vec!["Double".to_string()],
+            crate::BenchData::from_rows(1, [(10, vec![0.5])]).unwrap(),
+        );
+        HttpReporter::new(&url)
+            .header("Authorization", "Bearer secret")
+            .report(&results)
+            .unwrap();
+
+        let (head, body) = server.join().unwrap();
+        assert!(head[0].starts_with("POST /results "));
+        assert!(head.iter().any(|line| line
+            .eq_ignore_ascii_case("authorization: bearer secret\r\n")));
+        assert_eq!(body, results.to_json());
+    }
+}
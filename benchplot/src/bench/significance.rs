@@ -0,0 +1,222 @@
+/*
+Copyright 2024 Owain Davies
+SPDX-License-Identifier: Apache-2.0 OR MIT
+*/
+
+use crate::{Bench, Threading};
+
+/// The results of pairwise tests of whether the timings of the functions at
+/// an input size differ.
+///
+/// Each pair of functions is compared with a two-sided Mann-Whitney U test,
+/// which makes no assumption about how the timings are distributed. The
+/// p-values of an input size are adjusted for multiple comparisons with the
+/// Holm-Bonferroni method, so that the chance of any false claim at the input
+/// size is at most the chosen significance level.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SignificanceMatrix {
+    /// The input size the functions are compared at.
+    pub size: usize,
+
+    /// The adjusted p-value of the comparison of each function with each
+    /// other function, indexed by function index, in the order of
+    /// [`Bench::function_names`].
+    ///
+    /// The matrix is symmetric with ones on the diagonal. A p-value is `NaN`
+    /// if the timings of either function are unavailable.
+    #[cfg_attr(
+        feature = "serde",
+        serde(deserialize_with = "crate::util::deserialize_nan_matrix")
+    )]
+    pub p_values: Vec<Vec<f64>>,
+}
+
+impl SignificanceMatrix {
+    /// Returns `true` if the timings of the functions at `a` and `b` differ
+    /// at the significance level `alpha`, e.g., `0.05`.
+    ///
+    /// Which of the two is faster can be read off [`Bench::data`].
+    pub fn is_significant(&self, a: usize, b: usize, alpha: f64) -> bool {
+        self.p_values[a][b] < alpha
+    }
+}
+
+impl<T, R, M: Threading> Bench<'_, T, R, M> {
+    /// Returns, for each measured input size, the significance of the
+    /// differences between the timings of every pair of functions, so that
+    /// claims such as "A is faster than B" can be backed with statistics.
+    ///
+    /// The tests use the timings of every repetition, which are only kept if
+    /// [`BenchBuilder::keep_samples`](crate::BenchBuilder::keep_samples) is
+    /// set; otherwise, every p-value is `NaN`. They have little power with
+    /// fewer than about 5 repetitions.
+    pub fn significance_matrix(&self) -> Vec<SignificanceMatrix> {
+        let num_functions = self.functions.len();
+        self.data
+            .iter()
+            .map(|(size, _)| {
+                let mut p_values: Vec<Vec<f64>> = (0..num_functions)
+                    .map(|a| {
+                        (0..num_functions)
+                            .map(|b| if a == b { 1.0 } else { f64::NAN })
+                            .collect()
+                    })
+                    .collect();
+                let mut pairs = Vec::new();
+                for a in 0..num_functions {
+                    for b in a + 1..num_functions {
+                        if let (Some(x), Some(y)) =
+                            (self.samples_of(size, a), self.samples_of(size, b))
+                        {
+                            pairs.push(((a, b), mann_whitney(x, y)));
+                        }
+                    }
+                }
+
+                let raw: Vec<f64> = pairs.iter().map(|&(_, p)| p).collect();
+                for (&((a, b), _), p) in pairs.iter().zip(holm(&raw)) {
+                    p_values[a][b] = p;
+                    p_values[b][a] = p;
+                }
+                SignificanceMatrix { size, p_values }
+            })
+            .collect()
+    }
+}
+
+/// Returns the two-sided p-value of the Mann-Whitney U test of whether `x`
+/// and `y` come from the same distribution.
+///
+/// The normal approximation of the distribution of U is used, with
+/// corrections for ties and continuity.
+pub(crate) fn mann_whitney(x: &[f64], y: &[f64]) -> f64 {
+    let (n1, n2) = (x.len() as f64, y.len() as f64);
+    if x.is_empty() || y.is_empty() {
+        return f64::NAN;
+    }
+
+    // Rank the pooled samples, giving tied values their average rank.
+    let mut pooled: Vec<(f64, bool)> = x
+        .iter()
+        .map(|&t| (t, true))
+        .chain(y.iter().map(|&t| (t, false)))
+        .collect();
+    pooled.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let mut rank_sum_x = 0.0;
+    let mut tie_term = 0.0;
+    let mut start = 0;
+    while start < pooled.len() {
+        let end = start
+            + pooled[start..]
+                .iter()
+                .take_while(|&&(t, _)| t == pooled[start].0)
+                .count();
+        let rank = (start + end + 1) as f64 / 2.0;
+        let tied = (end - start) as f64;
+        tie_term += tied.powi(3) - tied;
+        rank_sum_x += rank
+            * pooled[start..end].iter().filter(|&&(_, in_x)| in_x).count()
+                as f64;
+        start = end;
+    }
+
+    let u = rank_sum_x - n1 * (n1 + 1.0) / 2.0;
+    let n = n1 + n2;
+    let mean = n1 * n2 / 2.0;
+    let variance = n1 * n2 / 12.0 * ((n + 1.0) - tie_term / (n * (n - 1.0)));
+    if variance <= 0.0 {
+        // Every timing is the same.
+        return 1.0;
+    }
+    let z = ((u - mean).abs() - 0.5).max(0.0) / variance.sqrt();
+    erfc(z / std::f64::consts::SQRT_2).min(1.0)
+}
+
+/// Adjusts `p_values` for multiple comparisons with the Holm-Bonferroni
+/// method, returning them in the same order.
+fn holm(p_values: &[f64]) -> Vec<f64> {
+    let m = p_values.len();
+    let mut order: Vec<usize> = (0..m).collect();
+    order.sort_by(|&a, &b| p_values[a].total_cmp(&p_values[b]));
+
+    let mut adjusted = vec![0.0; m];
+    let mut running_max: f64 = 0.0;
+    for (rank, &idx) in order.iter().enumerate() {
+        let scaled = ((m - rank) as f64 * p_values[idx]).min(1.0);
+        running_max = running_max.max(scaled);
+        adjusted[idx] = running_max;
+    }
+    adjusted
+}
+
+/// Returns the complementary error function of `x`, with an absolute error of
+/// less than 1.2e-7 (Numerical Recipes, `erfcc`).
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let poly = -z * z - 1.265_512_23
+        + t * (1.000_023_68
+            + t * (0.374_091_96
+                + t * (0.096_784_18
+                    + t * (-0.186_288_06
+                        + t * (0.278_868_07
+                            + t * (-1.135_203_98
+                                + t * (1.488_515_87
+                                    + t * (-0.822_152_23
+                                        + t * 0.170_872_77))))))));
+    let value = t * poly.exp();
+    if x >= 0.0 {
+        value
+    } else {
+        2.0 - value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_erfc() {
+        assert!((erfc(0.0) - 1.0).abs() < 1e-6);
+        assert!((erfc(1.0) - 0.157_299_207).abs() < 1e-6);
+        assert!((erfc(-1.0) - 1.842_700_793).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_mann_whitney() {
+        let x: Vec<f64> = (0..10).map(f64::from).collect();
+        let y: Vec<f64> = (20..30).map(f64::from).collect();
+        assert!(mann_whitney(&x, &y) < 0.001);
+        assert!(mann_whitney(&x, &x) > 0.9);
+        assert_eq!(mann_whitney(&[1.0; 4], &[1.0; 4]), 1.0);
+        assert!(mann_whitney(&x, &[]).is_nan());
+    }
+
+    #[test]
+    fn test_holm() {
+        let adjusted = holm(&[0.01, 0.04, 0.03]);
+        for (p, expected) in adjusted.into_iter().zip([0.03, 0.06, 0.06]) {
+            assert!((p - expected).abs() < 1e-12);
+        }
+        assert_eq!(holm(&[0.5, 0.9]), vec![1.0, 1.0]);
+        assert!(holm(&[]).is_empty());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let matrix = SignificanceMatrix {
+            size: 10,
+            p_values: vec![vec![1.0, f64::NAN], vec![f64::NAN, 1.0]],
+        };
+        let json = serde_json::to_string(&matrix).unwrap();
+        assert_eq!(json, r#"{"size":10,"p_values":[[1.0,null],[null,1.0]]}"#);
+
+        let loaded: SignificanceMatrix = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.size, 10);
+        assert_eq!(loaded.p_values[0][0], 1.0);
+        assert!(loaded.p_values[0][1].is_nan());
+    }
+}
@@ -0,0 +1,139 @@
+/*
+Copyright 2024 Owain Davies
+SPDX-License-Identifier: Apache-2.0 OR MIT
+*/
+
+/// How the curves of a plot are smoothed, set with
+/// [`PlotBuilder::smoothing`](crate::PlotBuilder::smoothing), to tame the
+/// jitter of densely sampled input sizes.
+///
+/// Values are smoothed over their neighbors in the order of the measured
+/// input sizes, and on the logarithm of the values, as the y-axis is
+/// logarithmic.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Smoothing {
+    /// The geometric mean of each value and of the values at the given
+    /// number of measured input sizes on each side of it, or fewer at the
+    /// ends of the curve.
+    MovingAverage(usize),
+
+    /// Locally weighted linear regression (LOESS) over the given fraction of
+    /// the values nearest to each value, with tricube weights. The fraction
+    /// must be positive and at most 1; larger fractions smooth more.
+    Loess(f64),
+}
+
+impl Smoothing {
+    /// Returns whether the parameter of the smoothing is valid.
+    pub(crate) fn is_valid(&self) -> bool {
+        match *self {
+            Smoothing::MovingAverage(_) => true,
+            Smoothing::Loess(span) => span > 0.0 && span <= 1.0,
+        }
+    }
+
+    /// Returns `points` with their y-coordinates, which must be positive,
+    /// smoothed.
+    pub(crate) fn smooth(&self, points: &[(f64, f64)]) -> Vec<(f64, f64)> {
+        let logs: Vec<f64> = points.iter().map(|&(_, y)| y.ln()).collect();
+        let n = points.len();
+        points
+            .iter()
+            .enumerate()
+            .map(|(i, &(x, _))| {
+                let log = match *self {
+                    Smoothing::MovingAverage(k) => {
+                        let window =
+                            &logs[i.saturating_sub(k)..n.min(i + k + 1)];
+                        window.iter().sum::<f64>() / window.len() as f64
+                    }
+                    Smoothing::Loess(span) => loess_at(&logs, i, span),
+                };
+                (x, log.exp())
+            })
+            .collect()
+    }
+}
+
+/// Returns the value at index `i` of the local linear fit of `values`
+/// against their indices, weighting the fraction `span` of the values
+/// nearest to `i` by the tricube of their distance.
+fn loess_at(values: &[f64], i: usize, span: f64) -> f64 {
+    let n = values.len();
+    let q = ((span * n as f64).ceil() as usize).clamp(1, n);
+    let mut distances: Vec<usize> = (0..n).map(|j| j.abs_diff(i)).collect();
+    distances.sort_unstable();
+    // One more than the distance of the farthest neighbor, so that every
+    // neighbor has a positive weight.
+    let bandwidth = (distances[q - 1] + 1) as f64;
+
+    let weights: Vec<f64> = (0..n)
+        .map(|j| {
+            let u = j.abs_diff(i) as f64 / bandwidth;
+            if u < 1.0 {
+                (1.0 - u.powi(3)).powi(3)
+            } else {
+                0.0
+            }
+        })
+        .collect();
+    let (mut sum_w, mut sum_x, mut sum_y) = (0.0, 0.0, 0.0);
+    for (j, (&w, &y)) in weights.iter().zip(values).enumerate() {
+        sum_w += w;
+        sum_x += w * j as f64;
+        sum_y += w * y;
+    }
+    let (mean_x, mean_y) = (sum_x / sum_w, sum_y / sum_w);
+    let (mut covariance, mut variance) = (0.0, 0.0);
+    for (j, (&w, &y)) in weights.iter().zip(values).enumerate() {
+        covariance += w * (j as f64 - mean_x) * (y - mean_y);
+        variance += w * (j as f64 - mean_x).powi(2);
+    }
+    if variance > 0.0 {
+        mean_y + covariance / variance * (i as f64 - mean_x)
+    } else {
+        mean_y
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_smooth() {
+        let points = [(1.0, 1.0), (2.0, 100.0), (3.0, 1.0), (4.0, 1.0)];
+        let smoothed = Smoothing::MovingAverage(1).smooth(&points);
+        let expected = [10.0, 100f64.powf(1.0 / 3.0), 100f64.powf(1.0 / 3.0)];
+        for (&(_, y), expected) in smoothed.iter().zip(expected) {
+            assert!((y - expected).abs() < 1e-9);
+        }
+        assert_eq!(smoothed[3], (4.0, 1.0));
+        for (&(x, y), &point) in Smoothing::MovingAverage(0)
+            .smooth(&points)
+            .iter()
+            .zip(&points)
+        {
+            assert_eq!(x, point.0);
+            assert!((y - point.1).abs() < 1e-9 * point.1);
+        }
+
+        // A power law is a straight line in log space, which LOESS keeps.
+        let power_law: Vec<(f64, f64)> =
+            (0..10).map(|i| (i as f64, 2f64.powi(i))).collect();
+        for (&(x, y), &(_, expected)) in Smoothing::Loess(0.5)
+            .smooth(&power_law)
+            .iter()
+            .zip(&power_law)
+        {
+            assert!((y - expected).abs() < 1e-9 * expected, "{}", x);
+        }
+        let single = Smoothing::Loess(1.0).smooth(&[(1.0, 3.0)]);
+        assert!((single[0].1 - 3.0).abs() < 1e-12);
+
+        assert!(Smoothing::Loess(0.3).is_valid());
+        assert!(!Smoothing::Loess(0.0).is_valid());
+        assert!(!Smoothing::Loess(1.5).is_valid());
+        assert!(!Smoothing::Loess(f64::NAN).is_valid());
+    }
+}
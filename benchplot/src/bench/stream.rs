@@ -0,0 +1,254 @@
+/*
+Copyright 2024 Owain Davies
+SPDX-License-Identifier: Apache-2.0 OR MIT
+*/
+
+//! Support for streaming the timings of each `(input size, function)` pair to
+//! a results file, and to a results database with the `sqlite` feature, as
+//! soon as the pair has been timed.
+//!
+//! The file is in the JSON Lines format, with one object per pair:
+//!
+//! ```text
+//! {"size":1024,"function":"Merge Sort","mean":0.0000151,"times":[0.0000152,0.000015]}
+//! ```
+
+use super::aggregation::to_nanos;
+#[cfg(feature = "sqlite")]
+use super::database::ResultsDatabase;
+use super::json::{self, Value};
+use crate::Aggregation;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, ErrorKind, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Sender;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Event sent by [`Bench::run_streaming`](crate::Bench::run_streaming) as
+/// soon as an `(input size, function)` pair has been timed.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CellEvent {
+    /// The input size.
+    pub size: usize,
+
+    /// The index of the function in the functions vector.
+    pub function: usize,
+
+    /// The benchmark value of the pair, combined from `times` with the
+    /// aggregation of the benchmark.
+    pub value: Duration,
+
+    /// The timing of each repetition.
+    pub times: Vec<Duration>,
+}
+
+/// Appends the timings of each `(input size, function)` pair to a results
+/// file and a results database, if any, and sends them as events to a
+/// receiver, if any.
+#[derive(Default)]
+pub(crate) struct ResultStream {
+    file: Option<Mutex<File>>,
+    #[cfg(feature = "sqlite")]
+    database: Option<Mutex<ResultsDatabase>>,
+    events: Option<Mutex<Sender<CellEvent>>>,
+    aborted: AtomicBool,
+}
+
+impl ResultStream {
+    /// Creates the results file, truncating it if it exists.
+    pub(crate) fn create_file(&mut self, path: &Path) -> io::Result<()> {
+        self.file = Some(Mutex::new(File::create(path)?));
+        Ok(())
+    }
+
+    /// Opens the results database and records the start of a run labeled
+    /// `label`.
+    #[cfg(feature = "sqlite")]
+    pub(crate) fn open_database(
+        &mut self,
+        path: &Path,
+        label: Option<&str>,
+    ) -> rusqlite::Result<()> {
+        self.database = Some(Mutex::new(ResultsDatabase::open(path, label)?));
+        Ok(())
+    }
+
+    /// Sets the sender to send an event to as each pair completes.
+    pub(crate) fn set_events(&mut self, events: Sender<CellEvent>) {
+        self.events = Some(Mutex::new(events));
+    }
+
+    /// Returns `true` if there is neither a results file, a results database,
+    /// nor a receiver of events.
+    pub(crate) fn is_empty(&self) -> bool {
+        #[cfg(feature = "sqlite")]
+        if self.database.is_some() {
+            return false;
+        }
+        self.file.is_none() && self.events.is_none()
+    }
+
+    /// Sends `event` to the receiver, if any.
+    ///
+    /// If the receiver has been dropped, the stream is marked as aborted
+    /// instead.
+    pub(crate) fn send_event(&self, event: CellEvent) {
+        if let Some(events) = &self.events {
+            let events = events.lock().unwrap_or_else(|err| err.into_inner());
+            if events.send(event).is_err() {
+                self.aborted.store(true, Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Returns `true` if the receiver of events has been dropped, so the run
+    /// should stop.
+    pub(crate) fn is_aborted(&self) -> bool {
+        self.aborted.load(Ordering::Relaxed)
+    }
+
+    /// Records the timings of a pair.
+    pub(crate) fn write_cell(
+        &self,
+        size: usize,
+        function: &str,
+        times: &[f64],
+    ) -> io::Result<()> {
+        if let Some(file) = &self.file {
+            let mut file = file.lock().unwrap_or_else(|err| err.into_inner());
+            write_line(&mut file, size, function, times)?;
+        }
+        #[cfg(feature = "sqlite")]
+        if let Some(database) = &self.database {
+            let mut database =
+                database.lock().unwrap_or_else(|err| err.into_inner());
+            database
+                .insert_cell(size, function, times)
+                .map_err(io::Error::other)?;
+        }
+        Ok(())
+    }
+}
+
+/// Appends a line with the timings of a pair to the results file.
+///
+/// Each line is written with a single write, so that the lines of pairs timed
+/// concurrently are not interleaved and the file holds every completed pair if
+/// the benchmark is interrupted.
+fn write_line(
+    file: &mut File,
+    size: usize,
+    function: &str,
+    times: &[f64],
+) -> io::Result<()> {
+    // The mean is taken as for the timings of a run, on the nanoseconds.
+    let mean = Aggregation::Mean.aggregate_nanos(&to_nanos(times));
+    let times: Vec<String> = times.iter().map(f64::to_string).collect();
+    let line = format!(
+        "{{\"size\":{},\"function\":{},\"mean\":{},\"times\":[{}]}}\n",
+        size,
+        json::string(function),
+        mean,
+        times.join(",")
+    );
+    file.write_all(line.as_bytes())
+}
+
+/// A line of a results file.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct StreamedCell {
+    pub(crate) size: usize,
+    pub(crate) function: String,
+    pub(crate) mean: f64,
+    pub(crate) times: Vec<f64>,
+}
+
+/// Reads the size, function name, and timings of every pair in a results
+/// file.
+pub(crate) fn read_cells(path: &Path) -> io::Result<Vec<StreamedCell>> {
+    BufReader::new(File::open(path)?)
+        .lines()
+        .filter(|line| !matches!(line, Ok(line) if line.is_empty()))
+        .map(|line| {
+            let line = line?;
+            parse_cell(&line).ok_or_else(|| {
+                io::Error::new(
+                    ErrorKind::InvalidData,
+                    format!("invalid results line: {}", line),
+                )
+            })
+        })
+        .collect()
+}
+
+fn parse_cell(line: &str) -> Option<StreamedCell> {
+    let cell = json::parse(line)?;
+    Some(StreamedCell {
+        size: cell.get("size")?.as_integer()?,
+        function: cell.get("function")?.as_str()?.to_string(),
+        mean: cell.get("mean")?.as_f64()?,
+        times: cell
+            .get("times")?
+            .as_array()?
+            .iter()
+            .map(Value::as_f64)
+            .collect::<Option<_>>()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_and_read_cells() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("results.jsonl");
+
+        let mut stream = ResultStream::default();
+        stream.create_file(&path).unwrap();
+        stream.write_cell(10, "Merge Sort", &[1.0, 3.0]).unwrap();
+        stream.write_cell(20, "Quote \" and \\ \n", &[0.5]).unwrap();
+
+        assert_eq!(
+            read_cells(&path).unwrap(),
+            [
+                StreamedCell {
+                    size: 10,
+                    function: "Merge Sort".to_string(),
+                    mean: 2.0,
+                    times: vec![1.0, 3.0],
+                },
+                StreamedCell {
+                    size: 20,
+                    function: "Quote \" and \\ \n".to_string(),
+                    mean: 0.5,
+                    times: vec![0.5],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_cell() {
+        assert_eq!(
+            parse_cell(
+                r#"{"size":8,"function":"A\u0001","mean":1.5,"times":[1.5]}"#
+            ),
+            Some(StreamedCell {
+                size: 8,
+                function: "A\u{1}".to_string(),
+                mean: 1.5,
+                times: vec![1.5],
+            })
+        );
+        assert_eq!(parse_cell(r#"{"size":8}"#), None);
+        assert_eq!(
+            parse_cell(r#"{"size":x,"function":"A","mean":1,"times":[1]}"#),
+            None
+        );
+    }
+}
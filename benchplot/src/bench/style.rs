@@ -0,0 +1,344 @@
+/*
+Copyright 2024 Owain Davies
+SPDX-License-Identifier: Apache-2.0 OR MIT
+*/
+
+use crate::{LegendPosition, TitleAlignment};
+use plotters::style::RGBColor;
+use std::io::{self, ErrorKind};
+use std::path::Path;
+
+/// A reusable plot style, e.g., the standard chart style of an organization,
+/// applied to a plot with [`PlotBuilder::style`](crate::PlotBuilder::style).
+///
+/// Only the fields that are set override the configuration of the plot. A
+/// style can be built in code or loaded from a template file, either in TOML
+/// with one `key = value` pair per line:
+///
+/// ```toml
+/// # Style of the charts in our reports.
+/// width = 1200
+/// height = 800
+/// font_family = "Helvetica"
+/// title_color = "#202020"
+/// title_alignment = "left"
+/// legend_position = "right"
+/// palette = ["#1F77B4", "#FF7F0E", "#2CA02C"]
+/// ```
+///
+/// or as a JSON object with the same keys:
+///
+/// ```json
+/// {"width": 1200, "height": 800, "palette": ["#1F77B4", "#FF7F0E"]}
+/// ```
+///
+/// The keys are `width`, `height`, `margin`, `font_family`, `title_color`,
+/// `title_font_size`, `title_alignment` (`left`, `center`, or `right`),
+/// `legend_position` (`inside`, `right`, or `bottom`), and `palette`. Colors
+/// are written as `#RRGGBB`. Nested tables and objects are not supported.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PlotStyle {
+    /// Width of the plot in pixels.
+    pub width: Option<u32>,
+
+    /// Height of the plot in pixels.
+    pub height: Option<u32>,
+
+    /// Margin in pixels around the chart.
+    pub margin: Option<u32>,
+
+    /// Font family of all text on the plot.
+    pub font_family: Option<String>,
+
+    /// Color of the title.
+    pub title_color: Option<RGBColor>,
+
+    /// Font size of the title in points.
+    pub title_font_size: Option<u32>,
+
+    /// Horizontal alignment of the title.
+    pub title_alignment: Option<TitleAlignment>,
+
+    /// Where the legend is drawn.
+    pub legend_position: Option<LegendPosition>,
+
+    /// Colors of the functions' lines.
+    pub palette: Option<Vec<RGBColor>>,
+}
+
+/// A value in a style template.
+#[derive(Debug, PartialEq)]
+enum Value {
+    String(String),
+    Integer(u32),
+    Array(Vec<Value>),
+}
+
+impl PlotStyle {
+    /// Loads a style from a TOML or JSON template file.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    /// Parses a style from a TOML or JSON template. JSON is recognized by the
+    /// opening brace of its object.
+    pub fn parse(template: &str) -> io::Result<Self> {
+        let mut style = Self::default();
+        for (key, value) in entries(template)? {
+            style.set(&key, value)?;
+        }
+        Ok(style)
+    }
+
+    /// Sets the field named `key` to `value`.
+    fn set(&mut self, key: &str, value: Value) -> io::Result<()> {
+        let mismatch = || invalid(format!("invalid value for `{}`", key));
+        let integer = |value: Value| match value {
+            Value::Integer(value) => Ok(value),
+            _ => Err(mismatch()),
+        };
+        let string = |value: Value| match value {
+            Value::String(value) => Ok(value),
+            _ => Err(mismatch()),
+        };
+        match key {
+            "width" => self.width = Some(integer(value)?),
+            "height" => self.height = Some(integer(value)?),
+            "margin" => self.margin = Some(integer(value)?),
+            "font_family" => self.font_family = Some(string(value)?),
+            "title_color" => {
+                self.title_color =
+                    Some(parse_color(&string(value)?).ok_or_else(mismatch)?);
+            }
+            "title_font_size" => self.title_font_size = Some(integer(value)?),
+            "title_alignment" => {
+                self.title_alignment = Some(match string(value)?.as_str() {
+                    "left" => TitleAlignment::Left,
+                    "center" => TitleAlignment::Center,
+                    "right" => TitleAlignment::Right,
+                    _ => return Err(mismatch()),
+                });
+            }
+            "legend_position" => {
+                self.legend_position = Some(match string(value)?.as_str() {
+                    "inside" => LegendPosition::Inside,
+                    "right" => LegendPosition::Right,
+                    "bottom" => LegendPosition::Bottom,
+                    _ => return Err(mismatch()),
+                });
+            }
+            "palette" => {
+                let Value::Array(colors) = value else {
+                    return Err(mismatch());
+                };
+                self.palette = Some(
+                    colors
+                        .into_iter()
+                        .map(|color| {
+                            string(color)
+                                .ok()
+                                .and_then(|color| parse_color(&color))
+                                .ok_or_else(mismatch)
+                        })
+                        .collect::<io::Result<_>>()?,
+                );
+            }
+            _ => return Err(invalid(format!("unknown style key `{}`", key))),
+        }
+        Ok(())
+    }
+}
+
+fn invalid(msg: String) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, msg)
+}
+
+/// Returns the key-value pairs of a TOML or JSON template, in order.
+fn entries(template: &str) -> io::Result<Vec<(String, Value)>> {
+    let trimmed = template.trim();
+    if let Some(body) = trimmed.strip_prefix('{') {
+        let body = body
+            .strip_suffix('}')
+            .ok_or_else(|| invalid("unterminated JSON object".to_string()))?;
+        split_top_level(body, ',')
+            .into_iter()
+            .filter(|entry| !entry.trim().is_empty())
+            .map(|entry| {
+                let (key, value) = split_once_top_level(entry, ':')
+                    .ok_or_else(|| {
+                        invalid(format!(
+                            "invalid JSON entry `{}`",
+                            entry.trim()
+                        ))
+                    })?;
+                let Value::String(key) = parse_value(key)? else {
+                    return Err(invalid(format!("invalid JSON key `{}`", key)));
+                };
+                Ok((key, parse_value(value)?))
+            })
+            .collect()
+    } else {
+        template
+            .lines()
+            .map(strip_comment)
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                let (key, value) =
+                    split_once_top_level(line, '=').ok_or_else(|| {
+                        invalid(format!("invalid TOML line `{}`", line.trim()))
+                    })?;
+                Ok((key.trim().to_string(), parse_value(value)?))
+            })
+            .collect()
+    }
+}
+
+/// Parses a quoted string, an unsigned integer, or an array of them.
+fn parse_value(value: &str) -> io::Result<Value> {
+    let value = value.trim();
+    if let Some(inner) = value.strip_prefix('"') {
+        let inner = inner
+            .strip_suffix('"')
+            .ok_or_else(|| invalid(format!("unterminated string {}", value)))?;
+        Ok(Value::String(
+            inner.replace("\\\"", "\"").replace("\\\\", "\\"),
+        ))
+    } else if let Some(inner) = value.strip_prefix('[') {
+        let inner = inner
+            .strip_suffix(']')
+            .ok_or_else(|| invalid(format!("unterminated array {}", value)))?;
+        split_top_level(inner, ',')
+            .into_iter()
+            .filter(|item| !item.trim().is_empty())
+            .map(parse_value)
+            .collect::<io::Result<_>>()
+            .map(Value::Array)
+    } else {
+        value
+            .parse()
+            .map(Value::Integer)
+            .map_err(|_| invalid(format!("invalid value `{}`", value)))
+    }
+}
+
+/// Removes a `#` comment from a TOML line, unless the `#` is quoted.
+fn strip_comment(line: &str) -> &str {
+    let mut quoted = false;
+    for (idx, c) in line.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '#' if !quoted => return &line[..idx],
+            _ => {}
+        }
+    }
+    line
+}
+
+/// Splits `s` at each `separator` that is neither quoted nor inside brackets.
+fn split_top_level(s: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut rest = s;
+    while let Some((part, tail)) = split_once_top_level(rest, separator) {
+        parts.push(part);
+        rest = tail;
+    }
+    parts.push(rest);
+    parts
+}
+
+/// Splits `s` at the first `separator` that is neither quoted nor inside
+/// brackets.
+fn split_once_top_level(s: &str, separator: char) -> Option<(&str, &str)> {
+    let mut quoted = false;
+    let mut escaped = false;
+    let mut depth = 0usize;
+    for (idx, c) in s.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            '[' if !quoted => depth += 1,
+            ']' if !quoted => depth = depth.saturating_sub(1),
+            _ if c == separator && !quoted && depth == 0 => {
+                return Some((&s[..idx], &s[idx + c.len_utf8()..]));
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Parses a color written as `#RRGGBB`.
+fn parse_color(color: &str) -> Option<RGBColor> {
+    let hex = color.strip_prefix('#')?;
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+    let channel = |idx: usize| u8::from_str_radix(&hex[idx..idx + 2], 16).ok();
+    Some(RGBColor(channel(0)?, channel(2)?, channel(4)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_toml_and_json() {
+        let toml = r##"
+            # Style of the charts in our reports.
+            width = 1200
+            height = 800  # pixels
+            font_family = "Helvetica # Neue"
+            title_color = "#202020"
+            title_alignment = "left"
+            legend_position = "right"
+            palette = ["#1F77B4", "#FF7F0E",]
+        "##;
+        let style = PlotStyle::parse(toml).unwrap();
+        assert_eq!(
+            style,
+            PlotStyle {
+                width: Some(1200),
+                height: Some(800),
+                font_family: Some("Helvetica # Neue".to_string()),
+                title_color: Some(RGBColor(0x20, 0x20, 0x20)),
+                title_alignment: Some(TitleAlignment::Left),
+                legend_position: Some(LegendPosition::Right),
+                palette: Some(vec![
+                    RGBColor(0x1F, 0x77, 0xB4),
+                    RGBColor(0xFF, 0x7F, 0x0E)
+                ]),
+                ..PlotStyle::default()
+            }
+        );
+
+        let json = r##"{
+            "width": 1200, "height": 800,
+            "font_family": "Helvetica # Neue",
+            "title_color": "#202020",
+            "title_alignment": "left",
+            "legend_position": "right",
+            "palette": ["#1F77B4", "#FF7F0E"]
+        }"##;
+        assert_eq!(PlotStyle::parse(json).unwrap(), style);
+        assert_eq!(PlotStyle::parse("").unwrap(), PlotStyle::default());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for template in [
+            "colour = \"#FFFFFF\"",
+            "width = \"wide\"",
+            "width = -1",
+            "title_color = \"#FFF\"",
+            "legend_position = \"top\"",
+            "palette = [\"#FFFFFF\", 1]",
+            "font_family = \"Helvetica",
+            "width",
+            "{\"width\": 1200",
+        ] {
+            let err = PlotStyle::parse(template).unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::InvalidData, "{}", template);
+        }
+    }
+}
@@ -0,0 +1,349 @@
+/*
+Copyright 2024 Owain Davies
+SPDX-License-Identifier: Apache-2.0 OR MIT
+*/
+
+#[cfg(feature = "plot")]
+use crate::bench::plot::present;
+#[cfg(feature = "plot")]
+use crate::util;
+use crate::{Baseline, Bench, BenchBuilderError, Threading};
+#[cfg(feature = "plot")]
+use crate::{PlotBuilder, PlotBuilderError};
+#[cfg(feature = "plot")]
+use plotters::coord::Shift;
+#[cfg(feature = "plot")]
+use plotters::prelude::*;
+use std::fmt::Debug;
+#[cfg(feature = "plot")]
+use std::path::{Path, PathBuf};
+
+/// Width and height in pixels of the plot of each benchmark in a suite.
+#[cfg(feature = "plot")]
+const PANEL_DIMENSIONS: (u32, u32) = (800, 600);
+
+/// A benchmark whose argument and return types are erased, so that benchmarks
+/// of different types can be run and plotted together in a [`BenchSuite`].
+///
+/// This trait is implemented for every [`Bench`] that can be run.
+pub trait Runnable {
+    /// Executes all benchmarks.
+    fn run(&mut self);
+
+    /// Returns whether there are results to plot.
+    fn has_results(&self) -> bool;
+
+    /// Replaces the input sizes to benchmark, for every function, except
+    /// those larger than the cap set for a function with
+    /// [`BenchBuilder::max_size_for`](crate::BenchBuilder::max_size_for).
+    ///
+    /// Fails like [`BenchBuilder::build`](crate::BenchBuilder::build) if
+    /// `sizes` is empty or misses the input size of a profiled pair.
+    fn set_sizes(&mut self, sizes: Vec<usize>)
+        -> Result<(), BenchBuilderError>;
+
+    /// Replaces the minimum number of times each (input size, function) pair
+    /// is timed, raising the maximum number of times if needed.
+    ///
+    /// Fails like [`BenchBuilder::build`](crate::BenchBuilder::build) if
+    /// `repetitions` is 0, or less than 2 with a target precision.
+    fn set_repetitions(
+        &mut self,
+        repetitions: usize,
+    ) -> Result<(), BenchBuilderError>;
+
+    /// Returns the results of the last run as a [`Baseline`].
+    fn baseline(&self) -> Baseline;
+
+    /// Draws a plot of the results with the given title onto `area`, with
+    /// `baselines` overlaid.
+    #[cfg(feature = "plot")]
+    fn draw(
+        &self,
+        area: &DrawingArea<SVGBackend<'_>, Shift>,
+        title: &str,
+        baselines: &[Baseline],
+    ) -> Result<(), PlotBuilderError>;
+}
+
+impl<
+        T: Clone + Send + Sync + 'static,
+        R: Send + Debug + PartialEq + 'static,
+        M: Threading,
+    > Runnable for Bench<'_, T, R, M>
+{
+    fn run(&mut self) {
+        Bench::run(self);
+    }
+
+    fn has_results(&self) -> bool {
+        !self.data.is_empty()
+    }
+
+    fn set_sizes(
+        &mut self,
+        sizes: Vec<usize>,
+    ) -> Result<(), BenchBuilderError> {
+        if sizes.is_empty() {
+            return Err(BenchBuilderError::NoSizes);
+        }
+        if let Some(cell) = self
+            .profiled
+            .iter()
+            .find(|cell| !sizes.contains(&cell.size))
+        {
+            return Err(BenchBuilderError::UnknownProfiledSize(cell.size));
+        }
+        self.function_sizes = self
+            .max_sizes
+            .iter()
+            .map(|max_size| {
+                sizes
+                    .iter()
+                    .copied()
+                    .filter(|&size| max_size.is_none_or(|max| size <= max))
+                    .collect()
+            })
+            .collect();
+        self.sizes = sizes;
+        Ok(())
+    }
+
+    fn set_repetitions(
+        &mut self,
+        repetitions: usize,
+    ) -> Result<(), BenchBuilderError> {
+        if repetitions == 0 {
+            return Err(BenchBuilderError::ZeroRepetitions);
+        }
+        if self.target_precision.is_some() && repetitions < 2 {
+            return Err(BenchBuilderError::TargetPrecisionWithoutRepetitions);
+        }
+        self.repetitions = repetitions;
+        self.max_repetitions = self.max_repetitions.max(repetitions);
+        Ok(())
+    }
+
+    fn baseline(&self) -> Baseline {
+        Bench::baseline(self)
+    }
+
+    #[cfg(feature = "plot")]
+    fn draw(
+        &self,
+        area: &DrawingArea<SVGBackend<'_>, Shift>,
+        title: &str,
+        baselines: &[Baseline],
+    ) -> Result<(), PlotBuilderError> {
+        let plot = baselines
+            .iter()
+            .fold(PlotBuilder::new(self, "").title(title), |plot, baseline| {
+                plot.overlay(baseline)
+            });
+        plot.validate()?;
+        plot.draw_on(area)
+    }
+}
+
+/// A collection of named benchmarks, possibly of different argument and
+/// return types, that are run and plotted together.
+///
+/// ```no_run
+/// use benchplot::{BenchBuilder, BenchFnArg, BenchSuite};
+///
+/// let sum: BenchFnArg<Vec<u64>> = Box::new(|n| (0..n as u64).collect());
+/// let concat: BenchFnArg<String> = Box::new(|n| "x".repeat(n));
+///
+/// let mut suite = BenchSuite::new()
+///     .add(
+///         "Sum",
+///         BenchBuilder::new(Vec::new(), sum, vec![10, 100])
+///             .add_function(|v: Vec<u64>| v.iter().sum::<u64>(), "Iterator")
+///             .build()
+///             .unwrap(),
+///     )
+///     .add(
+///         "Uppercase",
+///         BenchBuilder::new(Vec::new(), concat, vec![10, 100])
+///             .add_function(|s: String| s.to_uppercase(), "to_uppercase")
+///             .build()
+///             .unwrap(),
+///     );
+///
+/// suite.run().plot_combined("suite.svg").unwrap();
+/// ```
+#[derive(Default)]
+pub struct BenchSuite<'a> {
+    pub(crate) benches: Vec<(Box<dyn Runnable + 'a>, String)>,
+}
+
+impl<'a> BenchSuite<'a> {
+    /// Creates an empty `BenchSuite`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a benchmark with the given name, which is used as the title of
+    /// its plot.
+    pub fn add<B: Runnable + 'a>(mut self, name: &str, bench: B) -> Self {
+        self.benches.push((Box::new(bench), name.to_string()));
+        self
+    }
+
+    /// Returns the names of the benchmarks, in the order they were added.
+    pub fn names(&self) -> Vec<&str> {
+        self.benches.iter().map(|(_, name)| name.as_str()).collect()
+    }
+
+    /// Executes all benchmarks, in the order they were added.
+    pub fn run(&mut self) -> &mut Self {
+        for (bench, _) in &mut self.benches {
+            bench.run();
+        }
+        self
+    }
+}
+
+#[cfg(feature = "plot")]
+impl BenchSuite<'_> {
+    /// Creates a single figure with the plots of all benchmarks stacked
+    /// vertically and saves it to a file.
+    pub fn plot_combined<P: AsRef<Path>>(
+        &self,
+        filename: P,
+    ) -> Result<(), PlotBuilderError> {
+        self.check_results()?;
+
+        let (width, height) = PANEL_DIMENSIONS;
+        let path = filename.as_ref();
+        let root =
+            SVGBackend::new(path, (width, height * self.benches.len() as u32))
+                .into_drawing_area();
+        root.fill(&RGBColor(255, 255, 255).mix(0.0))?;
+
+        let panels = root.split_evenly((self.benches.len(), 1));
+        for ((bench, name), panel) in self.benches.iter().zip(&panels) {
+            bench.draw(panel, name, &[])?;
+        }
+        present(&root, path)
+    }
+
+    /// Saves the plot of each benchmark to its own file in the directory
+    /// `dir`, returning the paths of the files.
+    ///
+    /// Each file is named after its benchmark, e.g., `merge_sort.svg` for a
+    /// benchmark named `Merge Sort`.
+    pub fn plot_each<P: AsRef<Path>>(
+        &self,
+        dir: P,
+    ) -> Result<Vec<PathBuf>, PlotBuilderError> {
+        self.plot_each_overlaid(dir, &vec![Vec::new(); self.benches.len()])
+    }
+
+    /// Saves the plot of each benchmark like [`BenchSuite::plot_each`], with
+    /// the baselines at the same position in `overlays` overlaid.
+    pub(crate) fn plot_each_overlaid<P: AsRef<Path>>(
+        &self,
+        dir: P,
+        overlays: &[Vec<Baseline>],
+    ) -> Result<Vec<PathBuf>, PlotBuilderError> {
+        self.check_results()?;
+
+        let mut paths = Vec::with_capacity(self.benches.len());
+        for ((bench, name), baselines) in self.benches.iter().zip(overlays) {
+            let path =
+                dir.as_ref().join(format!("{}.svg", util::file_stem(name)));
+            {
+                let root = SVGBackend::new(&path, PANEL_DIMENSIONS)
+                    .into_drawing_area();
+                root.fill(&RGBColor(255, 255, 255).mix(0.0))?;
+                bench.draw(&root, name, baselines)?;
+                present(&root, &path)?;
+            }
+            paths.push(path);
+        }
+        Ok(paths)
+    }
+
+    /// Returns an error if the suite is empty or any benchmark has no results,
+    /// before any file is created.
+    fn check_results(&self) -> Result<(), PlotBuilderError> {
+        if self.benches.is_empty()
+            || self.benches.iter().any(|(bench, _)| !bench.has_results())
+        {
+            return Err(PlotBuilderError::NoData);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "plot"))]
+mod tests {
+    use super::*;
+    use crate::{BenchBuilder, BenchFnArg};
+    use tempfile::tempdir;
+
+    fn setup_suite() -> BenchSuite<'static> {
+        let numbers: BenchFnArg<usize> = Box::new(|x| x);
+        let strings: BenchFnArg<String> = Box::new(|n| "ab".repeat(n));
+
+        BenchSuite::new()
+            .add(
+                "Arithmetic",
+                BenchBuilder::new(Vec::new(), numbers, vec![10, 100])
+                    .add_function(|x: usize| x * 2, "Double")
+                    .build()
+                    .unwrap(),
+            )
+            .add(
+                "String Length",
+                BenchBuilder::new(Vec::new(), strings, vec![10, 100])
+                    .add_function(|s: String| s.len(), "len")
+                    .add_function(|s: String| s.chars().count(), "chars")
+                    .build()
+                    .unwrap(),
+            )
+    }
+
+    #[test]
+    fn test_plot_combined() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("suite.svg");
+
+        let mut suite = setup_suite();
+        suite.run().plot_combined(&path).unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.contains("Arithmetic"));
+        assert!(content.contains("String Length"));
+    }
+
+    #[test]
+    fn test_plot_each() {
+        let dir = tempdir().unwrap();
+
+        let mut suite = setup_suite();
+        let paths = suite.run().plot_each(dir.path()).unwrap();
+
+        assert_eq!(
+            paths,
+            [
+                dir.path().join("arithmetic.svg"),
+                dir.path().join("string_length.svg")
+            ]
+        );
+        assert!(paths.iter().all(|path| path.exists()));
+    }
+
+    #[test]
+    fn test_plot_without_run() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("suite.svg");
+
+        let suite = setup_suite();
+        let result = suite.plot_combined(&path);
+
+        assert!(matches!(result, Err(PlotBuilderError::NoData)));
+        assert!(!path.exists());
+    }
+}
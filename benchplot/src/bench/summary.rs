@@ -0,0 +1,209 @@
+/*
+Copyright 2024 Owain Davies
+SPDX-License-Identifier: Apache-2.0 OR MIT
+*/
+
+use crate::util;
+use crate::{Bench, Threading};
+use std::fmt::{self, Debug, Display};
+
+/// Marker appended to timings flagged as noisy in the summary table.
+const NOISY_MARKER: &str = "*";
+
+impl<T, R, M: Threading> Debug for Bench<'_, T, R, M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names = self.function_names();
+        f.debug_struct("Bench")
+            .field("functions", &names)
+            .field("label", &self.label)
+            .field("sizes", &self.sizes)
+            .field("repetitions", &self.repetitions)
+            .field("max_repetitions", &self.max_repetitions)
+            .field("parallel", &self.parallel)
+            .field("isolated", &self.isolated)
+            .field("cachegrind", &self.cachegrind)
+            .field("assert_equal", &self.assert_equal)
+            .field("strict_equal", &self.strict_equal)
+            .field("reference", &names[self.reference])
+            .field(
+                "mismatch_inputs",
+                &self.mismatch_inputs.as_ref().map(|(dir, _)| dir),
+            )
+            .field("inputs", &self.inputs.as_ref().map(|(dir, _)| dir))
+            .field("results_file", &self.results_file)
+            .field("live_plot", &self.live_plot)
+            .field("results_database", &self.results_database)
+            .field("cv_threshold", &self.cv_threshold)
+            .field("target_precision", &self.target_precision)
+            .field("priority", &self.priority)
+            .field("load_pause", &self.load_pause)
+            .field("cache_mode", &self.cache_mode)
+            .field("aggregation", &self.aggregation)
+            .field("confidence_level", &self.confidence_level)
+            .field("keep_samples", &self.keep_samples)
+            .field("print_verdict", &self.print_verdict)
+            .field("catch_panics", &self.catch_panics)
+            .field("verbosity", &self.verbosity)
+            .field("prefault", &self.prefault.is_some())
+            .field("reporters", &self.reporters.len())
+            .field("profiled", &self.profiled.len())
+            .field("metrics", &self.metric_names())
+            .field("measured_sizes", &self.data.num_sizes())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Displays the benchmark results as a table with a row per input size and a
+/// column per function.
+///
+/// Timings flagged as noisy are marked with an asterisk, and missing timings
+/// are shown as `-`. With several
+/// functions, the table is followed by their overall ranking, as computed by
+/// [`Bench::ranking`]. If the benchmark has not been run, only the
+/// configuration is summarized.
+impl<T, R, M: Threading> Display for Bench<'_, T, R, M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.data.is_empty() {
+            return writeln!(
+                f,
+                "Bench of {} function(s) over {} size(s) (not run)",
+                self.functions.len(),
+                self.sizes.len()
+            );
+        }
+
+        let mut rows: Vec<Vec<String>> =
+            Vec::with_capacity(self.data.num_sizes() + 1);
+        let mut header = vec!["n".to_string()];
+        header.extend(self.function_names().into_iter().map(String::from));
+        rows.push(header);
+
+        for (size, timings) in self.data.iter() {
+            let mut row = vec![size.to_string()];
+            row.extend(timings.iter().enumerate().map(|(func_idx, &time)| {
+                if time.is_nan() {
+                    return "-".to_string();
+                }
+                let mut cell = util::format_seconds(time);
+                if self.noisy.contains(&(size, func_idx)) {
+                    cell.push_str(NOISY_MARKER);
+                }
+                cell
+            }));
+            rows.push(row);
+        }
+
+        let widths: Vec<usize> = (0..rows[0].len())
+            .map(|col| {
+                rows.iter()
+                    .map(|row| row[col].chars().count())
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+
+        for row in &rows {
+            let cells: Vec<String> = row
+                .iter()
+                .zip(&widths)
+                .map(|(cell, &width)| format!("{:>width$}", cell))
+                .collect();
+            writeln!(f, "{}", cells.join("  "))?;
+        }
+
+        if self.functions.len() > 1 {
+            let names = self.function_names();
+            let ranked = self.ranked_sizes().len();
+            if ranked == self.data.num_sizes() {
+                writeln!(
+                    f,
+                    "\nRanking (geometric mean ratio to the fastest):"
+                )?;
+            } else {
+                writeln!(
+                    f,
+                    "\nRanking (geometric mean ratio to the fastest, over the \
+                     {} of {} input sizes at which every function was timed):",
+                    ranked,
+                    self.data.num_sizes()
+                )?;
+            }
+            for (rank, (func_idx, ratio)) in
+                self.ranking().into_iter().enumerate()
+            {
+                writeln!(
+                    f,
+                    "{:>3}. {}  {:.2}x",
+                    rank + 1,
+                    names[func_idx],
+                    ratio
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<T, R, M: Threading> Bench<'_, T, R, M> {
+    /// Returns a human-readable summary of the benchmark results, with a row
+    /// per input size and a column per function.
+    ///
+    /// This is the same as the `Display` rendering of the benchmark.
+    pub fn summary(&self) -> String {
+        self.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BenchBuilder, BenchFnArg, BenchFnNamed};
+
+    fn setup_bench() -> crate::Bench<'static, usize, usize> {
+        let functions: Vec<BenchFnNamed<'static, usize, usize>> = vec![
+            (Box::new(|x| x * 2), "Double"),
+            (Box::new(|x| x * x), "Square"),
+        ];
+        let argfunc: BenchFnArg<usize> = Box::new(|x| x);
+        BenchBuilder::new(functions, argfunc, vec![10, 1000])
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_debug_lists_configuration() {
+        let debug = format!("{:?}", setup_bench());
+
+        assert!(debug.starts_with("Bench {"));
+        assert!(debug.contains(r#"functions: ["Double", "Square"]"#));
+        assert!(debug.contains("sizes: [10, 1000]"));
+        assert!(debug.contains(r#"reference: "Double""#));
+    }
+
+    #[test]
+    fn test_display_before_run() {
+        assert_eq!(
+            setup_bench().to_string(),
+            "Bench of 2 function(s) over 2 size(s) (not run)\n"
+        );
+    }
+
+    #[test]
+    fn test_display_after_run() {
+        let mut bench = setup_bench();
+        bench.run();
+        let summary = bench.summary();
+        let lines: Vec<&str> = summary.lines().collect();
+
+        assert_eq!(lines.len(), 7);
+        assert_eq!(
+            lines[0].split_whitespace().collect::<Vec<_>>(),
+            ["n", "Double", "Square"]
+        );
+        assert!(lines[1].trim_start().starts_with("10 "));
+        assert!(lines[2].trim_start().starts_with("1000 "));
+        assert_eq!(lines[3], "");
+        assert!(lines[4].starts_with("Ranking"));
+        assert!(lines[5].starts_with("  1. "));
+        assert!(lines[6].starts_with("  2. "));
+    }
+}
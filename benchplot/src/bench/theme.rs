@@ -0,0 +1,102 @@
+/*
+Copyright 2024 Owain Davies
+SPDX-License-Identifier: Apache-2.0 OR MIT
+*/
+
+use super::plot::COLORS;
+use plotters::prelude::full_palette::GREY;
+use plotters::style::{Color, RGBAColor, RGBColor};
+
+/// A set of colors and fonts applied to a plot at once with
+/// [`PlotBuilder::theme`](crate::PlotBuilder::theme), so that a look can be
+/// implemented once and shared instead of configured setter by setter.
+///
+/// Every method has a default matching the default look of a plot, so a
+/// theme only implements the ones it changes:
+///
+/// ```no_run
+/// # use benchplot::{Bench, Theme};
+/// use plotters::style::{Color, RGBAColor, RGBColor};
+///
+/// struct Solarized;
+///
+/// impl Theme for Solarized {
+///     fn background(&self) -> RGBAColor {
+///         RGBColor(253, 246, 227).to_rgba()
+///     }
+///
+///     fn text(&self) -> RGBColor {
+///         RGBColor(101, 123, 131)
+///     }
+/// }
+///
+/// # fn example(bench: &Bench<usize, usize>) {
+/// bench.plot("plot.svg").theme(&Solarized).build().unwrap();
+/// # }
+/// ```
+pub trait Theme {
+    /// Returns the color the plot is filled with before anything is drawn.
+    ///
+    /// By default, the background is transparent, so that the plot blends
+    /// into the page it is shown on. Bitmap images are filled with white
+    /// first.
+    fn background(&self) -> RGBAColor {
+        RGBColor(255, 255, 255).mix(0.0)
+    }
+
+    /// Returns the colors of the functions' lines, assigned by position in
+    /// the list of functions.
+    ///
+    /// By default, 11 colors chosen to be distinguishable on light and dark
+    /// backgrounds are used.
+    fn palette(&self) -> Vec<RGBColor> {
+        COLORS.to_vec()
+    }
+
+    /// Returns the color of the grid lines at the axis ticks.
+    ///
+    /// By default, grid lines are black at 20% opacity.
+    fn grid(&self) -> RGBAColor {
+        RGBColor(0, 0, 0).mix(0.2)
+    }
+
+    /// Returns the color of the title, of the axes, and of their labels.
+    ///
+    /// By default, they are grey.
+    fn text(&self) -> RGBColor {
+        GREY
+    }
+
+    /// Returns the font family of all text on the plot.
+    ///
+    /// By default, the font family is `"sans-serif"`.
+    fn font_family(&self) -> String {
+        "sans-serif".to_string()
+    }
+}
+
+/// The default look of a plot: transparent background, black grid lines,
+/// and grey text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DefaultTheme;
+
+impl Theme for DefaultTheme {}
+
+/// A dark background with light grid lines and text, e.g., for slides or
+/// pages with a dark color scheme.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DarkTheme;
+
+impl Theme for DarkTheme {
+    fn background(&self) -> RGBAColor {
+        RGBColor(13, 17, 23).to_rgba()
+    }
+
+    fn grid(&self) -> RGBAColor {
+        RGBColor(255, 255, 255).mix(0.15)
+    }
+
+    fn text(&self) -> RGBColor {
+        RGBColor(201, 209, 217)
+    }
+}
@@ -0,0 +1,321 @@
+/*
+Copyright 2024 Owain Davies
+SPDX-License-Identifier: Apache-2.0 OR MIT
+*/
+
+use std::time::{Duration, Instant};
+
+/// A clock used to time functions.
+///
+/// Each timing is the difference between the readings taken immediately
+/// before and after calling the function, so readings only need to be
+/// monotonic relative to an arbitrary origin. Implementing this trait allows
+/// supplying alternative clocks, such as cycle counters, coarse clocks, or
+/// mock clocks for deterministic tests.
+///
+/// ```
+/// use benchplot::Timer;
+/// use std::sync::atomic::{AtomicU64, Ordering};
+/// use std::time::Duration;
+///
+/// /// A mock clock advancing by one millisecond on every reading.
+/// #[derive(Default)]
+/// struct StepTimer(AtomicU64);
+///
+/// impl Timer for StepTimer {
+///     fn now(&self) -> Duration {
+///         Duration::from_millis(self.0.fetch_add(1, Ordering::Relaxed))
+///     }
+/// }
+///
+/// let timer = StepTimer::default();
+/// let start = timer.now();
+/// assert_eq!(timer.now() - start, Duration::from_millis(1));
+/// ```
+pub trait Timer: Send + Sync {
+    /// Returns the current reading of the clock, relative to an arbitrary
+    /// fixed origin.
+    fn now(&self) -> Duration;
+}
+
+/// Cost and resolution of a [`Timer`], measured at the start of every run.
+///
+/// Timings within an order of magnitude of the granularity, or of the
+/// overhead, are dominated by the clock rather than by the timed function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimerCalibration {
+    /// The average time taken by one reading of the clock.
+    pub overhead: Duration,
+
+    /// The smallest observed nonzero difference between consecutive readings
+    /// of the clock, or zero if the clock did not advance during the
+    /// calibration.
+    pub granularity: Duration,
+}
+
+impl TimerCalibration {
+    /// Number of readings averaged to measure the overhead.
+    const OVERHEAD_READINGS: u32 = 1000;
+
+    /// Number of clock advances whose smallest step is the granularity.
+    const GRANULARITY_STEPS: usize = 8;
+
+    /// Maximum time to wait for the clock to advance.
+    const MAX_WAIT: Duration = Duration::from_secs(1);
+
+    /// Measures the overhead and granularity of `timer`.
+    pub(crate) fn measure(timer: &dyn Timer) -> Self {
+        let start = Instant::now();
+        for _ in 0..Self::OVERHEAD_READINGS {
+            std::hint::black_box(timer.now());
+        }
+        let overhead = start.elapsed() / Self::OVERHEAD_READINGS;
+
+        let start = Instant::now();
+        let mut granularity = Duration::MAX;
+        let mut previous = timer.now();
+        let mut steps = 0;
+        while steps < Self::GRANULARITY_STEPS
+            && start.elapsed() < Self::MAX_WAIT
+        {
+            let reading = timer.now();
+            if reading > previous {
+                granularity = granularity.min(reading - previous);
+                steps += 1;
+            }
+            previous = reading;
+        }
+        if steps == 0 {
+            granularity = Duration::ZERO;
+        }
+
+        Self {
+            overhead,
+            granularity,
+        }
+    }
+}
+
+/// The default [`Timer`], backed by [`std::time::Instant`].
+#[derive(Debug, Clone, Copy)]
+pub struct InstantTimer {
+    origin: Instant,
+}
+
+impl InstantTimer {
+    /// Creates a timer whose readings are relative to the current instant.
+    pub fn new() -> Self {
+        Self {
+            origin: Instant::now(),
+        }
+    }
+}
+
+impl Default for InstantTimer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Timer for InstantTimer {
+    fn now(&self) -> Duration {
+        self.origin.elapsed()
+    }
+}
+
+/// A [`Timer`] backed by a coarse monotonic clock, which is cheaper to read
+/// than [`InstantTimer`] at the cost of resolution.
+///
+/// On Linux, it reads `CLOCK_MONOTONIC_COARSE`, whose resolution is that of
+/// the scheduler tick (typically 1 to 4 ms), so it only suits functions
+/// taking much longer than a tick, or many repetitions. Elsewhere, it falls
+/// back to the clock of [`InstantTimer`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CoarseTimer {
+    _private: (),
+}
+
+impl CoarseTimer {
+    /// Creates a coarse timer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Timer for CoarseTimer {
+    #[cfg(target_os = "linux")]
+    fn now(&self) -> Duration {
+        let mut ts = libc::timespec {
+            tv_sec: 0,
+            tv_nsec: 0,
+        };
+        // SAFETY: `ts` is a valid timespec for clock_gettime to write to.
+        let err = unsafe {
+            libc::clock_gettime(libc::CLOCK_MONOTONIC_COARSE, &mut ts)
+        };
+        assert_eq!(err, 0, "failed to read CLOCK_MONOTONIC_COARSE");
+        Duration::new(ts.tv_sec as u64, ts.tv_nsec as u32)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn now(&self) -> Duration {
+        use std::sync::OnceLock;
+
+        static ORIGIN: OnceLock<Instant> = OnceLock::new();
+        ORIGIN.get_or_init(Instant::now).elapsed()
+    }
+}
+
+/// A [`Timer`] reading the time-stamp counter (TSC) of x86 processors, the
+/// cheapest clock to read, with a resolution of a CPU cycle.
+///
+/// The counter is converted to time with its frequency, which is calibrated
+/// against [`InstantTimer`] when the timer is created. Readings are only
+/// meaningful on processors with an invariant TSC, which ticks at a constant
+/// rate whatever the CPU frequency, as do most x86 processors of the last
+/// decade.
+///
+/// Requires the `tsc` feature, on x86 and x86-64 targets.
+#[cfg(all(feature = "tsc", any(target_arch = "x86", target_arch = "x86_64")))]
+#[derive(Debug, Clone, Copy)]
+pub struct TscTimer {
+    frequency: u64,
+}
+
+#[cfg(all(feature = "tsc", any(target_arch = "x86", target_arch = "x86_64")))]
+impl TscTimer {
+    /// Duration of the calibration of the counter frequency.
+    const CALIBRATION: Duration = Duration::from_millis(20);
+
+    /// Creates a timer, calibrating the frequency of the counter.
+    pub fn new() -> Self {
+        let start = Instant::now();
+        let start_cycles = Self::cycles();
+        while start.elapsed() < Self::CALIBRATION {
+            std::hint::spin_loop();
+        }
+        let cycles = Self::cycles().wrapping_sub(start_cycles);
+        let nanos = start.elapsed().as_nanos();
+        let frequency = u128::from(cycles) * 1_000_000_000 / nanos;
+        Self::with_frequency(frequency as u64)
+    }
+
+    /// Creates a timer for a counter ticking `frequency` times per second,
+    /// skipping the calibration.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `frequency` is zero.
+    pub fn with_frequency(frequency: u64) -> Self {
+        assert!(frequency > 0, "TSC frequency must be positive");
+        Self { frequency }
+    }
+
+    /// Returns the frequency of the counter, in ticks per second.
+    pub fn frequency(&self) -> u64 {
+        self.frequency
+    }
+
+    fn cycles() -> u64 {
+        #[cfg(target_arch = "x86")]
+        use std::arch::x86::_rdtsc;
+        #[cfg(target_arch = "x86_64")]
+        use std::arch::x86_64::_rdtsc;
+
+        // SAFETY: the time-stamp counter is available on every x86 processor
+        // that Rust supports.
+        #[allow(unused_unsafe)]
+        unsafe {
+            _rdtsc()
+        }
+    }
+}
+
+#[cfg(all(feature = "tsc", any(target_arch = "x86", target_arch = "x86_64")))]
+impl Default for TscTimer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(all(feature = "tsc", any(target_arch = "x86", target_arch = "x86_64")))]
+impl Timer for TscTimer {
+    fn now(&self) -> Duration {
+        let nanos = u128::from(Self::cycles()) * 1_000_000_000
+            / u128::from(self.frequency);
+        Duration::from_nanos(nanos as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_instant_timer_is_monotonic() {
+        let timer = InstantTimer::new();
+        let first = timer.now();
+        let second = timer.now();
+        assert!(second >= first);
+    }
+
+    #[test]
+    fn test_calibration() {
+        struct StepTimer(std::sync::atomic::AtomicU64);
+
+        impl Timer for StepTimer {
+            fn now(&self) -> Duration {
+                let step =
+                    self.0.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                Duration::from_millis(step * 3)
+            }
+        }
+
+        let calibration =
+            TimerCalibration::measure(&StepTimer(Default::default()));
+        assert_eq!(calibration.granularity, Duration::from_millis(3));
+
+        struct FrozenTimer;
+
+        impl Timer for FrozenTimer {
+            fn now(&self) -> Duration {
+                Duration::ZERO
+            }
+        }
+
+        let calibration = TimerCalibration::measure(&FrozenTimer);
+        assert_eq!(calibration.granularity, Duration::ZERO);
+
+        let calibration = TimerCalibration::measure(&InstantTimer::new());
+        assert!(calibration.granularity > Duration::ZERO);
+        assert!(calibration.overhead < Duration::from_millis(1));
+    }
+
+    #[test]
+    fn test_coarse_timer_is_monotonic() {
+        let timer = CoarseTimer::new();
+        let first = timer.now();
+        std::thread::sleep(Duration::from_millis(20));
+        let second = timer.now();
+        assert!(second > first);
+    }
+
+    #[cfg(all(
+        feature = "tsc",
+        any(target_arch = "x86", target_arch = "x86_64")
+    ))]
+    #[test]
+    fn test_tsc_timer() {
+        let timer = TscTimer::new();
+        assert!(timer.frequency() > 0);
+
+        let first = timer.now();
+        std::thread::sleep(Duration::from_millis(20));
+        let elapsed = timer.now() - first;
+        assert!(elapsed >= Duration::from_millis(10), "{:?}", elapsed);
+
+        let timer = TscTimer::with_frequency(1_000_000_000);
+        assert_eq!(timer.frequency(), 1_000_000_000);
+    }
+}
@@ -0,0 +1,25 @@
+/*
+Copyright 2024 Owain Davies
+SPDX-License-Identifier: Apache-2.0 OR MIT
+*/
+
+/// How much a benchmark prints while and after it runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Verbosity {
+    /// Nothing is printed, not even warnings, e.g., for benchmarks embedded
+    /// in tools whose output must stay clean. Failures still panic or are
+    /// returned as errors.
+    Quiet,
+
+    /// Warnings about the measurements, e.g., noisy or coarse timings, are
+    /// printed to standard error, along with the verdict of each run if
+    /// [`BenchBuilder::print_verdict`](crate::BenchBuilder::print_verdict)
+    /// is set.
+    #[default]
+    Normal,
+
+    /// As with `Normal`, and the timing of each (input size, function) pair
+    /// is printed to standard error as soon as it is measured, and the table
+    /// of results to standard output at the end of each run.
+    Verbose,
+}
@@ -0,0 +1,317 @@
+/*
+Copyright 2024 Owain Davies
+SPDX-License-Identifier: Apache-2.0 OR MIT
+*/
+
+//! Command-line interface for working with saved benchmark results, i.e.,
+//! baselines saved with `Baseline::save` or rendered with `Baseline::to_json`.
+
+use benchplot::{Baseline, PlotBuilder, PlotBuilderError};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+usage: benchplot <command> [options]
+
+commands:
+  compare <old> <new> [-o <plot.svg>] [--threshold <fraction>]
+      Lists the timings that changed by more than the threshold (default
+      0.05, i.e., 5%) from the old results to the new ones, and plots the
+      ratio of the new timing to the old one of each function and input
+      size, with reference lines at 1 for no change and at 1 plus or minus
+      the threshold.
+
+  plot <results> -o <plot.svg>
+      Plots the results, as SVG or as a bitmap depending on the extension of
+      the output file.
+
+  merge <results>... -o <merged.json>
+      Merges results of partial runs, e.g., from different sessions. A
+      timing measured in several files is taken from the last of them. The
+      merged results are written as JSON if the output file name ends with
+      .json, and in the format of Baseline::save otherwise.
+
+  report <results> -o <dir>
+      Writes an HTML report of the results to the directory: a page with
+      their plot, a table of the timings, and the metadata of the run.
+
+  tex <results> -o <figure.tex>
+      Writes the results as a pgfplots figure to include in LaTeX documents,
+      which must load the pgfplots package.
+
+A results file of `-` is read from standard input, and an output file of `-`
+is written to standard output, plots as SVG and merged results as JSON, e.g.,
+`benchplot plot - -o - < results.json > plot.svg`. The changes listed by
+compare are then printed to standard error.";
+
+/// Path standing for standard input as a results file and for standard output
+/// as an output file.
+const STDIO: &str = "-";
+
+/// An error ending the program, reported with the usage for invalid
+/// arguments.
+enum Error {
+    Usage(String),
+    Failed(String),
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let result = match args.first().map(String::as_str) {
+        Some("compare") => compare(&args[1..]),
+        Some("merge") => merge(&args[1..]),
+        Some("plot") => plot(&args[1..]),
+        Some("report") => report(&args[1..]),
+        Some("tex") => tex(&args[1..]),
+        Some("-h" | "--help") => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        Some(command) => {
+            Err(Error::Usage(format!("unknown command `{}`", command)))
+        }
+        None => Err(Error::Usage("missing command".to_string())),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(Error::Usage(msg)) => {
+            eprintln!("error: {}\n\n{}", msg, USAGE);
+            ExitCode::from(2)
+        }
+        Err(Error::Failed(msg)) => {
+            eprintln!("error: {}", msg);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Options and positional arguments of a command.
+struct Args {
+    positional: Vec<String>,
+    output: Option<PathBuf>,
+    threshold: Option<f64>,
+}
+
+impl Args {
+    fn parse(args: &[String]) -> Result<Self, Error> {
+        let mut parsed = Self {
+            positional: Vec::new(),
+            output: None,
+            threshold: None,
+        };
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next().ok_or_else(|| {
+                    Error::Usage(format!("missing value for `{}`", arg))
+                })
+            };
+            match arg.as_str() {
+                "-o" | "--output" => {
+                    parsed.output = Some(PathBuf::from(value()?));
+                }
+                "--threshold" => {
+                    let threshold = value()?;
+                    parsed.threshold = Some(
+                        threshold
+                            .parse()
+                            .ok()
+                            .filter(|t: &f64| *t >= 0.0)
+                            .ok_or_else(|| {
+                                Error::Usage(format!(
+                                    "invalid threshold `{}`",
+                                    threshold
+                                ))
+                            })?,
+                    );
+                }
+                option if option.starts_with('-') && option != STDIO => {
+                    return Err(Error::Usage(format!(
+                        "unknown option `{}`",
+                        option
+                    )));
+                }
+                _ => parsed.positional.push(arg.clone()),
+            }
+        }
+        if parsed.positional.iter().filter(|&arg| arg == STDIO).count() > 1 {
+            return Err(Error::Usage(
+                "only one results file can be read from standard input"
+                    .to_string(),
+            ));
+        }
+        Ok(parsed)
+    }
+}
+
+/// Returns whether `output` stands for standard output.
+fn is_stdout(output: &Path) -> bool {
+    output == Path::new(STDIO)
+}
+
+fn load(path: &str) -> Result<Baseline, Error> {
+    if path == STDIO {
+        Baseline::read(io::stdin().lock()).map_err(|err| {
+            Error::Failed(format!("cannot read standard input: {}", err))
+        })
+    } else {
+        Baseline::load(path).map_err(|err| {
+            Error::Failed(format!("cannot read {}: {}", path, err))
+        })
+    }
+}
+
+/// Writes `contents` to the file `output`, or to standard output.
+fn write(output: &Path, contents: &[u8]) -> Result<(), Error> {
+    let written = if is_stdout(output) {
+        io::stdout().lock().write_all(contents)
+    } else {
+        std::fs::write(output, contents)
+    };
+    written.map_err(|err| {
+        Error::Failed(format!("cannot write {}: {}", output.display(), err))
+    })
+}
+
+/// Plots `results`, with the plot configured by `configure`, to the file
+/// `output`, or as SVG to standard output.
+fn write_plot<F>(
+    output: &Path,
+    results: &Baseline,
+    configure: F,
+) -> Result<(), Error>
+where
+    F: for<'b> FnOnce(PlotBuilder<'b, (), ()>) -> PlotBuilder<'b, (), ()>,
+{
+    let cannot_plot = |err: PlotBuilderError| {
+        Error::Failed(format!("cannot plot {}: {}", output.display(), err))
+    };
+    if is_stdout(output) {
+        let svg = results.plot_svg(configure).map_err(cannot_plot)?;
+        write(output, svg.as_bytes())
+    } else {
+        results.plot(output, configure).map_err(cannot_plot)
+    }
+}
+
+fn compare(args: &[String]) -> Result<(), Error> {
+    let args = Args::parse(args)?;
+    let [old, new] = args.positional.as_slice() else {
+        return Err(Error::Usage(
+            "compare takes an old and a new results file".to_string(),
+        ));
+    };
+    let threshold = args.threshold.unwrap_or(0.05);
+    let (old, new) = (load(old)?, load(new)?);
+
+    let changes = old.changes(&new, threshold);
+    let regressions = changes.iter().filter(|c| c.is_regression()).count();
+    let mut listing = String::new();
+    for change in &changes {
+        let kind = if change.is_regression() {
+            "regression"
+        } else {
+            "improvement"
+        };
+        listing += &format!("{:<11}  {}\n", kind, change);
+    }
+    listing += &format!(
+        "{} regression(s) and {} improvement(s) beyond {:.1}%\n",
+        regressions,
+        changes.len() - regressions,
+        threshold * 100.0
+    );
+    // Standard output is kept for the plot if it is written there.
+    if args.output.as_deref().is_some_and(is_stdout) {
+        eprint!("{}", listing);
+    } else {
+        print!("{}", listing);
+    }
+
+    if let Some(output) = &args.output {
+        // Reference lines at no change and at the threshold of the listing.
+        let mut lines = vec![(1.0, "No change".to_string())];
+        if threshold > 0.0 {
+            let percent = threshold * 100.0;
+            lines.push((1.0 + threshold, format!("+{:.1}%", percent)));
+            if threshold < 1.0 {
+                lines.push((1.0 - threshold, format!("-{:.1}%", percent)));
+            }
+        }
+        write_plot(output, &old.ratios(&new), |plot| {
+            lines.iter().fold(
+                plot.y_axis_description("New time / old time"),
+                |plot, (value, label)| plot.hline(*value, label),
+            )
+        })?;
+    }
+    Ok(())
+}
+
+fn plot(args: &[String]) -> Result<(), Error> {
+    let args = Args::parse(args)?;
+    let Some(output) = &args.output else {
+        return Err(Error::Usage("plot requires an output file".to_string()));
+    };
+    let [results] = args.positional.as_slice() else {
+        return Err(Error::Usage("plot takes one results file".to_string()));
+    };
+    let results = load(results)?;
+    write_plot(output, &results, |plot| plot)
+}
+
+fn merge(args: &[String]) -> Result<(), Error> {
+    let args = Args::parse(args)?;
+    let Some(output) = &args.output else {
+        return Err(Error::Usage("merge requires an output file".to_string()));
+    };
+    let mut paths = args.positional.iter();
+    let first = paths.next().ok_or_else(|| {
+        Error::Usage("merge takes at least one results file".to_string())
+    })?;
+    let mut merged = load(first)?;
+    for path in paths {
+        merged = merged.merge(&load(path)?);
+    }
+
+    if is_stdout(output) || output.extension().is_some_and(|ext| ext == "json")
+    {
+        write(output, (merged.to_json() + "\n").as_bytes())
+    } else {
+        merged.save(output).map_err(|err| {
+            Error::Failed(format!("cannot write {}: {}", output.display(), err))
+        })
+    }
+}
+
+fn report(args: &[String]) -> Result<(), Error> {
+    let args = Args::parse(args)?;
+    let Some(output) = &args.output else {
+        return Err(Error::Usage(
+            "report requires an output directory".to_string(),
+        ));
+    };
+    if is_stdout(output) {
+        return Err(Error::Usage(
+            "report writes a directory, not standard output".to_string(),
+        ));
+    }
+    let [results] = args.positional.as_slice() else {
+        return Err(Error::Usage("report takes one results file".to_string()));
+    };
+    load(results)?.write_report(output).map_err(|err| {
+        Error::Failed(format!("cannot write {}: {}", output.display(), err))
+    })
+}
+
+fn tex(args: &[String]) -> Result<(), Error> {
+    let args = Args::parse(args)?;
+    let Some(output) = &args.output else {
+        return Err(Error::Usage("tex requires an output file".to_string()));
+    };
+    let [results] = args.positional.as_slice() else {
+        return Err(Error::Usage("tex takes one results file".to_string()));
+    };
+    write(output, load(results)?.to_pgfplots().as_bytes())
+}
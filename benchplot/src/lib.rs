@@ -7,9 +7,33 @@ SPDX-License-Identifier: Apache-2.0 OR MIT
 #![doc = include_str!("../README.md")]
 
 mod bench;
+mod macros;
 mod util;
 
+#[cfg(feature = "dhat")]
+pub use bench::DhatProfiler;
+#[cfg(feature = "upload")]
+pub use bench::HttpReporter;
+#[cfg(feature = "egui")]
+pub use bench::LiveChart;
+#[cfg(all(
+    feature = "tsc",
+    any(target_arch = "x86", target_arch = "x86_64")
+))]
+pub use bench::TscTimer;
 pub use bench::{
-    Bench, BenchBuilder, BenchBuilderError, BenchFn, BenchFnArg, BenchFnNamed,
-    PlotBuilder, PlotBuilderError,
+    thread_cpu_time, AbComparison, Aggregation, Baseline, Bench, BenchBuilder,
+    BenchBuilderError, BenchData, BenchFn, BenchFnArg, BenchFnNamed,
+    BenchSuite, CacheMode, CellChange, CellEvent, ChangePoint, CoarseTimer,
+    Complexity, ComplexityFit, ConfidenceInterval, CountingAllocator,
+    FallibleBenchFnArg, InstantTimer, InstructionCounts, Local, LocalBenchFn,
+    LocalBenchFnArg, LocalBenchFnNamed, Prefault, Priority, Profiler, Reporter,
+    RunError, Runnable, Shared, SignificanceMatrix, SizedBenchFn, Threading,
+    Timer, TimerCalibration, Verbosity, Winner,
+};
+#[cfg(feature = "plot")]
+pub use bench::{
+    DarkTheme, DefaultTheme, LegendPosition, LowerPanel, Marker, NumberFormat,
+    PlotBuilder, PlotBuilderError, PlotStyle, SeriesStyle, Smoothing, Theme,
+    TitleAlignment, TitleStyle,
 };
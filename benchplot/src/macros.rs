@@ -0,0 +1,90 @@
+/*
+Copyright 2024 Owain Davies
+SPDX-License-Identifier: Apache-2.0 OR MIT
+*/
+
+/// Creates a [`BenchFnNamed`](crate::BenchFnNamed) from a function, using the
+/// function's name as written as the name of the function to benchmark.
+///
+/// `named!(merge_sort)` expands to `(Box::new(merge_sort), "merge_sort")`, so
+/// the names in the plot legend stay in sync with the actual function names.
+///
+/// ```
+/// use benchplot::{named, BenchFnNamed};
+///
+/// fn double(x: u64) -> u64 {
+///     x * 2
+/// }
+///
+/// fn square(x: u64) -> u64 {
+///     x * x
+/// }
+///
+/// let functions: Vec<BenchFnNamed<u64, u64>> =
+///     vec![named!(double), named!(square)];
+///
+/// assert_eq!(functions[0].1, "double");
+/// assert_eq!(functions[1].1, "square");
+/// ```
+#[macro_export]
+macro_rules! named {
+    ($func:path) => {
+        (
+            ::std::boxed::Box::new($func) as $crate::BenchFn<_, _>,
+            ::std::stringify!($func),
+        )
+    };
+}
+
+/// Generates the `main` function of a benchmark binary running the
+/// [`BenchSuite`](crate::BenchSuite) returned by a function, with a standard
+/// command line, so small benchmark binaries get a consistent interface.
+///
+/// The plots of the benchmarks are saved to `target/benchplot` by default.
+/// The command line accepts:
+///
+/// - `--filter <text>`: runs only the benchmarks whose name contains the
+///   text.
+/// - `--sizes <n,n,...>`: benchmarks the given input sizes instead, for every
+///   function, up to its cap set with
+///   [`BenchBuilder::max_size_for`](crate::BenchBuilder::max_size_for).
+/// - `--reps <n>`: times each (input size, function) pair `n` times.
+/// - `--output <dir>`: saves the plots to the directory instead.
+/// - `--json`: also saves the results of each benchmark as JSON, e.g., to
+///   `merge_sort.json` for a benchmark named `Merge Sort`.
+/// - `--baseline <dir>`: overlays the results saved with `--json` in the
+///   directory on the plots.
+///
+/// With `harness = false` in the `[[bench]]` section of `Cargo.toml`, the
+/// binary runs with `cargo bench -- --sizes 10,100`. The `--bench` flag that
+/// `cargo bench` passes is accepted and ignored.
+///
+/// Requires the `plot` feature, which is enabled by default.
+///
+/// ```no_run
+/// use benchplot::{BenchBuilder, BenchFnArg, BenchSuite};
+///
+/// fn suite() -> BenchSuite<'static> {
+///     let argfunc: BenchFnArg<Vec<u64>> =
+///         Box::new(|n| (0..n as u64).rev().collect());
+///     BenchSuite::new().add(
+///         "Sorting",
+///         BenchBuilder::new(Vec::new(), argfunc, vec![100, 1000, 10000])
+///             .add_function(|mut v: Vec<u64>| v.sort(), "sort")
+///             .add_function(|mut v: Vec<u64>| v.sort_unstable(), "unstable")
+///             .build()
+///             .unwrap(),
+///     )
+/// }
+///
+/// benchplot::main!(suite);
+/// ```
+#[cfg(feature = "plot")]
+#[macro_export]
+macro_rules! main {
+    ($suite:path) => {
+        fn main() -> ::std::process::ExitCode {
+            $crate::BenchSuite::main($suite())
+        }
+    };
+}
@@ -19,6 +19,214 @@ where
     }
 }
 
+/// Function to truncate a string to at most `max_chars` characters.
+///
+/// If the string is truncated, the last character is replaced with an
+/// ellipsis (`…`).
+pub fn truncate(s: &str, max_chars: usize) -> String {
+    if s.chars().count() <= max_chars {
+        return s.to_string();
+    }
+    let mut truncated: String =
+        s.chars().take(max_chars.saturating_sub(1)).collect();
+    truncated.push('…');
+    truncated
+}
+
+/// Function to compute the coefficient of variation (the sample standard
+/// deviation divided by the mean) of a slice of samples.
+///
+/// Returns `None` if there are fewer than two samples or the mean is zero.
+pub fn coefficient_of_variation(samples: &[f64]) -> Option<f64> {
+    if samples.len() < 2 {
+        return None;
+    }
+    let n = samples.len() as f64;
+    let mean = samples.iter().sum::<f64>() / n;
+    if mean == 0.0 {
+        return None;
+    }
+    let variance =
+        samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1.0);
+    Some(variance.sqrt() / mean.abs())
+}
+
+/// Function to format a duration in seconds with a unit suited to its
+/// magnitude, e.g., `1.50 ms`.
+pub fn format_seconds(seconds: f64) -> String {
+    const UNITS: &[(f64, &str)] =
+        &[(1.0, "s"), (1e-3, "ms"), (1e-6, "µs"), (1e-9, "ns")];
+
+    let &(scale, unit) = UNITS
+        .iter()
+        .find(|&&(scale, _)| seconds.abs() >= scale)
+        .unwrap_or(&UNITS[UNITS.len() - 1]);
+    format!("{:.2} {}", seconds / scale, unit)
+}
+
+/// Function to derive a file name stem from a display name, e.g.,
+/// `merge_sort` from `Merge Sort`.
+///
+/// ASCII letters and digits are kept (lowercased) and every other run of
+/// characters is replaced with a single underscore.
+#[cfg(any(feature = "plot", feature = "dhat"))]
+pub fn file_stem(name: &str) -> String {
+    let mut stem = String::with_capacity(name.len());
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            stem.push(c.to_ascii_lowercase());
+        } else if !stem.is_empty() && !stem.ends_with('_') {
+            stem.push('_');
+        }
+    }
+    while stem.ends_with('_') {
+        stem.pop();
+    }
+    if stem.is_empty() {
+        stem.push_str("bench");
+    }
+    stem
+}
+
+/// Function to compute the 64-bit FNV-1a hash of `bytes`.
+///
+/// Unlike the hashers of the standard library, the hash is stable across Rust
+/// versions and platforms, so it can be persisted.
+pub fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Function to encode `bytes` in standard, padded base64, e.g., for data
+/// URLs.
+#[cfg(feature = "plot")]
+pub fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] =
+        b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |group, (i, &byte)| {
+            group | u32::from(byte) << (16 - 8 * i)
+        });
+        for i in 0..4 {
+            if i <= chunk.len() {
+                let index = (group >> (18 - 6 * i)) & 0x3f;
+                encoded.push(ALPHABET[index as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// Function to deserialize a number that may have been serialized as `null`,
+/// as `serde_json` serializes `NaN`, mapping `null` back to `NaN`.
+#[cfg(feature = "serde")]
+pub fn deserialize_nan<'de, D>(deserializer: D) -> Result<f64, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value: Option<f64> = serde::Deserialize::deserialize(deserializer)?;
+    Ok(value.unwrap_or(f64::NAN))
+}
+
+/// Function to deserialize a matrix of numbers that may have been serialized
+/// as `null`, mapping `null` back to `NaN` as [`deserialize_nan`] does.
+#[cfg(feature = "serde")]
+pub fn deserialize_nan_matrix<'de, D>(
+    deserializer: D,
+) -> Result<Vec<Vec<f64>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let rows: Vec<Vec<Option<f64>>> =
+        serde::Deserialize::deserialize(deserializer)?;
+    Ok(rows
+        .into_iter()
+        .map(|row| row.into_iter().map(|x| x.unwrap_or(f64::NAN)).collect())
+        .collect())
+}
+
+/// Function to fit a line `y = slope * x + intercept` to `points` by least
+/// squares, returning `(slope, intercept)`.
+///
+/// The slope is zero if there are fewer than two distinct x-coordinates.
+pub fn linear_fit(points: &[(f64, f64)]) -> (f64, f64) {
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|&(x, _)| x).sum::<f64>() / n;
+    let mean_y = points.iter().map(|&(_, y)| y).sum::<f64>() / n;
+    let (covariance, variance) =
+        points.iter().fold((0.0, 0.0), |(cov, var), &(x, y)| {
+            (
+                cov + (x - mean_x) * (y - mean_y),
+                var + (x - mean_x).powi(2),
+            )
+        });
+    let slope = if variance > 0.0 {
+        covariance / variance
+    } else {
+        0.0
+    };
+    (slope, mean_y - slope * mean_x)
+}
+
+/// Function to compute the quantile of the standard normal distribution at
+/// probability `p`, which must be strictly between 0 and 1.
+///
+/// Uses the rational approximation of Peter Acklam, with a relative error
+/// below 1.2e-9.
+pub fn normal_quantile(p: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969683028665376e1,
+        2.209460984245205e2,
+        -2.759285104469687e2,
+        1.38357751867269e2,
+        -3.066479806614716e1,
+        2.506628277459239,
+    ];
+    const B: [f64; 5] = [
+        -5.447609879822406e1,
+        1.615858368580409e2,
+        -1.556989798598866e2,
+        6.680131188771972e1,
+        -1.328068155288572e1,
+    ];
+    const C: [f64; 6] = [
+        -7.784894002430293e-3,
+        -3.223964580411365e-1,
+        -2.400758277161838,
+        -2.549732539343734,
+        4.374664141464968,
+        2.938163982698783,
+    ];
+    const D: [f64; 4] = [
+        7.784695709041462e-3,
+        3.224671290700398e-1,
+        2.445134137142996,
+        3.754408661907416,
+    ];
+    const P_LOW: f64 = 0.02425;
+
+    let tail = |q: f64| {
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    };
+    if p < P_LOW {
+        tail((-2.0 * p.ln()).sqrt())
+    } else if p > 1.0 - P_LOW {
+        -tail((-2.0 * (1.0 - p).ln()).sqrt())
+    } else {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5])
+            * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r
+                + 1.0)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -46,4 +254,78 @@ mod tests {
         let different_elements = vec![1, 2, 1, 1];
         assert!(!all_items_equal(different_elements));
     }
+
+    #[test]
+    fn test_truncate_short_string() {
+        assert_eq!(truncate("abc", 3), "abc");
+        assert_eq!(truncate("", 0), "");
+    }
+
+    #[test]
+    fn test_truncate_long_string() {
+        assert_eq!(truncate("abcdef", 4), "abc…");
+        assert_eq!(truncate("ααααα", 3), "αα…");
+    }
+
+    #[test]
+    fn test_format_seconds() {
+        assert_eq!(format_seconds(2.5), "2.50 s");
+        assert_eq!(format_seconds(0.0015), "1.50 ms");
+        assert_eq!(format_seconds(1.2e-6), "1.20 µs");
+        assert_eq!(format_seconds(3e-9), "3.00 ns");
+        assert_eq!(format_seconds(0.0), "0.00 ns");
+    }
+
+    #[cfg(any(feature = "plot", feature = "dhat"))]
+    #[test]
+    fn test_file_stem() {
+        assert_eq!(file_stem("Merge Sort"), "merge_sort");
+        assert_eq!(file_stem("  Vec<u8>::sort() "), "vec_u8_sort");
+        assert_eq!(file_stem("!!!"), "bench");
+    }
+
+    #[test]
+    fn test_fnv1a() {
+        assert_eq!(fnv1a(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(fnv1a(b"foobar"), 0x8594_4171_f739_67e8);
+    }
+
+    #[cfg(feature = "plot")]
+    #[test]
+    fn test_base64() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+        assert_eq!(base64(&[0xff, 0xfe]), "//4=");
+    }
+
+    #[test]
+    fn test_linear_fit() {
+        assert_eq!(
+            linear_fit(&[(0.0, 1.0), (1.0, 3.0), (2.0, 5.0)]),
+            (2.0, 1.0)
+        );
+        assert_eq!(linear_fit(&[(1.0, 2.0), (1.0, 4.0)]), (0.0, 3.0));
+    }
+
+    #[test]
+    fn test_normal_quantile() {
+        assert!(normal_quantile(0.5).abs() < 1e-9);
+        assert!((normal_quantile(0.975) - 1.959_963_985).abs() < 1e-8);
+        assert!((normal_quantile(0.01) + 2.326_347_874).abs() < 1e-8);
+    }
+
+    #[test]
+    fn test_coefficient_of_variation() {
+        assert_eq!(coefficient_of_variation(&[]), None);
+        assert_eq!(coefficient_of_variation(&[1.0]), None);
+        assert_eq!(coefficient_of_variation(&[0.0, 0.0]), None);
+        assert_eq!(coefficient_of_variation(&[2.0, 2.0, 2.0]), Some(0.0));
+
+        let cv = coefficient_of_variation(&[1.0, 3.0]).unwrap();
+        assert!((cv - 2.0_f64.sqrt() / 2.0).abs() < 1e-12);
+    }
 }